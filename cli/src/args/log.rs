@@ -59,6 +59,20 @@ impl LogArgs {
         pb
     }
 
+    pub fn init_playlist_progress_bar(&self, total: u64) -> ProgressBar {
+        let pb = ProgressBar::new(total);
+
+        pb.set_style(
+            ProgressStyle::with_template("{msg}\n\n{spinner:.blue} [{elapsed_precise}] [{wide_bar:.cyan/blue}] {pos}/{len} videos ({eta})")
+                .unwrap()
+                .with_key("eta", |state: &ProgressState, w: &mut dyn std::fmt::Write| write!(w, "{:.1}s", state.eta().as_secs_f64()).unwrap())
+                .progress_chars("█░░")
+                .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏")
+        );
+
+        pb
+    }
+
     fn log_msg_formatter(&self) -> fn(FormatCallback, &Arguments, &Record) {
         #[inline(always)]
         fn format_msg(
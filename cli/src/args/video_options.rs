@@ -1,5 +1,5 @@
 use clap::Parser;
-use rusty_ytdl::VideoQuality;
+use rusty_ytdl::{VideoOptions, VideoQuality, VideoSearchOptions};
 use serde::{Deserialize, Serialize};
 
 #[derive(Parser)]
@@ -20,7 +20,8 @@ pub struct VideoOptionsArgs {
 
     /// Download the stream with this quality
     ///
-    /// [possible_values: highest, lowest, highest_audio, lowest_audio, highest_video, lowest_video]
+    /// [possible_values: highest, lowest, highest_audio, lowest_audio, highest_video, lowest_video
+    /// or a quality label like 720p, 1080p60]
     #[clap(
     long,
     value_enum,
@@ -30,7 +31,29 @@ pub struct VideoOptionsArgs {
     pub quality: Option<Quality>,
 }
 
+impl VideoOptionsArgs {
+    pub fn to_video_options(&self) -> VideoOptions {
+        let filter = if self.only_audio {
+            VideoSearchOptions::Audio
+        } else if self.only_video {
+            VideoSearchOptions::Video
+        } else {
+            VideoSearchOptions::VideoAudio
+        };
+
+        VideoOptions {
+            quality: self.quality.clone().unwrap_or(Quality::Highest).into(),
+            filter,
+            ..Default::default()
+        }
+    }
+}
+
 fn parse_from_str(s: &str) -> anyhow::Result<Quality> {
+    if s.starts_with(|c: char| c.is_ascii_digit()) {
+        return Ok(Quality::Label(s.to_string()));
+    }
+
     Ok(serde_json::from_str(&format!("\"{s}\""))?)
 }
 
@@ -48,6 +71,7 @@ pub enum Quality {
     HighestVideo,
     #[serde(rename = "lowest_video")]
     LowestVideo,
+    Label(String),
 }
 
 impl From<Quality> for VideoQuality {
@@ -59,6 +83,7 @@ impl From<Quality> for VideoQuality {
             Quality::LowestAudio => VideoQuality::LowestAudio,
             Quality::HighestVideo => VideoQuality::HighestVideo,
             Quality::LowestVideo => VideoQuality::LowestVideo,
+            Quality::Label(label) => VideoQuality::Label(label),
         }
     }
 }
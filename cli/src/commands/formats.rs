@@ -0,0 +1,21 @@
+use clap::Parser;
+
+use crate::args::{log::LogArgs, video_options::VideoOptionsArgs};
+
+#[derive(Parser)]
+pub struct FormatsArgs {
+    #[clap(
+        short = 'i',
+        long = "id",
+        help = "Video ID or URL",
+        num_args = 1,
+        required = true
+    )]
+    pub id: String,
+
+    #[clap(flatten)]
+    pub video_options: VideoOptionsArgs,
+
+    #[clap(flatten)]
+    pub log: LogArgs,
+}
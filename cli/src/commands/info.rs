@@ -0,0 +1,25 @@
+use clap::Parser;
+
+use crate::args::{log::LogArgs, output::OutputArgs};
+
+#[derive(Parser)]
+pub struct InfoArgs {
+    #[clap(
+        short = 'i',
+        long = "id",
+        help = "Video ID or URL",
+        num_args = 1,
+        required = true
+    )]
+    pub id: String,
+
+    #[clap(flatten)]
+    pub log: LogArgs,
+
+    #[clap(flatten)]
+    pub output: OutputArgs,
+
+    /// Print a yt-dlp compatible `info.json` instead of the stream list
+    #[clap(long)]
+    pub info_json: bool,
+}
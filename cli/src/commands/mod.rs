@@ -1,8 +1,14 @@
 pub mod download;
+pub mod formats;
+pub mod info;
+pub mod playlist;
 
 use clap::Parser;
 
 use self::download::DownloadArgs;
+use self::formats::FormatsArgs;
+use self::info::InfoArgs;
+use self::playlist::PlaylistArgs;
 
 #[derive(Parser)]
 #[clap(
@@ -19,4 +25,16 @@ pub enum Commands {
     Download the video to spesific folder or stdout
     ")]
     Download(DownloadArgs),
+    #[clap(about = "\
+    Print the info of the video
+    ")]
+    Info(InfoArgs),
+    #[clap(about = "\
+    List the available formats of the video, the one picked by --quality is marked with *
+    ")]
+    Formats(FormatsArgs),
+    #[clap(about = "\
+    Download the videos of a playlist to spesific folder
+    ")]
+    Playlist(PlaylistArgs),
 }
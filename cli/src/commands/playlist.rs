@@ -0,0 +1,56 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::args::{log::LogArgs, video_options::VideoOptionsArgs};
+
+#[derive(Parser)]
+pub struct PlaylistArgs {
+    #[clap(
+        short = 'u',
+        long = "url",
+        help = "Playlist URL",
+        num_args = 1,
+        required = true
+    )]
+    pub url: String,
+
+    #[clap(flatten)]
+    pub video_options: VideoOptionsArgs,
+
+    #[clap(flatten)]
+    pub log: LogArgs,
+
+    /// Where to download the videos to, created if missing [default: **./**]
+    #[clap(
+        short = 'p',
+        long = "path",
+        help = "Location folder to download [default: ./]",
+        num_args = 1,
+        required = false
+    )]
+    pub path: Option<PathBuf>,
+
+    /// yt-dlp style file name template of every video
+    #[clap(short, long, default_value = "%(title)s [%(id)s].%(ext)s")]
+    pub template: String,
+
+    /// Count of videos downloaded at once
+    #[clap(short, long, default_value_t = 4)]
+    pub concurrency: usize,
+
+    /// Maximum count of videos to download
+    #[clap(long, default_value_t = 100, conflicts_with = "all")]
+    pub limit: u64,
+
+    /// Download every video of the playlist
+    #[clap(long)]
+    pub all: bool,
+
+    /// Stop at the first failed video
+    #[clap(long)]
+    pub stop_on_error: bool,
+
+    /// Resolve the formats and print estimated sizes without downloading
+    #[clap(long)]
+    pub dry_run: bool,
+}
@@ -11,11 +11,17 @@ use std::{
 use anyhow::{Error, Result};
 use clap::Parser;
 use colored::Colorize;
-use rusty_ytdl::{Video, VideoOptions, VideoSearchOptions};
+use indicatif::HumanBytes;
+use rusty_ytdl::search::{Playlist, PlaylistSearchOptions};
+use rusty_ytdl::{
+    choose_format, PlaylistDownloadOptions, PlaylistProgressCallback, Video, VideoFormat,
+};
 use tokio::io::{self, AsyncWriteExt};
 
-use args::video_options::Quality;
-use commands::{download::DownloadArgs, Commands};
+use args::output::OutputLevel;
+use commands::{
+    download::DownloadArgs, formats::FormatsArgs, info::InfoArgs, playlist::PlaylistArgs, Commands,
+};
 use utils::result_serializer::ResultSerializer;
 
 #[tokio::main]
@@ -24,6 +30,9 @@ async fn main() -> Result<()> {
 
     let res = match commands {
         Commands::Download(args) => download(args).await,
+        Commands::Info(args) => info(args).await,
+        Commands::Formats(args) => formats(args).await,
+        Commands::Playlist(args) => playlist(args).await,
     };
 
     if let Err(ref err) = res {
@@ -54,27 +63,9 @@ async fn download(args: DownloadArgs) -> Result<()> {
         return Err(Error::msg("Output path must be a directory!"));
     }
 
-    let filter = if args.video_options.only_audio {
-        VideoSearchOptions::Audio
-    } else if args.video_options.only_video {
-        VideoSearchOptions::Video
-    } else {
-        VideoSearchOptions::VideoAudio
-    };
-
-    let quality = args
-        .video_options
-        .quality
-        .unwrap_or(Quality::Highest)
-        .into();
-
-    let download_options = VideoOptions {
-        quality,
-        filter,
-        ..Default::default()
-    };
+    let download_options = args.video_options.to_video_options();
 
-    let video = Video::new_with_options(&video_identifier, download_options.clone());
+    let video = Video::new_with_options(&video_identifier, download_options);
 
     if let Err(err) = video {
         return Err(Error::msg(err.to_string()));
@@ -206,3 +197,177 @@ async fn download(args: DownloadArgs) -> Result<()> {
 
     Ok(())
 }
+
+async fn info(args: InfoArgs) -> Result<()> {
+    args.log.init_logger();
+
+    let video = Video::new(&args.id).map_err(|err| Error::msg(err.to_string()))?;
+    let video_info = video
+        .get_info()
+        .await
+        .map_err(|err| Error::msg(err.to_string()))?;
+
+    let output = if args.info_json {
+        args.output
+            .output_format
+            .serialize(&video_info.to_info_json())?
+    } else {
+        args.output.output_format.serialize(&ResultSerializer::new(
+            video_info,
+            args.output.output_level | OutputLevel::VIDEO,
+        ))?
+    };
+    println!("{output}");
+
+    Ok(())
+}
+
+async fn formats(args: FormatsArgs) -> Result<()> {
+    args.log.init_logger();
+
+    let options = args.video_options.to_video_options();
+    let video = Video::new_with_options(&args.id, options.clone())
+        .map_err(|err| Error::msg(err.to_string()))?;
+    let video_info = video
+        .get_info()
+        .await
+        .map_err(|err| Error::msg(err.to_string()))?;
+
+    let chosen = choose_format(&video_info.formats, &options)
+        .ok()
+        .map(|format| format.itag);
+
+    println!(
+        "{}\n",
+        format!(
+            "  {:<6} {:<5} {:<12} {:<4} {:<9} {:<11} {}",
+            "ITAG", "EXT", "RESOLUTION", "FPS", "BITRATE", "SIZE", "CODECS"
+        )
+        .white()
+        .bold()
+    );
+
+    for format in &video_info.formats {
+        let line = format_line(format);
+
+        if Some(format.itag) == chosen {
+            println!("{} {}", "*".green().bold(), line.green());
+        } else {
+            println!("  {line}");
+        }
+    }
+
+    Ok(())
+}
+
+fn format_line(format: &VideoFormat) -> String {
+    let resolution = match (format.has_video, format.width, format.height) {
+        (true, Some(width), Some(height)) => format!("{width}x{height}"),
+        (true, _, _) => format.quality_label.clone().unwrap_or_default(),
+        (false, _, _) => "audio only".to_string(),
+    };
+    let fps = format.fps.map(|fps| fps.to_string()).unwrap_or_default();
+    let bitrate = format!("{}k", format.bitrate / 1000);
+    let size = format
+        .content_length
+        .map(|x| HumanBytes(x).to_string())
+        .unwrap_or_else(|| "~".to_string());
+
+    format!(
+        "{:<6} {:<5} {:<12} {:<4} {:<9} {:<11} {}",
+        format.itag,
+        format.mime_type.container,
+        resolution,
+        fps,
+        bitrate,
+        size,
+        format.mime_type.codecs.join(", "),
+    )
+}
+
+async fn playlist(args: PlaylistArgs) -> Result<()> {
+    args.log.init_logger();
+
+    let download_path = args.path.unwrap_or(PathBuf::new().join("."));
+
+    let playlist = Playlist::get(
+        &args.url,
+        Some(&PlaylistSearchOptions {
+            limit: args.limit,
+            fetch_all: args.all,
+            ..Default::default()
+        }),
+    )
+    .await
+    .map_err(|err| Error::msg(err.to_string()))?;
+
+    let pb = args
+        .log
+        .init_playlist_progress_bar(playlist.videos.len() as u64);
+
+    pb.set_message(format!(
+        "{} {}",
+        playlist.name.cyan(),
+        "is downloading...".white().bold(),
+    ));
+
+    let pb_clone = pb.clone();
+    let playlist_name = playlist.name.clone();
+    let options = PlaylistDownloadOptions {
+        video_options: args.video_options.to_video_options(),
+        template: args.template,
+        concurrency: args.concurrency,
+        skip_errors: !args.stop_on_error,
+        dry_run: args.dry_run,
+        progress_callback: Some(PlaylistProgressCallback::new(move |progress| {
+            pb_clone.set_position((progress.completed + progress.failed) as u64);
+            pb_clone.set_message(format!(
+                "{} {} {}",
+                playlist_name.cyan(),
+                "is downloading...".white().bold(),
+                HumanBytes(progress.downloaded),
+            ));
+        })),
+    };
+
+    let results = playlist.download_all(&download_path, &options).await;
+
+    pb.finish_and_clear();
+
+    let results = results.map_err(|err| Error::msg(err.to_string()))?;
+    let mut failed = 0;
+
+    for (result, video) in results.iter().zip(&playlist.videos) {
+        match (&result.result, result.estimated_size) {
+            (Ok(path), Some(size)) => println!(
+                "{} {} ({})",
+                video.title.cyan(),
+                path.to_string_lossy().green(),
+                HumanBytes(size)
+            ),
+            (Ok(path), None) => println!(
+                "{} {}",
+                video.title.cyan(),
+                path.to_string_lossy().green().underline()
+            ),
+            (Err(err), _) => {
+                failed += 1;
+                println!("{} {}", video.title.cyan(), err.to_string().red());
+            }
+        }
+    }
+
+    println!(
+        "\n{} {}\n",
+        format!(
+            "{}/{} videos successfully downloaded to",
+            results.len() - failed,
+            results.len()
+        )
+        .white()
+        .bold(),
+        download_path.to_string_lossy().green().underline()
+    );
+
+    Ok(())
+}
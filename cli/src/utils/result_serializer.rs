@@ -82,7 +82,7 @@ impl Serialize for FormatSerializer {
         partly_serialize!(self, map;
             OutputLevel::GENERAL => {
                 itag, mime_type, quality, has_video, has_audio,
-                approx_duration_ms, url
+                approx_duration_ms
             }
             OutputLevel::GENERAL | OutputLevel::VERBOSE => {
 
@@ -107,6 +107,11 @@ impl Serialize for FormatSerializer {
             }
         );
 
+        // Deciphered only for printed formats, the raw URL of ciphered formats is not downloadable
+        if self.output_level.contains(OutputLevel::GENERAL) {
+            map.serialize_entry("url", &self.format.resolve_url())?;
+        }
+
         map.end()
     }
 }
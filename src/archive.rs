@@ -0,0 +1,104 @@
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::structs::VideoError;
+
+/// yt-dlp compatible `--download-archive` file, a `youtube <video id>` line per downloaded video.
+/// Both tools can share the same file
+/// # Example
+/// ```ignore
+///     let mut archive = DownloadArchive::open("archive.txt").unwrap();
+///
+///     if !archive.contains("FZ8BxMU3BYc") {
+///         video.download("video.mp4").await.unwrap();
+///         archive.insert("FZ8BxMU3BYc").unwrap();
+///     }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DownloadArchive {
+    path: PathBuf,
+    ids: HashSet<String>,
+}
+
+impl DownloadArchive {
+    /// Read the archive file, a missing file is an empty archive
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, VideoError> {
+        let path = path.as_ref().to_path_buf();
+
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(VideoError::DownloadError(e.to_string())),
+        };
+
+        let ids = content
+            .lines()
+            .filter_map(|line| line.trim().strip_prefix("youtube "))
+            .map(|id| id.trim().to_string())
+            .collect();
+
+        Ok(Self { path, ids })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn contains(&self, video_id: &str) -> bool {
+        self.ids.contains(video_id)
+    }
+
+    /// Add the video to the archive and append it to the file. Return `false` if it was already archived
+    pub fn insert(&mut self, video_id: impl Into<String>) -> Result<bool, VideoError> {
+        let video_id: String = video_id.into();
+
+        if self.ids.contains(&video_id) {
+            return Ok(false);
+        }
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(|e| VideoError::DownloadError(e.to_string()))?;
+
+        writeln!(file, "youtube {video_id}")
+            .map_err(|e| VideoError::DownloadError(e.to_string()))?;
+
+        self.ids.insert(video_id);
+
+        Ok(true)
+    }
+
+    pub fn len(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_download_archive() {
+        let path = std::env::temp_dir().join("rusty_ytdl_test_download_archive.txt");
+        std::fs::write(&path, "youtube FZ8BxMU3BYc\nvimeo 123\n").unwrap();
+
+        let mut archive = DownloadArchive::open(&path).unwrap();
+        assert!(archive.contains("FZ8BxMU3BYc"));
+        assert!(!archive.contains("123"));
+        assert!(archive.insert("jNQXAC9IVRw").unwrap());
+        assert!(!archive.insert("jNQXAC9IVRw").unwrap());
+
+        let archive = DownloadArchive::open(&path).unwrap();
+        assert_eq!(archive.len(), 2);
+        assert!(archive.contains("jNQXAC9IVRw"));
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
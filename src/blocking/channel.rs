@@ -0,0 +1,93 @@
+use crate::channel::{Channel as AsyncChannel, ChannelTab as AsyncChannelTab};
+use crate::search::SearchResult;
+use crate::{block_async, ChannelTabType, RequestOptions, VideoError};
+
+#[derive(Clone, derive_more::Display, derivative::Derivative)]
+#[display(fmt = "Channel()")]
+#[derivative(Debug, PartialEq, Eq)]
+pub struct Channel(AsyncChannel);
+
+impl Channel {
+    /// Try to resolve channel from channel URL, handle (`@handle`) or channel id (`UC...`) with default [`RequestOptions`]
+    pub fn get(url_or_id: impl Into<String>) -> Result<Self, VideoError> {
+        Ok(Self(block_async!(AsyncChannel::get(url_or_id))?))
+    }
+
+    /// Try to resolve channel from channel URL, handle (`@handle`) or channel id (`UC...`) with custom [`RequestOptions`]
+    pub fn get_with_options(
+        url_or_id: impl Into<String>,
+        request_options: &RequestOptions,
+    ) -> Result<Self, VideoError> {
+        Ok(Self(block_async!(AsyncChannel::get_with_options(
+            url_or_id,
+            request_options
+        ))?))
+    }
+
+    /// Get "Videos" tab of the channel
+    pub fn videos(&self) -> Result<ChannelTab, VideoError> {
+        self.tab(ChannelTabType::Videos)
+    }
+
+    /// Get "Shorts" tab of the channel
+    pub fn shorts(&self) -> Result<ChannelTab, VideoError> {
+        self.tab(ChannelTabType::Shorts)
+    }
+
+    /// Get "Live" tab of the channel
+    pub fn live(&self) -> Result<ChannelTab, VideoError> {
+        self.tab(ChannelTabType::Live)
+    }
+
+    /// Get "Playlists" tab of the channel
+    pub fn playlists(&self) -> Result<ChannelTab, VideoError> {
+        self.tab(ChannelTabType::Playlists)
+    }
+
+    /// Get first page of the given tab. Use [`ChannelTab::next`] to get next pages
+    pub fn tab(&self, tab: ChannelTabType) -> Result<ChannelTab, VideoError> {
+        Ok(ChannelTab(block_async!(self.0.tab(tab))?))
+    }
+}
+
+impl std::ops::Deref for Channel {
+    type Target = AsyncChannel;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[derive(Clone, derivative::Derivative)]
+#[derivative(Debug, PartialEq, Eq)]
+pub struct ChannelTab(AsyncChannelTab);
+
+impl ChannelTab {
+    /// Get next page of the tab and return fetched items.
+    /// - If there is no next page this function return empty [`Vec<SearchResult>`]
+    pub fn next(&mut self) -> Result<Vec<SearchResult>, VideoError> {
+        Ok(block_async!(self.0.next())?)
+    }
+
+    /// Try to fetch items until the limit reached or no next page left.
+    /// - If limit is [`None`] it will be [`u64::MAX`]
+    pub fn fetch(&mut self, limit: Option<u64>) -> Result<&mut Self, VideoError> {
+        block_async!(self.0.fetch(limit))?;
+
+        Ok(self)
+    }
+}
+
+impl std::ops::Deref for ChannelTab {
+    type Target = AsyncChannelTab;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for ChannelTab {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
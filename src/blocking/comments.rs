@@ -0,0 +1,37 @@
+use crate::block_async;
+use crate::comments::Comments as AsyncComments;
+use crate::structs::{Comment, VideoError};
+
+#[derive(Clone, derivative::Derivative)]
+#[derivative(Debug, PartialEq, Eq)]
+pub struct Comments(pub(super) AsyncComments);
+
+impl Comments {
+    /// Get next page of comments and return fetched [`Comment`] array.
+    /// - If there is no next page or comments are disabled this function return empty [`Vec<Comment>`]
+    pub fn next(&mut self) -> Result<Vec<Comment>, VideoError> {
+        Ok(block_async!(self.0.next())?)
+    }
+
+    /// Try to fetch comments until the limit reached or no next page left.
+    /// - If limit is [`None`] it will be [`u64::MAX`]
+    pub fn fetch(&mut self, limit: Option<u64>) -> Result<&mut Self, VideoError> {
+        block_async!(self.0.fetch(limit))?;
+
+        Ok(self)
+    }
+}
+
+impl std::ops::Deref for Comments {
+    type Target = AsyncComments;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for Comments {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
@@ -1,15 +1,19 @@
 use crate::block_async;
 #[cfg(feature = "live")]
 use crate::blocking::stream::LiveStream;
-use crate::blocking::stream::NonLiveStream;
-use crate::structs::{VideoError, VideoInfo, VideoOptions};
+use crate::blocking::stream::{DashStream, NonLiveStream, OtfStream};
+use crate::structs::{
+    CaptionFormat, CommentSort, DownloadEvent, QuickInfo, ThumbnailQuality, VideoError, VideoInfo,
+    VideoOptions, WatchNext,
+};
 use crate::utils::choose_format;
 use crate::Video as AsyncVideo;
 
-#[cfg(feature = "live")]
-use super::stream::LiveStreamOptions;
-use super::stream::{NonLiveStreamOptions, Stream};
+use super::stream::Stream;
+use super::{Comments, LiveChat, RelatedVideos};
 
+#[cfg(feature = "ffmpeg")]
+use super::stream::FFmpegStream;
 #[cfg(feature = "ffmpeg")]
 use crate::structs::FFmpegArgs;
 
@@ -42,6 +46,85 @@ impl Video {
         Ok(block_async!(self.0.get_info())?)
     }
 
+    /// [`Video::get_info`] of every URL or ID, running at most `concurrency` requests at once, see [`AsyncVideo::get_info_many`]
+    pub fn get_info_many<I, S>(
+        url_or_ids: I,
+        options: &VideoOptions,
+        concurrency: usize,
+    ) -> Result<Vec<Result<VideoInfo, VideoError>>, VideoError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Ok(block_async!(AsyncVideo::get_info_many(
+            url_or_ids,
+            options,
+            concurrency
+        ))?)
+    }
+
+    /// Try to get watch-next feed of the video
+    /// - If the video created with a URL contains `list` parameter, [`WatchNext::playlist`] is filled with the playlist panel
+    pub fn get_watch_next(&self) -> Result<WatchNext, VideoError> {
+        Ok(block_async!(self.0.get_watch_next())?)
+    }
+
+    /// Try to get first page of comments sorted by [`CommentSort`]. Use [`Comments::next`] to get next pages
+    pub fn comments(&self, sort: CommentSort) -> Result<Comments, VideoError> {
+        Ok(Comments(block_async!(self.0.comments(sort))?))
+    }
+
+    /// Try to get first page of related videos with full metadata. Use [`RelatedVideos::next`] to follow the watch next continuations
+    pub fn related(&self) -> Result<RelatedVideos, VideoError> {
+        Ok(RelatedVideos(block_async!(self.0.related())?))
+    }
+
+    /// Wait until an upcoming live stream or premiere starts and return its basic information.
+    /// Video is checked every `poll_interval`, or at the scheduled start time if it comes earlier
+    #[cfg(feature = "live")]
+    pub fn wait_until_live(
+        &self,
+        poll_interval: std::time::Duration,
+    ) -> Result<VideoInfo, VideoError> {
+        Ok(block_async!(self.0.wait_until_live(poll_interval))?)
+    }
+
+    /// Try to get the live chat of a currently live video. Use [`LiveChat::next`] to poll new messages
+    pub fn live_chat(&self) -> Result<LiveChat, VideoError> {
+        Ok(LiveChat(block_async!(self.0.live_chat())?))
+    }
+
+    /// Try to download caption track of the `lang` language code converted to the [`CaptionFormat`]
+    /// - Manually created tracks take precedence over auto-generated ones
+    pub fn download_caption(
+        &self,
+        lang: impl Into<String>,
+        format: CaptionFormat,
+    ) -> Result<String, VideoError> {
+        Ok(block_async!(self.0.download_caption(lang, format))?)
+    }
+
+    /// Check that the video exists with a single small request to the oEmbed endpoint, see [`AsyncVideo::exists`]
+    pub fn exists(&self) -> Result<bool, VideoError> {
+        Ok(block_async!(self.0.exists())?)
+    }
+
+    /// Title, author and thumbnail of the video from the oEmbed endpoint, see [`AsyncVideo::quick_info`]
+    pub fn quick_info(&self) -> Result<QuickInfo, VideoError> {
+        Ok(block_async!(self.0.quick_info())?)
+    }
+
+    /// Download the thumbnail of the video to the file as JPEG.
+    /// Falls back to the next lower [`ThumbnailQuality`] if the image does not exist (e.g. `Max` of SD videos),
+    /// then to [`Thumbnail::best`](crate::Thumbnail::best) of the video details
+    pub fn download_thumbnail<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        quality: ThumbnailQuality,
+    ) -> Result<(), VideoError> {
+        Ok(block_async!(self.0.download_thumbnail(path, quality))?)
+    }
+
     /// Try to turn [`Stream`] implemented [`LiveStream`] or [`NonLiveStream`] depend on the video.
     /// If function successfully return can download video chunk by chunk
     /// # Example
@@ -57,15 +140,16 @@ impl Video {
     ///     }
     /// ```
     pub fn stream(&self) -> Result<Box<dyn Stream + Send + Sync>, VideoError> {
-        let client = self.0.get_client();
-
-        let options = self.0.get_options();
+        self.stream_from(0)
+    }
 
-        let info = block_async!(self.0.get_info())?;
-        let format = choose_format(&info.formats, &options)
-            .map_err(|_op| VideoError::VideoSourceNotFound)?;
+    /// Same as [`Video::stream`] but start downloading from the `offset` byte with Range requests
+    /// - `offset` is ignored for live streams and OTF formats
+    /// - If `offset` is bigger than content length, the stream returns [`None`] directly
+    pub fn stream_from(&self, offset: u64) -> Result<Box<dyn Stream + Send + Sync>, VideoError> {
+        let (info, format) = block_async!(self.0.info_and_format())?;
 
-        let link = format.url;
+        let link = block_async!(self.0.format_url(&format))?;
 
         if link.is_empty() {
             return Err(VideoError::VideoSourceNotFound);
@@ -75,10 +159,7 @@ impl Video {
         if format.is_hls {
             #[cfg(feature = "live")]
             {
-                let stream = LiveStream::new(LiveStreamOptions {
-                    client: Some(client.clone()),
-                    stream_url: link,
-                })?;
+                let stream = LiveStream::new(self.0.live_options(&info, link))?;
 
                 return Ok(Box::new(stream));
             }
@@ -88,41 +169,24 @@ impl Video {
             }
         }
 
-        let dl_chunk_size = options
-            .download_options
-            .dl_chunk_size
-            // 1024 * 1024 * 10_u64 -> Default is 10MB to avoid Youtube throttle (Bigger than this value can be throttle by Youtube)
-            .unwrap_or(1024 * 1024 * 10_u64);
-
-        let start = 0;
-        let end = start + dl_chunk_size;
-
-        let mut content_length = format
-            .content_length
-            .unwrap_or("0".to_string())
-            .parse::<u64>()
-            .unwrap_or(0);
-
-        // Get content length from source url if content_length is 0
-        if content_length == 0 {
-            let content_length_response = block_async!(client.get(&link).send())
-                .map_err(VideoError::ReqwestMiddleware)?
-                .content_length()
-                .ok_or(VideoError::VideoNotFound)?;
-
-            content_length = content_length_response;
+        // OTF and segmented DASH formats have no byte ranges, `offset` is ignored like for live streams
+        if format.is_otf {
+            return Ok(Box::new(OtfStream::new(self.0.otf_options(link))?));
+        }
+
+        if format.is_dash_segmented {
+            return Ok(Box::new(DashStream::new(
+                self.0.dash_options(&format, link),
+            )?));
         }
 
-        let stream = NonLiveStream::new(NonLiveStreamOptions {
-            client: Some(client.clone()),
-            link,
-            content_length,
-            dl_chunk_size,
-            start,
-            end,
-            #[cfg(feature = "ffmpeg")]
-            ffmpeg_args: None,
-        })?;
+        let content_length = block_async!(self.0.format_content_length(&format, &link))?;
+
+        let stream =
+            NonLiveStream::new(
+                self.0
+                    .non_live_options(&format, link, offset, content_length),
+            )?;
 
         Ok(Box::new(stream))
     }
@@ -146,15 +210,9 @@ impl Video {
         &self,
         ffmpeg_args: Option<FFmpegArgs>,
     ) -> Result<Box<dyn Stream + Send + Sync>, VideoError> {
-        let client = self.0.get_client();
-
-        let options = self.0.get_options();
+        let (info, format) = block_async!(self.0.info_and_format())?;
 
-        let info = block_async!(self.0.get_info())?;
-        let format = choose_format(&info.formats, &options)
-            .map_err(|_op| VideoError::VideoSourceNotFound)?;
-
-        let link = format.url;
+        let link = block_async!(self.0.format_url(&format))?;
 
         if link.is_empty() {
             return Err(VideoError::VideoSourceNotFound);
@@ -164,10 +222,7 @@ impl Video {
         if format.is_hls {
             #[cfg(feature = "live")]
             {
-                let stream = LiveStream::new(LiveStreamOptions {
-                    client: Some(client.clone()),
-                    stream_url: link,
-                })?;
+                let stream = LiveStream::new(self.0.live_options(&info, link))?;
 
                 return Ok(Box::new(stream));
             }
@@ -177,52 +232,118 @@ impl Video {
             }
         }
 
-        let dl_chunk_size = options
-            .download_options
-            .dl_chunk_size
-            // 1024 * 1024 * 10_u64 -> Default is 10MB to avoid Youtube throttle (Bigger than this value can be throttle by Youtube)
-            .unwrap_or(1024 * 1024 * 10_u64);
-
-        let start = 0;
-        let end = start + dl_chunk_size;
-
-        let mut content_length = format
-            .content_length
-            .unwrap_or("0".to_string())
-            .parse::<u64>()
-            .unwrap_or(0);
-
-        // Get content length from source url if content_length is 0
-        if content_length == 0 {
-            let content_length_response = client
-                .get(&link)
-                .send()
-                .await
-                .map_err(VideoError::ReqwestMiddleware)?
-                .content_length()
-                .ok_or(VideoError::VideoNotFound)?;
-
-            content_length = content_length_response;
+        let ffmpeg_args = ffmpeg_args.map(|x| x.build()).unwrap_or_default();
+
+        if format.is_otf {
+            let stream_options = self.0.otf_options(link);
+
+            if ffmpeg_args.is_empty() {
+                return Ok(Box::new(OtfStream::new(stream_options)?));
+            }
+
+            // ffmpeg reads the source from the async stream in the background
+            let source = crate::stream::OtfStream::new(stream_options)?;
+
+            self.0.emit(DownloadEvent::FfmpegStarted);
+
+            return Ok(Box::new(FFmpegStream::new(Box::new(source), &ffmpeg_args)?));
         }
 
-        let stream = NonLiveStream::new(NonLiveStreamOptions {
-            client: Some(client.clone()),
-            link,
-            content_length,
-            dl_chunk_size,
-            start,
-            end,
-            ffmpeg_args,
-        })?;
+        if format.is_dash_segmented {
+            let stream_options = self.0.dash_options(&format, link);
 
-        Ok(Box::new(stream))
+            if ffmpeg_args.is_empty() {
+                return Ok(Box::new(DashStream::new(stream_options)?));
+            }
+
+            // ffmpeg reads the source from the async stream in the background
+            let source = crate::stream::DashStream::new(stream_options)?;
+
+            self.0.emit(DownloadEvent::FfmpegStarted);
+
+            return Ok(Box::new(FFmpegStream::new(Box::new(source), &ffmpeg_args)?));
+        }
+
+        let content_length = self.0.format_content_length(&format, &link).await?;
+
+        let stream_options = self.0.non_live_options(&format, link, 0, content_length);
+
+        if ffmpeg_args.is_empty() {
+            return Ok(Box::new(NonLiveStream::new(stream_options)?));
+        }
+
+        // ffmpeg reads the source from the async stream in the background
+        let source = crate::stream::NonLiveStream::new(stream_options)?;
+
+        self.0.emit(DownloadEvent::FfmpegStarted);
+
+        Ok(Box::new(FFmpegStream::new(Box::new(source), &ffmpeg_args)?))
     }
 
     /// Download video directly to the file
+    /// - Data is written to `<path>.part`, which is renamed to `path` after the download succeeds
+    /// - If [`DownloadOptions::resume`](crate::DownloadOptions::resume) is `true` and the `.part` file already exists,
+    /// download continues from the end of it instead of restarting from zero
     pub fn download<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), VideoError> {
         Ok(block_async!(self.0.download(path))?)
     }
 
+    /// Download video into `dir` with a file name rendered from a yt-dlp style `template`
+    /// (see [`render_filename_template`](crate::render_filename_template)) and return the path of the file
+    pub fn download_with_template<P: AsRef<std::path::Path>>(
+        &self,
+        dir: P,
+        template: &str,
+    ) -> Result<std::path::PathBuf, VideoError> {
+        Ok(block_async!(self.0.download_with_template(dir, template))?)
+    }
+
+    /// Estimate the size in bytes of [`Video::download`] without downloading.
+    /// [`None`] for live streams and formats without content length and bitrate
+    pub fn estimate_size(&self) -> Result<Option<u64>, VideoError> {
+        Ok(block_async!(self.0.estimate_size())?)
+    }
+
+    /// Download video into any [`std::io::Write`] (e.g. socket, in-memory buffer) instead of a file
+    /// - Returns [`VideoError::IncompleteDownload`] if the written bytes are different from the content length of the format
+    pub fn download_to_writer<W: std::io::Write + ?Sized>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), VideoError> {
+        let stream = self.stream()?;
+
+        let mut got = 0;
+
+        while let Some(chunk) = stream.chunk()? {
+            writer
+                .write_all(&chunk)
+                .map_err(|e| VideoError::DownloadError(e.to_string()))?;
+
+            got += chunk.len() as u64;
+        }
+
+        writer
+            .flush()
+            .map_err(|e| VideoError::DownloadError(e.to_string()))?;
+
+        let expected = stream.content_length() as u64;
+        if expected > 0 && got != expected {
+            return Err(VideoError::IncompleteDownload { expected, got });
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "checksum")]
+    /// Same as [`Video::download`] and return the lowercase hex hash of the downloaded data.
+    /// [`None`] if [`DownloadOptions::hash`](crate::DownloadOptions::hash) is not set
+    pub fn download_with_hash<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> Result<Option<String>, VideoError> {
+        Ok(block_async!(self.0.download_with_hash(path))?)
+    }
+
     #[cfg(feature = "ffmpeg")]
     /// Download video with ffmpeg args directly to the file
     pub async fn download_with_ffmpeg<P: AsRef<std::path::Path>>(
@@ -235,6 +356,49 @@ impl Video {
             .download_with_ffmpeg(path, ffmpeg_args))?)
     }
 
+    #[cfg(feature = "ffmpeg")]
+    /// Download the highest quality adaptive video and audio formats and mux them into one file with ffmpeg.
+    /// Progressive formats are capped at 720p, adaptive formats are not
+    /// - Streams are copied without re-encoding, so the container of `path` must support both codecs
+    /// (e.g. `.mkv` for VP9/AV1 video with Opus audio)
+    pub fn download_muxed<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), VideoError> {
+        Ok(block_async!(self.0.download_muxed(path))?)
+    }
+
+    /// Download the highest quality Opus audio and write it as an Ogg Opus file (`.opus`) without ffmpeg.
+    /// WebM container of the format is demuxed in memory
+    pub fn download_opus<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), VideoError> {
+        Ok(block_async!(self.0.download_opus(path))?)
+    }
+
+    /// Download the highest quality AAC audio and write it as a regular `.m4a` file without ffmpeg.
+    /// Fragmented MP4 container of the format is rewritten in memory, so players can seek without the DASH index
+    pub fn download_m4a<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), VideoError> {
+        Ok(block_async!(self.0.download_m4a(path))?)
+    }
+
+    #[cfg(feature = "tags")]
+    /// Write title, artist, album, year and video URL tags of the video into an already downloaded file.
+    /// ID3v2, MP4 or Vorbis comment tags are chosen by the file type
+    pub fn write_tags<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), VideoError> {
+        Ok(block_async!(self.0.write_tags(path))?)
+    }
+
+    /// Download only the `start`-`end` time window of the video directly to the file
+    /// - Progressive formats (video and audio in one file) are cut with byte ranges estimated from the format duration,
+    /// so bounds are approximate and the file may need remuxing by the player
+    /// - Adaptive formats are cut with ffmpeg `-ss`/`-to` and require `ffmpeg` feature, only the requested window is fetched.
+    /// Chapters inside the window are written into the output
+    /// - Live streams are not supported
+    pub fn download_section<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        start: std::time::Duration,
+        end: std::time::Duration,
+    ) -> Result<(), VideoError> {
+        Ok(block_async!(self.0.download_section(path, start, end))?)
+    }
+
     /// Get video URL
     pub fn get_video_url(&self) -> String {
         self.0.get_video_url()
@@ -0,0 +1,30 @@
+use crate::block_async;
+use crate::live_chat::LiveChat as AsyncLiveChat;
+use crate::structs::{LiveChatMessage, VideoError};
+
+#[derive(Clone, derivative::Derivative)]
+#[derivative(Debug, PartialEq, Eq)]
+pub struct LiveChat(pub(super) AsyncLiveChat);
+
+impl LiveChat {
+    /// Poll the chat once and return the new [`LiveChatMessage`] array.
+    /// - Wait [`LiveChat::timeout`](crate::LiveChat::timeout) between polls
+    /// - If the live stream ended this function return empty [`Vec<LiveChatMessage>`]
+    pub fn next(&mut self) -> Result<Vec<LiveChatMessage>, VideoError> {
+        Ok(block_async!(self.0.next())?)
+    }
+}
+
+impl std::ops::Deref for LiveChat {
+    type Target = AsyncLiveChat;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for LiveChat {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
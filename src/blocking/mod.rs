@@ -1,8 +1,15 @@
+mod comments;
 mod info;
+mod live_chat;
+mod related;
+mod resolve;
 
 #[cfg(feature = "search")]
 pub mod search;
 
+#[cfg(feature = "search")]
+mod channel;
+
 pub mod stream;
 
 use once_cell::sync::Lazy;
@@ -23,4 +30,11 @@ macro_rules! block_async {
     };
 }
 
+pub use comments::Comments;
 pub use info::Video;
+pub use live_chat::LiveChat;
+pub use related::RelatedVideos;
+pub use resolve::{resolve_url, resolve_url_with_options};
+
+#[cfg(feature = "search")]
+pub use channel::{Channel, ChannelTab};
@@ -0,0 +1,37 @@
+use crate::block_async;
+use crate::related::RelatedVideos as AsyncRelatedVideos;
+use crate::structs::{RelatedVideo, VideoError};
+
+#[derive(Clone, derivative::Derivative)]
+#[derivative(Debug, PartialEq, Eq)]
+pub struct RelatedVideos(pub(super) AsyncRelatedVideos);
+
+impl RelatedVideos {
+    /// Get next page of related videos and return fetched [`RelatedVideo`] array.
+    /// - If there is no next page this function return empty [`Vec<RelatedVideo>`]
+    pub fn next(&mut self) -> Result<Vec<RelatedVideo>, VideoError> {
+        Ok(block_async!(self.0.next())?)
+    }
+
+    /// Try to fetch related videos until the limit reached or no next page left.
+    /// - If limit is [`None`] it will be [`u64::MAX`]
+    pub fn fetch(&mut self, limit: Option<u64>) -> Result<&mut Self, VideoError> {
+        block_async!(self.0.fetch(limit))?;
+
+        Ok(self)
+    }
+}
+
+impl std::ops::Deref for RelatedVideos {
+    type Target = AsyncRelatedVideos;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for RelatedVideos {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
@@ -0,0 +1,19 @@
+use crate::block_async;
+use crate::resolve::{
+    resolve_url as async_resolve_url, resolve_url_with_options as async_resolve_url_with_options,
+    ResolvedUrl,
+};
+use crate::structs::{RequestOptions, VideoError};
+
+/// Classify any YouTube URL (video, shorts, channel handle, playlist, short link, attribution link) with default [`RequestOptions`]
+pub fn resolve_url(url: impl Into<String>) -> Result<ResolvedUrl, VideoError> {
+    block_async!(async_resolve_url(url))
+}
+
+/// Classify any YouTube URL (video, shorts, channel handle, playlist, short link, attribution link) with custom [`RequestOptions`]
+pub fn resolve_url_with_options(
+    url: impl Into<String>,
+    request_options: &RequestOptions,
+) -> Result<ResolvedUrl, VideoError> {
+    block_async!(async_resolve_url_with_options(url, request_options))
+}
@@ -1,6 +1,6 @@
 pub use crate::search::{
-    Channel, EmbedOptions, PlaylistSearchOptions, RequestOptions, SearchOptions, SearchResult,
-    SearchType, Video,
+    Channel, EmbedOptions, PlaylistSearchOptions, RequestOptions, SearchDuration, SearchOptions,
+    SearchResult, SearchType, SortBy, SuggestionOptions, TrendingCategory, UploadDate, Video,
 };
 use crate::search::{Playlist as AsyncPlaylist, YouTube as AsyncYouTube};
 use crate::{block_async, VideoError};
@@ -47,6 +47,24 @@ impl YouTube {
     ) -> Result<Option<SearchResult>, VideoError> {
         Ok(block_async!(self.0.search_one(query, search_options))?)
     }
+
+    /// Get autocomplete suggestions of the `query` ranked as YouTube returns them. If nothing found, its return empty [`Vec<String>`]
+    pub fn suggestions(
+        &self,
+        query: impl Into<String>,
+        suggestion_options: Option<&SuggestionOptions>,
+    ) -> Result<Vec<String>, VideoError> {
+        Ok(block_async!(self.0.suggestions(query, suggestion_options))?)
+    }
+
+    /// Get trending videos of the `region` (ISO 3166-1 alpha-2 country code, e.g. `US`) in the `category`
+    pub fn trending(
+        &self,
+        region: impl Into<String>,
+        category: TrendingCategory,
+    ) -> Result<Vec<Video>, VideoError> {
+        Ok(block_async!(self.0.trending(region, category))?)
+    }
 }
 
 impl std::ops::Deref for YouTube {
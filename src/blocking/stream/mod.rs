@@ -1,5 +1,10 @@
 mod streams;
 
+#[cfg(feature = "ffmpeg")]
+pub use streams::FFmpegStream;
+pub use streams::{
+    DashStream, DashStreamOptions, NonLiveStream, NonLiveStreamOptions, OtfStream,
+    OtfStreamOptions, Stream,
+};
 #[cfg(feature = "live")]
 pub use streams::{LiveStream, LiveStreamOptions};
-pub use streams::{NonLiveStream, NonLiveStreamOptions, Stream};
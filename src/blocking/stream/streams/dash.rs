@@ -0,0 +1,34 @@
+use bytes::Bytes;
+
+use crate::blocking::stream::Stream;
+use crate::stream::{DashStream as AsyncDashStream, DashStreamOptions};
+use crate::{block_async, VideoError};
+
+pub struct DashStream(AsyncDashStream);
+
+impl DashStream {
+    pub fn new(options: DashStreamOptions) -> Result<Self, VideoError> {
+        Ok(Self(AsyncDashStream::new(options)?))
+    }
+}
+
+impl Stream for DashStream {
+    fn chunk(&self) -> Result<Option<Bytes>, VideoError> {
+        use crate::stream::Stream;
+        Ok(block_async!(self.0.chunk())?)
+    }
+}
+
+impl std::ops::Deref for DashStream {
+    type Target = AsyncDashStream;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for DashStream {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
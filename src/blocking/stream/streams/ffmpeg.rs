@@ -0,0 +1,40 @@
+use bytes::Bytes;
+
+use crate::blocking::stream::Stream;
+use crate::stream::FFmpegStream as AsyncFFmpegStream;
+use crate::{block_async, VideoError};
+
+pub struct FFmpegStream(AsyncFFmpegStream);
+
+impl FFmpegStream {
+    pub fn new(
+        source: Box<dyn crate::stream::Stream + Send + Sync>,
+        ffmpeg_args: &[String],
+    ) -> Result<Self, VideoError> {
+        // Process and source feeder are bound to the blocking runtime
+        let _guard = crate::blocking::TOKIO_RT.enter();
+
+        Ok(Self(AsyncFFmpegStream::new(source, ffmpeg_args)?))
+    }
+}
+
+impl Stream for FFmpegStream {
+    fn chunk(&self) -> Result<Option<Bytes>, VideoError> {
+        use crate::stream::Stream;
+        Ok(block_async!(self.0.chunk())?)
+    }
+}
+
+impl std::ops::Deref for FFmpegStream {
+    type Target = AsyncFFmpegStream;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for FFmpegStream {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
@@ -1,18 +1,28 @@
 use bytes::Bytes;
 
+pub use crate::stream::DashStreamOptions;
 #[cfg(feature = "live")]
 pub use crate::stream::LiveStreamOptions;
 pub use crate::stream::NonLiveStreamOptions;
+pub use crate::stream::OtfStreamOptions;
 
 use crate::VideoError;
 
+mod dash;
+#[cfg(feature = "ffmpeg")]
+mod ffmpeg;
 #[cfg(feature = "live")]
 mod live;
 mod non_live;
+mod otf;
 
+pub use dash::DashStream;
+#[cfg(feature = "ffmpeg")]
+pub use ffmpeg::FFmpegStream;
 #[cfg(feature = "live")]
 pub use live::LiveStream;
 pub use non_live::NonLiveStream;
+pub use otf::OtfStream;
 
 pub trait Stream {
     /// Stream a chunk of the [`u8`] bytes
@@ -26,4 +36,11 @@ pub trait Stream {
     fn content_length(&self) -> usize {
         0
     }
+
+    /// Move the stream to the `offset` byte, next [`Stream::chunk`] call starts from there
+    ///
+    /// If stream is [`LiveStream`] returns always [`VideoError::StreamNotSeekable`]
+    fn seek(&self, _offset: u64) -> Result<(), VideoError> {
+        Err(VideoError::StreamNotSeekable)
+    }
 }
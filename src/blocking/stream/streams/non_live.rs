@@ -21,6 +21,11 @@ impl Stream for NonLiveStream {
     fn content_length(&self) -> usize {
         self.0.content_length() as usize
     }
+
+    fn seek(&self, offset: u64) -> Result<(), VideoError> {
+        use crate::stream::Stream;
+        Ok(block_async!(self.0.seek(offset))?)
+    }
 }
 
 impl std::ops::Deref for NonLiveStream {
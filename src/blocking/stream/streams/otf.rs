@@ -0,0 +1,34 @@
+use bytes::Bytes;
+
+use crate::blocking::stream::Stream;
+use crate::stream::{OtfStream as AsyncOtfStream, OtfStreamOptions};
+use crate::{block_async, VideoError};
+
+pub struct OtfStream(AsyncOtfStream);
+
+impl OtfStream {
+    pub fn new(options: OtfStreamOptions) -> Result<Self, VideoError> {
+        Ok(Self(AsyncOtfStream::new(options)?))
+    }
+}
+
+impl Stream for OtfStream {
+    fn chunk(&self) -> Result<Option<Bytes>, VideoError> {
+        use crate::stream::Stream;
+        Ok(block_async!(self.0.chunk())?)
+    }
+}
+
+impl std::ops::Deref for OtfStream {
+    type Target = AsyncOtfStream;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for OtfStream {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
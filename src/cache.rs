@@ -0,0 +1,109 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use crate::platform::Instant;
+
+/// Least recently used cache with optional lifetime for the entries
+#[derive(Debug)]
+pub(crate) struct LruCache<V> {
+    capacity: usize,
+    ttl: Option<Duration>,
+    // Most recently used entry is at the back
+    entries: VecDeque<(String, V, Instant)>,
+}
+
+impl<V: Clone> LruCache<V> {
+    pub(crate) fn new(capacity: usize, ttl: Option<Duration>) -> Self {
+        Self {
+            capacity,
+            ttl,
+            entries: VecDeque::new(),
+        }
+    }
+
+    /// Change the capacity and lifetime, entries over the new capacity are evicted
+    pub(crate) fn configure(&mut self, capacity: usize, ttl: Option<Duration>) {
+        self.capacity = capacity;
+        self.ttl = ttl;
+
+        while self.entries.len() > self.capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    pub(crate) fn get(&mut self, key: &str) -> Option<V> {
+        self.remove_expired();
+
+        let index = self.entries.iter().position(|(x, _, _)| x == key)?;
+        self.touch(index)
+    }
+
+    /// Get the entry only if it was inserted less than `max_age` ago
+    pub(crate) fn get_within(&mut self, key: &str, max_age: Duration) -> Option<V> {
+        self.remove_expired();
+
+        let index = self
+            .entries
+            .iter()
+            .position(|(x, _, inserted_at)| x == key && inserted_at.elapsed() < max_age)?;
+        self.touch(index)
+    }
+
+    /// Move the entry at `index` to the most recently used position
+    fn touch(&mut self, index: usize) -> Option<V> {
+        let entry = self.entries.remove(index)?;
+        let value = entry.1.clone();
+
+        self.entries.push_back(entry);
+
+        Some(value)
+    }
+
+    pub(crate) fn insert(&mut self, key: String, value: V) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        self.entries.retain(|(x, _, _)| x != &key);
+
+        while self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+
+        self.entries.push_back((key, value, Instant::now()));
+    }
+
+    fn remove_expired(&mut self) {
+        if let Some(ttl) = self.ttl {
+            self.entries
+                .retain(|(_, _, inserted_at)| inserted_at.elapsed() < ttl);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lru_cache() {
+        let mut cache = LruCache::new(2, None);
+
+        cache.insert("a".to_string(), 1);
+        cache.insert("b".to_string(), 2);
+
+        // `a` becomes the most recently used, so `b` is evicted
+        assert_eq!(cache.get("a"), Some(1));
+        cache.insert("c".to_string(), 3);
+
+        assert_eq!(cache.get("b"), None);
+        assert_eq!(cache.get("a"), Some(1));
+        assert_eq!(cache.get("c"), Some(3));
+
+        assert_eq!(cache.get_within("c", Duration::ZERO), None);
+        assert_eq!(cache.get_within("c", Duration::from_secs(60)), Some(3));
+
+        cache.configure(1, Some(Duration::ZERO));
+        assert_eq!(cache.get("c"), None);
+    }
+}
@@ -0,0 +1,567 @@
+use std::collections::HashMap;
+
+use crate::archive::DownloadArchive;
+use crate::feeds::channel_rss_with_options;
+use crate::resolve::{resolve_url_with_client, ResolvedUrl};
+use crate::search::{Channel as ChannelInfo, Playlist, SearchResult, Video};
+use crate::structs::{RequestOptions, Thumbnail, VideoError, VideoOptions};
+use crate::utils::{
+    build_client, get_text, innertube_request, is_verified, parse_count, parse_thumbnails,
+    time_to_ms,
+};
+
+/// Count of videos [`Channel::sync_since`] fetches the upload date of at the same time
+const SYNC_DATE_CONCURRENCY: usize = 4;
+
+#[derive(Clone, Debug, PartialEq, Eq, derive_more::Display)]
+pub enum ChannelTabType {
+    #[display(fmt = "Videos")]
+    Videos,
+    #[display(fmt = "Shorts")]
+    Shorts,
+    #[display(fmt = "Live")]
+    Live,
+    #[display(fmt = "Playlists")]
+    Playlists,
+}
+
+impl ChannelTabType {
+    /// InnerTube browse params of the tab
+    fn params(&self) -> &str {
+        match self {
+            ChannelTabType::Videos => "EgZ2aWRlb3PyBgQKAjoA",
+            ChannelTabType::Shorts => "EgZzaG9ydHPyBgUKA5oBAA==",
+            ChannelTabType::Live => "EgdzdHJlYW1z8gYECgJ6AA==",
+            ChannelTabType::Playlists => "EglwbGF5bGlzdHMQAfIGBAoCQgA=",
+        }
+    }
+}
+
+/// Last synced state of a channel for [`Channel::sync_since`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SyncCheckpoint {
+    /// Id of the newest video of the last sync
+    LastId(String),
+    /// Upload date as `YYYY-MM-DD` or `YYYYMMDD`, videos of this day are included
+    Date(String),
+}
+
+#[derive(Clone, derive_more::Display, derivative::Derivative)]
+#[display(fmt = "Channel({id})")]
+#[derivative(Debug, PartialEq, Eq)]
+pub struct Channel {
+    pub id: String,
+    pub name: String,
+    pub url: String,
+    pub description: String,
+    pub avatar: Vec<Thumbnail>,
+    pub verified: bool,
+    pub subscribers: u64,
+
+    #[derivative(PartialEq = "ignore")]
+    client: reqwest_middleware::ClientWithMiddleware,
+}
+
+impl Channel {
+    /// Try to resolve channel from channel URL, handle (`@handle`) or channel id (`UC...`) with default [`RequestOptions`]
+    pub async fn get(url_or_id: impl Into<String>) -> Result<Self, VideoError> {
+        Self::get_with_options(url_or_id, &RequestOptions::default()).await
+    }
+
+    /// Try to resolve channel from channel URL, handle (`@handle`) or channel id (`UC...`) with custom [`RequestOptions`]
+    pub async fn get_with_options(
+        url_or_id: impl Into<String>,
+        request_options: &RequestOptions,
+    ) -> Result<Self, VideoError> {
+        let url_or_id: String = url_or_id.into();
+        let url_or_id = url_or_id.trim();
+
+        let client = build_client(request_options)?;
+
+        let channel_id = if is_channel_id(url_or_id) {
+            url_or_id.to_string()
+        } else {
+            let url = if url_or_id.starts_with('@') {
+                format!("https://www.youtube.com/{url_or_id}")
+            } else {
+                url_or_id.to_string()
+            };
+
+            let channel_id = url::Url::parse(&url).ok().and_then(|x| {
+                let mut segments = x.path_segments()?;
+                match segments.next() {
+                    Some("channel") => segments.next().map(|x| x.to_string()),
+                    _ => None,
+                }
+            });
+
+            match channel_id {
+                Some(channel_id) if is_channel_id(&channel_id) => channel_id,
+                _ => resolve_channel_id(&client, &url).await?,
+            }
+        };
+
+        let response = innertube_request(
+            &client,
+            "browse",
+            serde_json::json!({
+                "browseId": channel_id,
+            }),
+        )
+        .await?;
+
+        let metadata = &response["metadata"]["channelMetadataRenderer"];
+
+        if metadata.is_null() {
+            return Err(VideoError::ChannelNotFound(channel_id));
+        }
+
+        let header = if response["header"]["c4TabbedHeaderRenderer"].is_object() {
+            &response["header"]["c4TabbedHeaderRenderer"]
+        } else {
+            &response["header"]["pageHeaderRenderer"]
+        };
+
+        // Subscriber text can be found in the old header or in the new page header metadata rows
+        let subscribers_text = if header["subscriberCountText"].is_object() {
+            get_text(&header["subscriberCountText"])
+                .as_str()
+                .unwrap_or("")
+                .to_string()
+        } else {
+            header["content"]["pageHeaderViewModel"]["metadata"]["contentMetadataViewModel"]
+                ["metadataRows"]
+                .as_array()
+                .and_then(|rows| {
+                    rows.iter()
+                        .flat_map(|row| {
+                            row["metadataParts"].as_array().cloned().unwrap_or_default()
+                        })
+                        .filter_map(|part| part["text"]["content"].as_str().map(|x| x.to_string()))
+                        .find(|x| x.contains("subscriber"))
+                })
+                .unwrap_or_default()
+        };
+
+        Ok(Self {
+            id: metadata["externalId"]
+                .as_str()
+                .unwrap_or(&channel_id)
+                .to_string(),
+            name: metadata["title"].as_str().unwrap_or("").to_string(),
+            url: metadata["channelUrl"]
+                .as_str()
+                .map(|x| x.to_string())
+                .unwrap_or(format!("https://www.youtube.com/channel/{channel_id}")),
+            description: metadata["description"].as_str().unwrap_or("").to_string(),
+            avatar: parse_thumbnails(&metadata["avatar"]["thumbnails"]),
+            verified: is_verified(&header["badges"]),
+            subscribers: parse_count(&subscribers_text),
+            client,
+        })
+    }
+
+    /// Get "Videos" tab of the channel
+    pub async fn videos(&self) -> Result<ChannelTab, VideoError> {
+        self.tab(ChannelTabType::Videos).await
+    }
+
+    /// Get "Shorts" tab of the channel
+    pub async fn shorts(&self) -> Result<ChannelTab, VideoError> {
+        self.tab(ChannelTabType::Shorts).await
+    }
+
+    /// Get "Live" tab of the channel
+    pub async fn live(&self) -> Result<ChannelTab, VideoError> {
+        self.tab(ChannelTabType::Live).await
+    }
+
+    /// Get "Playlists" tab of the channel
+    pub async fn playlists(&self) -> Result<ChannelTab, VideoError> {
+        self.tab(ChannelTabType::Playlists).await
+    }
+
+    /// Get first page of the given tab. Use [`ChannelTab::next`] to get next pages
+    pub async fn tab(&self, tab: ChannelTabType) -> Result<ChannelTab, VideoError> {
+        let response = innertube_request(
+            &self.client,
+            "browse",
+            serde_json::json!({
+                "browseId": self.id,
+                "params": tab.params(),
+            }),
+        )
+        .await?;
+
+        let empty_tabs = vec![];
+        let selected_tab = response["contents"]["twoColumnBrowseResultsRenderer"]["tabs"]
+            .as_array()
+            .unwrap_or(&empty_tabs)
+            .iter()
+            .find(|x| x["tabRenderer"]["selected"].as_bool().unwrap_or(false))
+            .map(|x| &x["tabRenderer"]["content"])
+            .unwrap_or(&serde_json::Value::Null);
+
+        let contents = if selected_tab["richGridRenderer"]["contents"].is_array() {
+            &selected_tab["richGridRenderer"]["contents"]
+        } else {
+            &selected_tab["sectionListRenderer"]["contents"][0]["itemSectionRenderer"]["contents"]
+                [0]["gridRenderer"]["items"]
+        };
+
+        let mut channel_tab = ChannelTab {
+            channel: self.info(),
+            tab,
+            items: vec![],
+            continuation: None,
+            client: self.client.clone(),
+        };
+
+        let items = channel_tab.parse_items(contents);
+        channel_tab.items = items;
+
+        Ok(channel_tab)
+    }
+
+    /// Get videos of the "Videos" tab newer than the checkpoint, newest first.
+    /// Videos of the `archive` are left out, so a [`SyncCheckpoint::Date`] sync does not return videos of the same day again.
+    /// - [`SyncCheckpoint::Date`] takes upload dates of the latest 15 videos from the channel feed,
+    ///   older videos need one request per video which run a few at a time
+    /// - If the [`SyncCheckpoint::LastId`] video is not found (e.g. deleted), all videos of the tab are returned
+    /// # Example
+    /// ```ignore
+    ///     let channel = Channel::get("@Google").await.unwrap();
+    ///     let mut archive = DownloadArchive::open("archive.txt").unwrap();
+    ///
+    ///     let videos = channel
+    ///         .sync_since(&SyncCheckpoint::Date("2024-01-01".to_string()), Some(&archive))
+    ///         .await
+    ///         .unwrap();
+    ///
+    ///     for video in videos {
+    ///         video.to_video().unwrap().download(format!("{}.mp4", video.id)).await.unwrap();
+    ///         archive.insert(video.id).unwrap();
+    ///     }
+    /// ```
+    pub async fn sync_since(
+        &self,
+        checkpoint: &SyncCheckpoint,
+        archive: Option<&DownloadArchive>,
+    ) -> Result<Vec<Video>, VideoError> {
+        let video_options = VideoOptions {
+            request_options: RequestOptions {
+                client: Some(std::sync::Arc::new(self.client.clone())),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let checkpoint_date = match checkpoint {
+            SyncCheckpoint::Date(date) => Some(date.replace('-', "")),
+            SyncCheckpoint::LastId(_) => None,
+        };
+
+        // Feed has the exact upload time of the latest videos, older ones need their info
+        let mut dates: HashMap<String, String> = HashMap::new();
+        if checkpoint_date.is_some() {
+            if let Ok(entries) =
+                channel_rss_with_options(&self.id, &video_options.request_options).await
+            {
+                dates.extend(entries.into_iter().map(|x| (x.id, x.published)));
+            }
+        }
+
+        let mut tab = self.videos().await?;
+        let mut page = tab.items.clone();
+        let mut videos = vec![];
+
+        loop {
+            let page_videos = page
+                .into_iter()
+                .filter_map(|item| match item {
+                    SearchResult::Video(video) => Some(video),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+
+            // Dates are fetched a few videos at a time, so a sync stops soon after the checkpoint
+            for chunk in page_videos.chunks(SYNC_DATE_CONCURRENCY) {
+                if checkpoint_date.is_some() {
+                    let fetched = futures::future::join_all(
+                        chunk
+                            .iter()
+                            .filter(|video| {
+                                !dates.contains_key(&video.id)
+                                    && !archive.map(|x| x.contains(&video.id)).unwrap_or(false)
+                            })
+                            .map(|video| upload_date(&video.id, &video_options)),
+                    )
+                    .await;
+
+                    for result in fetched {
+                        let (id, date) = result?;
+                        dates.insert(id, date);
+                    }
+                }
+
+                for video in chunk {
+                    if let SyncCheckpoint::LastId(last_id) = checkpoint {
+                        if video.id == *last_id {
+                            return Ok(videos);
+                        }
+                    }
+
+                    // Archived videos are skipped without fetching their date
+                    if archive.map(|x| x.contains(&video.id)).unwrap_or(false) {
+                        continue;
+                    }
+
+                    if let Some(checkpoint_date) = &checkpoint_date {
+                        // Dates are ISO 8601
+                        let date = dates
+                            .get(&video.id)
+                            .and_then(|x| x.get(..10))
+                            .unwrap_or("")
+                            .replace('-', "");
+                        if !date.is_empty() && date < *checkpoint_date {
+                            return Ok(videos);
+                        }
+                    }
+
+                    videos.push(video.clone());
+                }
+            }
+
+            if !tab.has_next() {
+                break;
+            }
+
+            page = tab.next().await?;
+            if page.is_empty() {
+                break;
+            }
+        }
+
+        Ok(videos)
+    }
+
+    /// Lightweight [`crate::search::Channel`] of the channel
+    pub fn info(&self) -> ChannelInfo {
+        ChannelInfo {
+            id: self.id.clone(),
+            name: self.name.clone(),
+            url: self.url.clone(),
+            icon: self.avatar.clone(),
+            verified: self.verified,
+            subscribers: self.subscribers,
+        }
+    }
+}
+
+/// Upload date of the video from its info, for dates [`Channel::sync_since`] does not find in the feed
+async fn upload_date(
+    video_id: &str,
+    video_options: &VideoOptions,
+) -> Result<(String, String), VideoError> {
+    let info = crate::Video::new_with_options(video_id, video_options.clone())?
+        .get_basic_info()
+        .await?;
+    let details = info.video_details;
+
+    let date = if details.upload_date.is_empty() {
+        details.publish_date
+    } else {
+        details.upload_date
+    };
+
+    Ok((video_id.to_string(), date))
+}
+
+#[derive(Clone, derivative::Derivative)]
+#[derivative(Debug, PartialEq, Eq)]
+pub struct ChannelTab {
+    pub channel: ChannelInfo,
+    pub tab: ChannelTabType,
+    /// All fetched items of the tab. [`SearchResult::Video`] for video tabs and [`SearchResult::Playlist`] for playlists tab
+    pub items: Vec<SearchResult>,
+
+    #[derivative(PartialEq = "ignore")]
+    continuation: Option<String>,
+    #[derivative(PartialEq = "ignore")]
+    client: reqwest_middleware::ClientWithMiddleware,
+}
+
+impl ChannelTab {
+    /// Get next page of the tab and return fetched items.
+    /// - If there is no next page this function return empty [`Vec<SearchResult>`]
+    pub async fn next(&mut self) -> Result<Vec<SearchResult>, VideoError> {
+        let continuation = match &self.continuation {
+            Some(continuation) => continuation.clone(),
+            None => return Ok(vec![]),
+        };
+
+        let response = innertube_request(
+            &self.client,
+            "browse",
+            serde_json::json!({
+                "continuation": continuation,
+            }),
+        )
+        .await?;
+
+        let contents = &response["onResponseReceivedActions"][0]["appendContinuationItemsAction"]
+            ["continuationItems"];
+
+        let items = self.parse_items(contents);
+        self.items.extend(items.clone());
+
+        Ok(items)
+    }
+
+    /// Try to fetch items until the limit reached or no next page left.
+    /// - If limit is [`None`] it will be [`u64::MAX`]
+    pub async fn fetch(&mut self, limit: Option<u64>) -> Result<&mut Self, VideoError> {
+        let limit = limit.unwrap_or(u64::MAX);
+
+        while self.continuation.is_some() && (self.items.len() as u64) < limit {
+            if self.next().await?.is_empty() {
+                break;
+            }
+        }
+
+        self.items.truncate(limit.min(usize::MAX as u64) as usize);
+
+        Ok(self)
+    }
+
+    /// There is a next page to fetch or not
+    pub fn has_next(&self) -> bool {
+        self.continuation.is_some()
+    }
+
+    fn parse_items(&mut self, contents: &serde_json::Value) -> Vec<SearchResult> {
+        let mut items = vec![];
+        self.continuation = None;
+
+        let empty_contents = vec![];
+        for item in contents.as_array().unwrap_or(&empty_contents) {
+            if let Some(token) = item["continuationItemRenderer"]["continuationEndpoint"]
+                ["continuationCommand"]["token"]
+                .as_str()
+            {
+                self.continuation = Some(token.to_string());
+                continue;
+            }
+
+            let content = if item["richItemRenderer"]["content"].is_object() {
+                &item["richItemRenderer"]["content"]
+            } else {
+                item
+            };
+
+            if content["videoRenderer"].is_object() {
+                items.push(SearchResult::Video(
+                    self.parse_video(&content["videoRenderer"]),
+                ));
+            } else if content["reelItemRenderer"].is_object() {
+                items.push(SearchResult::Video(
+                    self.parse_short(&content["reelItemRenderer"]),
+                ));
+            } else if content["gridPlaylistRenderer"].is_object() {
+                items.push(SearchResult::Playlist(
+                    self.parse_playlist(&content["gridPlaylistRenderer"]),
+                ));
+            }
+        }
+
+        items
+    }
+
+    fn parse_video(&self, renderer: &serde_json::Value) -> Video {
+        let id = renderer["videoId"].as_str().unwrap_or("").to_string();
+        let duration_raw = get_text(&renderer["lengthText"])
+            .as_str()
+            .unwrap_or("0:00")
+            .to_string();
+
+        Video {
+            url: format!("https://www.youtube.com/watch?v={id}"),
+            id,
+            title: get_text(&renderer["title"])
+                .as_str()
+                .unwrap_or("")
+                .to_string(),
+            description: get_text(&renderer["descriptionSnippet"])
+                .as_str()
+                .unwrap_or("")
+                .to_string(),
+            duration: time_to_ms(&duration_raw) as u64,
+            duration_raw,
+            thumbnails: parse_thumbnails(&renderer["thumbnail"]["thumbnails"]),
+            channel: self.channel.clone(),
+            uploaded_at: get_text(&renderer["publishedTimeText"])
+                .as_str()
+                .map(|x| x.to_string()),
+            views: parse_count(get_text(&renderer["viewCountText"]).as_str().unwrap_or("")),
+        }
+    }
+
+    fn parse_short(&self, renderer: &serde_json::Value) -> Video {
+        let id = renderer["videoId"].as_str().unwrap_or("").to_string();
+
+        Video {
+            url: format!("https://www.youtube.com/shorts/{id}"),
+            id,
+            title: get_text(&renderer["headline"])
+                .as_str()
+                .unwrap_or("")
+                .to_string(),
+            description: String::from(""),
+            duration: 0,
+            duration_raw: String::from("0:00"),
+            thumbnails: parse_thumbnails(&renderer["thumbnail"]["thumbnails"]),
+            channel: self.channel.clone(),
+            uploaded_at: None,
+            views: parse_count(get_text(&renderer["viewCountText"]).as_str().unwrap_or("")),
+        }
+    }
+
+    fn parse_playlist(&self, renderer: &serde_json::Value) -> Playlist {
+        let id = renderer["playlistId"].as_str().unwrap_or("").to_string();
+
+        Playlist {
+            url: format!("https://www.youtube.com/playlist?list={id}"),
+            id,
+            name: get_text(&renderer["title"])
+                .as_str()
+                .unwrap_or("")
+                .to_string(),
+            channel: self.channel.clone(),
+            thumbnails: parse_thumbnails(&renderer["thumbnail"]["thumbnails"]),
+            views: 0,
+            video_count: parse_count(
+                get_text(&renderer["videoCountShortText"])
+                    .as_str()
+                    .unwrap_or(""),
+            ),
+            videos: vec![],
+            last_update: None,
+            // continuation not available in channel tabs
+            continuation: None,
+            client: self.client.clone(),
+        }
+    }
+}
+
+fn is_channel_id(id: &str) -> bool {
+    id.len() == 24 && id.starts_with("UC")
+}
+
+async fn resolve_channel_id(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    url: &str,
+) -> Result<String, VideoError> {
+    match resolve_url_with_client(client, url).await {
+        Ok(ResolvedUrl::Channel { id }) if is_channel_id(&id) => Ok(id),
+        _ => Err(VideoError::ChannelNotFound(url.to_string())),
+    }
+}
@@ -0,0 +1,91 @@
+use std::io::Read;
+use std::path::Path;
+
+use md5::Md5;
+use sha2::{Digest, Sha256};
+
+use crate::structs::{HashAlgo, VideoError};
+
+/// Running hash of downloaded data
+pub(crate) enum Hasher {
+    Sha256(Sha256),
+    Md5(Md5),
+}
+
+impl Hasher {
+    pub(crate) fn new(algo: HashAlgo) -> Self {
+        match algo {
+            HashAlgo::Sha256 => Self::Sha256(Sha256::new()),
+            HashAlgo::Md5 => Self::Md5(Md5::new()),
+        }
+    }
+
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        match self {
+            Self::Sha256(hasher) => hasher.update(data),
+            Self::Md5(hasher) => hasher.update(data),
+        }
+    }
+
+    /// Hash the part of a resumed download which is already in the file
+    pub(crate) fn update_from_file(&mut self, path: &Path) -> Result<(), VideoError> {
+        let mut file =
+            std::fs::File::open(path).map_err(|e| VideoError::DownloadError(e.to_string()))?;
+        let mut buf = vec![0; 64 * 1024];
+
+        loop {
+            let read = file
+                .read(&mut buf)
+                .map_err(|e| VideoError::DownloadError(e.to_string()))?;
+
+            if read == 0 {
+                return Ok(());
+            }
+
+            self.update(&buf[..read]);
+        }
+    }
+
+    /// Lowercase hex digest
+    pub(crate) fn finalize(self) -> String {
+        match self {
+            Self::Sha256(hasher) => hex::encode(hasher.finalize()),
+            Self::Md5(hasher) => hex::encode(hasher.finalize()),
+        }
+    }
+}
+
+/// Write `<path>.sha256` or `<path>.md5` in the format of `sha256sum` and `md5sum`, so it can be checked with `-c`
+pub(crate) fn write_sidecar(path: &Path, algo: HashAlgo, hash: &str) -> Result<(), VideoError> {
+    let file_name = path
+        .file_name()
+        .map(|x| x.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut sidecar = path.as_os_str().to_owned();
+    sidecar.push(".");
+    sidecar.push(algo.extension());
+
+    std::fs::write(sidecar, format!("{hash}  {file_name}\n"))
+        .map_err(|e| VideoError::DownloadError(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hasher() {
+        let mut sha256 = Hasher::new(HashAlgo::Sha256);
+        sha256.update(b"a");
+        sha256.update(b"bc");
+        assert_eq!(
+            sha256.finalize(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+
+        let mut md5 = Hasher::new(HashAlgo::Md5);
+        md5.update(b"abc");
+        assert_eq!(md5.finalize(), "900150983cd24fb0d6963f7d28e17f72");
+    }
+}
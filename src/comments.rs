@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+
+use crate::structs::{Comment, CommentSort, Thumbnail, VideoError};
+use crate::utils::{get_text, innertube_request, parse_count, parse_thumbnails};
+
+#[derive(Clone, derivative::Derivative)]
+#[derivative(Debug, PartialEq, Eq)]
+pub struct Comments {
+    pub video_id: String,
+    pub sort: CommentSort,
+    /// All fetched comments
+    pub comments: Vec<Comment>,
+
+    #[derivative(PartialEq = "ignore")]
+    continuation: Option<String>,
+    #[derivative(PartialEq = "ignore")]
+    client: reqwest_middleware::ClientWithMiddleware,
+}
+
+impl Comments {
+    pub(crate) async fn get(
+        client: &reqwest_middleware::ClientWithMiddleware,
+        video_id: impl Into<String>,
+        sort: CommentSort,
+    ) -> Result<Self, VideoError> {
+        let video_id: String = video_id.into();
+
+        let response = innertube_request(
+            client,
+            "next",
+            serde_json::json!({
+                "videoId": video_id,
+            }),
+        )
+        .await?;
+
+        let empty_serde_array = vec![];
+
+        // Sort menu of the comments panel holds continuation tokens for every sort type
+        let sort_index = match sort {
+            CommentSort::Top => 0,
+            CommentSort::Newest => 1,
+        };
+        let sort_continuation = response["engagementPanels"]
+            .as_array()
+            .unwrap_or(&empty_serde_array)
+            .iter()
+            .map(|x| {
+                &x["engagementPanelSectionListRenderer"]["header"]
+                    ["engagementPanelTitleHeaderRenderer"]["menu"]["sortFilterSubMenuRenderer"]
+                    ["subMenuItems"][sort_index]["serviceEndpoint"]["continuationCommand"]["token"]
+            })
+            .find_map(|x| x.as_str());
+
+        // Fallback to the comment section of the watch page which is always sorted by top comments
+        let continuation = sort_continuation.or_else(|| {
+            response["contents"]["twoColumnWatchNextResults"]["results"]["results"]["contents"]
+                .as_array()
+                .unwrap_or(&empty_serde_array)
+                .iter()
+                .find(|x| {
+                    x["itemSectionRenderer"]["sectionIdentifier"].as_str()
+                        == Some("comment-item-section")
+                })
+                .and_then(|x| {
+                    x["itemSectionRenderer"]["contents"][0]["continuationItemRenderer"]
+                        ["continuationEndpoint"]["continuationCommand"]["token"]
+                        .as_str()
+                })
+        });
+
+        Ok(Self {
+            video_id,
+            sort,
+            comments: vec![],
+            continuation: continuation.map(|x| x.to_string()),
+            client: client.clone(),
+        })
+    }
+
+    /// Get next page of comments and return fetched [`Comment`] array.
+    /// - If there is no next page or comments are disabled this function return empty [`Vec<Comment>`]
+    pub async fn next(&mut self) -> Result<Vec<Comment>, VideoError> {
+        let continuation = match &self.continuation {
+            Some(continuation) => continuation.clone(),
+            None => return Ok(vec![]),
+        };
+
+        let response = innertube_request(
+            &self.client,
+            "next",
+            serde_json::json!({
+                "continuation": continuation,
+            }),
+        )
+        .await?;
+
+        let empty_serde_array = vec![];
+
+        // Newer responses only hold references in `commentViewModel`, data lives in framework updates
+        let payloads = response["frameworkUpdates"]["entityBatchUpdate"]["mutations"]
+            .as_array()
+            .unwrap_or(&empty_serde_array)
+            .iter()
+            .filter_map(|x| {
+                let payload = &x["payload"]["commentEntityPayload"];
+                x["entityKey"]
+                    .as_str()
+                    .filter(|_| payload.is_object())
+                    .map(|key| (key, payload))
+            })
+            .collect::<HashMap<&str, &serde_json::Value>>();
+
+        let mut comments = vec![];
+        self.continuation = None;
+
+        for endpoint in response["onResponseReceivedEndpoints"]
+            .as_array()
+            .unwrap_or(&empty_serde_array)
+        {
+            let items = if endpoint["reloadContinuationItemsCommand"].is_object() {
+                &endpoint["reloadContinuationItemsCommand"]["continuationItems"]
+            } else {
+                &endpoint["appendContinuationItemsAction"]["continuationItems"]
+            };
+
+            for item in items.as_array().unwrap_or(&empty_serde_array) {
+                if let Some(token) = item["continuationItemRenderer"]["continuationEndpoint"]
+                    ["continuationCommand"]["token"]
+                    .as_str()
+                {
+                    self.continuation = Some(token.to_string());
+                    continue;
+                }
+
+                let thread = &item["commentThreadRenderer"];
+
+                let comment = if thread["comment"]["commentRenderer"].is_object() {
+                    Some(parse_comment_renderer(
+                        &thread["comment"]["commentRenderer"],
+                    ))
+                } else {
+                    thread["commentViewModel"]["commentViewModel"]["commentKey"]
+                        .as_str()
+                        .and_then(|key| payloads.get(key))
+                        .map(|payload| parse_comment_payload(payload))
+                };
+
+                if let Some(comment) = comment {
+                    comments.push(comment);
+                }
+            }
+        }
+
+        self.comments.extend(comments.clone());
+
+        Ok(comments)
+    }
+
+    /// Try to fetch comments until the limit reached or no next page left.
+    /// - If limit is [`None`] it will be [`u64::MAX`]
+    pub async fn fetch(&mut self, limit: Option<u64>) -> Result<&mut Self, VideoError> {
+        let limit = limit.unwrap_or(u64::MAX);
+
+        while self.continuation.is_some() && (self.comments.len() as u64) < limit {
+            if self.next().await?.is_empty() {
+                break;
+            }
+        }
+
+        self.comments
+            .truncate(limit.min(usize::MAX as u64) as usize);
+
+        Ok(self)
+    }
+
+    /// There is a next page to fetch or not
+    pub fn has_next(&self) -> bool {
+        self.continuation.is_some()
+    }
+}
+
+fn parse_comment_renderer(renderer: &serde_json::Value) -> Comment {
+    let text = renderer["contentText"]["runs"]
+        .as_array()
+        .map(|x| {
+            x.iter()
+                .filter_map(|x| x["text"].as_str())
+                .collect::<String>()
+        })
+        .unwrap_or_default();
+
+    Comment {
+        id: renderer["commentId"].as_str().unwrap_or("").to_string(),
+        author: get_text(&renderer["authorText"])
+            .as_str()
+            .unwrap_or("")
+            .to_string(),
+        author_channel_id: renderer["authorEndpoint"]["browseEndpoint"]["browseId"]
+            .as_str()
+            .unwrap_or("")
+            .to_string(),
+        author_thumbnails: parse_thumbnails(&renderer["authorThumbnail"]["thumbnails"]),
+        author_is_owner: renderer["authorIsChannelOwner"].as_bool().unwrap_or(false),
+        text,
+        likes: parse_count(get_text(&renderer["voteCount"]).as_str().unwrap_or("")),
+        reply_count: renderer["replyCount"].as_u64().unwrap_or(0),
+        published_time: get_text(&renderer["publishedTimeText"])
+            .as_str()
+            .unwrap_or("")
+            .to_string(),
+    }
+}
+
+fn parse_comment_payload(payload: &serde_json::Value) -> Comment {
+    let properties = &payload["properties"];
+    let author = &payload["author"];
+    let toolbar = &payload["toolbar"];
+
+    Comment {
+        id: properties["commentId"].as_str().unwrap_or("").to_string(),
+        author: author["displayName"].as_str().unwrap_or("").to_string(),
+        author_channel_id: author["channelId"].as_str().unwrap_or("").to_string(),
+        author_thumbnails: author["avatarThumbnailUrl"]
+            .as_str()
+            .map(|x| {
+                vec![Thumbnail {
+                    width: 0,
+                    height: 0,
+                    url: x.to_string(),
+                }]
+            })
+            .unwrap_or_default(),
+        author_is_owner: author["isCreator"].as_bool().unwrap_or(false),
+        text: properties["content"]["content"]
+            .as_str()
+            .unwrap_or("")
+            .to_string(),
+        likes: parse_count(toolbar["likeCountNotliked"].as_str().unwrap_or("")),
+        reply_count: parse_count(toolbar["replyCount"].as_str().unwrap_or("")),
+        published_time: properties["publishedTime"]
+            .as_str()
+            .unwrap_or("")
+            .to_string(),
+    }
+}
@@ -0,0 +1,91 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use reqwest::header::{HeaderValue, COOKIE};
+use reqwest::{Request, Response};
+use reqwest_middleware::{Middleware, Next};
+use task_local_extensions::Extensions;
+
+use crate::platform::Jar;
+
+/// Cookies of the rejected consent form, enough to skip the interstitial
+const CONSENT_COOKIES: &[&str] = &["SOCS=CAI", "CONSENT=YES+"];
+
+/// Requests from EU addresses are redirected to `consent.youtube.com` until consent cookies are set.
+/// Set the cookies and send the request again
+pub(crate) struct ConsentMiddleware {
+    /// Cookie jar of the client, cookie headers of requests would replace its cookies
+    jar: Option<Arc<Jar>>,
+    /// Consent page was seen, cookies are sent with every request of a client without jar
+    consented: AtomicBool,
+}
+
+impl ConsentMiddleware {
+    pub fn new(jar: Option<Arc<Jar>>) -> Self {
+        Self {
+            jar,
+            consented: AtomicBool::new(false),
+        }
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl Middleware for ConsentMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let mut req = req;
+
+        if self.jar.is_none() && self.consented.load(Ordering::Relaxed) {
+            add_consent_header(&mut req);
+        }
+
+        let retry_req = req.try_clone();
+
+        let response = next.clone().run(req, extensions).await?;
+
+        let is_consent_page = response
+            .url()
+            .host_str()
+            .map(|x| x.starts_with("consent."))
+            .unwrap_or(false);
+
+        let mut retry_req = match retry_req {
+            Some(retry_req) if is_consent_page => retry_req,
+            _ => return Ok(response),
+        };
+
+        self.consented.store(true, Ordering::Relaxed);
+
+        match &self.jar {
+            Some(jar) => {
+                for domain in ["youtube.com", "google.com"] {
+                    let url = format!("https://www.{domain}").parse::<url::Url>().unwrap();
+
+                    for cookie in CONSENT_COOKIES {
+                        jar.add_cookie_str(&format!("{cookie}; Domain={domain}; Path=/"), &url);
+                    }
+                }
+            }
+            None => add_consent_header(&mut retry_req),
+        }
+
+        next.run(retry_req, extensions).await
+    }
+}
+
+fn add_consent_header(req: &mut Request) {
+    let cookies = match req.headers().get(COOKIE).and_then(|x| x.to_str().ok()) {
+        Some(cookies) if cookies.contains("SOCS=") => return,
+        Some(cookies) => format!("{cookies}; {}", CONSENT_COOKIES.join("; ")),
+        None => CONSENT_COOKIES.join("; "),
+    };
+
+    if let Ok(cookies) = HeaderValue::from_str(&cookies) {
+        req.headers_mut().insert(COOKIE, cookies);
+    }
+}
@@ -0,0 +1,304 @@
+mod mp4;
+mod ogg;
+mod webm;
+
+use crate::structs::VideoError;
+
+pub(crate) use mp4::fragmented_mp4_to_m4a;
+
+use ogg::OggWriter;
+
+/// Opus always runs at 48kHz regardless of the input sample rate
+const OPUS_SAMPLE_RATE: u64 = 48_000;
+/// Serial number of the single logical stream of the written files
+const OGG_SERIAL: u32 = 0x7974_646C;
+
+/// Extract the Opus track of a WebM file into an Ogg Opus file
+pub(crate) fn webm_to_ogg_opus(data: &[u8]) -> Result<Vec<u8>, VideoError> {
+    let audio = webm::demux_audio(data)?;
+
+    if audio.codec_id != "A_OPUS" {
+        return Err(VideoError::ContainerError(format!(
+            "Expected Opus audio, found {}",
+            audio.codec_id
+        )));
+    }
+
+    // WebM stores the full identification header, build it only for broken files
+    let opus_head = if audio.codec_private.starts_with(b"OpusHead") {
+        audio.codec_private.to_vec()
+    } else {
+        let pre_skip = audio.codec_delay_ns * OPUS_SAMPLE_RATE / 1_000_000_000;
+
+        [
+            b"OpusHead".as_slice(),
+            &[1, audio.channels],
+            &(pre_skip as u16).to_le_bytes(),
+            &(OPUS_SAMPLE_RATE as u32).to_le_bytes(),
+            // Output gain and channel mapping family
+            &[0, 0, 0],
+        ]
+        .concat()
+    };
+
+    let pre_skip = opus_head
+        .get(10..12)
+        .map(|x| u16::from_le_bytes([x[0], x[1]]) as u64)
+        .unwrap_or(0);
+
+    let vendor = concat!("rusty_ytdl ", env!("CARGO_PKG_VERSION"));
+    let opus_tags = [
+        b"OpusTags".as_slice(),
+        &(vendor.len() as u32).to_le_bytes(),
+        vendor.as_bytes(),
+        // No user comments
+        &0_u32.to_le_bytes(),
+    ]
+    .concat();
+
+    let mut writer = OggWriter::new(OGG_SERIAL);
+
+    writer.write_packet(&opus_head, 0);
+    writer.flush();
+    writer.write_packet(&opus_tags, 0);
+    writer.flush();
+
+    let end_trim = (audio.discard_padding_ns.max(0) as u64) * OPUS_SAMPLE_RATE / 1_000_000_000;
+    let mut granule = pre_skip;
+
+    for (index, frame) in audio.frames.iter().enumerate() {
+        granule += opus_packet_samples(frame)?;
+
+        // Granule of the last page marks where the decoded audio ends
+        if index == audio.frames.len() - 1 {
+            granule = granule.saturating_sub(end_trim).max(pre_skip);
+        }
+
+        writer.write_packet(frame, granule);
+    }
+
+    Ok(writer.finish())
+}
+
+/// Count of 48kHz samples in an Opus packet, read from its TOC byte (RFC 6716 section 3.1)
+fn opus_packet_samples(packet: &[u8]) -> Result<u64, VideoError> {
+    let toc = *packet
+        .first()
+        .ok_or(VideoError::ContainerError("Empty Opus packet".to_string()))?;
+
+    let config = toc >> 3;
+    let frame_samples: u64 = match config {
+        // SILK
+        0..=11 => [480, 960, 1920, 2880][(config % 4) as usize],
+        // Hybrid
+        12..=15 => [480, 960][(config % 2) as usize],
+        // CELT
+        _ => [120, 240, 480, 960][(config % 4) as usize],
+    };
+
+    let frame_count: u64 =
+        match toc & 0b11 {
+            0 => 1,
+            1 | 2 => 2,
+            _ => packet.get(1).map(|x| (x & 0b0011_1111) as u64).ok_or(
+                VideoError::ContainerError("Opus packet without frame count".to_string()),
+            )?,
+        };
+
+    Ok(frame_samples * frame_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build an EBML element with a one byte size
+    fn element(id: &[u8], body: &[u8]) -> Vec<u8> {
+        [id, &[0x80 | body.len() as u8], body].concat()
+    }
+
+    #[test]
+    fn test_webm_to_ogg_opus() {
+        let track = element(
+            &[0xAE],
+            &[
+                element(&[0xD7], &[1]),
+                element(&[0x83], &[2]),
+                element(&[0x86], b"A_OPUS"),
+                element(&[0xE1], &element(&[0x9F], &[2])),
+            ]
+            .concat(),
+        );
+        // 20ms CELT frames, one simple block and one block group with discard padding of 10ms
+        let frame = [0xFC_u8, 0xAA, 0xBB];
+        let cluster = [
+            element(&[0xE7], &[0]),
+            element(&[0xA3], &[&[0x81, 0, 0, 0x80][..], &frame].concat()),
+            element(
+                &[0xA0],
+                &[
+                    element(&[0xA1], &[&[0x81, 0, 20, 0][..], &frame].concat()),
+                    element(&[0x75, 0xA2], &10_000_000_u32.to_be_bytes()),
+                ]
+                .concat(),
+            ),
+        ]
+        .concat();
+
+        // Segment and cluster with unknown sizes
+        let webm = [
+            element(&[0x1A, 0x45, 0xDF, 0xA3], &[]),
+            vec![0x18, 0x53, 0x80, 0x67, 0xFF],
+            element(&[0x16, 0x54, 0xAE, 0x6B], &track),
+            vec![0x1F, 0x43, 0xB6, 0x75, 0xFF],
+            cluster,
+        ]
+        .concat();
+
+        let ogg = webm_to_ogg_opus(&webm).unwrap();
+
+        // Identification, comment and audio pages
+        let pages = ogg.windows(4).filter(|x| *x == b"OggS").count();
+        assert_eq!(pages, 3);
+        assert_eq!(&ogg[28..36], b"OpusHead");
+
+        // Last page: 2 frames of 960 samples minus 480 samples of padding
+        let last_page = ogg.windows(4).rposition(|x| x == b"OggS").unwrap();
+        let granule = u64::from_le_bytes(ogg[last_page + 6..last_page + 14].try_into().unwrap());
+        assert_eq!(ogg[last_page + 5], 0x04);
+        assert_eq!(granule, 960 + 960 - 480);
+    }
+
+    #[test]
+    fn test_fragmented_mp4_to_m4a() {
+        fn mp4_box(kind: &[u8], body: &[u8]) -> Vec<u8> {
+            [&((body.len() + 8) as u32).to_be_bytes()[..], kind, body].concat()
+        }
+
+        let version_flags = [0_u8; 4];
+        let tkhd = mp4_box(
+            b"tkhd",
+            &[&version_flags[..], &[0; 8], &1_u32.to_be_bytes(), &[0; 68]].concat(),
+        );
+        let mdhd = mp4_box(
+            b"mdhd",
+            &[
+                &version_flags[..],
+                &[0; 8],
+                &44_100_u32.to_be_bytes(),
+                &[0; 8],
+            ]
+            .concat(),
+        );
+        let minf = mp4_box(
+            b"minf",
+            &[
+                mp4_box(b"smhd", &[0; 8]),
+                mp4_box(b"dinf", &[]),
+                mp4_box(b"stbl", &mp4_box(b"stsd", &[0; 8])),
+            ]
+            .concat(),
+        );
+        let trak = mp4_box(
+            b"trak",
+            &[
+                tkhd,
+                mp4_box(b"mdia", &[mdhd, mp4_box(b"hdlr", b"soun"), minf].concat()),
+            ]
+            .concat(),
+        );
+        // Default sample duration 1024 and size 2
+        let trex = mp4_box(
+            b"trex",
+            &[
+                &version_flags[..],
+                &1_u32.to_be_bytes(),
+                &1_u32.to_be_bytes(),
+                &1024_u32.to_be_bytes(),
+                &2_u32.to_be_bytes(),
+                &[0; 4],
+            ]
+            .concat(),
+        );
+        let moov = mp4_box(b"moov", &[trak, mp4_box(b"mvex", &trex)].concat());
+
+        // Fragments with 3 default samples, data offset points into the following mdat
+        let fragment = |data: &[u8]| {
+            let tfhd = mp4_box(
+                b"tfhd",
+                &[&[0, 0x02, 0, 0][..], &1_u32.to_be_bytes()].concat(),
+            );
+            let trun_size = 8 + 12;
+            let traf_size = 8 + tfhd.len() + trun_size;
+            let moof_size = 8 + traf_size;
+            let trun = mp4_box(
+                b"trun",
+                &[
+                    &[0, 0, 0, 0x01][..],
+                    &3_u32.to_be_bytes(),
+                    &((moof_size + 8) as u32).to_be_bytes(),
+                ]
+                .concat(),
+            );
+            let moof = mp4_box(b"moof", &mp4_box(b"traf", &[tfhd, trun].concat()));
+
+            [moof, mp4_box(b"mdat", data)].concat()
+        };
+
+        let fmp4 = [
+            mp4_box(b"ftyp", b"dash"),
+            moov,
+            fragment(b"aabbcc"),
+            fragment(b"ddeeff"),
+        ]
+        .concat();
+
+        let m4a = fragmented_mp4_to_m4a(&fmp4).unwrap();
+
+        assert_eq!(&m4a[4..12], b"ftypM4A ");
+        assert!(m4a.ends_with(b"mdataabbccddeeff"));
+
+        // Single entry of 6 samples with duration 1024
+        let stts = m4a.windows(4).position(|x| x == b"stts").unwrap();
+        assert_eq!(
+            &m4a[stts + 8..stts + 20],
+            &[0, 0, 0, 1, 0, 0, 0, 6, 0, 0, 4, 0]
+        );
+
+        // Chunk offset points to the media data
+        let stco = m4a.windows(4).position(|x| x == b"stco").unwrap();
+        let offset = u32::from_be_bytes(m4a[stco + 12..stco + 16].try_into().unwrap());
+        assert_eq!(&m4a[offset as usize..offset as usize + 2], b"aa");
+
+        // Files without fragments are kept
+        let mp4 = [mp4_box(b"ftyp", b"M4A "), mp4_box(b"mdat", b"aa")].concat();
+        assert_eq!(fragmented_mp4_to_m4a(&mp4).unwrap(), mp4);
+    }
+
+    #[test]
+    fn test_opus_packet_samples() {
+        // SILK 60ms, single frame
+        assert_eq!(opus_packet_samples(&[0b0001_1000]).unwrap(), 2880);
+        // CELT 2.5ms, two frames
+        assert_eq!(opus_packet_samples(&[0b1000_0001]).unwrap(), 240);
+        // CELT 20ms, arbitrary frame count
+        assert_eq!(opus_packet_samples(&[0b1111_1011, 3]).unwrap(), 2880);
+    }
+
+    #[test]
+    fn test_mp4_huge_box_size() {
+        // 64-bit `largesize` far beyond the data
+        let mp4 = [
+            &1_u32.to_be_bytes()[..],
+            b"ftyp",
+            &u64::MAX.to_be_bytes(),
+            &[0; 8],
+        ]
+        .concat();
+
+        assert!(matches!(
+            fragmented_mp4_to_m4a(&mp4),
+            Err(VideoError::ContainerError(_))
+        ));
+    }
+}
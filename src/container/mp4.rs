@@ -0,0 +1,518 @@
+use crate::structs::VideoError;
+
+const TFHD_BASE_DATA_OFFSET: u32 = 0x01;
+const TFHD_SAMPLE_DESCRIPTION_INDEX: u32 = 0x02;
+const TFHD_DEFAULT_SAMPLE_DURATION: u32 = 0x08;
+const TFHD_DEFAULT_SAMPLE_SIZE: u32 = 0x10;
+const TFHD_DEFAULT_SAMPLE_FLAGS: u32 = 0x20;
+
+const TRUN_DATA_OFFSET: u32 = 0x01;
+const TRUN_FIRST_SAMPLE_FLAGS: u32 = 0x04;
+const TRUN_SAMPLE_DURATION: u32 = 0x100;
+const TRUN_SAMPLE_SIZE: u32 = 0x200;
+const TRUN_SAMPLE_FLAGS: u32 = 0x400;
+const TRUN_SAMPLE_COMPOSITION_TIME_OFFSET: u32 = 0x800;
+
+/// Identity transformation of `mvhd` and `tkhd`
+const UNITY_MATRIX: [u32; 9] = [0x0001_0000, 0, 0, 0, 0x0001_0000, 0, 0, 0, 0x4000_0000];
+
+/// Packed ISO 639-2 code of undetermined language
+const LANGUAGE_UNDETERMINED: u16 = 0x55C4;
+
+struct Mp4Box<'a> {
+    kind: [u8; 4],
+    /// Offset of the box header in the file
+    start: usize,
+    /// Whole box including the header
+    raw: &'a [u8],
+    body: &'a [u8],
+}
+
+struct Sample<'a> {
+    duration: u32,
+    data: &'a [u8],
+}
+
+/// Rewrite the fragmented MP4 (DASH) audio formats into a regular `.m4a` file
+/// with a complete sample table and the `moov` box before the media data.
+/// Files without fragments are returned as is
+pub(crate) fn fragmented_mp4_to_m4a(data: &[u8]) -> Result<Vec<u8>, VideoError> {
+    let top = parse_boxes(data, 0)?;
+
+    if !top.iter().any(|x| &x.kind == b"moof") {
+        return Ok(data.to_vec());
+    }
+
+    let moov = find_box(&top, b"moov")?;
+    let moov_children = parse_boxes(moov.body, body_offset(moov))?;
+
+    let trak = find_box(&moov_children, b"trak")?;
+    let trak_children = parse_boxes(trak.body, body_offset(trak))?;
+    let track_id = {
+        let tkhd = find_box(&trak_children, b"tkhd")?;
+        let mut cursor = Cursor::new(tkhd.body);
+        let version = cursor.u8()?;
+        cursor.skip(3 + if version == 1 { 16 } else { 8 })?;
+        cursor.u32()?
+    };
+
+    let mdia = find_box(&trak_children, b"mdia")?;
+    let mdia_children = parse_boxes(mdia.body, body_offset(mdia))?;
+    let timescale = {
+        let mdhd = find_box(&mdia_children, b"mdhd")?;
+        let mut cursor = Cursor::new(mdhd.body);
+        let version = cursor.u8()?;
+        cursor.skip(3 + if version == 1 { 16 } else { 8 })?;
+        cursor.u32()?
+    };
+    let hdlr = find_box(&mdia_children, b"hdlr")?;
+
+    let minf = find_box(&mdia_children, b"minf")?;
+    let minf_children = parse_boxes(minf.body, body_offset(minf))?;
+    let smhd = find_box(&minf_children, b"smhd")?;
+    let dinf = find_box(&minf_children, b"dinf")?;
+
+    let stbl = find_box(&minf_children, b"stbl")?;
+    let stbl_children = parse_boxes(stbl.body, body_offset(stbl))?;
+    let stsd = find_box(&stbl_children, b"stsd")?;
+
+    // Track defaults, overridden by the fragment headers
+    let (default_duration, default_size) = match moov_children
+        .iter()
+        .find(|x| &x.kind == b"mvex")
+        .map(|mvex| parse_boxes(mvex.body, body_offset(mvex)))
+        .transpose()?
+        .and_then(|mvex| {
+            mvex.into_iter()
+                .find(|x| &x.kind == b"trex" && x.body.get(4..8) == Some(&track_id.to_be_bytes()))
+        }) {
+        Some(trex) => {
+            let mut cursor = Cursor::new(trex.body);
+            cursor.skip(12)?;
+            (cursor.u32()?, cursor.u32()?)
+        }
+        None => (0, 0),
+    };
+
+    let mut samples = vec![];
+
+    for moof in top.iter().filter(|x| &x.kind == b"moof") {
+        for traf in parse_boxes(moof.body, body_offset(moof))?
+            .iter()
+            .filter(|x| &x.kind == b"traf")
+        {
+            samples.extend(parse_traf(
+                data,
+                moof,
+                traf,
+                track_id,
+                default_duration,
+                default_size,
+            )?);
+        }
+    }
+
+    let duration: u64 = samples.iter().map(|x| x.duration as u64).sum();
+    let media_size: u64 = samples.iter().map(|x| x.data.len() as u64).sum();
+
+    let ftyp = mp4_box(
+        b"ftyp",
+        &[
+            b"M4A ".as_slice(),
+            &0x200_u32.to_be_bytes(),
+            b"isomiso2M4A mp42",
+        ]
+        .concat(),
+    );
+
+    let mdat_header_size = if media_size + 8 > u32::MAX as u64 {
+        16
+    } else {
+        8
+    };
+
+    let build_moov = |chunk_offset: u64| {
+        let stbl = mp4_box(
+            b"stbl",
+            &[
+                stsd.raw.to_vec(),
+                stts(&samples),
+                // All samples are in a single chunk
+                full_box(b"stsc", 0, &[1_u32, 1, samples.len() as u32, 1]),
+                full_box(
+                    b"stsz",
+                    0,
+                    &[0_u32, samples.len() as u32]
+                        .into_iter()
+                        .chain(samples.iter().map(|x| x.data.len() as u32))
+                        .collect::<Vec<_>>(),
+                ),
+                if chunk_offset > u32::MAX as u64 {
+                    mp4_box(
+                        b"co64",
+                        &[
+                            &[0_u8; 4][..],
+                            &1_u32.to_be_bytes(),
+                            &chunk_offset.to_be_bytes(),
+                        ]
+                        .concat(),
+                    )
+                } else {
+                    full_box(b"stco", 0, &[1, chunk_offset as u32])
+                },
+            ]
+            .concat(),
+        );
+
+        let minf = mp4_box(
+            b"minf",
+            &[smhd.raw.to_vec(), dinf.raw.to_vec(), stbl].concat(),
+        );
+        let mdia = mp4_box(
+            b"mdia",
+            &[mdhd(timescale, duration), hdlr.raw.to_vec(), minf].concat(),
+        );
+        let trak = mp4_box(b"trak", &[tkhd(track_id, duration), mdia].concat());
+
+        mp4_box(
+            b"moov",
+            &[mvhd(timescale, duration, track_id), trak].concat(),
+        )
+    };
+
+    // Size of the offset box depends on the offset itself
+    let mut chunk_offset = (ftyp.len() + build_moov(0).len() + mdat_header_size) as u64;
+    if chunk_offset > u32::MAX as u64 {
+        chunk_offset = (ftyp.len() + build_moov(chunk_offset).len() + mdat_header_size) as u64;
+    }
+
+    let moov = build_moov(chunk_offset);
+
+    let mut output = Vec::with_capacity(chunk_offset as usize + media_size as usize);
+    output.extend_from_slice(&ftyp);
+    output.extend_from_slice(&moov);
+
+    if mdat_header_size == 16 {
+        output.extend_from_slice(&1_u32.to_be_bytes());
+        output.extend_from_slice(b"mdat");
+        output.extend_from_slice(&(media_size + 16).to_be_bytes());
+    } else {
+        output.extend_from_slice(&((media_size + 8) as u32).to_be_bytes());
+        output.extend_from_slice(b"mdat");
+    }
+
+    for sample in samples {
+        output.extend_from_slice(sample.data);
+    }
+
+    Ok(output)
+}
+
+/// Read the samples of a track fragment from the file
+fn parse_traf<'a>(
+    data: &'a [u8],
+    moof: &Mp4Box,
+    traf: &Mp4Box,
+    track_id: u32,
+    default_duration: u32,
+    default_size: u32,
+) -> Result<Vec<Sample<'a>>, VideoError> {
+    let children = parse_boxes(traf.body, body_offset(traf))?;
+
+    let tfhd = find_box(&children, b"tfhd")?;
+    let mut cursor = Cursor::new(tfhd.body);
+    let flags = cursor.u32()? & 0x00FF_FFFF;
+
+    if cursor.u32()? != track_id {
+        return Ok(vec![]);
+    }
+
+    // Offsets are relative to the moof box unless an explicit base is given
+    let base = if flags & TFHD_BASE_DATA_OFFSET != 0 {
+        cursor.u64()? as usize
+    } else {
+        moof.start
+    };
+    if flags & TFHD_SAMPLE_DESCRIPTION_INDEX != 0 {
+        cursor.skip(4)?;
+    }
+    let default_duration = if flags & TFHD_DEFAULT_SAMPLE_DURATION != 0 {
+        cursor.u32()?
+    } else {
+        default_duration
+    };
+    let default_size = if flags & TFHD_DEFAULT_SAMPLE_SIZE != 0 {
+        cursor.u32()?
+    } else {
+        default_size
+    };
+    if flags & TFHD_DEFAULT_SAMPLE_FLAGS != 0 {
+        cursor.skip(4)?;
+    }
+
+    let mut samples = vec![];
+    let mut position = base;
+
+    for trun in children.iter().filter(|x| &x.kind == b"trun") {
+        let mut cursor = Cursor::new(trun.body);
+        let flags = cursor.u32()? & 0x00FF_FFFF;
+        let sample_count = cursor.u32()?;
+
+        if flags & TRUN_DATA_OFFSET != 0 {
+            position = base
+                .checked_add_signed(cursor.u32()? as i32 as isize)
+                .ok_or(VideoError::ContainerError(
+                    "Invalid sample data offset".to_string(),
+                ))?;
+        }
+        if flags & TRUN_FIRST_SAMPLE_FLAGS != 0 {
+            cursor.skip(4)?;
+        }
+
+        for _ in 0..sample_count {
+            let duration = if flags & TRUN_SAMPLE_DURATION != 0 {
+                cursor.u32()?
+            } else {
+                default_duration
+            };
+            let size = if flags & TRUN_SAMPLE_SIZE != 0 {
+                cursor.u32()?
+            } else {
+                default_size
+            } as usize;
+            if flags & TRUN_SAMPLE_FLAGS != 0 {
+                cursor.skip(4)?;
+            }
+            if flags & TRUN_SAMPLE_COMPOSITION_TIME_OFFSET != 0 {
+                cursor.skip(4)?;
+            }
+
+            let sample = position
+                .checked_add(size)
+                .and_then(|end| data.get(position..end))
+                .ok_or(VideoError::ContainerError(
+                    "Sample is outside of the file".to_string(),
+                ))?;
+
+            samples.push(Sample {
+                duration,
+                data: sample,
+            });
+            position += size;
+        }
+    }
+
+    Ok(samples)
+}
+
+fn parse_boxes(data: &[u8], offset: usize) -> Result<Vec<Mp4Box<'_>>, VideoError> {
+    let mut boxes = vec![];
+    let mut cursor = Cursor::new(data);
+
+    while cursor.remaining() >= 8 {
+        let start = cursor.position;
+        let size = cursor.u32()? as usize;
+        let kind: [u8; 4] = cursor.bytes(4)?.try_into().unwrap_or_default();
+
+        let invalid_size = || {
+            VideoError::ContainerError(format!(
+                "Invalid size of {} box",
+                String::from_utf8_lossy(&kind)
+            ))
+        };
+
+        let size = match size {
+            // Box extends to the end of the file
+            0 => data.len() - start,
+            // 64-bit size may not fit in `usize` of 32-bit targets
+            1 => usize::try_from(cursor.u64()?).map_err(|_| invalid_size())?,
+            _ => size,
+        };
+
+        let header_size = cursor.position - start;
+        let end = start
+            .checked_add(size)
+            .filter(|end| size >= header_size && *end <= data.len())
+            .ok_or_else(invalid_size)?;
+
+        boxes.push(Mp4Box {
+            kind,
+            start: offset + start,
+            raw: &data[start..end],
+            body: &data[start + header_size..end],
+        });
+        cursor.position = end;
+    }
+
+    Ok(boxes)
+}
+
+fn body_offset(mp4_box: &Mp4Box) -> usize {
+    mp4_box.start + mp4_box.raw.len() - mp4_box.body.len()
+}
+
+fn find_box<'a, 'b>(boxes: &'b [Mp4Box<'a>], kind: &[u8; 4]) -> Result<&'b Mp4Box<'a>, VideoError> {
+    boxes
+        .iter()
+        .find(|x| &x.kind == kind)
+        .ok_or(VideoError::ContainerError(format!(
+            "{} box not found",
+            String::from_utf8_lossy(kind)
+        )))
+}
+
+fn mp4_box(kind: &[u8; 4], body: &[u8]) -> Vec<u8> {
+    [
+        &((body.len() + 8) as u32).to_be_bytes(),
+        kind.as_slice(),
+        body,
+    ]
+    .concat()
+}
+
+/// Version 0 full box made of 32-bit fields
+fn full_box(kind: &[u8; 4], flags: u32, fields: &[u32]) -> Vec<u8> {
+    let body = std::iter::once(flags & 0x00FF_FFFF)
+        .chain(fields.iter().copied())
+        .flat_map(|x| x.to_be_bytes())
+        .collect::<Vec<_>>();
+
+    mp4_box(kind, &body)
+}
+
+/// Run-length encoded sample durations
+fn stts(samples: &[Sample]) -> Vec<u8> {
+    let mut entries: Vec<(u32, u32)> = vec![];
+
+    for sample in samples {
+        match entries.last_mut() {
+            Some((count, duration)) if *duration == sample.duration => *count += 1,
+            _ => entries.push((1, sample.duration)),
+        }
+    }
+
+    let fields = std::iter::once(entries.len() as u32)
+        .chain(
+            entries
+                .into_iter()
+                .flat_map(|(count, duration)| [count, duration]),
+        )
+        .collect::<Vec<_>>();
+
+    full_box(b"stts", 0, &fields)
+}
+
+fn mvhd(timescale: u32, duration: u64, track_id: u32) -> Vec<u8> {
+    let mut body = time_fields(timescale, duration);
+    // Rate 1.0, volume 1.0 and reserved bytes
+    body.extend_from_slice(&0x0001_0000_u32.to_be_bytes());
+    body.extend_from_slice(&0x0100_u16.to_be_bytes());
+    body.extend_from_slice(&[0; 10]);
+    body.extend(UNITY_MATRIX.iter().flat_map(|x| x.to_be_bytes()));
+    // Pre-defined
+    body.extend_from_slice(&[0; 24]);
+    body.extend_from_slice(&(track_id + 1).to_be_bytes());
+
+    mp4_box(b"mvhd", &body)
+}
+
+fn tkhd(track_id: u32, duration: u64) -> Vec<u8> {
+    let long = duration > u32::MAX as u64;
+
+    // Track is enabled and used in the presentation
+    let mut body = vec![u8::from(long), 0, 0, 0x03];
+    body.extend_from_slice(&[0; 16][..if long { 16 } else { 8 }]);
+    body.extend_from_slice(&track_id.to_be_bytes());
+    body.extend_from_slice(&[0; 4]);
+    if long {
+        body.extend_from_slice(&duration.to_be_bytes());
+    } else {
+        body.extend_from_slice(&(duration as u32).to_be_bytes());
+    }
+    // Reserved, layer and alternate group
+    body.extend_from_slice(&[0; 12]);
+    body.extend_from_slice(&0x0100_u16.to_be_bytes());
+    body.extend_from_slice(&[0; 2]);
+    body.extend(UNITY_MATRIX.iter().flat_map(|x| x.to_be_bytes()));
+    // Audio tracks have no width and height
+    body.extend_from_slice(&[0; 8]);
+
+    mp4_box(b"tkhd", &body)
+}
+
+fn mdhd(timescale: u32, duration: u64) -> Vec<u8> {
+    let mut body = time_fields(timescale, duration);
+    body.extend_from_slice(&LANGUAGE_UNDETERMINED.to_be_bytes());
+    body.extend_from_slice(&[0; 2]);
+
+    mp4_box(b"mdhd", &body)
+}
+
+/// Version, flags, creation and modification times, timescale and duration of `mvhd` and `mdhd`
+fn time_fields(timescale: u32, duration: u64) -> Vec<u8> {
+    if duration > u32::MAX as u64 {
+        [
+            &[1, 0, 0, 0][..],
+            &[0; 16],
+            &timescale.to_be_bytes(),
+            &duration.to_be_bytes(),
+        ]
+        .concat()
+    } else {
+        [
+            &[0, 0, 0, 0][..],
+            &[0; 8],
+            &timescale.to_be_bytes(),
+            &(duration as u32).to_be_bytes(),
+        ]
+        .concat()
+    }
+}
+
+struct Cursor<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len().saturating_sub(self.position)
+    }
+
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], VideoError> {
+        if count > self.remaining() {
+            return Err(VideoError::ContainerError(
+                "Unexpected end of MP4 data".to_string(),
+            ));
+        }
+
+        let bytes = &self.data[self.position..self.position + count];
+        self.position += count;
+
+        Ok(bytes)
+    }
+
+    fn skip(&mut self, count: usize) -> Result<(), VideoError> {
+        self.bytes(count).map(|_| ())
+    }
+
+    fn u8(&mut self) -> Result<u8, VideoError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, VideoError> {
+        Ok(u32::from_be_bytes(
+            self.bytes(4)?.try_into().unwrap_or_default(),
+        ))
+    }
+
+    fn u64(&mut self) -> Result<u64, VideoError> {
+        Ok(u64::from_be_bytes(
+            self.bytes(8)?.try_into().unwrap_or_default(),
+        ))
+    }
+}
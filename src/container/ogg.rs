@@ -0,0 +1,155 @@
+/// Maximum lacing values of a page
+const MAX_SEGMENTS: usize = 255;
+/// Pages are flushed around this size like libogg does
+const PAGE_DATA_SIZE: usize = 4096;
+
+const HEADER_CONTINUED: u8 = 0x01;
+const HEADER_BEGIN_OF_STREAM: u8 = 0x02;
+const HEADER_END_OF_STREAM: u8 = 0x04;
+
+/// CRC-32 of Ogg pages (polynomial `0x04C11DB7`, no reflection)
+const CRC_TABLE: [u32; 256] = crc_table();
+
+const fn crc_table() -> [u32; 256] {
+    let mut table = [0; 256];
+    let mut index = 0;
+
+    while index < 256 {
+        let mut crc = (index as u32) << 24;
+        let mut bit = 0;
+
+        while bit < 8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04C1_1DB7
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+
+        table[index] = crc;
+        index += 1;
+    }
+
+    table
+}
+
+/// Writes packets of a single logical stream into Ogg pages
+pub(crate) struct OggWriter {
+    serial: u32,
+    sequence: u32,
+    output: Vec<u8>,
+    /// Lacing values and data of the page being filled
+    segments: Vec<u8>,
+    data: Vec<u8>,
+    granule: u64,
+    begin: bool,
+    /// Page being filled starts with the rest of a packet
+    continued: bool,
+}
+
+impl OggWriter {
+    pub(crate) fn new(serial: u32) -> Self {
+        Self {
+            serial,
+            sequence: 0,
+            output: vec![],
+            segments: vec![],
+            data: vec![],
+            granule: 0,
+            begin: true,
+            continued: false,
+        }
+    }
+
+    /// Add a packet ending at `granule`, packets bigger than a page continue on the next pages
+    pub(crate) fn write_packet(&mut self, packet: &[u8], granule: u64) {
+        let mut remaining = packet;
+        let mut first_segment = true;
+
+        loop {
+            if self.segments.len() == MAX_SEGMENTS {
+                self.flush_page(false, !first_segment);
+            }
+            first_segment = false;
+
+            let size = remaining.len().min(255);
+            self.segments.push(size as u8);
+            self.data.extend_from_slice(&remaining[..size]);
+            remaining = &remaining[size..];
+
+            // Packet ends with a lacing value under 255
+            if size < 255 {
+                break;
+            }
+        }
+
+        self.granule = granule;
+
+        if self.data.len() >= PAGE_DATA_SIZE {
+            self.flush_page(false, false);
+        }
+    }
+
+    /// Put the next packets on a new page, header packets must be alone on their pages
+    pub(crate) fn flush(&mut self) {
+        if !self.segments.is_empty() {
+            self.flush_page(false, false);
+        }
+    }
+
+    /// Write the last page and return the stream
+    pub(crate) fn finish(mut self) -> Vec<u8> {
+        self.flush_page(true, false);
+
+        self.output
+    }
+
+    fn flush_page(&mut self, end: bool, packet_continues: bool) {
+        let mut header_type = 0;
+
+        if self.begin {
+            header_type |= HEADER_BEGIN_OF_STREAM;
+            self.begin = false;
+        }
+
+        if end {
+            header_type |= HEADER_END_OF_STREAM;
+        }
+
+        if self.continued {
+            header_type |= HEADER_CONTINUED;
+        }
+
+        // Pages without a finished packet have granule position -1
+        let granule = if packet_continues && self.segments.iter().all(|x| *x == 255) {
+            u64::MAX
+        } else {
+            self.granule
+        };
+
+        let start = self.output.len();
+
+        self.output.extend_from_slice(b"OggS");
+        self.output.push(0);
+        self.output.push(header_type);
+        self.output.extend_from_slice(&granule.to_le_bytes());
+        self.output.extend_from_slice(&self.serial.to_le_bytes());
+        self.output.extend_from_slice(&self.sequence.to_le_bytes());
+        // CRC is calculated with zeroes in its place
+        self.output.extend_from_slice(&[0; 4]);
+        self.output.push(self.segments.len() as u8);
+        self.output.extend_from_slice(&self.segments);
+        self.output.extend_from_slice(&self.data);
+
+        let crc = self.output[start..].iter().fold(0_u32, |crc, byte| {
+            (crc << 8) ^ CRC_TABLE[(((crc >> 24) as u8) ^ byte) as usize]
+        });
+        self.output[start + 22..start + 26].copy_from_slice(&crc.to_le_bytes());
+
+        self.sequence += 1;
+        self.segments.clear();
+        self.data.clear();
+        self.continued = packet_continues;
+    }
+}
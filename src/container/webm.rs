@@ -0,0 +1,314 @@
+use crate::structs::VideoError;
+
+const SEGMENT: u32 = 0x1853_8067;
+const TRACKS: u32 = 0x1654_AE6B;
+const TRACK_ENTRY: u32 = 0xAE;
+const TRACK_NUMBER: u32 = 0xD7;
+const TRACK_TYPE: u32 = 0x83;
+const CODEC_ID: u32 = 0x86;
+const CODEC_PRIVATE: u32 = 0x63A2;
+const CODEC_DELAY: u32 = 0x56AA;
+const AUDIO: u32 = 0xE1;
+const CHANNELS: u32 = 0x9F;
+const CLUSTER: u32 = 0x1F43_B675;
+const BLOCK_GROUP: u32 = 0xA0;
+const BLOCK: u32 = 0xA1;
+const SIMPLE_BLOCK: u32 = 0xA3;
+const DISCARD_PADDING: u32 = 0x75A2;
+
+/// Matroska track type of audio tracks
+const TRACK_TYPE_AUDIO: u64 = 2;
+
+/// First audio track of a WebM file
+#[derive(Debug, Default)]
+pub(crate) struct WebmAudio<'a> {
+    pub codec_id: String,
+    pub codec_private: &'a [u8],
+    pub codec_delay_ns: u64,
+    pub channels: u8,
+    /// Frames of the track in file order
+    pub frames: Vec<&'a [u8]>,
+    /// Nanoseconds to discard from the end of the last frame
+    pub discard_padding_ns: i64,
+}
+
+/// Demux the first audio track of a WebM file
+pub(crate) fn demux_audio(data: &[u8]) -> Result<WebmAudio<'_>, VideoError> {
+    let mut reader = Reader::new(data);
+    let mut audio = WebmAudio::default();
+    let mut track_number = None;
+
+    while !reader.is_empty() {
+        let (id, size) = reader.element_header()?;
+
+        match id {
+            // Masters of blocks are flattened, so unknown sizes of live files work
+            SEGMENT | CLUSTER | BLOCK_GROUP => {}
+            TRACKS => {
+                let body = reader.body(size)?;
+
+                if let Some((number, track)) = parse_tracks(body)? {
+                    track_number = Some(number);
+                    audio = WebmAudio {
+                        frames: audio.frames,
+                        ..track
+                    };
+                }
+            }
+            SIMPLE_BLOCK | BLOCK => {
+                let body = reader.body(size)?;
+                let number = track_number.ok_or(VideoError::ContainerError(
+                    "Block found before the audio track".to_string(),
+                ))?;
+
+                let mut block = Reader::new(body);
+                if block.vint(false)? != number {
+                    continue;
+                }
+
+                audio.frames.extend(parse_block_frames(&mut block)?);
+                // Padding belongs to the block group of the last block
+                audio.discard_padding_ns = 0;
+            }
+            DISCARD_PADDING => {
+                audio.discard_padding_ns = read_int(reader.body(size)?);
+            }
+            _ => {
+                reader.body(size)?;
+            }
+        }
+    }
+
+    if track_number.is_none() {
+        return Err(VideoError::ContainerError(
+            "Audio track not found".to_string(),
+        ));
+    }
+
+    Ok(audio)
+}
+
+/// Find the first audio track and return its number
+fn parse_tracks(data: &[u8]) -> Result<Option<(u64, WebmAudio<'_>)>, VideoError> {
+    let mut tracks = Reader::new(data);
+
+    while !tracks.is_empty() {
+        let (id, size) = tracks.element_header()?;
+        let body = tracks.body(size)?;
+
+        if id != TRACK_ENTRY {
+            continue;
+        }
+
+        let mut entry = Reader::new(body);
+        let mut number = 0;
+        let mut track_type = 0;
+        let mut audio = WebmAudio {
+            channels: 2,
+            ..Default::default()
+        };
+
+        while !entry.is_empty() {
+            let (id, size) = entry.element_header()?;
+            let body = entry.body(size)?;
+
+            match id {
+                TRACK_NUMBER => number = read_uint(body),
+                TRACK_TYPE => track_type = read_uint(body),
+                CODEC_ID => audio.codec_id = String::from_utf8_lossy(body).to_string(),
+                CODEC_PRIVATE => audio.codec_private = body,
+                CODEC_DELAY => audio.codec_delay_ns = read_uint(body),
+                AUDIO => {
+                    let mut settings = Reader::new(body);
+
+                    while !settings.is_empty() {
+                        let (id, size) = settings.element_header()?;
+                        let body = settings.body(size)?;
+
+                        if id == CHANNELS {
+                            audio.channels = read_uint(body) as u8;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if track_type == TRACK_TYPE_AUDIO {
+            return Ok(Some((number, audio)));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Split the frames of a block after its track number
+fn parse_block_frames<'a>(block: &mut Reader<'a>) -> Result<Vec<&'a [u8]>, VideoError> {
+    // Relative timecode is not needed, frames are already in order
+    block.bytes(2)?;
+    let flags = block.bytes(1)?[0];
+
+    let lacing = (flags >> 1) & 0b11;
+    if lacing == 0 {
+        return Ok(vec![block.rest()]);
+    }
+
+    let count = block.bytes(1)?[0] as usize + 1;
+    let mut sizes = Vec::with_capacity(count);
+
+    match lacing {
+        // Xiph lacing
+        0b01 => {
+            for _ in 0..count - 1 {
+                let mut size = 0;
+
+                loop {
+                    let byte = block.bytes(1)?[0];
+                    size += byte as usize;
+
+                    if byte != 255 {
+                        break;
+                    }
+                }
+
+                sizes.push(size);
+            }
+        }
+        // EBML lacing, sizes after the first are signed differences
+        0b11 => {
+            let mut size = block.vint(false)? as i64;
+            sizes.push(size as usize);
+
+            for _ in 1..count - 1 {
+                let (value, length) = block.vint_with_length()?;
+                let bias = (1_i64 << (7 * length - 1)) - 1;
+
+                size += value as i64 - bias;
+                if size < 0 {
+                    return Err(VideoError::ContainerError(
+                        "Invalid EBML lacing".to_string(),
+                    ));
+                }
+
+                sizes.push(size as usize);
+            }
+        }
+        // Fixed-size lacing
+        _ => {
+            let size = block.remaining() / count;
+            sizes.resize(count - 1, size);
+        }
+    }
+
+    let mut frames = Vec::with_capacity(count);
+    for size in sizes {
+        frames.push(block.bytes(size)?);
+    }
+    frames.push(block.rest());
+
+    Ok(frames)
+}
+
+fn read_uint(data: &[u8]) -> u64 {
+    data.iter()
+        .fold(0, |value, byte| (value << 8) | *byte as u64)
+}
+
+fn read_int(data: &[u8]) -> i64 {
+    if data.is_empty() {
+        return 0;
+    }
+
+    // Sign extend from the highest bit of the first byte
+    let shift = 64 - 8 * data.len().min(8) as u32;
+    ((read_uint(data) << shift) as i64) >> shift
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, position: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.position >= self.data.len()
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len().saturating_sub(self.position)
+    }
+
+    fn bytes(&mut self, count: usize) -> Result<&'a [u8], VideoError> {
+        if count > self.remaining() {
+            return Err(VideoError::ContainerError(
+                "Unexpected end of WebM data".to_string(),
+            ));
+        }
+
+        let bytes = &self.data[self.position..self.position + count];
+        self.position += count;
+
+        Ok(bytes)
+    }
+
+    fn rest(&mut self) -> &'a [u8] {
+        let rest = &self.data[self.position.min(self.data.len())..];
+        self.position = self.data.len();
+
+        rest
+    }
+
+    /// Read a variable size integer and its length in bytes
+    fn vint_with_length(&mut self) -> Result<(u64, usize), VideoError> {
+        let first = self.bytes(1)?[0];
+        let length = first.leading_zeros() as usize + 1;
+
+        if length > 8 {
+            return Err(VideoError::ContainerError(
+                "Invalid variable size integer".to_string(),
+            ));
+        }
+
+        let mut value = (first as u64) & (0xFF >> length);
+        for byte in self.bytes(length - 1)? {
+            value = (value << 8) | *byte as u64;
+        }
+
+        Ok((value, length))
+    }
+
+    /// Read a variable size integer, element IDs keep their length marker
+    fn vint(&mut self, keep_marker: bool) -> Result<u64, VideoError> {
+        let start = self.position;
+        let (value, length) = self.vint_with_length()?;
+
+        if keep_marker {
+            return Ok(read_uint(&self.data[start..start + length]));
+        }
+
+        Ok(value)
+    }
+
+    /// Read an element ID and its size, [`None`] if the size is unknown
+    fn element_header(&mut self) -> Result<(u32, Option<usize>), VideoError> {
+        let id = self.vint(true)? as u32;
+        let (size, length) = self.vint_with_length()?;
+
+        // All value bits set is reserved for unknown sizes
+        let unknown = size == (1_u64 << (7 * length)) - 1;
+
+        Ok((id, (!unknown).then_some(size as usize)))
+    }
+
+    /// Read the body of an element, unknown sizes extend to the end of the data
+    fn body(&mut self, size: Option<usize>) -> Result<&'a [u8], VideoError> {
+        match size {
+            Some(size) => self.bytes(size),
+            None => Ok(self.rest()),
+        }
+    }
+}
@@ -0,0 +1,387 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::info::Video;
+use crate::structs::{DownloadProgress, ProgressCallback, VideoError, VideoOptions};
+use crate::utils::build_client;
+
+/// Where a [`DownloadJob`] is written
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DownloadOutput {
+    /// Exact file path
+    Path(PathBuf),
+    /// File in `dir` named by a yt-dlp style template, see [`crate::render_filename_template`]
+    Template { dir: PathBuf, template: String },
+}
+
+/// A video of a [`DownloadManager`] batch
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DownloadJob {
+    pub url_or_id: String,
+    pub output: DownloadOutput,
+    /// Jobs with a higher priority start first, jobs with the same priority start in order. Default is `0`
+    pub priority: i32,
+}
+
+impl DownloadJob {
+    pub fn new(url_or_id: impl Into<String>, path: impl Into<PathBuf>) -> Self {
+        Self {
+            url_or_id: url_or_id.into(),
+            output: DownloadOutput::Path(path.into()),
+            priority: 0,
+        }
+    }
+
+    pub fn with_template(
+        url_or_id: impl Into<String>,
+        dir: impl Into<PathBuf>,
+        template: impl Into<String>,
+    ) -> Self {
+        Self {
+            url_or_id: url_or_id.into(),
+            output: DownloadOutput::Template {
+                dir: dir.into(),
+                template: template.into(),
+            },
+            priority: 0,
+        }
+    }
+
+    pub fn with_priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+}
+
+/// Outcome of a [`DownloadJob`]
+#[derive(Debug)]
+pub struct DownloadResult {
+    /// Index of the job in the batch
+    pub index: usize,
+    pub url_or_id: String,
+    /// Path of the downloaded file
+    pub result: Result<PathBuf, VideoError>,
+    /// Size of [`Video::estimate_size`] in dry-run mode, [`None`] otherwise or if it is unknown
+    pub estimated_size: Option<u64>,
+}
+
+/// Progress of one job of a batch, called with the index of the job
+#[derive(Clone)]
+pub struct BatchProgressCallback(Arc<dyn Fn(usize, &DownloadProgress) + Sync + Send + 'static>);
+
+impl BatchProgressCallback {
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: Fn(usize, &DownloadProgress) + Sync + Send + 'static,
+    {
+        Self(Arc::new(callback))
+    }
+
+    pub fn call(&self, index: usize, progress: &DownloadProgress) {
+        (self.0)(index, progress)
+    }
+}
+
+impl std::fmt::Debug for BatchProgressCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "BatchProgressCallback()")
+    }
+}
+
+/// Download many videos with a bounded count of concurrent downloads.
+/// Videos share one client built from [`VideoOptions::request_options`], so cookies and the IPv6 address are shared too.
+/// With [`RequestOptions::identity_rotation`](crate::RequestOptions::identity_rotation) every video builds its own client instead
+/// # Example
+/// ```ignore
+///     let manager = DownloadManager::new(VideoOptions::default(), 4).with_progress_callback(
+///         BatchProgressCallback::new(|index, progress| {
+///             println!("#{index}: {}/{} bytes", progress.downloaded, progress.total);
+///         }),
+///     );
+///
+///     let results = manager
+///         .download_all(vec![
+///             DownloadJob::new("FZ8BxMU3BYc", "first.mp4"),
+///             DownloadJob::with_template("https://www.youtube.com/watch?v=jNQXAC9IVRw", "videos", "%(title)s.%(ext)s"),
+///         ])
+///         .await
+///         .unwrap();
+///
+///     for result in results {
+///         if let Err(e) = result.result {
+///             println!("{} failed: {e}", result.url_or_id);
+///         }
+///     }
+/// ```
+#[derive(Clone, derivative::Derivative)]
+#[derivative(Debug)]
+pub struct DownloadManager {
+    options: VideoOptions,
+    concurrency: usize,
+    progress_callback: Option<BatchProgressCallback>,
+    stop_on_error: bool,
+    dry_run: bool,
+    shutdown: Arc<AtomicBool>,
+    /// Jobs which are not started yet with their index in the batch
+    queue: Arc<Mutex<Vec<(usize, DownloadJob)>>>,
+    /// Called with the index of every finished job and whether it succeeded
+    #[derivative(Debug = "ignore")]
+    finish_callback: Option<Arc<dyn Fn(usize, bool) + Sync + Send + 'static>>,
+}
+
+impl DownloadManager {
+    /// Manager running at most `concurrency` downloads at once
+    pub fn new(options: VideoOptions, concurrency: usize) -> Self {
+        Self {
+            options,
+            concurrency: concurrency.max(1),
+            progress_callback: None,
+            stop_on_error: false,
+            dry_run: false,
+            shutdown: Arc::new(AtomicBool::new(false)),
+            queue: Arc::new(Mutex::new(vec![])),
+            finish_callback: None,
+        }
+    }
+
+    /// Report the progress of every job, [`DownloadOptions::progress_callback`](crate::DownloadOptions::progress_callback) of the options is replaced
+    pub fn with_progress_callback(mut self, progress_callback: BatchProgressCallback) -> Self {
+        self.progress_callback = Some(progress_callback);
+        self
+    }
+
+    /// [`shutdown`](Self::shutdown) the manager when a job fails
+    pub fn with_stop_on_error(mut self, stop_on_error: bool) -> Self {
+        self.stop_on_error = stop_on_error;
+        self
+    }
+
+    /// Resolve formats and paths and estimate sizes without downloading.
+    /// Sum [`DownloadResult::estimated_size`] of the results to get the disk requirement
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub(crate) fn with_finish_callback<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(usize, bool) + Sync + Send + 'static,
+    {
+        self.finish_callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Stop starting new downloads, running downloads are finished.
+    /// Jobs which were not started return [`VideoError::Cancelled`]. Clones of the manager share the state,
+    /// the next [`DownloadManager::download_all`] starts again
+    pub fn shutdown(&self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_shutdown(&self) -> bool {
+        self.shutdown.load(Ordering::SeqCst)
+    }
+
+    /// Change the priority of a job which is not started yet, by its index in the batch.
+    /// Return `false` if the job is already started or unknown. Clones of the manager share the queue
+    pub fn set_priority(&self, index: usize, priority: i32) -> bool {
+        let mut queue = self.queue.lock().unwrap();
+
+        match queue.iter_mut().find(|(x, _)| *x == index) {
+            Some((_, job)) => {
+                job.priority = priority;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Start a job which is not started yet before all other queued jobs.
+    /// Return `false` if the job is already started or unknown
+    pub fn move_to_front(&self, index: usize) -> bool {
+        let priority = {
+            let queue = self.queue.lock().unwrap();
+
+            queue
+                .iter()
+                .filter(|(x, _)| *x != index)
+                .map(|(_, job)| job.priority)
+                .max()
+                .unwrap_or(0)
+                .saturating_add(1)
+        };
+
+        self.set_priority(index, priority)
+    }
+
+    /// Indexes of the jobs which are not started yet, in start order
+    pub fn queued(&self) -> Vec<usize> {
+        let mut queue = self.queue.lock().unwrap().clone();
+        queue.sort_by_key(|(index, job)| (std::cmp::Reverse(job.priority), *index));
+
+        queue.into_iter().map(|(index, _)| index).collect()
+    }
+
+    /// Take the queued job with the highest priority
+    fn next_job(&self) -> Option<(usize, DownloadJob)> {
+        let mut queue = self.queue.lock().unwrap();
+
+        let position = queue
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, (index, job))| (std::cmp::Reverse(job.priority), *index))
+            .map(|(position, _)| position)?;
+
+        Some(queue.remove(position))
+    }
+
+    /// Download every job and return the results in the order of the jobs.
+    /// A failed job does not stop the others, only invalid [`VideoOptions::request_options`] return an error directly.
+    /// Jobs start by [`DownloadJob::priority`], which can be changed while running with [`DownloadManager::set_priority`]
+    /// and [`DownloadManager::move_to_front`]. Run one batch at a time per manager
+    pub async fn download_all(
+        &self,
+        jobs: impl IntoIterator<Item = DownloadJob>,
+    ) -> Result<Vec<DownloadResult>, VideoError> {
+        let mut options = self.options.clone();
+
+        // Built once, so all videos share the connection pool. A shared client would make all videos use one identity
+        if options.request_options.identity_rotation.is_none() {
+            options.request_options.client =
+                Some(Arc::new(build_client(&options.request_options)?));
+        }
+
+        // Shutdown of the previous batch does not cancel this one
+        self.shutdown.store(false, Ordering::SeqCst);
+
+        let options = &options;
+
+        let count = {
+            let mut queue = self.queue.lock().unwrap();
+            *queue = jobs.into_iter().enumerate().collect();
+            queue.len()
+        };
+
+        // Every worker takes the next job by priority when its download finishes
+        let workers = (0..self.concurrency.min(count)).map(|_| async move {
+            let mut results = vec![];
+
+            while let Some((index, job)) = self.next_job() {
+                let (result, estimated_size) = match self.download_job(index, &job, options).await {
+                    Ok((path, estimated_size)) => (Ok(path), estimated_size),
+                    Err(e) => (Err(e), None),
+                };
+
+                if self.stop_on_error && result.is_err() {
+                    self.shutdown();
+                }
+
+                if let Some(finish_callback) = &self.finish_callback {
+                    finish_callback(index, result.is_ok());
+                }
+
+                results.push(DownloadResult {
+                    index,
+                    url_or_id: job.url_or_id,
+                    result,
+                    estimated_size,
+                });
+            }
+
+            results
+        });
+
+        let mut results = futures::future::join_all(workers)
+            .await
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>();
+
+        results.sort_by_key(|x| x.index);
+
+        Ok(results)
+    }
+
+    async fn download_job(
+        &self,
+        index: usize,
+        job: &DownloadJob,
+        options: &VideoOptions,
+    ) -> Result<(PathBuf, Option<u64>), VideoError> {
+        if self.is_shutdown() {
+            return Err(VideoError::Cancelled);
+        }
+
+        let mut options = options.clone();
+
+        if let Some(progress_callback) = self.progress_callback.clone() {
+            options.download_options.progress_callback =
+                Some(ProgressCallback::new(move |progress| {
+                    progress_callback.call(index, progress)
+                }));
+        }
+
+        let video = Video::new_with_options(&job.url_or_id, options)?;
+
+        if self.dry_run {
+            let path = match &job.output {
+                DownloadOutput::Path(path) => path.clone(),
+                DownloadOutput::Template { dir, template } => {
+                    video.template_path(dir, template).await?
+                }
+            };
+
+            return Ok((path, video.estimate_size().await?));
+        }
+
+        match &job.output {
+            DownloadOutput::Path(path) => {
+                video.download(path).await?;
+
+                Ok((path.clone(), None))
+            }
+            DownloadOutput::Template { dir, template } => {
+                Ok((video.download_with_template(dir, template).await?, None))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_priority_queue() {
+        let manager = DownloadManager::new(VideoOptions::default(), 1);
+        *manager.queue.lock().unwrap() = vec![
+            DownloadJob::new("a", "a.mp4"),
+            DownloadJob::new("b", "b.mp4").with_priority(1),
+            DownloadJob::new("c", "c.mp4"),
+            DownloadJob::new("d", "d.mp4"),
+        ]
+        .into_iter()
+        .enumerate()
+        .collect();
+
+        assert_eq!(manager.queued(), vec![1, 0, 2, 3]);
+        assert!(manager.move_to_front(3));
+        assert!(manager.set_priority(2, -1));
+        assert_eq!(manager.next_job().map(|(index, _)| index), Some(3));
+        assert!(!manager.set_priority(3, 5));
+        assert_eq!(manager.queued(), vec![1, 0, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_is_reset() {
+        let manager = DownloadManager::new(VideoOptions::default(), 1);
+
+        manager.shutdown();
+        assert!(manager.is_shutdown());
+
+        let results = manager.download_all(vec![]).await.unwrap();
+        assert!(results.is_empty());
+        assert!(!manager.is_shutdown());
+    }
+}
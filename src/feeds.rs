@@ -0,0 +1,185 @@
+//! YouTube's Atom feeds of channel uploads. Feeds have the latest 15 videos without API quota or page scraping,
+//! e.g. for polling new uploads of many channels
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::parser::decode_entities;
+use crate::structs::{RequestOptions, VideoError};
+use crate::utils::build_client;
+
+static REGEX_ENTRY: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?s)<entry>(.*?)</entry>").unwrap());
+static REGEX_LINK: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"<link rel="alternate" href="([^"]*)""#).unwrap());
+static REGEX_THUMBNAIL: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"<media:thumbnail url="([^"]*)""#).unwrap());
+static REGEX_VIEWS: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"<media:statistics views="(\d+)""#).unwrap());
+
+/// Video of a channel feed
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeedEntry {
+    pub id: String,
+    pub title: String,
+    pub url: String,
+    pub author: String,
+    pub channel_id: String,
+    /// Upload time as RFC 3339 (e.g. `2024-03-01T17:00:06+00:00`)
+    pub published: String,
+    /// Time of the last metadata change as RFC 3339
+    pub updated: String,
+    pub description: String,
+    pub thumbnail: Option<String>,
+    pub views: Option<u64>,
+}
+
+/// Fetch the latest uploads of the channel from its feed with default [`RequestOptions`]
+/// # Example
+/// ```ignore
+///     let entries = feeds::channel_rss("UCBR8-60-B28hp2BmDPdntcQ").await.unwrap();
+///
+///     for entry in entries {
+///         println!("{} {} {}", entry.published, entry.id, entry.title);
+///     }
+/// ```
+pub async fn channel_rss(channel_id: impl Into<String>) -> Result<Vec<FeedEntry>, VideoError> {
+    channel_rss_with_options(channel_id, &RequestOptions::default()).await
+}
+
+/// Fetch the latest uploads of the channel from its feed with custom [`RequestOptions`]
+/// - Returns [`VideoError::ChannelNotFound`] if the channel does not exist
+pub async fn channel_rss_with_options(
+    channel_id: impl Into<String>,
+    request_options: &RequestOptions,
+) -> Result<Vec<FeedEntry>, VideoError> {
+    let channel_id: String = channel_id.into();
+    let client = build_client(request_options)?;
+
+    let url = url::Url::parse_with_params(
+        "https://www.youtube.com/feeds/videos.xml",
+        &[("channel_id", &channel_id)],
+    )
+    .map_err(VideoError::URLParseError)?;
+
+    let response = client
+        .get(url.as_str())
+        .send()
+        .await
+        .map_err(VideoError::from)?;
+
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(VideoError::ChannelNotFound(channel_id));
+    }
+
+    let body = response
+        .error_for_status()
+        .map_err(VideoError::Reqwest)?
+        .text()
+        .await
+        .map_err(VideoError::Reqwest)?;
+
+    Ok(parse_channel_feed(&body))
+}
+
+/// Parse the entries of a feed body, entries without video ID are skipped
+pub fn parse_channel_feed(body: &str) -> Vec<FeedEntry> {
+    REGEX_ENTRY
+        .captures_iter(body)
+        .filter_map(|entry| {
+            let entry = &entry[1];
+
+            let capture = |regex: &Regex| regex.captures(entry).map(|x| decode_entities(&x[1]));
+
+            let id = element_text(entry, "yt:videoId")?;
+
+            Some(FeedEntry {
+                url: capture(&REGEX_LINK)
+                    .unwrap_or_else(|| format!("https://www.youtube.com/watch?v={id}")),
+                title: element_text(entry, "title").unwrap_or_default(),
+                author: element_text(entry, "name").unwrap_or_default(),
+                channel_id: element_text(entry, "yt:channelId").unwrap_or_default(),
+                published: element_text(entry, "published").unwrap_or_default(),
+                updated: element_text(entry, "updated").unwrap_or_default(),
+                description: element_text(entry, "media:description").unwrap_or_default(),
+                thumbnail: capture(&REGEX_THUMBNAIL),
+                views: capture(&REGEX_VIEWS).and_then(|x| x.parse::<u64>().ok()),
+                id,
+            })
+        })
+        .collect()
+}
+
+/// Decoded text of the first `<name>...</name>` element
+fn element_text(body: &str, name: &str) -> Option<String> {
+    let start_tag = format!("<{name}>");
+    let start = body.find(&start_tag)? + start_tag.len();
+    let end = start + body[start..].find(&format!("</{name}>"))?;
+
+    Some(decode_entities(body[start..end].trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_channel_feed() {
+        let body = r#"<?xml version="1.0" encoding="UTF-8"?>
+<feed xmlns:yt="http://www.youtube.com/xml/schemas/2015" xmlns:media="http://search.yahoo.com/mrss/" xmlns="http://www.w3.org/2005/Atom">
+ <title>YouTube</title>
+ <author><name>YouTube</name><uri>https://www.youtube.com/channel/UCBR8-60-B28hp2BmDPdntcQ</uri></author>
+ <entry>
+  <id>yt:video:FZ8BxMU3BYc</id>
+  <yt:videoId>FZ8BxMU3BYc</yt:videoId>
+  <yt:channelId>UCBR8-60-B28hp2BmDPdntcQ</yt:channelId>
+  <title>What&#39;s new &amp; next</title>
+  <link rel="alternate" href="https://www.youtube.com/watch?v=FZ8BxMU3BYc"/>
+  <author>
+   <name>YouTube</name>
+   <uri>https://www.youtube.com/channel/UCBR8-60-B28hp2BmDPdntcQ</uri>
+  </author>
+  <published>2024-03-01T17:00:06+00:00</published>
+  <updated>2024-03-02T08:12:45+00:00</updated>
+  <media:group>
+   <media:title>What&#39;s new &amp; next</media:title>
+   <media:thumbnail url="https://i2.ytimg.com/vi/FZ8BxMU3BYc/hqdefault.jpg" width="480" height="360"/>
+   <media:description>Line 1
+Line 2</media:description>
+   <media:community>
+    <media:starRating count="1200" average="5.00" min="1" max="5"/>
+    <media:statistics views="34567"/>
+   </media:community>
+  </media:group>
+ </entry>
+ <entry>
+  <id>yt:video:jNQXAC9IVRw</id>
+  <yt:videoId>jNQXAC9IVRw</yt:videoId>
+  <title>Me at the zoo</title>
+  <published>2005-04-24T03:31:52+00:00</published>
+ </entry>
+</feed>"#;
+
+        let entries = parse_channel_feed(body);
+        assert_eq!(entries.len(), 2);
+
+        let entry = &entries[0];
+        assert_eq!(entry.id, "FZ8BxMU3BYc");
+        assert_eq!(entry.title, "What's new & next");
+        assert_eq!(entry.author, "YouTube");
+        assert_eq!(entry.channel_id, "UCBR8-60-B28hp2BmDPdntcQ");
+        assert_eq!(entry.published, "2024-03-01T17:00:06+00:00");
+        assert_eq!(entry.description, "Line 1\nLine 2");
+        assert_eq!(
+            entry.thumbnail.as_deref(),
+            Some("https://i2.ytimg.com/vi/FZ8BxMU3BYc/hqdefault.jpg")
+        );
+        assert_eq!(entry.views, Some(34567));
+
+        assert_eq!(
+            entries[1].url,
+            "https://www.youtube.com/watch?v=jNQXAC9IVRw"
+        );
+        assert_eq!(entries[1].views, None);
+    }
+}
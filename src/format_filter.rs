@@ -0,0 +1,398 @@
+use std::cmp::Ordering;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use crate::structs::{VideoError, VideoFormat, VideoQuality, VideoSearchOptions};
+use crate::template::format_extension;
+use crate::utils::{sort_formats, sort_formats_by_audio, sort_formats_by_video};
+
+/// yt-dlp style format selection like `bestaudio[acodec=opus][abr>=128]` or `bestvideo[height<=720]/best`,
+/// compiled into [`VideoSearchOptions::Custom`] and [`VideoQuality::Custom`]
+///
+/// Selectors: `best` (`b`, video and audio), `worst` (`w`), `bestaudio` (`ba`), `worstaudio` (`wa`), `bestvideo` (`bv`)
+/// and `worstvideo` (`wv`). Alternatives separated by `/` are tried in order.
+///
+/// Numeric fields: `height`, `width`, `fps`, `tbr`, `abr` (kbps), `asr`, `filesize` (`K`, `M` and `G` suffixes),
+/// `audio_channels`, with `=`, `!=`, `<`, `<=`, `>`, `>=`.
+/// String fields: `ext`, `acodec`, `vcodec`, `format_id`, `format_note`, with `=`, `!=`, `^=` (starts with),
+/// `$=` (ends with) and `*=` (contains). Formats without the field do not match unless the operator ends with `?`,
+/// e.g. `[fps>?30]`
+/// # Example
+/// ```ignore
+///     let filter = "bestaudio[acodec=opus][abr>=128]/bestaudio".parse::<FormatFilter>().unwrap();
+///
+///     let options = VideoOptions {
+///         quality: filter.quality(),
+///         filter: filter.filter(),
+///         ..Default::default()
+///     };
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct FormatFilter {
+    alternatives: Vec<Selector>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Selector {
+    kind: SelectorKind,
+    best: bool,
+    conditions: Vec<Condition>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SelectorKind {
+    VideoAudio,
+    Audio,
+    Video,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Condition {
+    field: String,
+    operator: Operator,
+    value: ConditionValue,
+    /// Formats without the field match
+    optional: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    Equal,
+    NotEqual,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    StartsWith,
+    EndsWith,
+    Contains,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum ConditionValue {
+    Number(f64),
+    String(String),
+}
+
+/// Longer operators first, so `<=` is not parsed as `<`
+const OPERATORS: [(&str, Operator); 9] = [
+    ("!=", Operator::NotEqual),
+    ("<=", Operator::LessOrEqual),
+    (">=", Operator::GreaterOrEqual),
+    ("^=", Operator::StartsWith),
+    ("$=", Operator::EndsWith),
+    ("*=", Operator::Contains),
+    ("=", Operator::Equal),
+    ("<", Operator::Less),
+    (">", Operator::Greater),
+];
+
+const NUMBER_FIELDS: [&str; 8] = [
+    "height",
+    "width",
+    "fps",
+    "tbr",
+    "abr",
+    "asr",
+    "filesize",
+    "audio_channels",
+];
+
+const STRING_FIELDS: [&str; 5] = ["ext", "acodec", "vcodec", "format_id", "format_note"];
+
+impl FormatFilter {
+    /// Format passes one of the alternatives
+    pub fn matches(&self, format: &VideoFormat) -> bool {
+        self.alternative_index(format).is_some()
+    }
+
+    /// Filter of [`crate::VideoOptions::filter`], [`crate::choose_format`] filters formats with it before the quality
+    pub fn filter(&self) -> VideoSearchOptions {
+        let this = self.clone();
+
+        VideoSearchOptions::Custom(Arc::new(move |format| this.matches(format)))
+    }
+
+    /// Quality of [`crate::VideoOptions::quality`], formats of earlier alternatives rank first
+    pub fn quality(&self) -> VideoQuality {
+        let this = self.clone();
+
+        VideoQuality::Custom(self.filter(), Arc::new(move |a, b| this.compare(a, b)))
+    }
+
+    fn alternative_index(&self, format: &VideoFormat) -> Option<usize> {
+        self.alternatives.iter().position(|x| x.matches(format))
+    }
+
+    fn compare(&self, a: &VideoFormat, b: &VideoFormat) -> Ordering {
+        let index_a = self.alternative_index(a).unwrap_or(usize::MAX);
+        let index_b = self.alternative_index(b).unwrap_or(usize::MAX);
+
+        index_a
+            .cmp(&index_b)
+            .then_with(|| match self.alternatives.get(index_a) {
+                Some(selector) => selector.compare(a, b),
+                None => Ordering::Equal,
+            })
+    }
+}
+
+impl FromStr for FormatFilter {
+    type Err = VideoError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let alternatives = s
+            .split('/')
+            .map(|x| parse_selector(x.trim()))
+            .collect::<Option<Vec<_>>>()
+            .ok_or_else(|| VideoError::InvalidFormatFilter(s.to_string()))?;
+
+        Ok(Self { alternatives })
+    }
+}
+
+impl Selector {
+    fn matches(&self, format: &VideoFormat) -> bool {
+        let kind = match self.kind {
+            SelectorKind::VideoAudio => format.has_video && format.has_audio,
+            SelectorKind::Audio => format.has_audio && !format.has_video,
+            SelectorKind::Video => format.has_video && !format.has_audio,
+        };
+
+        kind && self.conditions.iter().all(|x| x.matches(format))
+    }
+
+    fn compare(&self, a: &VideoFormat, b: &VideoFormat) -> Ordering {
+        let ordering = match self.kind {
+            SelectorKind::VideoAudio => sort_formats(a, b),
+            SelectorKind::Audio => sort_formats_by_audio(a, b),
+            SelectorKind::Video => sort_formats_by_video(a, b),
+        };
+
+        if self.best {
+            ordering
+        } else {
+            ordering.reverse()
+        }
+    }
+}
+
+impl Condition {
+    fn matches(&self, format: &VideoFormat) -> bool {
+        match &self.value {
+            ConditionValue::Number(value) => match number_field(format, &self.field) {
+                Some(field) => match self.operator {
+                    Operator::Equal => field == *value,
+                    Operator::NotEqual => field != *value,
+                    Operator::Less => field < *value,
+                    Operator::LessOrEqual => field <= *value,
+                    Operator::Greater => field > *value,
+                    Operator::GreaterOrEqual => field >= *value,
+                    _ => false,
+                },
+                None => self.optional,
+            },
+            ConditionValue::String(value) => match string_field(format, &self.field) {
+                Some(field) => match self.operator {
+                    Operator::Equal => field == *value,
+                    Operator::NotEqual => field != *value,
+                    Operator::StartsWith => field.starts_with(value.as_str()),
+                    Operator::EndsWith => field.ends_with(value.as_str()),
+                    Operator::Contains => field.contains(value.as_str()),
+                    _ => false,
+                },
+                None => self.optional,
+            },
+        }
+    }
+}
+
+/// `bestaudio[acodec=opus][abr>=128]`
+fn parse_selector(raw: &str) -> Option<Selector> {
+    let (name, mut rest) = raw.split_at(raw.find('[').unwrap_or(raw.len()));
+
+    let (kind, best) = match name.trim() {
+        "best" | "b" => (SelectorKind::VideoAudio, true),
+        "worst" | "w" => (SelectorKind::VideoAudio, false),
+        "bestaudio" | "ba" => (SelectorKind::Audio, true),
+        "worstaudio" | "wa" => (SelectorKind::Audio, false),
+        "bestvideo" | "bv" => (SelectorKind::Video, true),
+        "worstvideo" | "wv" => (SelectorKind::Video, false),
+        _ => return None,
+    };
+
+    let mut conditions = vec![];
+
+    while !rest.trim().is_empty() {
+        let inner = rest.trim().strip_prefix('[')?;
+        let end = inner.find(']')?;
+
+        conditions.push(parse_condition(&inner[..end])?);
+        rest = &inner[end + 1..];
+    }
+
+    Some(Selector {
+        kind,
+        best,
+        conditions,
+    })
+}
+
+/// `abr>=128`, `acodec^=mp4a` or `fps>?30`
+fn parse_condition(raw: &str) -> Option<Condition> {
+    let field_end = raw.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))?;
+    let field = raw[..field_end].trim().to_string();
+    let rest = &raw[field_end..];
+
+    let (operator_raw, operator) = OPERATORS.iter().find(|(x, _)| rest.starts_with(x))?;
+    let rest = &rest[operator_raw.len()..];
+    let (optional, rest) = match rest.strip_prefix('?') {
+        Some(rest) => (true, rest),
+        None => (false, rest),
+    };
+    let raw_value = rest.trim().trim_matches(|c| c == '"' || c == '\'');
+
+    let value = if NUMBER_FIELDS.contains(&field.as_str()) {
+        if matches!(
+            operator,
+            Operator::StartsWith | Operator::EndsWith | Operator::Contains
+        ) {
+            return None;
+        }
+
+        ConditionValue::Number(parse_number(raw_value)?)
+    } else if STRING_FIELDS.contains(&field.as_str()) {
+        if matches!(
+            operator,
+            Operator::Less | Operator::LessOrEqual | Operator::Greater | Operator::GreaterOrEqual
+        ) {
+            return None;
+        }
+
+        ConditionValue::String(raw_value.to_string())
+    } else {
+        return None;
+    };
+
+    Some(Condition {
+        field,
+        operator: *operator,
+        value,
+        optional,
+    })
+}
+
+/// Number with optional `K`, `M` or `G` suffix
+fn parse_number(raw: &str) -> Option<f64> {
+    let (number, multiplier) = match raw.char_indices().last()? {
+        (index, 'k' | 'K') => (&raw[..index], 1e3),
+        (index, 'm' | 'M') => (&raw[..index], 1e6),
+        (index, 'g' | 'G') => (&raw[..index], 1e9),
+        _ => (raw, 1f64),
+    };
+
+    number.trim().parse::<f64>().ok().map(|x| x * multiplier)
+}
+
+fn number_field(format: &VideoFormat, field: &str) -> Option<f64> {
+    match field {
+        "height" => format.height.map(|x| x as f64),
+        "width" => format.width.map(|x| x as f64),
+        "fps" => format.fps.map(|x| x as f64),
+        "tbr" => Some(format.bitrate as f64 / 1000f64),
+        "abr" => format
+            .audio_bitrate
+            .filter(|_| format.has_audio)
+            .map(|x| x as f64),
+        "asr" => format
+            .audio_sample_rate
+            .as_ref()
+            .and_then(|x| x.parse::<f64>().ok()),
+        "filesize" => format.content_length.map(|x| x as f64),
+        "audio_channels" => format.audio_channels.map(|x| x as f64),
+        _ => None,
+    }
+}
+
+fn string_field(format: &VideoFormat, field: &str) -> Option<String> {
+    // Same as yt-dlp, `none` if the format does not have the track
+    let codec = |has: bool, codec: &Option<String>| {
+        if has {
+            codec.clone()
+        } else {
+            Some("none".to_string())
+        }
+    };
+
+    match field {
+        // Audio-only MP4 is `m4a` like in yt-dlp and the filename templates
+        "ext" => Some(format_extension(format)),
+        "acodec" => codec(format.has_audio, &format.mime_type.audio_codec),
+        "vcodec" => codec(format.has_video, &format.mime_type.video_codec),
+        "format_id" => Some(format.itag.to_string()),
+        "format_note" => format.quality_label.clone(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::test_format as format;
+
+    #[test]
+    fn test_format_filter() {
+        let formats = [
+            format(
+                140,
+                r#"audio/mp4; codecs="mp4a.40.2""#,
+                130_000,
+                serde_json::json!({ "audioQuality": "AUDIO_QUALITY_MEDIUM", "audioBitrate": 128 }),
+            ),
+            format(
+                251,
+                r#"audio/webm; codecs="opus""#,
+                160_000,
+                serde_json::json!({ "audioQuality": "AUDIO_QUALITY_MEDIUM", "audioBitrate": 160 }),
+            ),
+            format(
+                249,
+                r#"audio/webm; codecs="opus""#,
+                50_000,
+                serde_json::json!({ "audioQuality": "AUDIO_QUALITY_LOW", "audioBitrate": 48 }),
+            ),
+            format(
+                136,
+                r#"video/mp4; codecs="avc1.4d401f""#,
+                1_000_000,
+                serde_json::json!({ "qualityLabel": "720p", "height": 720, "width": 1280 }),
+            ),
+        ];
+        let choose = |spec: &str| {
+            let filter = spec.parse::<FormatFilter>().unwrap();
+            let options = crate::VideoOptions {
+                quality: filter.quality(),
+                filter: filter.filter(),
+                ..Default::default()
+            };
+            crate::choose_format(&formats, &options).map(|x| x.itag)
+        };
+
+        assert_eq!(choose("bestaudio[acodec=opus]").unwrap(), 251);
+        assert_eq!(choose("worstaudio[acodec=opus]").unwrap(), 249);
+        assert_eq!(choose("ba[ext=m4a]/ba").unwrap(), 140);
+        assert_eq!(choose("ba[ext=m4a][abr>=256]/ba").unwrap(), 251);
+        assert!(choose("ba[ext=mp4]").is_err());
+        assert_eq!(choose("bv[ext=mp4]").unwrap(), 136);
+        assert_eq!(choose("bv[height<=720][vcodec^=avc1]").unwrap(), 136);
+        assert_eq!(choose("bv[fps>?30]").unwrap(), 136);
+        assert!(choose("bv[fps>30]").is_err());
+        assert!(choose("best").is_err());
+
+        assert!("bestaudio[abr^=1]".parse::<FormatFilter>().is_err());
+        assert!("bestaudio[unknown=1]".parse::<FormatFilter>().is_err());
+        assert!("bestaudio[abr>=128".parse::<FormatFilter>().is_err());
+        assert!("all".parse::<FormatFilter>().is_err());
+        assert_eq!(parse_number("50M"), Some(50e6));
+    }
+}
@@ -0,0 +1,119 @@
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+use rand::seq::SliceRandom;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, USER_AGENT};
+use reqwest::{Request, Response};
+use reqwest_middleware::{Middleware, Next};
+use task_local_extensions::Extensions;
+
+use crate::constants::BROWSER_IDENTITIES;
+use crate::structs::{BrowserIdentity, UserAgentRotation};
+
+static BROWSERS: Lazy<RwLock<Vec<BrowserIdentity>>> = Lazy::new(|| {
+    RwLock::new(
+        BROWSER_IDENTITIES
+            .iter()
+            .map(
+                |(user_agent, sec_ch_ua, sec_ch_ua_platform)| BrowserIdentity {
+                    user_agent: user_agent.to_string(),
+                    sec_ch_ua: sec_ch_ua.map(|x| x.to_string()),
+                    sec_ch_ua_platform: sec_ch_ua_platform.map(|x| x.to_string()),
+                    mobile: false,
+                },
+            )
+            .collect(),
+    )
+});
+
+/// Replace the browser list of [`crate::RequestOptions::user_agent_rotation`], e.g. with newer browser versions.
+/// Clients which already picked a browser keep it
+/// - Empty list disables the rotation
+pub fn set_browser_identities(browsers: Vec<BrowserIdentity>) {
+    *BROWSERS.write().expect("IMPOSSIBLE") = browsers;
+}
+
+pub(crate) fn random_browser_identity() -> Option<BrowserIdentity> {
+    BROWSERS
+        .read()
+        .expect("IMPOSSIBLE")
+        .choose(&mut rand::thread_rng())
+        .cloned()
+}
+
+/// Set [`crate::RequestOptions::headers`] and [`crate::RequestOptions::user_agent`] on every request,
+/// replacing headers with the same name that are set by the request itself
+pub(crate) struct HeadersMiddleware {
+    pub headers: HeaderMap,
+    pub user_agent_rotation: Option<UserAgentRotation>,
+    /// Browser picked for [`UserAgentRotation::PerClient`]
+    pub browser: Option<BrowserIdentity>,
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl Middleware for HeadersMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let mut req = req;
+
+        let browser = match self.user_agent_rotation {
+            Some(UserAgentRotation::PerClient) => self.browser.clone(),
+            Some(UserAgentRotation::PerRequest) => random_browser_identity(),
+            None => None,
+        };
+
+        // Android and iOS InnerTube clients must keep the user agent of the app
+        let is_app_request = req
+            .headers()
+            .get(USER_AGENT)
+            .and_then(|x| x.to_str().ok())
+            .map(|x| x.starts_with("com.google."))
+            .unwrap_or(false);
+
+        if let Some(browser) = browser.filter(|_| !is_app_request) {
+            set_browser_headers(req.headers_mut(), &browser);
+        }
+
+        for (name, value) in self.headers.iter() {
+            req.headers_mut().insert(name, value.clone());
+        }
+
+        next.run(req, extensions).await
+    }
+}
+
+fn set_browser_headers(headers: &mut HeaderMap, browser: &BrowserIdentity) {
+    let client_hints = [
+        ("sec-ch-ua", browser.sec_ch_ua.clone()),
+        ("sec-ch-ua-platform", browser.sec_ch_ua_platform.clone()),
+        (
+            "sec-ch-ua-mobile",
+            browser
+                .sec_ch_ua
+                .as_ref()
+                .map(|_| if browser.mobile { "?1" } else { "?0" }.to_string()),
+        ),
+    ];
+
+    if let Ok(user_agent) = HeaderValue::from_str(&browser.user_agent) {
+        headers.insert(USER_AGENT, user_agent);
+    }
+
+    for (name, value) in client_hints {
+        let name = HeaderName::from_static(name);
+
+        match value.and_then(|x| HeaderValue::from_str(&x).ok()) {
+            Some(value) => {
+                headers.insert(name, value);
+            }
+            None => {
+                headers.remove(name);
+            }
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use reqwest::{Request, Response, StatusCode, Url};
+use reqwest_middleware::{Middleware, Next};
+use task_local_extensions::Extensions;
+
+use crate::platform::Instant;
+
+/// Callbacks around every HTTP request of the clients built from [`crate::RequestOptions`],
+/// e.g. for custom logging, quota accounting or debugging responses of YouTube.
+/// Retried requests call the hooks again
+///
+/// # Example
+/// ```ignore
+///     struct Logger;
+///
+///     impl RequestHook for Logger {
+///         fn after_response(&self, url: &reqwest::Url, status: Option<reqwest::StatusCode>, latency: Duration) {
+///             println!("{url} {status:?} in {latency:?}");
+///         }
+///     }
+///
+///     let video_options = VideoOptions {
+///          request_options: RequestOptions {
+///               hooks: Some(Arc::new(Logger)),
+///                ..Default::default()
+///          },
+///          ..Default::default()
+///     };
+/// ```
+pub trait RequestHook: Send + Sync {
+    /// Called before the request is sent, headers are final
+    fn before_request(&self, _request: &Request) {}
+
+    /// Called when the response headers arrive, `status` is [`None`] if the request failed.
+    /// `latency` does not include reading the body
+    fn after_response(&self, _url: &Url, _status: Option<StatusCode>, _latency: Duration) {}
+}
+
+pub(crate) struct HookMiddleware {
+    pub hooks: std::sync::Arc<dyn RequestHook>,
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl Middleware for HookMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        self.hooks.before_request(&req);
+
+        let url = req.url().clone();
+        let started_at = Instant::now();
+
+        let response = next.run(req, extensions).await;
+
+        self.hooks.after_response(
+            &url,
+            response.as_ref().ok().map(|x| x.status()),
+            started_at.elapsed(),
+        );
+
+        response
+    }
+}
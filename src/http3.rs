@@ -0,0 +1,34 @@
+use reqwest::{Request, Response};
+use reqwest_middleware::{Middleware, Next};
+use task_local_extensions::Extensions;
+
+/// Send googlevideo requests with a HTTP/3 client, other hosts keep the default client
+pub(crate) struct Http3Middleware {
+    pub client: reqwest::Client,
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl Middleware for Http3Middleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let is_googlevideo = req
+            .url()
+            .host_str()
+            .map(|x| x.ends_with(".googlevideo.com"))
+            .unwrap_or(false);
+
+        if !is_googlevideo {
+            return next.run(req, extensions).await;
+        }
+
+        self.client
+            .execute(req)
+            .await
+            .map_err(reqwest_middleware::Error::Reqwest)
+    }
+}
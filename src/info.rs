@@ -1,34 +1,141 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
 
+use once_cell::sync::Lazy;
+use regex::Regex;
 use scraper::{Html, Selector};
 
-// Disabled due to not using DASHMPD
-//
-// use xml_oxide::{sax::parser::Parser, sax::Event};
-
+use crate::cache::LruCache;
+use crate::comments::Comments;
 use crate::constants::{BASE_URL, FORMATS};
-use crate::info_extras::{get_media, get_related_videos};
-use crate::parser::parse_video_formats;
+use crate::container::{fragmented_mp4_to_m4a, webm_to_ogg_opus};
+use crate::info_extras::{
+    convert_captions, get_captions, get_media, get_related_videos, get_watch_next,
+};
+use crate::js_runtime::default_js_runtime;
+use crate::live_chat::LiveChat;
+use crate::parser::{parse_dash_manifest, parse_hls_master_playlist, parse_video_formats};
+use crate::related::RelatedVideos;
+use crate::stream::{
+    DashStream, DashStreamOptions, LinkRefresher, NonLiveStream, NonLiveStreamOptions, OtfStream,
+    OtfStreamOptions, Stream,
+};
 #[cfg(feature = "live")]
 use crate::stream::{LiveStream, LiveStreamOptions};
-use crate::stream::{NonLiveStream, NonLiveStreamOptions, Stream};
 
-use crate::structs::{VideoError, VideoFormat, VideoInfo, VideoOptions};
+use crate::structs::{
+    CaptionFormat, CommentSort, DownloadEvent, InnertubeClient, OverwritePolicy, QuickInfo,
+    RequestOptions, Thumbnail, ThumbnailQuality, VideoError, VideoFormat, VideoInfo, VideoOptions,
+    WatchNext,
+};
+
+#[cfg(feature = "ffmpeg")]
+use crate::stream::FFmpegStream;
+use crate::structs::{VideoQuality, VideoSearchOptions};
 
 #[cfg(feature = "ffmpeg")]
 use crate::structs::FFmpegArgs;
+#[cfg(feature = "ffmpeg")]
+use crate::utils::{ffmpeg_remux, remux_chapters, RemuxInput, RemuxStream};
+
+#[cfg(feature = "tags")]
+use crate::tags::write_tags;
+
+#[cfg(feature = "checksum")]
+use crate::checksum::{write_sidecar, Hasher};
+
+#[cfg(feature = "ffmpeg")]
+use crate::template::format_extension;
+use crate::template::render_filename_template;
 
 use crate::utils::{
-    add_format_meta, between, choose_format, clean_video_details, get_functions, get_html,
-    get_html5player, get_random_v6_ip, get_video_id, is_not_yet_broadcasted, is_play_error,
-    is_private_video, is_rental, sort_formats,
+    add_format_meta, add_po_token, between, build_client, choose_format, clean_video_details,
+    get_clip_id, get_functions, get_html, get_html5player, get_iframe_html5player,
+    get_live_poll_delay, get_playability_status, get_signature_timestamp, get_start_time,
+    get_url_param, get_video_id, innertube_request, innertube_request_with_client, is_age_gated,
+    is_age_restricted, is_drm_protected, is_rental, make_absolute_url, needs_client_fallback,
+    set_url_param, sort_formats,
 };
 
+/// Shortest interval between checks of [`Video::wait_until_live`]
+#[cfg(feature = "live")]
+const MIN_LIVE_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Results of [`Video::get_basic_info`] and [`Video::get_info`] kept by [`VideoOptions::info_cache_ttl`]
+static INFO_CACHE: Lazy<Mutex<LruCache<VideoInfo>>> =
+    Lazy::new(|| Mutex::new(LruCache::new(128, None)));
+
+/// Configure the count of entries in the info cache of [`VideoOptions::info_cache_ttl`], shared by all videos.
+/// Least recently used entries are evicted first, `0` disables the cache. Default is `128`
+/// # Example
+/// ```ignore
+/// set_info_cache_capacity(1024);
+/// ```
+pub fn set_info_cache_capacity(capacity: usize) {
+    INFO_CACHE
+        .lock()
+        .expect("IMPOSSIBLE")
+        .configure(capacity, None);
+}
+
+/// Key of the info cache. Options which change the player response (proxy, cookies, headers, middlewares,
+/// injected client, InnerTube clients, PO token provider) are hashed into the key, so videos fetched with
+/// other options do not share entries
+fn info_cache_key(video_id: &str, kind: &str, options: &VideoOptions) -> String {
+    use std::hash::{Hash, Hasher};
+
+    let request_options = &options.request_options;
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+
+    #[cfg(not(target_arch = "wasm32"))]
+    format!("{:?}", request_options.proxy).hash(&mut hasher);
+    request_options.cookies.hash(&mut hasher);
+    request_options.cookies_file.hash(&mut hasher);
+    request_options.ipv6_block.hash(&mut hasher);
+    if let Some(headers) = &request_options.headers {
+        let mut headers: Vec<(&str, &[u8])> = headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_bytes()))
+            .collect();
+        headers.sort();
+        headers.hash(&mut hasher);
+    }
+    request_options.user_agent.hash(&mut hasher);
+    for middleware in &request_options.middlewares {
+        (Arc::as_ptr(middleware) as *const () as usize).hash(&mut hasher);
+    }
+    // Clients carry their own cookie jar and middlewares, only clones of the same client share entries
+    request_options
+        .client
+        .as_ref()
+        .map(|x| Arc::as_ptr(x) as usize)
+        .hash(&mut hasher);
+    options
+        .po_token_provider
+        .as_ref()
+        .map(|x| Arc::as_ptr(x) as *const () as usize)
+        .hash(&mut hasher);
+    options.client.to_string().hash(&mut hasher);
+    for client in &options.fallback_clients {
+        client.to_string().hash(&mut hasher);
+    }
+    options.use_player_endpoint.hash(&mut hasher);
+
+    format!("{video_id}:{kind}:{:016x}", hasher.finish())
+}
+
 #[derive(Clone, derive_more::Display, derivative::Derivative)]
 #[display(fmt = "Video({video_id})")]
 #[derivative(Debug, PartialEq, Eq)]
 pub struct Video {
+    /// Video id, or clip id until the clip is resolved
     video_id: String,
+    playlist_id: Option<String>,
+    /// Source video and range of `youtube.com/clip/...` URLs, resolved on the first request
+    #[derivative(PartialEq = "ignore")]
+    clip: Option<Arc<tokio::sync::OnceCell<Clip>>>,
     options: VideoOptions,
     #[derivative(PartialEq = "ignore")]
     client: reqwest_middleware::ClientWithMiddleware,
@@ -38,27 +145,26 @@ impl Video {
     /// Crate [`Video`] struct to get info or download with default [`VideoOptions`]
     #[cfg_attr(feature = "performance_analysis", flamer::flame)]
     pub fn new(url_or_id: impl Into<String>) -> Result<Self, VideoError> {
-        let video_id = get_video_id(&url_or_id.into()).ok_or(VideoError::VideoNotFound)?;
-
-        let client = reqwest::Client::builder()
-            .build()
-            .map_err(VideoError::Reqwest)?;
+        let url_or_id: String = url_or_id.into();
+        let (video_id, clip) = match get_clip_id(&url_or_id) {
+            Some(clip_id) => (clip_id, Some(Arc::new(tokio::sync::OnceCell::new()))),
+            None => (
+                get_video_id(&url_or_id).ok_or(VideoError::VideoNotFound)?,
+                None,
+            ),
+        };
+        let playlist_id = get_url_param(&url_or_id, "list");
 
-        let retry_policy = reqwest_retry::policies::ExponentialBackoff::builder()
-            .retry_bounds(
-                std::time::Duration::from_millis(500),
-                std::time::Duration::from_millis(10000),
-            )
-            .build_with_max_retries(3);
-        let client = reqwest_middleware::ClientBuilder::new(client)
-            .with(reqwest_retry::RetryTransientMiddleware::new_with_policy(
-                retry_policy,
-            ))
-            .build();
+        let client = build_client(&RequestOptions::default())?;
 
         Ok(Self {
             video_id,
-            options: VideoOptions::default(),
+            playlist_id,
+            clip,
+            options: VideoOptions {
+                start_time: get_start_time(&url_or_id),
+                ..Default::default()
+            },
             client,
         })
     }
@@ -66,47 +172,29 @@ impl Video {
     /// Crate [`Video`] struct to get info or download with custom [`VideoOptions`]
     pub fn new_with_options(
         url_or_id: impl Into<String>,
-        options: VideoOptions,
+        mut options: VideoOptions,
     ) -> Result<Self, VideoError> {
-        let video_id = get_video_id(&url_or_id.into()).ok_or(VideoError::VideoNotFound)?;
-
-        let mut client = reqwest::Client::builder();
-
-        if options.request_options.proxy.is_some() {
-            client = client.proxy(options.request_options.proxy.as_ref().unwrap().clone());
-        }
-
-        if options.request_options.ipv6_block.is_some() {
-            let ipv6 = get_random_v6_ip(options.request_options.ipv6_block.as_ref().unwrap())?;
-            client = client.local_address(ipv6);
-        }
-
-        if options.request_options.cookies.is_some() {
-            let cookie = options.request_options.cookies.as_ref().unwrap();
-            let host = "https://youtube.com".parse::<url::Url>().unwrap();
+        let url_or_id: String = url_or_id.into();
 
-            let jar = reqwest::cookie::Jar::default();
-            jar.add_cookie_str(cookie.as_str(), &host);
-
-            client = client.cookie_provider(Arc::new(jar));
+        if options.start_time.is_none() {
+            options.start_time = get_start_time(&url_or_id);
         }
 
-        let client = client.build().map_err(VideoError::Reqwest)?;
+        let (video_id, clip) = match get_clip_id(&url_or_id) {
+            Some(clip_id) => (clip_id, Some(Arc::new(tokio::sync::OnceCell::new()))),
+            None => (
+                get_video_id(&url_or_id).ok_or(VideoError::VideoNotFound)?,
+                None,
+            ),
+        };
+        let playlist_id = get_url_param(&url_or_id, "list");
 
-        let retry_policy = reqwest_retry::policies::ExponentialBackoff::builder()
-            .retry_bounds(
-                std::time::Duration::from_millis(500),
-                std::time::Duration::from_millis(10000),
-            )
-            .build_with_max_retries(3);
-        let client = reqwest_middleware::ClientBuilder::new(client)
-            .with(reqwest_retry::RetryTransientMiddleware::new_with_policy(
-                retry_policy,
-            ))
-            .build();
+        let client = build_client(&options.request_options)?;
 
         Ok(Self {
             video_id,
+            playlist_id,
+            clip,
             options,
             client,
         })
@@ -115,74 +203,172 @@ impl Video {
     /// Try to get basic information about video
     /// - `HLS` and `DashMPD` formats excluded!
     #[cfg_attr(feature = "performance_analysis", flamer::flame)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip(self), fields(video_id = %self.video_id), err)
+    )]
     pub async fn get_basic_info(&self) -> Result<VideoInfo, VideoError> {
+        if let Some(info) = self.get_cached_info("basic") {
+            return Ok(info);
+        }
+
+        let info = self.fetch_basic_info().await?;
+        self.cache_info("basic", &info);
+
+        Ok(info)
+    }
+
+    /// Wait until an upcoming live stream or premiere starts and return its basic information.
+    /// Video is checked every `poll_interval`, or at the scheduled start time if it comes earlier
+    /// - `poll_interval` is at least 5 seconds and at least the poll delay YouTube asks for
+    /// - Returns directly if the video is not upcoming
+    /// # Example
+    /// ```ignore
+    ///     let video = Video::new("https://www.youtube.com/watch?v=jfKfPfyJRdk").unwrap();
+    ///
+    ///     let info = video.wait_until_live(Duration::from_secs(60)).await.unwrap();
+    ///
+    ///     video.download("live.ts").await.unwrap();
+    /// ```
+    #[cfg(feature = "live")]
+    pub async fn wait_until_live(
+        &self,
+        poll_interval: std::time::Duration,
+    ) -> Result<VideoInfo, VideoError> {
+        loop {
+            // Cached information would never leave the upcoming state
+            let info = self.fetch_basic_info().await?;
+
+            if !info.video_details.is_upcoming {
+                self.cache_info("basic", &info);
+
+                return Ok(info);
+            }
+
+            // Polling faster than YouTube asks for does not start the stream earlier
+            let poll_interval = poll_interval
+                .max(MIN_LIVE_POLL_INTERVAL)
+                .max(get_live_poll_delay(info.raw_player_response()).unwrap_or_default());
+
+            let until_start = info
+                .video_details
+                .scheduled_start_time
+                .map(|x| std::time::UNIX_EPOCH + std::time::Duration::from_secs(x))
+                .and_then(|x| x.duration_since(crate::platform::now()).ok())
+                .filter(|x| !x.is_zero());
+
+            crate::platform::sleep(until_start.map_or(poll_interval, |x| {
+                x.clamp(MIN_LIVE_POLL_INTERVAL, poll_interval)
+            }))
+            .await;
+        }
+    }
+
+    async fn fetch_basic_info(&self) -> Result<VideoInfo, VideoError> {
         let client = &self.client;
 
-        let url_parsed =
-            url::Url::parse_with_params(self.get_video_url().as_str(), &[("hl", "en")])
-                .map_err(VideoError::URLParseError)?;
+        let video_id = self.resolve_video_id().await?;
 
-        let response = get_html(client, url_parsed.as_str(), None).await?;
+        let po_token = self.get_po_token().await?;
 
-        let (player_response, initial_response): (serde_json::Value, serde_json::Value) = {
-            let document = Html::parse_document(&response);
-            let scripts_selector = Selector::parse("script").unwrap();
-            let player_response_string = document
-                .select(&scripts_selector)
-                .filter(|x| x.inner_html().contains("var ytInitialPlayerResponse ="))
-                .map(|x| x.inner_html().replace("var ytInitialPlayerResponse =", ""))
-                .next()
-                .unwrap_or(String::from(""))
-                .trim()
-                .to_string();
-            let mut initial_response_string = document
-                .select(&scripts_selector)
-                .filter(|x| x.inner_html().contains("var ytInitialData ="))
-                .map(|x| x.inner_html().replace("var ytInitialData =", ""))
-                .next()
-                .unwrap_or(String::from(""))
-                .trim()
-                .to_string();
+        let (web_player_response, initial_response, html5player) =
+            if self.options.use_player_endpoint {
+                self.get_player_endpoint_responses(po_token.as_deref())
+                    .await?
+            } else {
+                self.get_watch_page_responses().await?
+            };
 
-            // remove json object last element (;)
-            initial_response_string.pop();
+        // Web client response is already fetched above, other clients are requested from InnerTube
+        let mut player_response = if self.options.client == InnertubeClient::Web {
+            web_player_response.clone()
+        } else {
+            self.get_player_response(&self.options.client, &html5player, po_token.as_deref())
+                .await?
+        };
 
-            let player_response: serde_json::Value = serde_json::from_str(
-                format!(
-                    "{{{}}}}}}}",
-                    between(player_response_string.as_str(), "{", "}}};")
+        let age_restricted = is_age_gated(&player_response)
+            || get_media(&initial_response)
+                .map(|x| is_age_restricted(&x))
+                .unwrap_or(false);
+
+        // Embedded TV player skips the age verification, try it before other fallbacks
+        if age_restricted && needs_client_fallback(&player_response) {
+            if let Ok(embedded_response) = self
+                .get_player_response(
+                    &InnertubeClient::TvEmbedded,
+                    &html5player,
+                    po_token.as_deref(),
                 )
-                .as_str(),
-            )
-            .unwrap();
-            let initial_response: serde_json::Value =
-                serde_json::from_str(&initial_response_string).unwrap();
+                .await
+            {
+                if !needs_client_fallback(&embedded_response) {
+                    player_response = embedded_response;
+                }
+            }
+        }
 
-            (player_response, initial_response)
-        };
+        for fallback_client in self.options.fallback_clients.iter() {
+            if !needs_client_fallback(&player_response) {
+                break;
+            }
 
-        if is_play_error(&player_response, ["ERROR"].to_vec()) {
-            return Err(VideoError::VideoNotFound);
+            if fallback_client == &self.options.client {
+                continue;
+            }
+
+            // Keep the first response to report its error if every fallback fails
+            if let Ok(fallback_response) = self
+                .get_player_response(fallback_client, &html5player, po_token.as_deref())
+                .await
+            {
+                if !needs_client_fallback(&fallback_response) {
+                    player_response = fallback_response;
+                }
+            }
         }
 
-        if is_private_video(&player_response) {
-            return Err(VideoError::VideoIsPrivate);
+        let playability_status = get_playability_status(&player_response);
+
+        if playability_status.is_bot_check() {
+            return Err(self.bot_check_error());
         }
 
-        if player_response.get("streamingData").is_none()
-            || is_rental(&player_response)
-            || is_not_yet_broadcasted(&player_response)
-        {
+        if playability_status.is_removed() || playability_status.is_login_required() {
+            return Err(VideoError::NotPlayable(playability_status));
+        }
+
+        if is_rental(&player_response) {
             return Err(VideoError::VideoSourceNotFound);
         }
 
-        let video_details = clean_video_details(
+        if is_drm_protected(&player_response) {
+            return Err(VideoError::DrmProtected);
+        }
+
+        // Upcoming videos do not have formats yet, details are still returned to show the schedule
+        if player_response.get("streamingData").is_none() && !playability_status.is_upcoming() {
+            if playability_status.is_ok() {
+                return Err(VideoError::VideoSourceNotFound);
+            }
+
+            return Err(VideoError::NotPlayable(playability_status));
+        }
+
+        let mut video_details = clean_video_details(
             &initial_response,
-            &player_response,
-            get_media(&initial_response).unwrap(),
-            self.video_id.clone(),
+            &web_player_response,
+            get_media(&initial_response).unwrap_or_default(),
+            video_id,
         );
 
+        if let Some(clip) = self.clip.as_ref().and_then(|x| x.get()) {
+            video_details.clip_start_ms = Some(clip.start_ms);
+            video_details.clip_end_ms = Some(clip.end_ms);
+        }
+
+        video_details.start_offset = self.options.start_time.map(|x| x.as_secs());
+
         let dash_manifest_url = player_response
             .get("streamingData")
             .and_then(|x| x.get("dashManifestUrl"))
@@ -195,110 +381,82 @@ impl Video {
             .and_then(|x| x.as_str())
             .map(|x| x.to_string());
 
+        let mut formats = parse_video_formats(
+            &player_response,
+            get_functions(
+                html5player,
+                client,
+                self.options.player_cache_dir.as_deref(),
+            )
+            .await?,
+            self.options.js_runtime.clone().or_else(default_js_runtime),
+        )
+        .unwrap_or_default();
+
+        if let Some(po_token) = po_token {
+            for format in formats.iter_mut() {
+                format.raw_url = add_po_token(&format.raw_url, &po_token);
+            }
+        }
+
         Ok(VideoInfo {
             dash_manifest_url,
             hls_manifest_url,
-            formats: {
-                parse_video_formats(
-                    &player_response,
-                    get_functions(get_html5player(response.as_str()).unwrap(), client).await?,
-                )
-                .unwrap_or_default()
-            },
+            formats,
             related_videos: { get_related_videos(&initial_response).unwrap_or_default() },
             video_details,
+            captions: get_captions(&player_response),
+            playability_status,
+            player_response: Arc::new(player_response),
+            initial_response: Arc::new(initial_response),
         })
     }
 
     /// Try to get full information about video
     /// - `HLS` and `DashMPD` formats included!
     #[cfg_attr(feature = "performance_analysis", flamer::flame)]
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip(self), fields(video_id = %self.video_id), err)
+    )]
     pub async fn get_info(&self) -> Result<VideoInfo, VideoError> {
+        if let Some(info) = self.get_cached_info("full") {
+            return Ok(info);
+        }
+
         let client = &self.client;
 
         let mut info = self.get_basic_info().await?;
 
-        let has_manifest = info.dash_manifest_url.is_some() || info.hls_manifest_url.is_some();
-
-        if has_manifest && info.dash_manifest_url.is_some() {
-            // only support HLS-Formats for livestreams for now so all non-HLS streams will be ignored
-            //
-            // let url = info.dash_manifest_url.as_ref().unwrap();
-            // let mut dash_manifest_formats = get_dash_manifest(url, &client).await;
-
-            // for format in dash_manifest_formats.iter_mut() {
-            //     let format_as_object = format.as_object_mut();
-            //     if format_as_object.is_some() {
-            //         let format_as_object = format_as_object.unwrap();
-
-            //         // Insert url
-            //         format_as_object.insert(
-            //             "url".to_string(),
-            //             serde_json::Value::String(url.to_string()),
-            //         );
-
-            //         // Add other metadatas to format map
-            //         add_format_meta(format_as_object);
-
-            //         let format: Result<VideoFormat, serde_json::Error> =
-            //             serde_json::from_value(format.clone());
-            //         if format.is_err() {
-            //             continue;
-            //         }
-            //         info.formats.insert(info.formats.len(), format.unwrap());
-            //     }
-            // }
-        }
-
-        if has_manifest && info.hls_manifest_url.is_some() {
-            let url = info.hls_manifest_url.as_ref().expect("IMPOSSIBLE");
-            let unformated_formats = get_m3u8(url, client).await;
+        if let Some(url) = &info.dash_manifest_url {
+            let dash_manifest_formats = get_dash_manifest(url, client).await;
 
             // Skip if error occured
-            if let Ok(unformated_formats) = unformated_formats {
-                let default_formats = FORMATS.as_object().expect("IMPOSSIBLE");
-                // Push formated infos to formats
-                for (itag, url) in unformated_formats {
-                    let static_format = default_formats.get(&itag);
-                    if static_format.is_none() {
+            if let Ok(dash_manifest_formats) = dash_manifest_formats {
+                for format in dash_manifest_formats {
+                    let format: Result<VideoFormat, serde_json::Error> =
+                        serde_json::from_value(format);
+                    if format.is_err() {
                         continue;
                     }
-                    let static_format = static_format.unwrap();
-
-                    let mut format = serde_json::json!({
-                        "itag": itag.parse::<i32>().unwrap_or(0),
-                        "mimeType": static_format.get("mimeType").expect("IMPOSSIBLE"),
-                    });
-
-                    let format_as_object_mut = format.as_object_mut().unwrap();
-
-                    if !static_format.get("qualityLabel").unwrap().is_null() {
-                        format_as_object_mut.insert(
-                            "qualityLabel".to_string(),
-                            static_format.get("qualityLabel").unwrap().clone(),
-                        );
-                    }
-
-                    if !static_format.get("bitrate").unwrap().is_null() {
-                        format_as_object_mut.insert(
-                            "bitrate".to_string(),
-                            static_format.get("bitrate").unwrap().clone(),
-                        );
-                    }
+                    let format = format.unwrap();
 
-                    if !static_format.get("audioBitrate").unwrap().is_null() {
-                        format_as_object_mut.insert(
-                            "audioBitrate".to_string(),
-                            static_format.get("audioBitrate").unwrap().clone(),
-                        );
+                    // Prefer formats coming from player response
+                    if info.formats.iter().any(|x| x.itag == format.itag) {
+                        continue;
                     }
 
-                    // Insert stream url to format map
-                    format_as_object_mut.insert("url".to_string(), serde_json::Value::String(url));
+                    info.formats.push(format);
+                }
+            }
+        }
 
-                    // Add other metadatas to format map
-                    add_format_meta(format_as_object_mut);
+        if let Some(url) = &info.hls_manifest_url {
+            let hls_formats = get_m3u8(url, client).await;
 
+            // Skip if error occured
+            if let Ok(hls_formats) = hls_formats {
+                for format in hls_formats {
                     let format: Result<VideoFormat, serde_json::Error> =
                         serde_json::from_value(format);
                     if format.is_err() {
@@ -309,214 +467,1386 @@ impl Video {
             }
         }
 
+        // Live formats of the player response are segmented and cannot be downloaded with byte ranges,
+        // only HLS variants of the DVR manifest cover the whole stream
+        if info.video_details.is_post_live_dvr {
+            info.formats.retain(|x| x.is_hls || !x.is_live);
+
+            for format in info.formats.iter_mut().filter(|x| x.is_hls) {
+                format.is_live = true;
+            }
+        }
+
         // Last sort formats
         info.formats.sort_by(sort_formats);
+
+        self.cache_info("full", &info);
+
         Ok(info)
     }
 
-    /// Try to turn [`Stream`] implemented [`LiveStream`] or [`NonLiveStream`] depend on the video.
-    /// If function successfully return can download video chunk by chunk
+    /// [`Video::get_info`] of every URL or ID, running at most `concurrency` requests at once over one shared client.
+    /// Results are in the order of `url_or_ids`, a failed video does not stop the others
+    /// - The first video is fetched alone, so the player script is extracted once and the others find it in the player cache
     /// # Example
     /// ```ignore
-    ///     let video_url = "https://www.youtube.com/watch?v=FZ8BxMU3BYc";
-    ///
-    ///     let video = Video::new(video_url).unwrap();
+    ///     let results = Video::get_info_many(["FZ8BxMU3BYc", "jNQXAC9IVRw"], &VideoOptions::default(), 4)
+    ///         .await
+    ///         .unwrap();
     ///
-    ///     let stream = video.stream().await.unwrap();
-    ///
-    ///     while let Some(chunk) = stream.chunk().await.unwrap() {
-    ///           println!("{:#?}", chunk);
+    ///     for result in results {
+    ///         match result {
+    ///             Ok(info) => println!("{}", info.video_details.title),
+    ///             Err(e) => println!("{e}"),
+    ///         }
     ///     }
     /// ```
-    pub async fn stream(&self) -> Result<Box<dyn Stream + Send + Sync>, VideoError> {
-        let client = &self.client;
-
-        let info = self.get_info().await?;
-        let format = choose_format(&info.formats, &self.options)
-            .map_err(|_op| VideoError::VideoSourceNotFound)?;
+    pub async fn get_info_many<I, S>(
+        url_or_ids: I,
+        options: &VideoOptions,
+        concurrency: usize,
+    ) -> Result<Vec<Result<VideoInfo, VideoError>>, VideoError>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        use futures::StreamExt;
+
+        let mut options = options.clone();
+
+        // Built once, so all videos share the connection pool
+        options.request_options.client = Some(Arc::new(build_client(&options.request_options)?));
+
+        let options = &options;
+        let get_info = |url_or_id: S| async move {
+            Video::new_with_options(url_or_id, options.clone())?
+                .get_info()
+                .await
+        };
 
-        let link = format.url;
+        let mut url_or_ids = url_or_ids.into_iter();
+        let mut results = vec![];
 
-        if link.is_empty() {
-            return Err(VideoError::VideoSourceNotFound);
+        if let Some(url_or_id) = url_or_ids.next() {
+            results.push(get_info(url_or_id).await);
         }
 
-        // Only check for HLS formats for live streams
-        if format.is_hls {
-            #[cfg(feature = "live")]
-            {
-                let stream = LiveStream::new(LiveStreamOptions {
-                    client: Some(client.clone()),
-                    stream_url: link,
-                })?;
+        let rest = futures::stream::iter(url_or_ids.map(get_info))
+            .buffered(concurrency.max(1))
+            .collect::<Vec<_>>()
+            .await;
 
-                return Ok(Box::new(stream));
-            }
-            #[cfg(not(feature = "live"))]
-            {
-                return Err(VideoError::LiveStreamNotSupported);
-            }
-        }
+        results.extend(rest);
 
-        let dl_chunk_size = self
-            .options
-            .download_options
-            .dl_chunk_size
-            // 1024 * 1024 * 10_u64 -> Default is 10MB to avoid Youtube throttle (Bigger than this value can be throttle by Youtube)
-            .unwrap_or(1024 * 1024 * 10_u64);
+        Ok(results)
+    }
 
-        let start = 0;
-        let end = start + dl_chunk_size;
+    /// Try to get watch-next feed of the video
+    /// - If the video created with a URL contains `list` parameter, [`WatchNext::playlist`] is filled with the playlist panel
+    pub async fn get_watch_next(&self) -> Result<WatchNext, VideoError> {
+        let mut body = serde_json::json!({
+            "videoId": self.resolve_video_id().await?,
+        });
 
-        let mut content_length = format
-            .content_length
-            .unwrap_or("0".to_string())
-            .parse::<u64>()
-            .unwrap_or(0);
+        if let Some(playlist_id) = &self.playlist_id {
+            body["playlistId"] = serde_json::Value::String(playlist_id.clone());
+        }
 
-        // Get content length from source url if content_length is 0
-        if content_length == 0 {
-            let content_length_response = client
-                .get(&link)
-                .send()
-                .await
-                .map_err(VideoError::ReqwestMiddleware)?
-                .content_length()
-                .ok_or(VideoError::VideoNotFound)?;
+        let response = innertube_request(&self.client, "next", body).await?;
 
-            content_length = content_length_response;
-        }
+        Ok(get_watch_next(&response))
+    }
 
-        let stream = NonLiveStream::new(NonLiveStreamOptions {
-            client: Some(client.clone()),
-            link,
-            content_length,
-            dl_chunk_size,
-            start,
-            end,
-            #[cfg(feature = "ffmpeg")]
-            ffmpeg_args: None,
-        })?;
+    /// Try to get first page of comments sorted by [`CommentSort`]. Use [`Comments::next`] to get next pages
+    /// # Example
+    /// ```ignore
+    ///     let video = Video::new("https://www.youtube.com/watch?v=FZ8BxMU3BYc").unwrap();
+    ///
+    ///     let mut comments = video.comments(CommentSort::Top).await.unwrap();
+    ///
+    ///     while comments.has_next() {
+    ///         println!("{:#?}", comments.next().await.unwrap());
+    ///     }
+    /// ```
+    pub async fn comments(&self, sort: CommentSort) -> Result<Comments, VideoError> {
+        let mut comments =
+            Comments::get(&self.client, self.resolve_video_id().await?, sort).await?;
 
-        Ok(Box::new(stream))
+        comments.next().await?;
+
+        Ok(comments)
     }
 
-    #[cfg(feature = "ffmpeg")]
-    /// Try to turn [`Stream`] implemented [`LiveStream`] or [`NonLiveStream`] depend on the video with [`FFmpegArgs`].
-    /// If function successfully return can download video with applied ffmpeg filters and formats chunk by chunk
+    /// Try to get first page of related videos with full metadata. Use [`RelatedVideos::next`] to follow the watch next continuations
     /// # Example
     /// ```ignore
-    ///     let video_url = "https://www.youtube.com/watch?v=FZ8BxMU3BYc";
+    ///     let video = Video::new("https://www.youtube.com/watch?v=FZ8BxMU3BYc").unwrap();
     ///
-    ///     let video = Video::new(video_url).unwrap();
+    ///     let mut related = video.related().await.unwrap();
     ///
-    ///     let stream = video.stream().await.unwrap();
+    ///     related.fetch(Some(50)).await.unwrap();
     ///
-    ///     while let Some(chunk) = stream.chunk().await.unwrap() {
-    ///           println!("{:#?}", chunk);
+    ///     println!("{:#?}", related.videos);
+    /// ```
+    pub async fn related(&self) -> Result<RelatedVideos, VideoError> {
+        RelatedVideos::get(&self.client, self.resolve_video_id().await?).await
+    }
+
+    /// Try to get the live chat of a currently live video. Use [`LiveChat::next`] to poll new messages
+    /// or [`LiveChat::stream`] to receive them as an async stream
+    /// # Example
+    /// ```ignore
+    ///     let video = Video::new("https://www.youtube.com/watch?v=jfKfPfyJRdk").unwrap();
+    ///
+    ///     let mut chat = video.live_chat().await.unwrap();
+    ///
+    ///     while chat.has_next() {
+    ///         println!("{:#?}", chat.next().await.unwrap());
+    ///         tokio::time::sleep(chat.timeout).await;
     ///     }
     /// ```
-    pub async fn stream_with_ffmpeg(
-        &self,
-        ffmpeg_args: Option<FFmpegArgs>,
-    ) -> Result<Box<dyn Stream + Send + Sync>, VideoError> {
-        let client = &self.client;
+    pub async fn live_chat(&self) -> Result<LiveChat, VideoError> {
+        LiveChat::get(&self.client, self.resolve_video_id().await?).await
+    }
 
-        let info = self.get_info().await?;
-        let format = choose_format(&info.formats, &self.options)
-            .map_err(|_op| VideoError::VideoSourceNotFound)?;
+    /// Try to download caption track of the `lang` language code converted to the [`CaptionFormat`]
+    /// - Manually created tracks take precedence over auto-generated ones
+    /// # Example
+    /// ```ignore
+    ///     let video = Video::new("https://www.youtube.com/watch?v=FZ8BxMU3BYc").unwrap();
+    ///
+    ///     let srt = video.download_caption("en", CaptionFormat::Srt).await.unwrap();
+    /// ```
+    pub async fn download_caption(
+        &self,
+        lang: impl Into<String>,
+        format: CaptionFormat,
+    ) -> Result<String, VideoError> {
+        let lang: String = lang.into();
+
+        let info = self.get_basic_info().await?;
+
+        let track = info
+            .captions
+            .iter()
+            .filter(|x| x.language_code == lang)
+            .min_by_key(|x| x.is_auto_generated)
+            .ok_or(VideoError::CaptionNotFound(lang.clone()))?;
+
+        // Base URL may already carry a `fmt` (e.g. `srv3`), which would win over an appended one
+        let url =
+            set_url_param(&track.base_url, "fmt", "json3").map_err(VideoError::URLParseError)?;
+
+        let body: serde_json::Value = self
+            .client
+            .get(url.as_str())
+            .send()
+            .await
+            .map_err(VideoError::from)?
+            .json()
+            .await
+            .map_err(|_x| VideoError::BodyCannotParsed)?;
+
+        Ok(convert_captions(&body, &format))
+    }
 
-        let link = format.url;
+    /// Download the thumbnail of the video to the file as JPEG.
+    /// Falls back to the next lower [`ThumbnailQuality`] if the image does not exist (e.g. `Max` of SD videos),
+    /// then to [`Thumbnail::best`] of the video details
+    /// # Example
+    /// ```ignore
+    ///     let video = Video::new("https://www.youtube.com/watch?v=FZ8BxMU3BYc").unwrap();
+    ///
+    ///     video
+    ///         .download_thumbnail("thumbnail.jpg", ThumbnailQuality::Max)
+    ///         .await
+    ///         .unwrap();
+    /// ```
+    pub async fn download_thumbnail<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        quality: ThumbnailQuality,
+    ) -> Result<(), VideoError> {
+        let video_id = self.resolve_video_id().await?;
 
-        if link.is_empty() {
-            return Err(VideoError::VideoSourceNotFound);
-        }
+        for fallback in ThumbnailQuality::ALL.iter().skip_while(|x| **x != quality) {
+            let url = format!(
+                "https://i.ytimg.com/vi/{video_id}/{}.jpg",
+                fallback.file_name()
+            );
 
-        // Only check for HLS formats for live streams
-        if format.is_hls {
-            #[cfg(feature = "live")]
-            {
-                let stream = LiveStream::new(LiveStreamOptions {
-                    client: Some(client.clone()),
-                    stream_url: link,
-                })?;
+            let response = self
+                .client
+                .get(&url)
+                .send()
+                .await
+                .map_err(VideoError::from)?;
 
-                return Ok(Box::new(stream));
+            // Missing sizes respond 404 with a placeholder image
+            if !response.status().is_success() {
+                continue;
             }
-            #[cfg(not(feature = "live"))]
+
+            let image = response.bytes().await.map_err(VideoError::Reqwest)?;
+
+            return std::fs::write(path.as_ref(), image)
+                .map_err(|e| VideoError::DownloadError(e.to_string()));
+        }
+
+        let info = self.get_basic_info().await?;
+        let thumbnail =
+            Thumbnail::best(&info.video_details.thumbnails).ok_or(VideoError::ThumbnailNotFound)?;
+
+        let image = self
+            .client
+            .get(&thumbnail.url)
+            .send()
+            .await
+            .map_err(VideoError::from)?
+            .error_for_status()
+            .map_err(|_| VideoError::ThumbnailNotFound)?
+            .bytes()
+            .await
+            .map_err(VideoError::Reqwest)?;
+
+        std::fs::write(path.as_ref(), image).map_err(|e| VideoError::DownloadError(e.to_string()))
+    }
+
+    /// Check that the video exists with a single small request to the oEmbed endpoint,
+    /// without the watch page and player script requests of [`Video::get_basic_info`]
+    /// - Private videos and videos with disabled embedding respond `401` and count as existing
+    /// # Example
+    /// ```ignore
+    ///     let video = Video::new("https://www.youtube.com/watch?v=FZ8BxMU3BYc").unwrap();
+    ///
+    ///     if video.exists().await.unwrap() {
+    ///         video.download("video.mp4").await.unwrap();
+    ///     }
+    /// ```
+    pub async fn exists(&self) -> Result<bool, VideoError> {
+        let response = self.oembed().await?;
+
+        match response.status().as_u16() {
+            401 | 403 => return Ok(true),
+            400 | 404 => return Ok(false),
+            _ => {}
+        }
+
+        response.error_for_status().map_err(VideoError::Reqwest)?;
+
+        Ok(true)
+    }
+
+    /// Title, author and thumbnail of the video from the oEmbed endpoint with a single small request.
+    /// Use [`Video::get_basic_info`] for everything else
+    /// - Returns [`VideoError::VideoNotFound`] for missing videos
+    /// - Private videos and videos with disabled embedding return a `401` [`VideoError::Reqwest`] error
+    pub async fn quick_info(&self) -> Result<QuickInfo, VideoError> {
+        let response = self.oembed().await?;
+
+        if matches!(response.status().as_u16(), 400 | 404) {
+            return Err(VideoError::VideoNotFound);
+        }
+
+        response
+            .error_for_status()
+            .map_err(VideoError::Reqwest)?
+            .json()
+            .await
+            .map_err(|_x| VideoError::BodyCannotParsed)
+    }
+
+    async fn oembed(&self) -> Result<reqwest::Response, VideoError> {
+        let video_id = self.resolve_video_id().await?;
+
+        let url = url::Url::parse_with_params(
+            "https://www.youtube.com/oembed",
+            &[
+                ("url", format!("https://www.youtube.com/watch?v={video_id}")),
+                ("format", "json".to_string()),
+            ],
+        )
+        .map_err(VideoError::URLParseError)?;
+
+        self.client
+            .get(url.as_str())
+            .send()
+            .await
+            .map_err(VideoError::from)
+    }
+
+    /// Try to turn [`Stream`] implemented [`LiveStream`] or [`NonLiveStream`] depend on the video.
+    /// If function successfully return can download video chunk by chunk
+    /// # Example
+    /// ```ignore
+    ///     let video_url = "https://www.youtube.com/watch?v=FZ8BxMU3BYc";
+    ///
+    ///     let video = Video::new(video_url).unwrap();
+    ///
+    ///     let stream = video.stream().await.unwrap();
+    ///
+    ///     while let Some(chunk) = stream.chunk().await.unwrap() {
+    ///           println!("{:#?}", chunk);
+    ///     }
+    /// ```
+    pub async fn stream(&self) -> Result<Box<dyn Stream + Send + Sync>, VideoError> {
+        self.stream_from(0).await
+    }
+
+    /// Same as [`Video::stream`] but start downloading from the `offset` byte with Range requests
+    /// - `offset` is ignored for live streams and OTF formats
+    /// - If `offset` is bigger than content length, the stream returns [`None`] directly
+    pub async fn stream_from(
+        &self,
+        offset: u64,
+    ) -> Result<Box<dyn Stream + Send + Sync>, VideoError> {
+        let (info, format) = self.info_and_format().await?;
+
+        self.stream_format_from(&info, &format, offset).await
+    }
+
+    /// [`Video::stream_from`] of the format chosen from already fetched `info`
+    #[cfg_attr(not(feature = "live"), allow(unused_variables))]
+    async fn stream_format_from(
+        &self,
+        info: &VideoInfo,
+        format: &VideoFormat,
+        offset: u64,
+    ) -> Result<Box<dyn Stream + Send + Sync>, VideoError> {
+        let link = self.format_url(format).await?;
+
+        if link.is_empty() {
+            return Err(VideoError::VideoSourceNotFound);
+        }
+
+        // Only check for HLS formats for live streams
+        if format.is_hls {
+            #[cfg(feature = "live")]
+            {
+                let stream = LiveStream::new(self.live_options(info, link))?;
+
+                return Ok(Box::new(stream));
+            }
+            #[cfg(not(feature = "live"))]
+            {
+                return Err(VideoError::LiveStreamNotSupported);
+            }
+        }
+
+        // OTF and segmented DASH formats have no byte ranges, `offset` is ignored like for live streams
+        if let Some(stream) = self.segmented_stream(format, &link)? {
+            return Ok(stream);
+        }
+
+        let content_length = self.format_content_length(format, &link).await?;
+
+        let stream =
+            NonLiveStream::new(self.non_live_options(format, link, offset, content_length))?;
+
+        Ok(Box::new(stream))
+    }
+
+    #[cfg(feature = "ffmpeg")]
+    /// Try to turn [`Stream`] implemented [`LiveStream`] or [`NonLiveStream`] depend on the video with [`FFmpegArgs`].
+    /// If function successfully return can download video with applied ffmpeg filters and formats chunk by chunk
+    /// # Example
+    /// ```ignore
+    ///     let video_url = "https://www.youtube.com/watch?v=FZ8BxMU3BYc";
+    ///
+    ///     let video = Video::new(video_url).unwrap();
+    ///
+    ///     let stream = video.stream().await.unwrap();
+    ///
+    ///     while let Some(chunk) = stream.chunk().await.unwrap() {
+    ///           println!("{:#?}", chunk);
+    ///     }
+    /// ```
+    pub async fn stream_with_ffmpeg(
+        &self,
+        ffmpeg_args: Option<FFmpegArgs>,
+    ) -> Result<Box<dyn Stream + Send + Sync>, VideoError> {
+        let (info, format) = self.info_and_format().await?;
+
+        self.stream_format_with_ffmpeg(&info, &format, ffmpeg_args)
+            .await
+    }
+
+    #[cfg(feature = "ffmpeg")]
+    /// [`Video::stream_with_ffmpeg`] of the format chosen from already fetched `info`
+    async fn stream_format_with_ffmpeg(
+        &self,
+        info: &VideoInfo,
+        format: &VideoFormat,
+        ffmpeg_args: Option<FFmpegArgs>,
+    ) -> Result<Box<dyn Stream + Send + Sync>, VideoError> {
+        let link = self.format_url(format).await?;
+
+        if link.is_empty() {
+            return Err(VideoError::VideoSourceNotFound);
+        }
+
+        // Only check for HLS formats for live streams
+        if format.is_hls {
+            #[cfg(feature = "live")]
+            {
+                let stream = LiveStream::new(self.live_options(info, link))?;
+
+                return Ok(Box::new(stream));
+            }
+            #[cfg(not(feature = "live"))]
             {
                 return Err(VideoError::LiveStreamNotSupported);
             }
         }
 
-        let dl_chunk_size = self
-            .options
-            .download_options
-            .dl_chunk_size
-            // 1024 * 1024 * 10_u64 -> Default is 10MB to avoid Youtube throttle (Bigger than this value can be throttle by Youtube)
-            .unwrap_or(1024 * 1024 * 10_u64);
+        if let Some(stream) = self.segmented_stream(format, &link)? {
+            let ffmpeg_args = ffmpeg_args.map(|x| x.build()).unwrap_or_default();
+
+            if ffmpeg_args.is_empty() {
+                return Ok(stream);
+            }
+
+            self.emit(DownloadEvent::FfmpegStarted);
+
+            return Ok(Box::new(FFmpegStream::new(stream, &ffmpeg_args)?));
+        }
+
+        let content_length = self.format_content_length(format, &link).await?;
+
+        let stream = NonLiveStream::new(self.non_live_options(format, link, 0, content_length))?;
+
+        let ffmpeg_args = ffmpeg_args.map(|x| x.build()).unwrap_or_default();
+
+        if ffmpeg_args.is_empty() {
+            return Ok(Box::new(stream));
+        }
+
+        self.emit(DownloadEvent::FfmpegStarted);
+
+        Ok(Box::new(FFmpegStream::new(Box::new(stream), &ffmpeg_args)?))
+    }
+
+    /// Download video directly to the file
+    /// - Data is written to `<path>.part`, which is renamed to `path` after the download succeeds
+    /// - If [`DownloadOptions::resume`](crate::DownloadOptions::resume) is `true` and the `.part` file already exists,
+    ///   download continues from the end of it instead of restarting from zero
+    /// - Existing files are handled by [`DownloadOptions::overwrite`](crate::DownloadOptions::overwrite)
+    /// - Returns [`VideoError::IncompleteDownload`] if the file size is different from the content length of the format
+    pub async fn download<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), VideoError> {
+        self.download_file(path.as_ref()).await?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "checksum")]
+    /// Same as [`Video::download`] and return the lowercase hex hash of the downloaded data.
+    /// [`None`] if [`DownloadOptions::hash`](crate::DownloadOptions::hash) is not set
+    pub async fn download_with_hash<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> Result<Option<String>, VideoError> {
+        self.download_file(path.as_ref()).await
+    }
+
+    /// Download video into `dir` with a file name rendered from a yt-dlp style `template`
+    /// (see [`render_filename_template`](crate::render_filename_template)) and return the path of the file
+    /// # Example
+    /// ```ignore
+    ///     let video = Video::new("https://www.youtube.com/watch?v=FZ8BxMU3BYc").unwrap();
+    ///
+    ///     let path = video
+    ///         .download_with_template("downloads", "%(title)s [%(id)s].%(ext)s")
+    ///         .await
+    ///         .unwrap();
+    /// ```
+    pub async fn download_with_template<P: AsRef<std::path::Path>>(
+        &self,
+        dir: P,
+        template: &str,
+    ) -> Result<std::path::PathBuf, VideoError> {
+        let path = self.template_path(dir.as_ref(), template).await?;
+
+        self.download(&path).await?;
+
+        Ok(path)
+    }
+
+    /// Path of [`Video::download_with_template`] without downloading
+    pub(crate) async fn template_path(
+        &self,
+        dir: &std::path::Path,
+        template: &str,
+    ) -> Result<std::path::PathBuf, VideoError> {
+        let info = self.get_info().await?;
+        let format = choose_format(&info.formats, &self.options)
+            .map_err(|_op| VideoError::VideoSourceNotFound)?;
+
+        Ok(dir.join(render_filename_template(
+            template,
+            &info.video_details,
+            &format,
+        )))
+    }
+
+    /// Estimate the size in bytes of [`Video::download`] without downloading, so disk requirements can be checked first.
+    /// Uses the content length of the chosen format, otherwise bitrate × duration.
+    /// [`None`] for live streams and formats without both
+    /// # Example
+    /// ```ignore
+    ///     let video = Video::new("https://www.youtube.com/watch?v=FZ8BxMU3BYc").unwrap();
+    ///
+    ///     if let Some(size) = video.estimate_size().await.unwrap() {
+    ///         println!("~{} MB", size / 1024 / 1024);
+    ///     }
+    /// ```
+    pub async fn estimate_size(&self) -> Result<Option<u64>, VideoError> {
+        let info = self.get_info().await?;
+        let format = choose_format(&info.formats, &self.options)
+            .map_err(|_op| VideoError::VideoSourceNotFound)?;
+
+        if format.is_live || format.is_hls {
+            return Ok(None);
+        }
+
+        if let Some(content_length) = format.content_length.filter(|x| *x > 0) {
+            return Ok(Some(content_length));
+        }
+
+        let duration_ms = format
+            .approx_duration_ms
+            .or_else(|| {
+                info.video_details
+                    .length_seconds
+                    .parse::<u64>()
+                    .ok()
+                    .map(|x| x * 1000)
+            })
+            .unwrap_or(0);
+        let bitrate = format.average_bitrate.unwrap_or(format.bitrate);
+
+        if duration_ms == 0 || bitrate == 0 {
+            return Ok(None);
+        }
+
+        // Bitrate is bits per second
+        Ok(Some(bitrate * duration_ms / 8000))
+    }
+
+    /// Download to the file and return its hash if [`DownloadOptions::hash`](crate::DownloadOptions::hash) is set
+    async fn download_file(&self, path: &std::path::Path) -> Result<Option<String>, VideoError> {
+        let result = self.download_file_inner(path).await;
+
+        match &result {
+            Ok(_) => self.emit(DownloadEvent::Completed {
+                path: path.to_path_buf(),
+            }),
+            Err(e) => self.emit(DownloadEvent::Failed {
+                error: e.to_string(),
+            }),
+        }
+
+        result
+    }
+
+    async fn download_file_inner(
+        &self,
+        path: &std::path::Path,
+    ) -> Result<Option<String>, VideoError> {
+        let overwrite = self.options.download_options.overwrite;
+
+        if path.exists() {
+            match overwrite {
+                OverwritePolicy::Overwrite => {}
+                OverwritePolicy::Skip | OverwritePolicy::Resume => return Ok(None),
+                OverwritePolicy::Error => {
+                    return Err(VideoError::FileAlreadyExists(path.to_path_buf()))
+                }
+            }
+        }
+
+        let (info, format) = self.info_and_format().await?;
+
+        let part_path = part_path(path);
+
+        let hash = match self.download_part(&part_path, &info, &format).await {
+            Ok(hash) => hash,
+            Err(e) => {
+                if !self.options.download_options.keep_part_file
+                    && overwrite != OverwritePolicy::Resume
+                {
+                    let _ = std::fs::remove_file(&part_path);
+                }
+
+                return Err(e);
+            }
+        };
+
+        #[cfg(feature = "ffmpeg")]
+        self.embed_chapters(&part_path, &info, &format).await?;
+
+        // Rename replaces the file in one step, so a file at `path` is always complete
+        std::fs::rename(&part_path, path).map_err(|e| VideoError::DownloadError(e.to_string()))?;
+
+        #[cfg(feature = "tags")]
+        self.tag_download(path, &info)?;
+
+        if self.options.download_options.write_info_json {
+            std::fs::write(
+                path.with_extension("info.json"),
+                serde_json::to_vec_pretty(&info.to_info_json())
+                    .map_err(|e| VideoError::DownloadError(e.to_string()))?,
+            )
+            .map_err(|e| VideoError::DownloadError(e.to_string()))?;
+        }
+
+        #[cfg(feature = "checksum")]
+        if let (Some(hash), Some(algo)) = (&hash, self.options.download_options.hash) {
+            if self.options.download_options.hash_sidecar {
+                write_sidecar(path, algo, hash)?;
+            }
+        }
+
+        Ok(hash)
+    }
+
+    #[cfg(feature = "ffmpeg")]
+    /// Remux the downloaded `.part` file with the chapters of the video if
+    /// [`DownloadOptions::embed_chapters`](crate::DownloadOptions::embed_chapters) is set
+    async fn embed_chapters(
+        &self,
+        part_path: &std::path::Path,
+        info: &VideoInfo,
+        format: &VideoFormat,
+    ) -> Result<(), VideoError> {
+        if !self.options.download_options.embed_chapters
+            || info.video_details.chapters.is_empty()
+            || format.is_hls
+        {
+            return Ok(());
+        }
+
+        let Some(duration) = format_duration(format, info) else {
+            return Ok(());
+        };
+
+        // ffmpeg picks the output container by the extension, so the output can not end with `.part`
+        let mut output = part_path.as_os_str().to_owned();
+        output.push(".chapters.");
+        output.push(format_extension(format));
+        let output = std::path::PathBuf::from(output);
+
+        self.emit(DownloadEvent::FfmpegStarted);
+
+        let result = ffmpeg_remux(
+            vec![RemuxInput {
+                url: part_path.to_string_lossy().to_string(),
+                stream: RemuxStream::All,
+                start: None,
+                end: None,
+            }],
+            remux_chapters(
+                &info.video_details.chapters,
+                duration,
+                std::time::Duration::ZERO,
+                None,
+            ),
+            &output,
+        )
+        .await;
+
+        if let Err(e) = result {
+            let _ = std::fs::remove_file(&output);
+
+            return Err(e);
+        }
+
+        std::fs::rename(&output, part_path).map_err(|e| VideoError::DownloadError(e.to_string()))
+    }
+
+    /// Download to the `.part` file, continuing it if [`DownloadOptions::resume`](crate::DownloadOptions::resume) is set
+    async fn download_part(
+        &self,
+        path: &std::path::Path,
+        info: &VideoInfo,
+        format: &VideoFormat,
+    ) -> Result<Option<String>, VideoError> {
+        use std::{fs::OpenOptions, io::Write};
+
+        let resume = self.options.download_options.resume
+            || self.options.download_options.overwrite == OverwritePolicy::Resume;
+
+        let offset = if resume {
+            std::fs::metadata(path).map(|x| x.len()).unwrap_or(0)
+        } else {
+            0
+        };
+
+        let stream = self.stream_format_from(info, format, offset).await?;
+
+        // Live streams cannot be resumed, so start over
+        let append = offset > 0 && stream.content_length() > 0;
+
+        #[cfg(feature = "checksum")]
+        let mut hasher = match self.options.download_options.hash {
+            Some(algo) => {
+                let mut hasher = Hasher::new(algo);
+
+                if append {
+                    hasher.update_from_file(path)?;
+                }
+
+                Some(hasher)
+            }
+            None => None,
+        };
+
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .append(append)
+            .truncate(!append)
+            .open(path)
+            .map_err(|e| VideoError::DownloadError(e.to_string()))?;
+
+        while let Some(chunk) = stream.chunk().await? {
+            file.write_all(&chunk)
+                .map_err(|e| VideoError::DownloadError(e.to_string()))?;
+
+            #[cfg(feature = "checksum")]
+            if let Some(hasher) = &mut hasher {
+                hasher.update(&chunk);
+            }
+        }
+
+        // Live streams have no content length
+        let expected = stream.content_length() as u64;
+        if expected > 0 {
+            let got = file
+                .metadata()
+                .map_err(|e| VideoError::DownloadError(e.to_string()))?
+                .len();
+
+            if got != expected {
+                return Err(VideoError::IncompleteDownload { expected, got });
+            }
+        }
+
+        #[cfg(feature = "checksum")]
+        if let Some(hasher) = hasher {
+            return Ok(Some(hasher.finalize()));
+        }
+
+        Ok(None)
+    }
+
+    /// Download video into any [`tokio::io::AsyncWrite`] (e.g. socket, in-memory buffer) instead of a file
+    /// - Returns [`VideoError::IncompleteDownload`] if the written bytes are different from the content length of the format
+    /// # Example
+    /// ```ignore
+    ///     let video = Video::new("https://www.youtube.com/watch?v=FZ8BxMU3BYc").unwrap();
+    ///
+    ///     let mut buf = vec![];
+    ///     video.download_to_writer(&mut buf).await.unwrap();
+    /// ```
+    pub async fn download_to_writer<W: tokio::io::AsyncWrite + Unpin + ?Sized>(
+        &self,
+        writer: &mut W,
+    ) -> Result<(), VideoError> {
+        let stream = self.stream().await?;
+
+        write_stream(stream.as_ref(), writer).await
+    }
+
+    #[cfg(feature = "object_store")]
+    /// Upload video to `path` of an [`object_store::ObjectStore`] (e.g. S3, GCS, Azure) with a multipart upload,
+    /// without staging it on the local disk. The upload is aborted if the download fails
+    /// # Example
+    /// ```ignore
+    ///     let store = object_store::aws::AmazonS3Builder::from_env()
+    ///         .with_bucket_name("videos")
+    ///         .build()
+    ///         .unwrap();
+    ///
+    ///     let video = Video::new("https://www.youtube.com/watch?v=FZ8BxMU3BYc").unwrap();
+    ///
+    ///     video
+    ///         .download_to_store(&store, &object_store::path::Path::from("video.mp4"))
+    ///         .await
+    ///         .unwrap();
+    /// ```
+    pub async fn download_to_store(
+        &self,
+        store: &dyn object_store::ObjectStore,
+        path: &object_store::path::Path,
+    ) -> Result<(), VideoError> {
+        use tokio::io::AsyncWriteExt;
+
+        let (multipart_id, mut writer) = store
+            .put_multipart(path)
+            .await
+            .map_err(|e| VideoError::ObjectStore(e.to_string()))?;
+
+        let result = match self.download_to_writer(&mut writer).await {
+            // Shutdown completes the multipart upload
+            Ok(()) => writer
+                .shutdown()
+                .await
+                .map_err(|e| VideoError::ObjectStore(e.to_string())),
+            Err(e) => Err(e),
+        };
+
+        if result.is_err() {
+            // Parts of an unfinished upload are billed until they are aborted
+            let _ = store.abort_multipart(path, &multipart_id).await;
+        }
+
+        result
+    }
+
+    #[cfg(feature = "ffmpeg")]
+    /// Same as [`Video::download_to_writer`] with ffmpeg args applied to the data
+    pub async fn download_with_ffmpeg_to_writer<W: tokio::io::AsyncWrite + Unpin + ?Sized>(
+        &self,
+        writer: &mut W,
+        ffmpeg_args: Option<FFmpegArgs>,
+    ) -> Result<(), VideoError> {
+        let stream = self.stream_with_ffmpeg(ffmpeg_args).await?;
+
+        write_stream(stream.as_ref(), writer).await
+    }
+
+    #[cfg(feature = "ffmpeg")]
+    /// Download video with ffmpeg args directly to the file
+    pub async fn download_with_ffmpeg<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        ffmpeg_args: Option<FFmpegArgs>,
+    ) -> Result<(), VideoError> {
+        use std::{fs::File, io::Write};
+
+        let path = path.as_ref();
+
+        let (info, format) = self.info_and_format().await?;
+
+        let stream = self
+            .stream_format_with_ffmpeg(&info, &format, ffmpeg_args)
+            .await?;
+
+        let mut file = File::create(path).map_err(|e| VideoError::DownloadError(e.to_string()))?;
+
+        while let Some(chunk) = stream.chunk().await? {
+            file.write_all(&chunk)
+                .map_err(|e| VideoError::DownloadError(e.to_string()))?;
+        }
+
+        #[cfg(feature = "tags")]
+        self.tag_download(path, &info)?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "ffmpeg")]
+    /// Download the highest quality adaptive video and audio formats and mux them into one file with ffmpeg.
+    /// Progressive formats are capped at 720p, adaptive formats are not
+    /// - Streams are copied without re-encoding, so the container of `path` must support both codecs
+    /// (e.g. `.mkv` for VP9/AV1 video with Opus audio)
+    /// - [`VideoOptions::quality`] and [`VideoOptions::filter`] are ignored
+    /// - Chapters of the video are written into the output if the container supports them
+    /// # Example
+    /// ```ignore
+    ///     let video = Video::new("https://www.youtube.com/watch?v=FZ8BxMU3BYc").unwrap();
+    ///
+    ///     video.download_muxed("video.mkv").await.unwrap();
+    /// ```
+    pub async fn download_muxed<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> Result<(), VideoError> {
+        let path = path.as_ref();
+
+        let info = self.get_info().await?;
+
+        let video_format = choose_format(
+            &info.formats,
+            &VideoOptions {
+                quality: VideoQuality::HighestVideo,
+                filter: VideoSearchOptions::Video,
+                ..self.options.clone()
+            },
+        )
+        .map_err(|_op| VideoError::VideoSourceNotFound)?;
+        let audio_format = choose_format(
+            &info.formats,
+            &VideoOptions {
+                quality: VideoQuality::HighestAudio,
+                filter: VideoSearchOptions::Audio,
+                ..self.options.clone()
+            },
+        )
+        .map_err(|_op| VideoError::VideoSourceNotFound)?;
+
+        if video_format.is_hls || audio_format.is_hls {
+            return Err(VideoError::DownloadError(
+                "Live streams can not be muxed".to_string(),
+            ));
+        }
+
+        let mut video_path = path.as_os_str().to_owned();
+        video_path.push(".video.part");
+        let mut audio_path = path.as_os_str().to_owned();
+        audio_path.push(".audio.part");
+
+        let result = async {
+            self.download_format(&video_format, video_path.as_ref())
+                .await?;
+            self.download_format(&audio_format, audio_path.as_ref())
+                .await?;
+
+            self.emit(DownloadEvent::FfmpegStarted);
+
+            ffmpeg_remux(
+                vec![
+                    RemuxInput {
+                        url: video_path.to_string_lossy().to_string(),
+                        stream: RemuxStream::Video,
+                        start: None,
+                        end: None,
+                    },
+                    RemuxInput {
+                        url: audio_path.to_string_lossy().to_string(),
+                        stream: RemuxStream::Audio,
+                        start: None,
+                        end: None,
+                    },
+                ],
+                format_duration(&video_format, &info)
+                    .map(|duration| {
+                        remux_chapters(
+                            &info.video_details.chapters,
+                            duration,
+                            std::time::Duration::ZERO,
+                            None,
+                        )
+                    })
+                    .unwrap_or_default(),
+                path,
+            )
+            .await
+        }
+        .await;
+
+        // Intermediate files are removed even if the download or muxing failed
+        let _ = std::fs::remove_file(&video_path);
+        let _ = std::fs::remove_file(&audio_path);
+
+        result?;
+
+        #[cfg(feature = "tags")]
+        self.tag_download(path, &info)?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "ffmpeg")]
+    /// Download a single non-live format to the file
+    async fn download_format(
+        &self,
+        format: &VideoFormat,
+        path: &std::path::Path,
+    ) -> Result<(), VideoError> {
+        use std::{fs::File, io::Write};
+
+        let stream = self.format_stream(format).await?;
+
+        let mut file = File::create(path).map_err(|e| VideoError::DownloadError(e.to_string()))?;
+
+        while let Some(chunk) = stream.chunk().await? {
+            file.write_all(&chunk)
+                .map_err(|e| VideoError::DownloadError(e.to_string()))?;
+        }
+
+        Ok(())
+    }
+
+    /// [`NonLiveStreamOptions`] of the format from the `start` byte, `content_length` is where the stream ends
+    pub(crate) fn non_live_options(
+        &self,
+        format: &VideoFormat,
+        link: String,
+        start: u64,
+        content_length: u64,
+    ) -> NonLiveStreamOptions {
+        let download_options = &self.options.download_options;
+
+        let dl_chunk_size = download_options
+            .dl_chunk_size
+            // 1024 * 1024 * 10_u64 -> Default is 10MB to avoid Youtube throttle (Bigger than this value can be throttle by Youtube)
+            .unwrap_or(1024 * 1024 * 10_u64);
+
+        NonLiveStreamOptions {
+            client: Some(self.client.clone()),
+            link,
+            content_length,
+            dl_chunk_size,
+            start,
+            end: start + dl_chunk_size,
+            progress_callback: download_options.progress_callback.clone(),
+            event_callback: download_options.event_callback.clone(),
+            concurrency: download_options.concurrency.unwrap_or(1),
+            link_refresher: Some(self.link_refresher(format)),
+            request_options: Some(self.options.request_options.clone()),
+            adaptive_chunk_size: download_options.adaptive_chunk_size,
+            chunk_retry: download_options.chunk_retry.clone(),
+            max_bytes_per_second: download_options.max_bytes_per_second,
+        }
+    }
+
+    #[cfg(feature = "live")]
+    /// [`LiveStreamOptions`] of an HLS format of the video
+    pub(crate) fn live_options(&self, info: &VideoInfo, link: String) -> LiveStreamOptions {
+        let download_options = &self.options.download_options;
+
+        LiveStreamOptions {
+            client: Some(self.client.clone()),
+            stream_url: if download_options.live_audio_only {
+                info.hls_manifest_url.clone().unwrap_or(link)
+            } else {
+                link
+            },
+            progress_callback: download_options.progress_callback.clone(),
+            event_callback: download_options.event_callback.clone(),
+            poll_interval: download_options.live_poll_interval,
+            segment_retries: download_options.live_segment_retries,
+            stall_timeout: download_options.live_stall_timeout,
+            max_bytes_per_second: download_options.max_bytes_per_second,
+            ended: info.video_details.is_post_live_dvr,
+            audio_only: download_options.live_audio_only,
+            latency: download_options.live_latency,
+        }
+    }
+
+    /// [`OtfStreamOptions`] of an OTF format
+    pub(crate) fn otf_options(&self, link: String) -> OtfStreamOptions {
+        let download_options = &self.options.download_options;
+
+        OtfStreamOptions {
+            client: Some(self.client.clone()),
+            link,
+            progress_callback: download_options.progress_callback.clone(),
+            event_callback: download_options.event_callback.clone(),
+            segment_retries: download_options.live_segment_retries,
+            max_bytes_per_second: download_options.max_bytes_per_second,
+        }
+    }
+
+    /// [`DashStreamOptions`] of a segmented DASH format, `link` is the manifest URL
+    pub(crate) fn dash_options(&self, format: &VideoFormat, link: String) -> DashStreamOptions {
+        let download_options = &self.options.download_options;
+
+        DashStreamOptions {
+            client: Some(self.client.clone()),
+            manifest_url: link,
+            representation_id: format.itag.to_string(),
+            progress_callback: download_options.progress_callback.clone(),
+            event_callback: download_options.event_callback.clone(),
+            poll_interval: download_options.live_poll_interval,
+            segment_retries: download_options.live_segment_retries,
+            max_bytes_per_second: download_options.max_bytes_per_second,
+        }
+    }
+
+    /// Content length of the format, requested from the URL if the player response does not have it
+    pub(crate) async fn format_content_length(
+        &self,
+        format: &VideoFormat,
+        link: &str,
+    ) -> Result<u64, VideoError> {
+        if let Some(content_length) = format.content_length.filter(|x| *x > 0) {
+            return Ok(content_length);
+        }
+
+        self.client
+            .get(link)
+            .send()
+            .await
+            .map_err(VideoError::from)?
+            .content_length()
+            .ok_or(VideoError::VideoNotFound)
+    }
+
+    /// [`OtfStream`] or [`DashStream`] of formats downloaded segment by segment, [`None`] for other formats
+    pub(crate) fn segmented_stream(
+        &self,
+        format: &VideoFormat,
+        link: &str,
+    ) -> Result<Option<Box<dyn Stream + Send + Sync>>, VideoError> {
+        if format.is_otf {
+            return Ok(Some(Box::new(OtfStream::new(
+                self.otf_options(link.to_string()),
+            )?)));
+        }
+
+        if format.is_dash_segmented {
+            return Ok(Some(Box::new(DashStream::new(
+                self.dash_options(format, link.to_string()),
+            )?)));
+        }
+
+        Ok(None)
+    }
+
+    /// Stream a single non-live format from the beginning
+    async fn format_stream(
+        &self,
+        format: &VideoFormat,
+    ) -> Result<Box<dyn Stream + Send + Sync>, VideoError> {
+        let link = self.format_url(format).await?;
+
+        if link.is_empty() {
+            return Err(VideoError::VideoSourceNotFound);
+        }
+
+        if let Some(stream) = self.segmented_stream(format, &link)? {
+            return Ok(stream);
+        }
 
-        let start = 0;
-        let end = start + dl_chunk_size;
+        let content_length = self.format_content_length(format, &link).await?;
 
-        let mut content_length = format
-            .content_length
-            .unwrap_or("0".to_string())
-            .parse::<u64>()
-            .unwrap_or(0);
+        let stream = NonLiveStream::new(self.non_live_options(format, link, 0, content_length))?;
 
-        // Get content length from source url if content_length is 0
-        if content_length == 0 {
-            let content_length_response = client
-                .get(&link)
-                .send()
-                .await
-                .map_err(VideoError::ReqwestMiddleware)?
-                .content_length()
-                .ok_or(VideoError::VideoNotFound)?;
+        Ok(Box::new(stream))
+    }
+
+    /// Download the highest quality Opus audio and write it as an Ogg Opus file (`.opus`) without ffmpeg.
+    /// WebM container of the format is demuxed in memory
+    /// - [`VideoOptions::quality`] and [`VideoOptions::filter`] are ignored
+    /// # Example
+    /// ```ignore
+    ///     let video = Video::new("https://www.youtube.com/watch?v=FZ8BxMU3BYc").unwrap();
+    ///
+    ///     video.download_opus("audio.opus").await.unwrap();
+    /// ```
+    pub async fn download_opus<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+    ) -> Result<(), VideoError> {
+        #[cfg_attr(not(feature = "tags"), allow(unused_variables))]
+        let (info, data) = self
+            .download_audio_format(|x| {
+                x.mime_type.container == "webm"
+                    && x.mime_type.audio_codec.as_deref() == Some("opus")
+            })
+            .await?;
+
+        let ogg = webm_to_ogg_opus(&data)?;
+
+        std::fs::write(path.as_ref(), ogg).map_err(|e| VideoError::DownloadError(e.to_string()))?;
+
+        #[cfg(feature = "tags")]
+        self.tag_download(path.as_ref(), &info)?;
+
+        Ok(())
+    }
+
+    /// Download the highest quality AAC audio and write it as a regular `.m4a` file without ffmpeg.
+    /// Fragmented MP4 container of the format is rewritten in memory, so players can seek without the DASH index
+    /// - [`VideoOptions::quality`] and [`VideoOptions::filter`] are ignored
+    /// # Example
+    /// ```ignore
+    ///     let video = Video::new("https://www.youtube.com/watch?v=FZ8BxMU3BYc").unwrap();
+    ///
+    ///     video.download_m4a("audio.m4a").await.unwrap();
+    /// ```
+    pub async fn download_m4a<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), VideoError> {
+        #[cfg_attr(not(feature = "tags"), allow(unused_variables))]
+        let (info, data) = self
+            .download_audio_format(|x| {
+                x.mime_type.container == "mp4"
+                    && x.mime_type
+                        .audio_codec
+                        .as_deref()
+                        .map(|x| x.starts_with("mp4a"))
+                        .unwrap_or(false)
+            })
+            .await?;
+
+        let m4a = fragmented_mp4_to_m4a(&data)?;
+
+        std::fs::write(path.as_ref(), m4a).map_err(|e| VideoError::DownloadError(e.to_string()))?;
+
+        #[cfg(feature = "tags")]
+        self.tag_download(path.as_ref(), &info)?;
+
+        Ok(())
+    }
+
+    #[cfg(feature = "tags")]
+    /// Write title, artist, album, year and video URL tags of the video into an already downloaded file.
+    /// ID3v2, MP4 or Vorbis comment tags are chosen by the file type
+    /// - Returns an error if the file type does not support tags (e.g. WebM)
+    /// # Example
+    /// ```ignore
+    ///     let video = Video::new("https://www.youtube.com/watch?v=FZ8BxMU3BYc").unwrap();
+    ///
+    ///     video.download_m4a("audio.m4a").await.unwrap();
+    ///     video.write_tags("audio.m4a").await.unwrap();
+    /// ```
+    pub async fn write_tags<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), VideoError> {
+        let info = self.get_basic_info().await?;
 
-            content_length = content_length_response;
+        if !write_tags(path.as_ref(), &info)? {
+            return Err(VideoError::Tags("File type is not supported".to_string()));
         }
 
-        let stream = NonLiveStream::new(NonLiveStreamOptions {
-            client: Some(client.clone()),
-            link,
-            content_length,
-            dl_chunk_size,
-            start,
-            end,
-            ffmpeg_args,
-        })?;
+        Ok(())
+    }
 
-        Ok(Box::new(stream))
+    #[cfg(feature = "tags")]
+    /// Tag a finished download if [`DownloadOptions::write_tags`](crate::DownloadOptions::write_tags) is set, unsupported file types are skipped
+    fn tag_download(&self, path: &std::path::Path, info: &VideoInfo) -> Result<(), VideoError> {
+        if !self.options.download_options.write_tags {
+            return Ok(());
+        }
+
+        write_tags(path, info)?;
+
+        Ok(())
     }
 
-    /// Download video directly to the file
-    pub async fn download<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), VideoError> {
-        use std::{fs::File, io::Write};
+    /// Download the highest quality audio-only format matching `filter` into memory, with the info it was chosen from
+    async fn download_audio_format(
+        &self,
+        filter: impl Fn(&VideoFormat) -> bool + Sync + Send + 'static,
+    ) -> Result<(VideoInfo, Vec<u8>), VideoError> {
+        let info = self.get_info().await?;
 
-        let stream = self.stream().await?;
+        let format = choose_format(
+            &info.formats,
+            &VideoOptions {
+                quality: VideoQuality::HighestAudio,
+                filter: VideoSearchOptions::Custom(Arc::new(filter)),
+                ..self.options.clone()
+            },
+        )
+        .map_err(|_op| VideoError::FormatNotFound)?;
 
-        let mut file = File::create(path).map_err(|e| VideoError::DownloadError(e.to_string()))?;
+        if format.is_hls {
+            return Err(VideoError::DownloadError(
+                "Audio of live streams can not be extracted".to_string(),
+            ));
+        }
+
+        let stream = self.format_stream(&format).await?;
+        let mut data = Vec::with_capacity(stream.content_length());
 
         while let Some(chunk) = stream.chunk().await? {
-            file.write_all(&chunk)
-                .map_err(|e| VideoError::DownloadError(e.to_string()))?;
+            data.extend_from_slice(&chunk);
         }
 
-        Ok(())
+        Ok((info, data))
     }
 
-    #[cfg(feature = "ffmpeg")]
-    /// Download video with ffmpeg args directly to the file
-    pub async fn download_with_ffmpeg<P: AsRef<std::path::Path>>(
+    /// Download only the `start`-`end` time window of the video directly to the file
+    /// - Progressive formats (video and audio in one file) are cut with byte ranges estimated from the format duration,
+    ///   so bounds are approximate and the file may need remuxing by the player
+    /// - Adaptive formats are cut with ffmpeg `-ss`/`-to` and require `ffmpeg` feature, only the requested window is fetched.
+    ///   Chapters inside the window are written into the output
+    /// - Live streams are not supported
+    /// # Example
+    /// ```ignore
+    ///     let video = Video::new("https://www.youtube.com/watch?v=FZ8BxMU3BYc").unwrap();
+    ///
+    ///     video
+    ///         .download_section("section.mp4", Duration::from_secs(30), Duration::from_secs(60))
+    ///         .await
+    ///         .unwrap();
+    /// ```
+    pub async fn download_section<P: AsRef<std::path::Path>>(
         &self,
         path: P,
-        ffmpeg_args: Option<FFmpegArgs>,
+        start: std::time::Duration,
+        end: std::time::Duration,
     ) -> Result<(), VideoError> {
         use std::{fs::File, io::Write};
 
-        let stream = self.stream_with_ffmpeg(ffmpeg_args).await?;
+        if end <= start {
+            return Err(VideoError::DownloadError(format!(
+                "Section end ({end:?}) must be after its start ({start:?})"
+            )));
+        }
+
+        let info = self.get_info().await?;
+        let format = choose_format(&info.formats, &self.options)
+            .map_err(|_op| VideoError::VideoSourceNotFound)?;
+
+        if format.is_hls {
+            return Err(VideoError::DownloadError(
+                "Sections of live streams can not be downloaded".to_string(),
+            ));
+        }
+
+        let link = self.format_url(&format).await?;
+
+        if link.is_empty() {
+            return Err(VideoError::VideoSourceNotFound);
+        }
+
+        if !(format.has_video && format.has_audio) {
+            #[cfg(feature = "ffmpeg")]
+            {
+                self.emit(DownloadEvent::FfmpegStarted);
+
+                // Input seeking makes ffmpeg request only the needed byte ranges of the source
+                return ffmpeg_remux(
+                    vec![RemuxInput {
+                        url: link,
+                        stream: RemuxStream::All,
+                        start: Some(start),
+                        end: Some(end),
+                    }],
+                    format_duration(&format, &info)
+                        .map(|duration| {
+                            remux_chapters(&info.video_details.chapters, duration, start, Some(end))
+                        })
+                        .unwrap_or_default(),
+                    path.as_ref(),
+                )
+                .await;
+            }
+            #[cfg(not(feature = "ffmpeg"))]
+            {
+                return Err(VideoError::DownloadError(
+                    "Sections of adaptive formats require `ffmpeg` feature".to_string(),
+                ));
+            }
+        }
+
+        let duration_ms = format_duration(&format, &info)
+            .map(|x| x.as_millis() as u64)
+            .ok_or(VideoError::DownloadError(
+                "Video duration is unknown".to_string(),
+            ))?;
+
+        let content_length = self.format_content_length(&format, &link).await?;
+
+        // Bytes are assumed to be spread evenly over the duration
+        let byte_at = |time: std::time::Duration| {
+            ((time.as_millis() * content_length as u128) / duration_ms as u128)
+                .min(content_length as u128) as u64
+        };
+
+        let start_byte = byte_at(start);
+        let end_byte = byte_at(end);
+
+        if start_byte >= end_byte {
+            return Err(VideoError::DownloadError(format!(
+                "Section start ({start:?}) is after the end of the video"
+            )));
+        }
+
+        // Content length of the stream is the end of the section
+        let stream =
+            NonLiveStream::new(self.non_live_options(&format, link, start_byte, end_byte))?;
 
         let mut file = File::create(path).map_err(|e| VideoError::DownloadError(e.to_string()))?;
 
@@ -529,13 +1859,250 @@ impl Video {
     }
 
     /// Get video URL
+    /// - Clip URL is returned for `youtube.com/clip/...` URLs until the clip is resolved by a request
     pub fn get_video_url(&self) -> String {
-        format!("{}{}", BASE_URL, &self.video_id)
+        match &self.clip {
+            Some(clip) if !clip.initialized() => {
+                format!("https://www.youtube.com/clip/{}", &self.video_id)
+            }
+            _ => format!("{}{}", BASE_URL, self.get_video_id()),
+        }
     }
 
     /// Get video id
+    /// - Clip id is returned for `youtube.com/clip/...` URLs until the clip is resolved by a request
     pub fn get_video_id(&self) -> String {
-        self.video_id.clone()
+        match self.clip.as_ref().and_then(|x| x.get()) {
+            Some(clip) => clip.video_id.clone(),
+            None => self.video_id.clone(),
+        }
+    }
+
+    /// Get playlist id if the video created with a URL contains `list` parameter
+    pub fn get_playlist_id(&self) -> Option<String> {
+        self.playlist_id.clone()
+    }
+
+    /// Scrape player response, initial data and player script path from the watch page
+    async fn get_watch_page_responses(
+        &self,
+    ) -> Result<(serde_json::Value, serde_json::Value, String), VideoError> {
+        let url_parsed =
+            url::Url::parse_with_params(self.get_video_url().as_str(), &[("hl", "en")])
+                .map_err(VideoError::URLParseError)?;
+
+        let response = get_html(&self.client, url_parsed.as_str(), None).await?;
+
+        if is_bot_check_page(&response) {
+            return Err(self.bot_check_error());
+        }
+
+        let (player_response, initial_response): (serde_json::Value, serde_json::Value) = {
+            let document = Html::parse_document(&response);
+            let scripts_selector = Selector::parse("script").unwrap();
+            let player_response_string = document
+                .select(&scripts_selector)
+                .filter(|x| x.inner_html().contains("var ytInitialPlayerResponse ="))
+                .map(|x| x.inner_html().replace("var ytInitialPlayerResponse =", ""))
+                .next()
+                .unwrap_or(String::from(""))
+                .trim()
+                .to_string();
+            let mut initial_response_string = document
+                .select(&scripts_selector)
+                .filter(|x| x.inner_html().contains("var ytInitialData ="))
+                .map(|x| x.inner_html().replace("var ytInitialData =", ""))
+                .next()
+                .unwrap_or(String::from(""))
+                .trim()
+                .to_string();
+
+            // remove json object last element (;)
+            initial_response_string.pop();
+
+            let player_response: serde_json::Value = serde_json::from_str(
+                format!(
+                    "{{{}}}}}}}",
+                    between(player_response_string.as_str(), "{", "}}};")
+                )
+                .as_str(),
+            )
+            .map_err(|_| VideoError::BodyCannotParsed)?;
+            let initial_response: serde_json::Value =
+                serde_json::from_str(&initial_response_string)
+                    .map_err(|_| VideoError::BodyCannotParsed)?;
+
+            (player_response, initial_response)
+        };
+
+        let html5player = get_html5player(response.as_str()).ok_or(VideoError::BodyCannotParsed)?;
+
+        Ok((player_response, initial_response, html5player))
+    }
+
+    /// Request player response and initial data from InnerTube `player` and `next` endpoints
+    /// with web client, without downloading the watch page
+    async fn get_player_endpoint_responses(
+        &self,
+        po_token: Option<&str>,
+    ) -> Result<(serde_json::Value, serde_json::Value, String), VideoError> {
+        let html5player = get_iframe_html5player(&self.client).await?;
+
+        let player_response = self
+            .get_player_response(&InnertubeClient::Web, &html5player, po_token)
+            .await?;
+
+        let initial_response = innertube_request(
+            &self.client,
+            "next",
+            serde_json::json!({
+                "videoId": self.get_video_id(),
+            }),
+        )
+        .await?;
+
+        Ok((player_response, initial_response, html5player))
+    }
+
+    /// Request player response from InnerTube `player` endpoint as the given client
+    async fn get_player_response(
+        &self,
+        innertube_client: &InnertubeClient,
+        html5player: &str,
+        po_token: Option<&str>,
+    ) -> Result<serde_json::Value, VideoError> {
+        let mut body = serde_json::json!({
+            "videoId": self.get_video_id(),
+            "playbackContext": {
+                "contentPlaybackContext": {
+                    "html5Preference": "HTML5_PREF_WANTS",
+                },
+            },
+            "contentCheckOk": true,
+            "racyCheckOk": true,
+        });
+
+        // Ciphered signatures must match the player script which deciphers them
+        if innertube_client.is_ciphered() {
+            if let Some(signature_timestamp) = get_signature_timestamp(
+                html5player,
+                &self.client,
+                self.options.player_cache_dir.as_deref(),
+            )
+            .await?
+            {
+                body["playbackContext"]["contentPlaybackContext"]["signatureTimestamp"] =
+                    serde_json::json!(signature_timestamp);
+            }
+        }
+
+        if let Some(po_token) = po_token {
+            body["serviceIntegrityDimensions"] = serde_json::json!({
+                "poToken": po_token,
+            });
+        }
+
+        innertube_request_with_client(&self.client, "player", body, innertube_client).await
+    }
+
+    /// Get `kind` info from the info cache if [`VideoOptions::info_cache_ttl`] is set and the entry is not older
+    fn get_cached_info(&self, kind: &str) -> Option<VideoInfo> {
+        let ttl = self.options.info_cache_ttl?;
+
+        INFO_CACHE
+            .lock()
+            .expect("IMPOSSIBLE")
+            .get_within(&info_cache_key(&self.video_id, kind, &self.options), ttl)
+    }
+
+    fn cache_info(&self, kind: &str, info: &VideoInfo) {
+        if self.options.info_cache_ttl.is_none() {
+            return;
+        }
+
+        INFO_CACHE.lock().expect("IMPOSSIBLE").insert(
+            info_cache_key(&self.video_id, kind, &self.options),
+            info.clone(),
+        );
+    }
+
+    /// Get id of the video, `youtube.com/clip/...` URLs are resolved to the source video once
+    async fn resolve_video_id(&self) -> Result<String, VideoError> {
+        match &self.clip {
+            Some(clip) => Ok(clip
+                .get_or_try_init(|| get_clip(&self.client, &self.video_id))
+                .await?
+                .video_id
+                .clone()),
+            None => Ok(self.video_id.clone()),
+        }
+    }
+
+    /// Get PO token from [`VideoOptions::po_token_provider`] if it exists
+    pub(crate) async fn get_po_token(&self) -> Result<Option<String>, VideoError> {
+        match &self.options.po_token_provider {
+            Some(provider) => provider.get_po_token(&self.get_video_id()).await,
+            None => Ok(None),
+        }
+    }
+
+    fn bot_check_error(&self) -> VideoError {
+        let request_options = &self.options.request_options;
+
+        VideoError::BotCheckRequired {
+            cookies_may_help: request_options.cookies.is_none()
+                && request_options.cookies_file.is_none(),
+            po_token_may_help: self.options.po_token_provider.is_none(),
+        }
+    }
+
+    /// Info of the video and the format chosen by the options, reported as [`DownloadEvent`]s
+    pub(crate) async fn info_and_format(&self) -> Result<(VideoInfo, VideoFormat), VideoError> {
+        let info = self.get_info().await?;
+
+        self.emit(DownloadEvent::InfoFetched {
+            video_id: info.video_details.video_id.clone(),
+            title: info.video_details.title.clone(),
+        });
+
+        let format = choose_format(&info.formats, &self.options)
+            .map_err(|_op| VideoError::VideoSourceNotFound)?;
+
+        self.emit(DownloadEvent::FormatChosen {
+            itag: format.itag,
+            mime_type: format.mime_type.mime.to_string(),
+            content_length: format.content_length,
+        });
+
+        Ok((info, format))
+    }
+
+    /// Send the event to [`DownloadOptions::event_callback`](crate::DownloadOptions::event_callback)
+    pub(crate) fn emit(&self, event: DownloadEvent) {
+        if let Some(event_callback) = &self.options.download_options.event_callback {
+            event_callback.call(&event);
+        }
+    }
+
+    /// URL of the format, a new one is requested if it is already expired
+    pub(crate) async fn format_url(&self, format: &VideoFormat) -> Result<String, VideoError> {
+        if format.is_expired() {
+            return self.link_refresher(format).refresh_link(&self.client).await;
+        }
+
+        Ok(format.resolve_url())
+    }
+
+    /// [`LinkRefresher`] that finds `format` in a new player response when its URL is rejected
+    pub(crate) fn link_refresher(&self, format: &VideoFormat) -> Arc<dyn LinkRefresher> {
+        let mut video = self.clone();
+        // Cached info has the same rejected URL
+        video.options.info_cache_ttl = None;
+
+        Arc::new(FormatLinkRefresher {
+            video,
+            format: format.clone(),
+        })
     }
 
     #[allow(dead_code)]
@@ -549,127 +2116,168 @@ impl Video {
     }
 }
 
-// #[allow(dead_code)]
-// async fn get_dash_manifest(
-//     url: &str,
-//     client: &reqwest_middleware::ClientWithMiddleware,
-// ) -> Result<Vec<serde_json::Value>, VideoError> {
-//     let base_url = url::Url::parse(BASE_URL).expect("BASE_URL corrapt");
-//     let base_url_host = base_url.host_str().expect("BASE_URL host corrapt");
-
-//     let url = url::Url::parse(url)
-//         .and_then(|mut x| {
-//             let set_host_result = x.set_host(Some(base_url_host));
-//             if set_host_result.is_err() {
-//                 return Err(set_host_result.expect_err("How can be possible"));
-//             }
-//             Ok(x)
-//         })
-//         .map(|x| x.as_str().to_string())
-//         .unwrap_or("".to_string());
-
-//     let body = get_html(client, &url, None).await?;
-
-//     let mut parser = Parser::from_reader(body.as_bytes());
-
-//     let mut formats: Vec<serde_json::Value> = vec![];
-//     let mut adaptation_set: HashMap<String, String> = HashMap::default();
-
-//     loop {
-//         let res = parser.read_event();
-
-//         match res {
-//             Ok(event) => match event {
-//                 Event::EndDocument => {
-//                     break;
-//                 }
-//                 Event::StartElement(node) => {
-//                     if node.name.to_lowercase() == "ADAPTATIONSET".to_lowercase() {
-//                         adaptation_set = node
-//                             .attributes()
-//                             .map(|x| (x.name.to_lowercase(), x.value.to_lowercase()))
-//                             .collect();
-//                     } else if node.name.to_lowercase() == "REPRESENTATION".to_lowercase() {
-//                         let representation: HashMap<String, String> = node
-//                             .attributes()
-//                             .map(|x| (x.name.to_lowercase(), x.value.to_lowercase()))
-//                             .collect();
-//                         if representation.contains_key("id") {
-//                             let itag = representation.get("id").unwrap();
-//                             let itag = itag.parse::<i32>();
-//                             if let Ok(itag) = itag {
-//                                 let mut format = serde_json::json!({
-//                                     "itag": itag,
-//                                     "bitrate": representation.get("bandwith").and_then(|x| x.parse::<i32>().ok()).unwrap_or(0),
-//                                     "mimeType": format!(r#"{}; codecs="{}""#,adaptation_set.get("mimetype").map(|x| x.as_str()).unwrap_or(""),representation.get("codecs").map(|x| x.as_str()).unwrap_or(""))
-//                                 });
-//                                 let format_as_object_mut =
-//                                     format.as_object_mut().expect("IMPOSSIBLE");
-//                                 if representation.contains_key("height") {
-//                                     format_as_object_mut.insert(
-//                                         "width".to_string(),
-//                                         serde_json::Value::Number(
-//                                             representation
-//                                                 .get("width")
-//                                                 .and_then(|x| x.parse::<i32>().ok())
-//                                                 .unwrap_or(0)
-//                                                 .into(),
-//                                         ),
-//                                     );
-//                                     format_as_object_mut.insert(
-//                                         "height".to_string(),
-//                                         serde_json::Value::Number(
-//                                             representation
-//                                                 .get("height")
-//                                                 .and_then(|x| x.parse::<i32>().ok())
-//                                                 .unwrap_or(0)
-//                                                 .into(),
-//                                         ),
-//                                     );
-//                                     format_as_object_mut.insert(
-//                                         "fps".to_string(),
-//                                         serde_json::Value::Number(
-//                                             representation
-//                                                 .get("framerate")
-//                                                 .and_then(|x| x.parse::<i32>().ok())
-//                                                 .unwrap_or(0)
-//                                                 .into(),
-//                                         ),
-//                                     );
-//                                 } else {
-//                                     format_as_object_mut.insert(
-//                                         "audioSampleRate".to_string(),
-//                                         serde_json::Value::Number(
-//                                             representation
-//                                                 .get("audiosamplingrate")
-//                                                 .and_then(|x| x.parse::<i32>().ok())
-//                                                 .unwrap_or(0)
-//                                                 .into(),
-//                                         ),
-//                                     );
-//                                 }
-
-//                                 formats.insert(formats.len(), format);
-//                             }
-//                         }
-//                     }
-//                 }
-//                 _ => {}
-//             },
-//             Err(err) => {
-//                 println!("{}", err);
-//                 break;
-//             }
-//         }
-//     }
-
-//     Ok(formats)
-// }
+/// Temporary file of a download, `video.mp4` is written as `video.mp4.part`
+fn part_path(path: &std::path::Path) -> std::path::PathBuf {
+    let mut part_path = path.as_os_str().to_owned();
+    part_path.push(".part");
+
+    part_path.into()
+}
+
+/// Write every chunk of the stream into `writer` and check the written size
+async fn write_stream<W: tokio::io::AsyncWrite + Unpin + ?Sized>(
+    stream: &(dyn Stream + Send + Sync),
+    writer: &mut W,
+) -> Result<(), VideoError> {
+    use tokio::io::AsyncWriteExt;
+
+    let mut got = 0;
+
+    while let Some(chunk) = stream.chunk().await? {
+        writer
+            .write_all(&chunk)
+            .await
+            .map_err(|e| VideoError::DownloadError(e.to_string()))?;
+
+        got += chunk.len() as u64;
+    }
+
+    writer
+        .flush()
+        .await
+        .map_err(|e| VideoError::DownloadError(e.to_string()))?;
+
+    // Live and transcoded streams have no content length
+    let expected = stream.content_length() as u64;
+    if expected > 0 && got != expected {
+        return Err(VideoError::IncompleteDownload { expected, got });
+    }
+
+    Ok(())
+}
+
+/// Watch page is replaced by the bot check interstitial, it does not have the player response
+fn is_bot_check_page(html: &str) -> bool {
+    !html.contains("ytInitialPlayerResponse")
+        && (html.contains("confirm you’re not a bot") || html.contains("confirm you're not a bot"))
+}
+
+/// Duration of the format, or of the video if the format does not have one
+fn format_duration(format: &VideoFormat, info: &VideoInfo) -> Option<std::time::Duration> {
+    format
+        .approx_duration_ms
+        .or_else(|| {
+            info.video_details
+                .length_seconds
+                .parse::<u64>()
+                .ok()
+                .map(|x| x * 1000)
+        })
+        .filter(|x| *x > 0)
+        .map(std::time::Duration::from_millis)
+}
+
+struct FormatLinkRefresher {
+    video: Video,
+    format: VideoFormat,
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl LinkRefresher for FormatLinkRefresher {
+    async fn refresh_link(
+        &self,
+        client: &reqwest_middleware::ClientWithMiddleware,
+    ) -> Result<String, VideoError> {
+        let mut video = self.video.clone();
+        video.client = client.clone();
+
+        let info = video.get_info().await?;
+
+        // Same itag and size, so the download can continue from the same offset
+        let link = info
+            .formats
+            .iter()
+            .find(|x| {
+                x.itag == self.format.itag
+                    && x.is_dash_mpd == self.format.is_dash_mpd
+                    && x.content_length == self.format.content_length
+            })
+            .map(|x| x.resolve_url())
+            .unwrap_or_default();
+
+        if link.is_empty() {
+            return Err(VideoError::VideoSourceNotFound);
+        }
+
+        Ok(link)
+    }
+}
+
+/// Source video and range of a clip
+#[derive(Clone, Debug)]
+struct Clip {
+    video_id: String,
+    start_ms: u64,
+    end_ms: u64,
+}
+
+async fn get_clip(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    clip_id: &str,
+) -> Result<Clip, VideoError> {
+    static CLIP_VIDEO_ID_REGEX: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r#""currentVideoEndpoint":\{.*?"watchEndpoint":\{"videoId":"([\w-]{11})""#)
+            .unwrap()
+    });
+    static CLIP_RANGE_REGEX: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r#""startTimeMs":"(\d+)","endTimeMs":"(\d+)""#).unwrap());
+
+    let body = get_html(
+        client,
+        format!("https://www.youtube.com/clip/{clip_id}"),
+        None,
+    )
+    .await?;
+
+    let video_id = CLIP_VIDEO_ID_REGEX
+        .captures(&body)
+        .and_then(|x| x.get(1))
+        .map(|x| x.as_str().to_string())
+        .ok_or(VideoError::VideoNotFound)?;
+
+    let (start_ms, end_ms) = CLIP_RANGE_REGEX
+        .captures(&body)
+        .and_then(|x| {
+            Some((
+                x.get(1)?.as_str().parse::<u64>().ok()?,
+                x.get(2)?.as_str().parse::<u64>().ok()?,
+            ))
+        })
+        .ok_or(VideoError::BodyCannotParsed)?;
+
+    Ok(Clip {
+        video_id,
+        start_ms,
+        end_ms,
+    })
+}
+
+async fn get_dash_manifest(
+    url: &str,
+    client: &reqwest_middleware::ClientWithMiddleware,
+) -> Result<Vec<serde_json::Value>, VideoError> {
+    let url = make_absolute_url(BASE_URL, url)?;
+
+    let body = get_html(client, url.as_str(), None).await?;
+
+    Ok(parse_dash_manifest(&body, url.as_str()))
+}
 
 async fn get_m3u8(
     url: &str,
     client: &reqwest_middleware::ClientWithMiddleware,
-) -> Result<Vec<(String, String)>, VideoError> {
+) -> Result<Vec<serde_json::Value>, VideoError> {
     let base_url = url::Url::parse(BASE_URL).expect("BASE_URL corrapt");
     let base_url_host = base_url.host_str().expect("BASE_URL host corrapt");
 
@@ -686,26 +2294,65 @@ async fn get_m3u8(
 
     let body = get_html(client, &url, None).await?;
 
-    let http_regex = regex::Regex::new(r"^https?://").unwrap();
-    let itag_regex = regex::Regex::new(r"/itag/(\d+)/").unwrap();
-
-    let itag_and_url = body
-        .split('\n')
-        .filter(|x| http_regex.is_match(x) && itag_regex.is_match(x));
-
-    let itag_and_url: Vec<(String, String)> = itag_and_url
-        .map(|line| {
-            let itag = itag_regex
-                .captures(line)
-                .expect("IMPOSSIBLE")
-                .get(1)
-                .map(|x| x.as_str())
-                .unwrap_or("");
+    Ok(parse_hls_master_playlist(&body, &url))
+}
 
-            // println!("itag: {}, url: {}", itag, line);
-            (itag.to_string(), line.to_string())
-        })
-        .collect();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_info_cache_key() {
+        let options = VideoOptions::default();
+        let key = info_cache_key("FZ8BxMU3BYc", "full", &options);
+
+        assert_eq!(key, info_cache_key("FZ8BxMU3BYc", "full", &options));
+        assert_ne!(key, info_cache_key("FZ8BxMU3BYc", "basic", &options));
+
+        let mut with_cookies = options.clone();
+        with_cookies.request_options.cookies = Some("SID=abc".to_string());
+        assert_ne!(key, info_cache_key("FZ8BxMU3BYc", "full", &with_cookies));
+
+        let mut other_client = options.clone();
+        other_client.client = InnertubeClient::Ios;
+        assert_ne!(key, info_cache_key("FZ8BxMU3BYc", "full", &other_client));
+
+        let header_options = |headers: &[(&'static str, &'static str)]| {
+            let mut options = options.clone();
+            options.request_options.headers = Some(
+                headers
+                    .iter()
+                    .map(|(name, value)| {
+                        (
+                            reqwest::header::HeaderName::from_static(name),
+                            reqwest::header::HeaderValue::from_static(value),
+                        )
+                    })
+                    .collect(),
+            );
+            options
+        };
+        assert_eq!(
+            info_cache_key(
+                "FZ8BxMU3BYc",
+                "full",
+                &header_options(&[("accept-language", "en"), ("x-a", "1")])
+            ),
+            info_cache_key(
+                "FZ8BxMU3BYc",
+                "full",
+                &header_options(&[("x-a", "1"), ("accept-language", "en")])
+            ),
+        );
 
-    Ok(itag_and_url)
+        let injected = |client: &Arc<reqwest_middleware::ClientWithMiddleware>| {
+            let mut options = options.clone();
+            options.request_options.client = Some(client.clone());
+            info_cache_key("FZ8BxMU3BYc", "full", &options)
+        };
+        let client = Arc::new(build_client(&RequestOptions::default()).unwrap());
+        let other = Arc::new(build_client(&RequestOptions::default()).unwrap());
+        assert_eq!(injected(&client), injected(&client.clone()));
+        assert_ne!(injected(&client), injected(&other));
+    }
 }
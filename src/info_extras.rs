@@ -2,8 +2,13 @@ use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 use crate::constants::BASE_URL;
-use crate::structs::{Author, Chapter, RelatedVideo, StoryBoard, Thumbnail};
-use crate::utils::{get_text, is_verified, parse_abbreviated_number, time_to_ms};
+use crate::structs::{
+    Author, CaptionFormat, CaptionTrack, Chapter, PlaylistPanelVideo, RelatedVideo, StoryBoard,
+    Thumbnail, WatchNext, WatchNextPlaylist,
+};
+use crate::utils::{
+    get_text, is_verified, parse_abbreviated_number, parse_count, parse_thumbnails, time_to_ms,
+};
 
 pub fn get_related_videos(info: &serde_json::Value) -> Option<Vec<RelatedVideo>> {
     let mut rvs_params: Vec<&str> = vec![];
@@ -59,6 +64,10 @@ pub fn get_related_videos(info: &serde_json::Value) -> Option<Vec<RelatedVideo>>
             if let Some(video_some) = video {
                 videos.push(video_some)
             }
+        } else if result.get("lockupViewModel").is_some() {
+            if let Some(video) = parse_related_lockup(&result["lockupViewModel"]) {
+                videos.push(video)
+            }
         } else {
             let autoplay = result
                 .as_object()
@@ -172,6 +181,8 @@ pub fn parse_related_video(
                         .filter(|x| {
                             let json = serde_json::json!(x);
                             json["metadataBadgeRenderer"]["label"] == "LIVE NOW"
+                                || json["metadataBadgeRenderer"]["style"]
+                                    == "BADGE_STYLE_TYPE_LIVE_NOW"
                         })
                         .count()
                         > 0
@@ -388,11 +399,111 @@ pub fn parse_related_video(
             vec![]
         },
         is_live,
+        channel_id: channel_id.as_str().unwrap_or("").to_string(),
+        duration_seconds: if details.contains_key("lengthText") {
+            (time_to_ms(get_text(&details["lengthText"]).as_str().unwrap_or("0")) / 1000) as u64
+        } else {
+            0
+        },
     };
 
     Some(video)
 }
 
+/// Parse `lockupViewModel` which replaces `compactVideoRenderer` in newer watch next responses
+pub fn parse_related_lockup(lockup: &serde_json::Value) -> Option<RelatedVideo> {
+    if lockup["contentType"].as_str() != Some("LOCKUP_CONTENT_TYPE_VIDEO") {
+        return None;
+    }
+
+    let id = lockup["contentId"].as_str()?.to_string();
+
+    let metadata = &lockup["metadata"]["lockupMetadataViewModel"];
+    let avatar = &metadata["image"]["decoratedAvatarViewModel"];
+    let channel_id = avatar["rendererContext"]["commandContext"]["onTap"]["innertubeCommand"]
+        ["browseEndpoint"]["browseId"]
+        .as_str()
+        .unwrap_or("")
+        .to_string();
+
+    // First row is the channel name, second row is the view count and publish time
+    let rows = metadata["metadata"]["contentMetadataViewModel"]["metadataRows"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    let row_part = |row: usize, part: usize| {
+        rows.get(row)
+            .and_then(|x| x["metadataParts"][part]["text"]["content"].as_str())
+            .unwrap_or("")
+            .to_string()
+    };
+
+    let short_view_count_text = row_part(1, 0);
+    let published = row_part(1, 1);
+
+    let thumbnail = &lockup["contentImage"]["thumbnailViewModel"];
+    let badges = thumbnail["overlays"]
+        .as_array()
+        .map(|x| {
+            x.iter()
+                .filter_map(|x| {
+                    x["thumbnailOverlayBadgeViewModel"]["thumbnailBadges"][0]
+                        ["thumbnailBadgeViewModel"]
+                        .as_object()
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    let is_live = badges.iter().any(|x| {
+        x.get("badgeStyle").and_then(|x| x.as_str()) == Some("THUMBNAIL_OVERLAY_BADGE_STYLE_LIVE")
+    });
+    let duration_seconds = badges
+        .iter()
+        .filter_map(|x| x.get("text").and_then(|x| x.as_str()))
+        .find(|x| x.contains(':'))
+        .map(|x| (time_to_ms(x) / 1000) as u64)
+        .unwrap_or(0);
+
+    let author = if !channel_id.is_empty() {
+        Some(Author {
+            id: channel_id.clone(),
+            name: row_part(0, 0),
+            user: String::from(""),
+            channel_url: format!("https://www.youtube.com/channel/{channel_id}"),
+            external_channel_url: format!("https://www.youtube.com/channel/{channel_id}"),
+            user_url: String::from(""),
+            thumbnails: parse_thumbnails(&avatar["avatar"]["avatarViewModel"]["image"]["sources"]),
+            verified: false,
+            subscriber_count: 0,
+        })
+    } else {
+        None
+    };
+
+    Some(RelatedVideo {
+        url: format!("{}{}", BASE_URL, id),
+        id,
+        title: metadata["title"]["content"]
+            .as_str()
+            .unwrap_or("")
+            .to_string(),
+        published,
+        author,
+        view_count: parse_count(&short_view_count_text).to_string(),
+        short_view_count_text: short_view_count_text
+            .split(' ')
+            .next()
+            .unwrap_or("")
+            .to_string(),
+        length_seconds: duration_seconds.to_string(),
+        thumbnails: parse_thumbnails(&thumbnail["image"]["sources"]),
+        is_live,
+        channel_id,
+        duration_seconds,
+    })
+}
+
 pub fn get_media(info: &serde_json::Value) -> Option<serde_json::Value> {
     let empty_serde_array = serde_json::json!([]);
     let empty_serde_object_array = vec![serde_json::json!({})];
@@ -990,6 +1101,14 @@ pub fn get_storyboards(info: &serde_json::Value) -> Option<Vec<StoryBoard>> {
 
     let mut url = url::Url::parse(parts.remove(0))
         .unwrap_or(url::Url::parse("https://i.ytimg.com/").unwrap());
+
+    let length_ms = info
+        .get("videoDetails")
+        .and_then(|x| x.get("lengthSeconds"))
+        .and_then(|x| x.as_str())
+        .and_then(|x| x.parse::<i32>().ok())
+        .unwrap_or(0)
+        .saturating_mul(1000);
     Some(
         parts
             .iter()
@@ -1011,8 +1130,18 @@ pub fn get_storyboards(info: &serde_json::Value) -> Option<Vec<StoryBoard>> {
                 let columns_parsed = columns.parse::<i32>().unwrap_or(0i32);
                 let rows_parsed = rows.parse::<i32>().unwrap_or(0i32);
 
-                let storyboard_count_ceiled =
-                    thumbnail_count_parsed / (columns_parsed * rows_parsed);
+                let frames_per_sheet = columns_parsed * rows_parsed;
+                let storyboard_count_ceiled = if frames_per_sheet > 0 {
+                    (thumbnail_count_parsed + frames_per_sheet - 1) / frames_per_sheet
+                } else {
+                    0
+                };
+
+                // Frames of the first level are spread over the whole video instead of a fixed interval
+                let interval_parsed = match interval.parse::<i32>().unwrap_or(0i32) {
+                    0 if thumbnail_count_parsed > 0 => length_ms / thumbnail_count_parsed,
+                    interval => interval,
+                };
 
                 let template_url = url
                     .as_str()
@@ -1024,7 +1153,7 @@ pub fn get_storyboards(info: &serde_json::Value) -> Option<Vec<StoryBoard>> {
                     thumbnail_width: thumbnail_width.parse::<i32>().unwrap_or(0i32),
                     thumbnail_height: thumbnail_height.parse::<i32>().unwrap_or(0i32),
                     thumbnail_count: thumbnail_count_parsed,
-                    interval: interval.parse::<i32>().unwrap_or(0i32),
+                    interval: interval_parsed,
                     columns: columns_parsed,
                     rows: rows_parsed,
                     storyboard_count: storyboard_count_ceiled,
@@ -1102,3 +1231,175 @@ pub fn get_chapters(info: &serde_json::Value) -> Option<Vec<Chapter>> {
             .collect::<Vec<Chapter>>(),
     )
 }
+
+pub fn get_captions(info: &serde_json::Value) -> Vec<CaptionTrack> {
+    let empty_serde_array = vec![];
+
+    info["captions"]["playerCaptionsTracklistRenderer"]["captionTracks"]
+        .as_array()
+        .unwrap_or(&empty_serde_array)
+        .iter()
+        .map(|x| {
+            let kind = x["kind"].as_str().map(|x| x.to_string());
+
+            CaptionTrack {
+                base_url: x["baseUrl"].as_str().unwrap_or("").to_string(),
+                language_code: x["languageCode"].as_str().unwrap_or("").to_string(),
+                name: get_text(&x["name"]).as_str().unwrap_or("").to_string(),
+                is_auto_generated: kind.as_deref() == Some("asr"),
+                kind,
+                is_translatable: x["isTranslatable"].as_bool().unwrap_or(false),
+            }
+        })
+        .collect()
+}
+
+/// Convert `json3` formatted caption body to the given [`CaptionFormat`]
+pub fn convert_captions(body: &serde_json::Value, format: &CaptionFormat) -> String {
+    let empty_serde_array = vec![];
+
+    let mut output = match format {
+        CaptionFormat::Srt => String::new(),
+        CaptionFormat::Vtt => String::from("WEBVTT\n\n"),
+    };
+
+    let mut index = 1;
+    for event in body["events"].as_array().unwrap_or(&empty_serde_array) {
+        let text = event["segs"]
+            .as_array()
+            .unwrap_or(&empty_serde_array)
+            .iter()
+            .filter_map(|x| x["utf8"].as_str())
+            .collect::<String>();
+        let text = text.trim();
+
+        // Events without segments are only window/position definitions
+        if text.is_empty() {
+            continue;
+        }
+
+        let start = event["tStartMs"].as_u64().unwrap_or(0);
+        let end = start + event["dDurationMs"].as_u64().unwrap_or(0);
+
+        match format {
+            CaptionFormat::Srt => {
+                output.push_str(&format!(
+                    "{index}\n{} --> {}\n{text}\n\n",
+                    format_caption_time(start, ','),
+                    format_caption_time(end, ',')
+                ));
+            }
+            CaptionFormat::Vtt => {
+                output.push_str(&format!(
+                    "{} --> {}\n{text}\n\n",
+                    format_caption_time(start, '.'),
+                    format_caption_time(end, '.')
+                ));
+            }
+        }
+
+        index += 1;
+    }
+
+    output
+}
+
+fn format_caption_time(ms: u64, separator: char) -> String {
+    format!(
+        "{:02}:{:02}:{:02}{separator}{:03}",
+        ms / 3_600_000,
+        (ms / 60_000) % 60,
+        (ms / 1000) % 60,
+        ms % 1000
+    )
+}
+
+pub fn get_watch_next(info: &serde_json::Value) -> WatchNext {
+    let up_next = get_related_videos(info).unwrap_or_default();
+
+    let watch_next_results = &info["contents"]["twoColumnWatchNextResults"];
+
+    let autoplay_id = watch_next_results["autoplay"]["autoplay"]["sets"][0]["autoplayVideo"]
+        ["watchEndpoint"]["videoId"]
+        .as_str()
+        .unwrap_or("");
+
+    // Autoplay video is always a part of the "Up next" queue
+    let autoplay = if autoplay_id.is_empty() {
+        up_next.first().cloned()
+    } else {
+        up_next.iter().find(|x| x.id == autoplay_id).cloned()
+    };
+
+    let playlist = &watch_next_results["playlist"]["playlist"];
+
+    let playlist = if playlist.is_object() {
+        let videos = playlist["contents"]
+            .as_array()
+            .map(|x| {
+                x.iter()
+                    .filter_map(|x| x.get("playlistPanelVideoRenderer"))
+                    .map(|video| {
+                        let id = video["videoId"].as_str().unwrap_or("").to_string();
+
+                        PlaylistPanelVideo {
+                            url: if !id.is_empty() {
+                                format!("{}{}", BASE_URL, id)
+                            } else {
+                                String::from("")
+                            },
+                            id,
+                            title: get_text(&video["title"]).as_str().unwrap_or("").to_string(),
+                            author: get_text(&video["shortBylineText"])
+                                .as_str()
+                                .unwrap_or("")
+                                .to_string(),
+                            length_seconds: (time_to_ms(
+                                get_text(&video["lengthText"]).as_str().unwrap_or("0"),
+                            ) / 1000)
+                                .to_string(),
+                            index: get_text(&video["indexText"])
+                                .as_str()
+                                .and_then(|x| x.parse::<u64>().ok())
+                                .unwrap_or(0),
+                            selected: video["selected"].as_bool().unwrap_or(false),
+                            thumbnails: video["thumbnail"]["thumbnails"]
+                                .as_array()
+                                .map(|x| {
+                                    x.iter()
+                                        .map(|x| Thumbnail {
+                                            width: x["width"].as_u64().unwrap_or(0),
+                                            height: x["height"].as_u64().unwrap_or(0),
+                                            url: x["url"].as_str().unwrap_or("").to_string(),
+                                        })
+                                        .collect::<Vec<Thumbnail>>()
+                                })
+                                .unwrap_or_default(),
+                        }
+                    })
+                    .collect::<Vec<PlaylistPanelVideo>>()
+            })
+            .unwrap_or_default();
+
+        Some(WatchNextPlaylist {
+            id: playlist["playlistId"].as_str().unwrap_or("").to_string(),
+            title: playlist["title"].as_str().unwrap_or("").to_string(),
+            owner_name: get_text(&playlist["ownerName"])
+                .as_str()
+                .unwrap_or("")
+                .to_string(),
+            current_index: playlist["currentIndex"].as_u64().unwrap_or(0),
+            total_videos: playlist["totalVideos"].as_u64().unwrap_or(0),
+            is_infinite: playlist["isInfinite"].as_bool().unwrap_or(false),
+            videos,
+        })
+    } else {
+        None
+    };
+
+    WatchNext {
+        autoplay,
+        up_next,
+        playlist,
+    }
+}
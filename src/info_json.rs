@@ -0,0 +1,195 @@
+use serde_json::{json, Map, Value};
+
+use crate::structs::{VideoFormat, VideoInfo};
+use crate::template::format_extension;
+
+impl VideoInfo {
+    /// Build a yt-dlp compatible `info.json` document, so tools reading the files of yt-dlp can read it.
+    /// Fields which are not available are left out
+    /// - `url` of a format is only deciphered if [`VideoFormat::resolve_url`] was already called for it,
+    ///   e.g. the downloaded format, others have [`VideoFormat::raw_url`]
+    /// # Example
+    /// ```ignore
+    ///     let info = video.get_info().await.unwrap();
+    ///
+    ///     std::fs::write("video.info.json", info.to_info_json().to_string()).unwrap();
+    /// ```
+    pub fn to_info_json(&self) -> Value {
+        let details = &self.video_details;
+
+        let (uploader, uploader_id, uploader_url, channel_url) = match &details.author {
+            Some(author) => (
+                author.name.clone(),
+                author.user.clone(),
+                author.user_url.clone(),
+                author.channel_url.clone(),
+            ),
+            None => (
+                details.owner_channel_name.clone(),
+                String::new(),
+                details.owner_profile_url.clone(),
+                format!("https://www.youtube.com/channel/{}", details.channel_id),
+            ),
+        };
+
+        let duration = details.length_seconds.parse::<u64>().ok();
+
+        let upload_date = if details.upload_date.is_empty() {
+            &details.publish_date
+        } else {
+            &details.upload_date
+        };
+
+        let live_status = if details.is_upcoming {
+            "is_upcoming"
+        } else if details.is_post_live_dvr {
+            "post_live"
+        } else if self.formats.iter().any(|x| x.is_live) {
+            "is_live"
+        } else if details.is_live_content {
+            "was_live"
+        } else {
+            "not_live"
+        };
+
+        let availability = if details.is_private {
+            "private"
+        } else if details.is_unlisted {
+            "unlisted"
+        } else {
+            "public"
+        };
+
+        let chapters = details
+            .chapters
+            .iter()
+            .enumerate()
+            .map(|(index, chapter)| {
+                let end_time = details
+                    .chapters
+                    .get(index + 1)
+                    .map(|x| x.start_time as u64)
+                    .or(duration);
+
+                json!({
+                    "title": chapter.title,
+                    "start_time": chapter.start_time,
+                    "end_time": end_time,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let mut subtitles = Map::new();
+        let mut automatic_captions = Map::new();
+
+        for caption in &self.captions {
+            let captions = if caption.is_auto_generated {
+                &mut automatic_captions
+            } else {
+                &mut subtitles
+            };
+
+            if let Value::Array(tracks) = captions
+                .entry(caption.language_code.clone())
+                .or_insert_with(|| Value::Array(vec![]))
+            {
+                tracks.push(json!({
+                    "url": caption.base_url,
+                    "name": caption.name,
+                }));
+            }
+        }
+
+        let mut info_json = json!({
+            "_type": "video",
+            "id": details.video_id,
+            "title": details.title,
+            "fulltitle": details.title,
+            "description": details.description,
+            "uploader": uploader,
+            "uploader_id": uploader_id,
+            "uploader_url": uploader_url,
+            "channel": uploader,
+            "channel_id": details.channel_id,
+            "channel_url": channel_url,
+            "duration": duration,
+            "view_count": details.view_count.parse::<u64>().ok(),
+            "like_count": (details.likes >= 0).then_some(details.likes),
+            "upload_date": upload_date.get(..10).map(|x| x.replace('-', "")),
+            "thumbnails": details.thumbnails,
+            "thumbnail": details.thumbnails.last().map(|x| x.url.clone()),
+            "tags": details.keywords,
+            "categories": [details.category],
+            "age_limit": if details.age_restricted { 18 } else { 0 },
+            "webpage_url": details.video_url,
+            "original_url": details.video_url,
+            "is_live": live_status == "is_live",
+            "was_live": live_status == "was_live" || live_status == "post_live",
+            "live_status": live_status,
+            "availability": availability,
+            "chapters": chapters,
+            "subtitles": subtitles,
+            "automatic_captions": automatic_captions,
+            "formats": self.formats.iter().map(format_info_json).collect::<Vec<_>>(),
+            "extractor": "youtube",
+            "extractor_key": "Youtube",
+        });
+
+        remove_nulls(&mut info_json);
+
+        info_json
+    }
+}
+
+fn format_info_json(format: &VideoFormat) -> Value {
+    let protocol = if format.is_hls {
+        "m3u8_native"
+    } else if format.is_dash_mpd {
+        "http_dash_segments"
+    } else {
+        "https"
+    };
+
+    let codec = |has: bool, codec: &Option<String>| match (has, codec) {
+        (false, _) => "none".to_string(),
+        (true, Some(codec)) => codec.clone(),
+        (true, None) => "unknown".to_string(),
+    };
+
+    let mut format_json = json!({
+        "format_id": if format.is_drc {
+            format!("{}-drc", format.itag)
+        } else {
+            format.itag.to_string()
+        },
+        "format_note": format
+            .quality_label
+            .clone()
+            .or_else(|| format.audio_quality.clone())
+            .map(|x| if format.is_drc { format!("{x}, DRC") } else { x }),
+        // Deciphering every format would run the player script ~25 times
+        "url": format.resolved_or_raw_url(),
+        "ext": format_extension(format),
+        "protocol": protocol,
+        "acodec": codec(format.has_audio, &format.mime_type.audio_codec),
+        "vcodec": codec(format.has_video, &format.mime_type.video_codec),
+        "width": format.width,
+        "height": format.height,
+        "fps": format.fps,
+        "tbr": format.bitrate as f64 / 1000f64,
+        "asr": format.audio_sample_rate.as_ref().and_then(|x| x.parse::<u64>().ok()),
+        "audio_channels": format.audio_channels,
+        "filesize": format.content_length,
+    });
+
+    remove_nulls(&mut format_json);
+
+    format_json
+}
+
+/// yt-dlp leaves unknown fields out instead of writing `null`
+fn remove_nulls(value: &mut Value) {
+    if let Value::Object(object) = value {
+        object.retain(|_, x| !x.is_null());
+    }
+}
@@ -0,0 +1,135 @@
+use std::sync::Arc;
+
+use crate::{parser::call_native, structs::VideoError};
+
+/// JavaScript engine that runs the signature decipher and `n` transform functions of the player script
+///
+/// The default engine is [`BoaRuntime`] (`boa` feature), [`NativeRuntime`] runs the common functions without an engine. Implement this trait to use another engine
+/// (e.g. quickjs, deno_core or an external solver process) with [`crate::VideoOptions::js_runtime`]
+///
+/// # Example
+/// ```ignore
+///     struct ExternalSolver;
+///
+///     impl JsRuntime for ExternalSolver {
+///         fn call(&self, script: &str, function_name: &str, argument: &str) -> Result<String, VideoError> {
+///             solve_with_external_process(script, function_name, argument)
+///         }
+///     }
+/// ```
+pub trait JsRuntime: Send + Sync {
+    /// Evaluate `script` and return the string result of `function_name(argument)`
+    fn call(&self, script: &str, function_name: &str, argument: &str)
+        -> Result<String, VideoError>;
+}
+
+/// [`JsRuntime`] backed by the boa engine
+#[cfg(feature = "boa")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BoaRuntime;
+
+#[cfg(feature = "boa")]
+impl JsRuntime for BoaRuntime {
+    #[cfg_attr(feature = "performance_analysis", flamer::flame)]
+    fn call(
+        &self,
+        script: &str,
+        function_name: &str,
+        argument: &str,
+    ) -> Result<String, VideoError> {
+        // Caching the context would be great (~2ms x 2 gain/req on Ryzen 9 5950XT) but is quite hard because of the !Send nature of boa
+        let mut context = boa_engine::Context::default();
+        context
+            .eval(boa_engine::Source::from_bytes(script))
+            .map_err(|x| VideoError::JsRuntime(x.to_string()))?;
+
+        // Optimizing the script would be great (~20ms x 2 gain/req on Ryzen 9 5950XT) but quite some work on boa side
+        // This is where most of the time is spent
+        context
+            .eval(boa_engine::Source::from_bytes(&format!(
+                r#"{function_name}("{argument}")"#
+            )))
+            .map_err(|x| VideoError::JsRuntime(x.to_string()))?
+            .as_string()
+            .ok_or(VideoError::JsRuntime(
+                "Function result is not a string".to_string(),
+            ))?
+            .to_std_string()
+            .map_err(|x| VideoError::JsRuntime(x.to_string()))
+    }
+}
+
+/// Pure Rust [`JsRuntime`] that recognizes the common decipher (reverse/splice/swap) and `n` transform
+/// array operations without evaluating the script
+///
+/// Functions that do not match the known patterns are passed to the `fallback` runtime.
+/// The default fallback is [`BoaRuntime`] if the `boa` feature is enabled
+///
+/// # Example
+/// ```ignore
+///     let video_options = VideoOptions {
+///         js_runtime: Some(Arc::new(NativeRuntime::default())),
+///         ..Default::default()
+///     };
+/// ```
+#[derive(Clone, derivative::Derivative)]
+#[derivative(Debug)]
+pub struct NativeRuntime {
+    #[derivative(Debug = "ignore")]
+    fallback: Option<Arc<dyn JsRuntime>>,
+}
+
+impl NativeRuntime {
+    pub fn new(fallback: Option<Arc<dyn JsRuntime>>) -> Self {
+        Self { fallback }
+    }
+}
+
+impl Default for NativeRuntime {
+    fn default() -> Self {
+        #[cfg(feature = "boa")]
+        {
+            Self::new(Some(Arc::new(BoaRuntime)))
+        }
+
+        #[cfg(not(feature = "boa"))]
+        {
+            Self::new(None)
+        }
+    }
+}
+
+impl JsRuntime for NativeRuntime {
+    #[cfg_attr(feature = "performance_analysis", flamer::flame)]
+    fn call(
+        &self,
+        script: &str,
+        function_name: &str,
+        argument: &str,
+    ) -> Result<String, VideoError> {
+        if let Some(result) = call_native(script, function_name, argument) {
+            return Ok(result);
+        }
+
+        match &self.fallback {
+            Some(fallback) => fallback.call(script, function_name, argument),
+            None => Err(VideoError::JsRuntime(
+                "Function is not supported by the native runtime".to_string(),
+            )),
+        }
+    }
+}
+
+/// Default [`JsRuntime`] of enabled features
+/// - [`BoaRuntime`] if the `boa` feature is enabled, [`NativeRuntime`] without fallback otherwise
+pub(crate) fn default_js_runtime() -> Option<Arc<dyn JsRuntime>> {
+    #[cfg(feature = "boa")]
+    {
+        Some(Arc::new(BoaRuntime))
+    }
+
+    #[cfg(not(feature = "boa"))]
+    {
+        Some(Arc::new(NativeRuntime::new(None)))
+    }
+}
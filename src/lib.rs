@@ -9,31 +9,104 @@ pub extern crate flame;
 #[macro_use]
 extern crate flamer;
 
+mod archive;
+mod cache;
+#[cfg(feature = "checksum")]
+mod checksum;
+mod comments;
+mod consent;
+mod container;
+mod download_manager;
+mod format_filter;
+mod headers;
+mod hooks;
+#[cfg(feature = "http3")]
+mod http3;
 mod info;
 mod info_extras;
+mod info_json;
+mod js_runtime;
+#[cfg(feature = "libav-remux")]
+mod libav;
+mod live_chat;
+mod platform;
+#[cfg(feature = "python")]
+mod python;
+mod rate_limit;
+mod related;
+mod resolve;
+#[cfg(feature = "storyboard")]
+mod storyboard;
 mod structs;
+#[cfg(feature = "tags")]
+mod tags;
+mod template;
 mod utils;
 mod parser;
 
 pub mod constants;
+pub mod feeds;
 pub mod stream;
 
-#[cfg(feature = "blocking")]
+// Blocking calls need a tokio runtime of their own, which browsers and workers do not have
+#[cfg(all(feature = "blocking", not(target_arch = "wasm32")))]
 pub mod blocking;
 
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
 #[cfg(feature = "search")]
 pub mod search;
 
-pub use info::Video;
+#[cfg(feature = "search")]
+mod channel;
+
+#[cfg(feature = "search")]
+mod playlist_download;
+
+pub use archive::DownloadArchive;
+pub use comments::Comments;
+pub use download_manager::{
+    BatchProgressCallback, DownloadJob, DownloadManager, DownloadOutput, DownloadResult,
+};
+pub use format_filter::FormatFilter;
+pub use headers::set_browser_identities;
+pub use hooks::RequestHook;
+pub use info::{set_info_cache_capacity, Video};
+pub use js_runtime::{JsRuntime, NativeRuntime};
+pub use live_chat::LiveChat;
+pub use related::RelatedVideos;
+pub use resolve::{resolve_url, resolve_url_with_options, ResolvedUrl};
 pub use structs::{
-    Author, Chapter, ColorInfo, DownloadOptions, Embed, MimeType, RangeObject, RelatedVideo,
-    RequestOptions, StoryBoard, Thumbnail, VideoDetails, VideoError, VideoFormat, VideoInfo,
-    VideoOptions, VideoQuality, VideoSearchOptions,
+    Author, BrowserIdentity, CaptionFormat, CaptionTrack, Chapter, ChunkRetry, ColorInfo, Comment,
+    CommentSort, DownloadEvent, DownloadEventCallback, DownloadOptions, DownloadProgress,
+    DrcPolicy, Embed, IdentityRotation, InnertubeClient, LatencyClass, LiveChatMessage,
+    LiveLatency, MimeType, OverwritePolicy, PlayabilityStatus, PlaylistPanelVideo, PoTokenProvider,
+    ProgressCallback, QuickInfo, RangeObject, RateLimitBackoff, RelatedVideo, RequestOptions,
+    SanitizeOptions, StoryBoard, Thumbnail, ThumbnailQuality, UserAgentRotation, VideoDetails,
+    VideoError, VideoFormat, VideoInfo, VideoOptions, VideoQuality, VideoSearchOptions, WatchNext,
+    WatchNextPlaylist,
 };
+pub use template::render_filename_template;
 
 #[cfg(feature = "ffmpeg")]
 pub use structs::FFmpegArgs;
 
-pub use utils::{choose_format, get_random_v6_ip, get_video_id};
+#[cfg(feature = "checksum")]
+pub use structs::HashAlgo;
+
+#[cfg(feature = "boa")]
+pub use js_runtime::BoaRuntime;
+
+#[cfg(feature = "search")]
+pub use channel::{Channel, ChannelTab, ChannelTabType, SyncCheckpoint};
+
+#[cfg(feature = "search")]
+pub use playlist_download::{PlaylistDownloadOptions, PlaylistProgress, PlaylistProgressCallback};
+
+pub use utils::{
+    choose_format, choose_format_pair, get_clip_id, get_random_v6_ip, get_start_time, get_video_id,
+    sanitize_filename, set_player_cache_options,
+};
 // export to access proxy feature
 pub use reqwest;
@@ -0,0 +1,178 @@
+use std::path::Path;
+
+use ffmpeg_next as ffmpeg;
+use ffmpeg_next::Rescale;
+
+use crate::structs::VideoError;
+use crate::utils::{RemuxChapter, RemuxInput, RemuxStream};
+
+/// Microsecond time base of `AV_TIME_BASE` timestamps
+const TIME_BASE: ffmpeg::Rational = ffmpeg::Rational(1, 1_000_000);
+
+struct OpenedInput {
+    context: ffmpeg::format::context::Input,
+    /// Output stream index of every input stream, [`None`] if the stream is dropped
+    mapping: Vec<Option<usize>>,
+    /// Microseconds removed from timestamps so sections start at zero
+    offset: Option<i64>,
+    end: Option<i64>,
+    /// Timestamp of the last read packet in microseconds, used to interleave inputs
+    position: i64,
+    finished: bool,
+}
+
+/// Copy the streams of `inputs` into `output` without re-encoding, like `ffmpeg -c copy`
+pub fn remux(
+    inputs: &[RemuxInput],
+    chapters: &[RemuxChapter],
+    output: &Path,
+) -> Result<(), VideoError> {
+    ffmpeg::init().map_err(libav_error)?;
+
+    let mut output_context = ffmpeg::format::output(output).map_err(libav_error)?;
+    let mut opened = Vec::with_capacity(inputs.len());
+
+    for input in inputs {
+        let mut context = ffmpeg::format::input(&input.url).map_err(libav_error)?;
+
+        let wanted = match input.stream {
+            RemuxStream::Video => context
+                .streams()
+                .best(ffmpeg::media::Type::Video)
+                .map(|x| x.index()),
+            RemuxStream::Audio => context
+                .streams()
+                .best(ffmpeg::media::Type::Audio)
+                .map(|x| x.index()),
+            RemuxStream::All => None,
+        };
+
+        let mut mapping = vec![None; context.nb_streams() as usize];
+
+        for stream in context.streams() {
+            let medium = stream.parameters().medium();
+
+            let keep = match input.stream {
+                RemuxStream::All => matches!(
+                    medium,
+                    ffmpeg::media::Type::Video
+                        | ffmpeg::media::Type::Audio
+                        | ffmpeg::media::Type::Subtitle
+                ),
+                _ => wanted == Some(stream.index()),
+            };
+
+            if !keep {
+                continue;
+            }
+
+            let mut output_stream = output_context
+                .add_stream(ffmpeg::encoder::find(ffmpeg::codec::Id::None))
+                .map_err(libav_error)?;
+            output_stream.set_parameters(stream.parameters());
+            // Codec tag of the source container can be invalid for the output container
+            unsafe {
+                (*output_stream.parameters().as_mut_ptr()).codec_tag = 0;
+            }
+
+            mapping[stream.index()] = Some(output_stream.index());
+        }
+
+        if let Some(start) = input.start {
+            let start = start.as_micros() as i64;
+            context.seek(start, ..start).map_err(libav_error)?;
+        }
+
+        opened.push(OpenedInput {
+            context,
+            mapping,
+            offset: if input.start.is_some() { None } else { Some(0) },
+            end: input.end.map(|x| x.as_micros() as i64),
+            position: 0,
+            finished: false,
+        });
+    }
+
+    for (index, chapter) in chapters.iter().enumerate() {
+        output_context
+            .add_chapter(
+                index as i64,
+                ffmpeg::Rational(1, 1000),
+                chapter.start.as_millis() as i64,
+                chapter.end.as_millis() as i64,
+                &chapter.title,
+            )
+            .map_err(libav_error)?;
+    }
+
+    output_context.write_header().map_err(libav_error)?;
+
+    // Always read from the input that is the most behind so the muxer does not buffer a whole input
+    while let Some(input) = opened
+        .iter_mut()
+        .filter(|x| !x.finished)
+        .min_by_key(|x| x.position)
+    {
+        let mut packet = ffmpeg::Packet::empty();
+
+        match packet.read(&mut input.context) {
+            Ok(()) => {}
+            Err(ffmpeg::Error::Eof) => {
+                input.finished = true;
+                continue;
+            }
+            Err(e) => return Err(libav_error(e)),
+        }
+
+        let input_index = packet.stream();
+        let output_index = match input.mapping.get(input_index).copied().flatten() {
+            Some(index) => index,
+            None => continue,
+        };
+
+        let input_time_base = input
+            .context
+            .stream(input_index)
+            .map(|x| x.time_base())
+            .ok_or(VideoError::FFmpeg("Input stream not found".to_string()))?;
+
+        let timestamp = packet
+            .dts()
+            .or(packet.pts())
+            .map(|x| x.rescale(input_time_base, TIME_BASE))
+            .unwrap_or(input.position);
+
+        if input.end.map(|end| timestamp > end).unwrap_or(false) {
+            input.finished = true;
+            continue;
+        }
+
+        // Seeking lands on the keyframe before the start, sections begin from its timestamp
+        let offset = *input.offset.get_or_insert(timestamp);
+        input.position = timestamp;
+
+        let offset = offset.rescale(TIME_BASE, input_time_base);
+        packet.set_pts(packet.pts().map(|x| x - offset));
+        packet.set_dts(packet.dts().map(|x| x - offset));
+
+        let output_time_base = output_context
+            .stream(output_index)
+            .map(|x| x.time_base())
+            .ok_or(VideoError::FFmpeg("Output stream not found".to_string()))?;
+
+        packet.rescale_ts(input_time_base, output_time_base);
+        packet.set_stream(output_index);
+        packet.set_position(-1);
+        packet
+            .write_interleaved(&mut output_context)
+            .map_err(libav_error)?;
+    }
+
+    output_context.write_trailer().map_err(libav_error)?;
+
+    Ok(())
+}
+
+fn libav_error(error: ffmpeg::Error) -> VideoError {
+    VideoError::FFmpeg(error.to_string())
+}
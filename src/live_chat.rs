@@ -0,0 +1,210 @@
+use std::time::Duration;
+
+use crate::structs::{LiveChatMessage, VideoError};
+use crate::utils::{get_text, innertube_request, parse_thumbnails};
+
+/// Waiting time between polls if the server does not suggest one
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Clone, derivative::Derivative)]
+#[derivative(Debug, PartialEq, Eq)]
+pub struct LiveChat {
+    pub video_id: String,
+    /// Waiting time before the next poll suggested by the server
+    pub timeout: Duration,
+
+    #[derivative(PartialEq = "ignore")]
+    continuation: Option<String>,
+    #[derivative(PartialEq = "ignore")]
+    client: reqwest_middleware::ClientWithMiddleware,
+}
+
+impl LiveChat {
+    pub(crate) async fn get(
+        client: &reqwest_middleware::ClientWithMiddleware,
+        video_id: impl Into<String>,
+    ) -> Result<Self, VideoError> {
+        let video_id: String = video_id.into();
+
+        let response = innertube_request(
+            client,
+            "next",
+            serde_json::json!({
+                "videoId": video_id,
+            }),
+        )
+        .await?;
+
+        let renderer = &response["contents"]["twoColumnWatchNextResults"]["conversationBar"]
+            ["liveChatRenderer"];
+
+        // "Live chat" view has all messages, the default "Top chat" view is filtered
+        let continuation = renderer["header"]["liveChatHeaderRenderer"]["viewSelector"]
+            ["sortFilterSubMenuRenderer"]["subMenuItems"][1]["continuation"]
+            ["reloadContinuationData"]["continuation"]
+            .as_str()
+            .or_else(|| {
+                renderer["continuations"][0]["reloadContinuationData"]["continuation"].as_str()
+            })
+            .ok_or(VideoError::LiveChatNotFound)?;
+
+        Ok(Self {
+            video_id,
+            timeout: Duration::ZERO,
+            continuation: Some(continuation.to_string()),
+            client: client.clone(),
+        })
+    }
+
+    /// Poll the chat once and return the new [`LiveChatMessage`] array.
+    /// - Wait [`LiveChat::timeout`] between polls, [`LiveChat::stream`] does it automatically
+    /// - If the live stream ended this function return empty [`Vec<LiveChatMessage>`]
+    pub async fn next(&mut self) -> Result<Vec<LiveChatMessage>, VideoError> {
+        let continuation = match &self.continuation {
+            Some(continuation) => continuation.clone(),
+            None => return Ok(vec![]),
+        };
+
+        let response = innertube_request(
+            &self.client,
+            "live_chat/get_live_chat",
+            serde_json::json!({
+                "continuation": continuation,
+            }),
+        )
+        .await?;
+
+        let empty_serde_array = vec![];
+
+        let chat = &response["continuationContents"]["liveChatContinuation"];
+
+        self.continuation = None;
+        self.timeout = DEFAULT_POLL_INTERVAL;
+
+        // Continuation type changes with the state of the chat, all of them have the same fields
+        if let Some(data) = chat["continuations"][0]
+            .as_object()
+            .and_then(|x| x.values().find(|data| data["continuation"].is_string()))
+        {
+            self.continuation = data["continuation"].as_str().map(|x| x.to_string());
+
+            if let Some(timeout) = data["timeoutMs"].as_u64() {
+                self.timeout = Duration::from_millis(timeout);
+            }
+        }
+
+        let messages = chat["actions"]
+            .as_array()
+            .unwrap_or(&empty_serde_array)
+            .iter()
+            .filter_map(|action| {
+                let item = &action["addChatItemAction"]["item"];
+
+                if item["liveChatTextMessageRenderer"].is_object() {
+                    Some(parse_chat_message(&item["liveChatTextMessageRenderer"]))
+                } else if item["liveChatPaidMessageRenderer"].is_object() {
+                    Some(parse_chat_message(&item["liveChatPaidMessageRenderer"]))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        Ok(messages)
+    }
+
+    /// Turn the chat into an async stream of messages, polling every `poll_interval`
+    /// or every [`LiveChat::timeout`] suggested by the server if it is [`None`].
+    /// The stream ends with the live stream or after the first error
+    /// # Example
+    /// ```ignore
+    ///     let video = Video::new("https://www.youtube.com/watch?v=jfKfPfyJRdk").unwrap();
+    ///
+    ///     let chat = video.live_chat().await.unwrap();
+    ///     let mut messages = Box::pin(chat.stream(None));
+    ///
+    ///     while let Some(message) = messages.next().await {
+    ///         let message = message.unwrap();
+    ///         println!("{}: {}", message.author, message.message);
+    ///     }
+    /// ```
+    #[cfg(feature = "live")]
+    pub fn stream(
+        self,
+        poll_interval: Option<Duration>,
+    ) -> impl futures::Stream<Item = Result<LiveChatMessage, VideoError>> {
+        use std::collections::VecDeque;
+
+        futures::stream::unfold(
+            (self, VecDeque::new(), true),
+            move |(mut chat, mut buffer, mut first)| async move {
+                loop {
+                    if let Some(message) = buffer.pop_front() {
+                        return Some((Ok(message), (chat, buffer, false)));
+                    }
+
+                    if !chat.has_next() {
+                        return None;
+                    }
+
+                    if !first {
+                        crate::platform::sleep(poll_interval.unwrap_or(chat.timeout)).await;
+                    }
+
+                    first = false;
+
+                    match chat.next().await {
+                        Ok(messages) => buffer.extend(messages),
+                        Err(e) => {
+                            chat.continuation = None;
+                            return Some((Err(e), (chat, buffer, false)));
+                        }
+                    }
+                }
+            },
+        )
+    }
+
+    /// The live stream is still running and the chat can be polled
+    pub fn has_next(&self) -> bool {
+        self.continuation.is_some()
+    }
+}
+
+fn parse_chat_message(renderer: &serde_json::Value) -> LiveChatMessage {
+    let empty_serde_array = vec![];
+
+    let message = renderer["message"]["runs"]
+        .as_array()
+        .unwrap_or(&empty_serde_array)
+        .iter()
+        .map(|run| {
+            run["text"]
+                .as_str()
+                .or_else(|| run["emoji"]["shortcuts"][0].as_str())
+                .or_else(|| run["emoji"]["emojiId"].as_str())
+                .unwrap_or("")
+        })
+        .collect::<String>();
+
+    LiveChatMessage {
+        id: renderer["id"].as_str().unwrap_or("").to_string(),
+        author: get_text(&renderer["authorName"])
+            .as_str()
+            .unwrap_or("")
+            .to_string(),
+        author_channel_id: renderer["authorExternalChannelId"]
+            .as_str()
+            .unwrap_or("")
+            .to_string(),
+        author_thumbnails: parse_thumbnails(&renderer["authorPhoto"]["thumbnails"]),
+        message,
+        purchase_amount: get_text(&renderer["purchaseAmountText"])
+            .as_str()
+            .map(|x| x.to_string()),
+        timestamp_usec: renderer["timestampUsec"]
+            .as_str()
+            .and_then(|x| x.parse::<u64>().ok())
+            .unwrap_or(0),
+    }
+}
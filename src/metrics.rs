@@ -0,0 +1,78 @@
+use reqwest::{Request, Response, StatusCode};
+use reqwest_middleware::{Middleware, Next};
+use task_local_extensions::Extensions;
+
+/// Sent HTTP requests, retries included
+pub const REQUESTS: &str = "rusty_ytdl_requests_total";
+/// Bytes of downloaded streams
+pub const DOWNLOADED_BYTES: &str = "rusty_ytdl_downloaded_bytes_total";
+/// Player script requests answered by the cipher cache
+pub const CIPHER_CACHE_HITS: &str = "rusty_ytdl_cipher_cache_hits_total";
+/// Player script requests fetched and deciphered again
+pub const CIPHER_CACHE_MISSES: &str = "rusty_ytdl_cipher_cache_misses_total";
+/// Requests, chunks and live segments sent again after a failure
+pub const RETRIES: &str = "rusty_ytdl_retries_total";
+/// `403 Forbidden` responses, e.g. expired or IP bound format URLs
+pub const FORBIDDEN_RESPONSES: &str = "rusty_ytdl_forbidden_responses_total";
+
+/// Register the descriptions of the counters to the installed `metrics` recorder.
+/// Counters are recorded without calling it, exporters only miss the help texts
+pub fn describe_metrics() {
+    ::metrics::describe_counter!(REQUESTS, "Sent HTTP requests, retries included");
+    ::metrics::describe_counter!(
+        DOWNLOADED_BYTES,
+        ::metrics::Unit::Bytes,
+        "Bytes of downloaded streams"
+    );
+    ::metrics::describe_counter!(
+        CIPHER_CACHE_HITS,
+        "Player script requests answered by the cipher cache"
+    );
+    ::metrics::describe_counter!(
+        CIPHER_CACHE_MISSES,
+        "Player script requests fetched and deciphered again"
+    );
+    ::metrics::describe_counter!(
+        RETRIES,
+        "Requests, chunks and live segments sent again after a failure"
+    );
+    ::metrics::describe_counter!(FORBIDDEN_RESPONSES, "403 Forbidden responses");
+}
+
+pub(crate) fn increment(name: &'static str, value: u64) {
+    ::metrics::counter!(name).increment(value);
+}
+
+/// Marks extensions of a request which was already sent once
+struct Sent;
+
+/// Count every sent request and its `403 Forbidden` responses.
+/// Retry middlewares send the same extensions again, so the second pass of a request is a retry
+pub(crate) struct MetricsMiddleware;
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl Middleware for MetricsMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        increment(REQUESTS, 1);
+
+        if extensions.get::<Sent>().is_some() {
+            increment(RETRIES, 1);
+        } else {
+            extensions.insert(Sent);
+        }
+
+        let response = next.run(req, extensions).await?;
+
+        if response.status() == StatusCode::FORBIDDEN {
+            increment(FORBIDDEN_RESPONSES, 1);
+        }
+
+        Ok(response)
+    }
+}
@@ -0,0 +1,670 @@
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value;
+
+use crate::constants::FORMATS;
+use crate::utils::{add_format_meta, make_absolute_url};
+
+static REGEX_TAG: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r#"<(/?)([A-Za-z][\w:.-]*)((?:\s+[\w:.-]+\s*=\s*"[^"]*")*)\s*(/?)>"#).unwrap()
+});
+static REGEX_ATTRIBUTE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"([\w:.-]+)\s*=\s*"([^"]*)""#).unwrap());
+
+/// Parse DASH MPD manifest body to the format objects same as `streamingData.adaptiveFormats`
+/// - Representations with `BaseURL` are downloaded with Range requests
+/// - Representations with `SegmentList` or `SegmentTemplate` get `manifest_url` as URL and `isDashSegmented`,
+///   they are downloaded with [`crate::stream::DashStream`]
+/// - Other representations are skipped
+#[cfg_attr(feature = "performance_analysis", flamer::flame)]
+pub fn parse_dash_manifest(body: &str, manifest_url: &str) -> Vec<Value> {
+    let mut formats: Vec<Value> = vec![];
+
+    let mut adaptation_set: HashMap<String, String> = HashMap::new();
+    let mut adaptation_set_segmented = false;
+    let mut representation: Option<HashMap<String, String>> = None;
+    let mut segmented = false;
+    let mut base_url: Option<String> = None;
+    let mut index_range: Option<String> = None;
+    let mut init_range: Option<String> = None;
+    let mut adaptation_set_channels: Option<String> = None;
+    let mut audio_channels: Option<String> = None;
+    let mut duration_ms: Option<u64> = None;
+    let mut in_base_url = false;
+    let mut last_end = 0;
+
+    for tag in REGEX_TAG.captures_iter(body) {
+        let whole = tag.get(0).expect("IMPOSSIBLE");
+
+        // Text between previous tag and current tag
+        if in_base_url && representation.is_some() {
+            let text = decode_entities(body[last_end..whole.start()].trim());
+            if !text.is_empty() {
+                base_url = Some(text);
+            }
+        }
+        last_end = whole.end();
+
+        let is_closing = !tag[1].is_empty();
+        let is_self_closing = !tag[4].is_empty();
+        let name = tag[2].to_lowercase();
+        let attributes = parse_attributes(tag.get(3).map(|x| x.as_str()).unwrap_or(""));
+
+        match (name.as_str(), is_closing) {
+            ("mpd", false) => {
+                duration_ms = attributes
+                    .get("mediapresentationduration")
+                    .and_then(|x| parse_duration_ms(x))
+            }
+            ("adaptationset", false) => {
+                adaptation_set = attributes;
+                adaptation_set_channels = None;
+                adaptation_set_segmented = false;
+            }
+            ("adaptationset", true) => adaptation_set = HashMap::new(),
+            ("representation", false) => {
+                // Self-closing representations have no `BaseURL`, only a segment template of the adaptation set
+                representation = if is_self_closing && !adaptation_set_segmented {
+                    None
+                } else {
+                    Some(attributes)
+                };
+                segmented = adaptation_set_segmented;
+                base_url = None;
+                index_range = None;
+                init_range = None;
+                audio_channels = None;
+            }
+            ("segmentlist", false) | ("segmenttemplate", false) => {
+                if representation.is_some() {
+                    segmented = true;
+                } else {
+                    adaptation_set_segmented = true;
+                }
+            }
+            ("baseurl", false) => in_base_url = !is_self_closing,
+            ("baseurl", true) => in_base_url = false,
+            ("segmentbase", false) => index_range = attributes.get("indexrange").cloned(),
+            ("initialization", false) => init_range = attributes.get("range").cloned(),
+            // Can be in the adaptation set or in the representation
+            ("audiochannelconfiguration", false) => {
+                let value = attributes.get("value").cloned();
+                if representation.is_some() {
+                    audio_channels = value;
+                } else {
+                    adaptation_set_channels = value;
+                }
+            }
+            _ => {}
+        }
+
+        if name == "representation" && (is_closing || is_self_closing) {
+            let url = if segmented {
+                Some(manifest_url.to_string())
+            } else {
+                base_url.take()
+            };
+
+            if let (Some(representation), Some(url)) = (representation.take(), url) {
+                if let Some(mut format) = build_format(
+                    &adaptation_set,
+                    &representation,
+                    url,
+                    init_range.take(),
+                    index_range.take(),
+                    audio_channels.take().or(adaptation_set_channels.clone()),
+                    duration_ms,
+                ) {
+                    if segmented {
+                        format["isDashSegmented"] = Value::Bool(true);
+                    }
+
+                    formats.push(format);
+                }
+            }
+        }
+    }
+
+    formats
+}
+
+/// Media segments of a DASH representation, see [`parse_dash_segments`]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct DashSegments {
+    pub(crate) initialization: Option<String>,
+    pub(crate) segments: Vec<String>,
+    /// `type="dynamic"` manifests get new segments while the video is live
+    pub(crate) is_dynamic: bool,
+}
+
+/// `SegmentTemplate` element with the entries of its `SegmentTimeline`
+struct SegmentTemplate {
+    attributes: HashMap<String, String>,
+    timeline: Vec<SegmentTimelineEntry>,
+}
+
+/// `S` element of a `SegmentTimeline`
+struct SegmentTimelineEntry {
+    start: Option<u64>,
+    duration: u64,
+    repeat: u64,
+}
+
+/// Absolute URLs of the initialization and media segments of the representation with `representation_id`
+/// from its `SegmentList` or `SegmentTemplate`. Relative URLs are resolved against `BaseURL` and `manifest_url`
+pub(crate) fn parse_dash_segments(
+    body: &str,
+    manifest_url: &str,
+    representation_id: &str,
+) -> Option<DashSegments> {
+    let mut is_dynamic = false;
+    let mut duration_ms: Option<u64> = None;
+    let mut base_url: Option<String> = None;
+    let mut representation_base_url: Option<String> = None;
+    let mut in_base_url = false;
+    let mut in_adaptation_set = false;
+    let mut representation: Option<HashMap<String, String>> = None;
+    let mut adaptation_set_template: Option<SegmentTemplate> = None;
+    let mut representation_template: Option<SegmentTemplate> = None;
+    let mut initialization: Option<String> = None;
+    let mut segments: Vec<String> = vec![];
+    let mut last_end = 0;
+
+    for tag in REGEX_TAG.captures_iter(body) {
+        let whole = tag.get(0).expect("IMPOSSIBLE");
+
+        // Text between previous tag and current tag
+        if in_base_url {
+            let text = decode_entities(body[last_end..whole.start()].trim());
+            if !text.is_empty() {
+                if representation.is_some() {
+                    representation_base_url = Some(text);
+                } else if !in_adaptation_set {
+                    base_url = Some(text);
+                }
+            }
+        }
+        last_end = whole.end();
+
+        let is_closing = !tag[1].is_empty();
+        let is_self_closing = !tag[4].is_empty();
+        let name = tag[2].to_lowercase();
+        let mut attributes = parse_attributes(tag.get(3).map(|x| x.as_str()).unwrap_or(""));
+
+        match (name.as_str(), is_closing) {
+            ("mpd", false) => {
+                is_dynamic = attributes.get("type").map(|x| x.as_str()) == Some("dynamic");
+                duration_ms = attributes
+                    .get("mediapresentationduration")
+                    .and_then(|x| parse_duration_ms(x));
+            }
+            ("adaptationset", false) => {
+                in_adaptation_set = true;
+                adaptation_set_template = None;
+            }
+            ("adaptationset", true) => in_adaptation_set = false,
+            ("representation", false) => {
+                representation = Some(std::mem::take(&mut attributes));
+                representation_base_url = None;
+                representation_template = None;
+                initialization = None;
+                segments.clear();
+            }
+            ("baseurl", false) => in_base_url = !is_self_closing,
+            ("baseurl", true) => in_base_url = false,
+            ("segmenttemplate", false) => {
+                let template = SegmentTemplate {
+                    attributes: std::mem::take(&mut attributes),
+                    timeline: vec![],
+                };
+
+                if representation.is_some() {
+                    representation_template = Some(template);
+                } else if in_adaptation_set {
+                    adaptation_set_template = Some(template);
+                }
+            }
+            ("s", false) => {
+                let template = if representation.is_some() {
+                    representation_template.as_mut()
+                } else {
+                    adaptation_set_template.as_mut()
+                };
+
+                let duration = attributes.get("d").and_then(|x| x.parse::<u64>().ok());
+
+                if let (Some(template), Some(duration)) = (template, duration) {
+                    template.timeline.push(SegmentTimelineEntry {
+                        start: attributes.get("t").and_then(|x| x.parse::<u64>().ok()),
+                        duration,
+                        // Negative repeat (until the next period) is not supported
+                        repeat: attributes
+                            .get("r")
+                            .and_then(|x| x.parse::<i64>().ok())
+                            .unwrap_or(0)
+                            .max(0) as u64,
+                    });
+                }
+            }
+            // `Initialization` of `SegmentBase` has a `range` instead
+            ("initialization", false) if representation.is_some() => {
+                if let Some(url) = attributes.get("sourceurl") {
+                    initialization = Some(url.clone());
+                }
+            }
+            ("segmenturl", false) if representation.is_some() => {
+                if let Some(url) = attributes.get("media") {
+                    segments.push(url.clone());
+                }
+            }
+            _ => {}
+        }
+
+        if name != "representation" || !(is_closing || is_self_closing) {
+            continue;
+        }
+
+        let Some(current) = representation.take() else {
+            continue;
+        };
+
+        if current.get("id").map(|x| x.as_str()) != Some(representation_id) {
+            continue;
+        }
+
+        let base = match representation_base_url.or(base_url) {
+            Some(base) => make_absolute_url(manifest_url, &base).ok()?.to_string(),
+            None => manifest_url.to_string(),
+        };
+
+        let (initialization, segments) = match representation_template.or(adaptation_set_template) {
+            Some(template) => expand_segment_template(&template, &current, duration_ms)?,
+            None => (initialization, segments),
+        };
+
+        let resolve = |url: &str| {
+            make_absolute_url(&base, url)
+                .ok()
+                .map(|url| url.to_string())
+        };
+
+        return Some(DashSegments {
+            initialization: initialization.as_deref().and_then(resolve),
+            segments: segments.iter().filter_map(|x| resolve(x)).collect(),
+            is_dynamic,
+        });
+    }
+
+    None
+}
+
+/// Initialization and media URLs of a `SegmentTemplate`. Segment count comes from the `SegmentTimeline`,
+/// otherwise from the `duration` of the segments and `mediaPresentationDuration`
+fn expand_segment_template(
+    template: &SegmentTemplate,
+    representation: &HashMap<String, String>,
+    duration_ms: Option<u64>,
+) -> Option<(Option<String>, Vec<String>)> {
+    let attribute = |name: &str| template.attributes.get(name).map(|x| x.as_str());
+
+    let id = representation.get("id").map(|x| x.as_str()).unwrap_or("");
+    let bandwidth = representation
+        .get("bandwidth")
+        .map(|x| x.as_str())
+        .unwrap_or("");
+
+    let fill = |pattern: &str, number: u64, time: u64| {
+        pattern
+            .replace("$RepresentationID$", id)
+            .replace("$Bandwidth$", bandwidth)
+            .replace("$Number$", &number.to_string())
+            .replace("$Time$", &time.to_string())
+            .replace("$$", "$")
+    };
+
+    let media = attribute("media")?;
+    let start_number = attribute("startnumber")
+        .and_then(|x| x.parse::<u64>().ok())
+        .unwrap_or(1);
+    let initialization = attribute("initialization").map(|x| fill(x, start_number, 0));
+
+    let mut segments = vec![];
+
+    if template.timeline.is_empty() {
+        let duration = attribute("duration")
+            .and_then(|x| x.parse::<u64>().ok())
+            .filter(|x| *x > 0)?;
+        let timescale = attribute("timescale")
+            .and_then(|x| x.parse::<u64>().ok())
+            .unwrap_or(1);
+
+        // Last segment can be shorter than `duration`
+        let count = (duration_ms? * timescale + duration * 1000 - 1) / (duration * 1000);
+
+        for index in 0..count {
+            segments.push(fill(media, start_number + index, index * duration));
+        }
+    } else {
+        let mut number = start_number;
+        let mut time = 0;
+
+        for entry in &template.timeline {
+            if let Some(start) = entry.start {
+                time = start;
+            }
+
+            for _ in 0..=entry.repeat {
+                segments.push(fill(media, number, time));
+                number += 1;
+                time += entry.duration;
+            }
+        }
+    }
+
+    Some((initialization, segments))
+}
+
+fn build_format(
+    adaptation_set: &HashMap<String, String>,
+    representation: &HashMap<String, String>,
+    url: String,
+    init_range: Option<String>,
+    index_range: Option<String>,
+    audio_channels: Option<String>,
+    duration_ms: Option<u64>,
+) -> Option<Value> {
+    let attribute = |name: &str| {
+        representation
+            .get(name)
+            .or_else(|| adaptation_set.get(name))
+            .map(|x| x.as_str())
+    };
+
+    let itag = attribute("id")?.parse::<u64>().ok()?;
+    let mime_type = attribute("mimetype")?;
+    let codecs = attribute("codecs").unwrap_or("");
+    let bitrate = attribute("bandwidth")
+        .and_then(|x| x.parse::<u64>().ok())
+        .unwrap_or(0);
+
+    let mut format = serde_json::json!({
+        "itag": itag,
+        "mimeType": format!(r#"{mime_type}; codecs="{codecs}""#),
+        "bitrate": bitrate,
+        "url": url,
+    });
+
+    let format_as_object_mut = format.as_object_mut().expect("IMPOSSIBLE");
+
+    let static_format = FORMATS.get(itag.to_string());
+
+    if let Some(height) = attribute("height").and_then(|x| x.parse::<u64>().ok()) {
+        let width = attribute("width")
+            .and_then(|x| x.parse::<u64>().ok())
+            .unwrap_or(0);
+        let fps = attribute("framerate").map(parse_frame_rate).unwrap_or(0);
+
+        format_as_object_mut.insert("width".to_string(), Value::from(width));
+        format_as_object_mut.insert("height".to_string(), Value::from(height));
+        format_as_object_mut.insert("fps".to_string(), Value::from(fps));
+
+        let quality_label = if fps > 30 {
+            format!("{height}p{fps}")
+        } else {
+            format!("{height}p")
+        };
+
+        format_as_object_mut.insert("qualityLabel".to_string(), Value::String(quality_label));
+    }
+
+    if mime_type.starts_with("audio") {
+        let audio_bitrate = static_format
+            .and_then(|x| x.get("audioBitrate"))
+            .and_then(|x| x.as_u64())
+            .unwrap_or(bitrate / 1000);
+
+        format_as_object_mut.insert("audioBitrate".to_string(), Value::from(audio_bitrate));
+
+        if let Some(sample_rate) = attribute("audiosamplingrate") {
+            format_as_object_mut.insert(
+                "audioSampleRate".to_string(),
+                Value::String(sample_rate.to_string()),
+            );
+        }
+
+        if let Some(channels) = audio_channels.and_then(|x| x.parse::<u8>().ok()) {
+            format_as_object_mut.insert("audioChannels".to_string(), Value::from(channels));
+        }
+    }
+
+    if let Some(duration_ms) = duration_ms {
+        format_as_object_mut.insert(
+            "approxDurationMs".to_string(),
+            Value::String(duration_ms.to_string()),
+        );
+    }
+
+    if let Some(init_range) = init_range.as_deref().and_then(parse_range) {
+        format_as_object_mut.insert("initRange".to_string(), init_range);
+    }
+
+    if let Some(index_range) = index_range.as_deref().and_then(parse_range) {
+        format_as_object_mut.insert("indexRange".to_string(), index_range);
+    }
+
+    add_format_meta(format_as_object_mut);
+
+    // Representation URLs do not contain `/manifest/dash/` so flag them manually
+    format_as_object_mut.insert("isDashMPD".to_string(), Value::Bool(true));
+
+    Some(format)
+}
+
+fn parse_attributes(raw: &str) -> HashMap<String, String> {
+    REGEX_ATTRIBUTE
+        .captures_iter(raw)
+        .map(|x| (x[1].to_lowercase(), decode_entities(&x[2])))
+        .collect()
+}
+
+/// Parse `frameRate` attribute. It can be a fraction like `30000/1001`
+fn parse_frame_rate(raw: &str) -> u64 {
+    match raw.split_once('/') {
+        Some((numerator, denominator)) => {
+            let numerator = numerator.parse::<f64>().unwrap_or(0f64);
+            let denominator = denominator.parse::<f64>().unwrap_or(1f64);
+
+            if denominator == 0f64 {
+                0
+            } else {
+                (numerator / denominator).round() as u64
+            }
+        }
+        None => raw.parse::<f64>().map(|x| x.round() as u64).unwrap_or(0),
+    }
+}
+
+/// Parse ISO 8601 duration of `mediaPresentationDuration` attribute like `PT1H2M3.5S` to milliseconds
+fn parse_duration_ms(raw: &str) -> Option<u64> {
+    let raw = raw.strip_prefix("PT")?;
+    let mut seconds = 0f64;
+    let mut number = String::new();
+
+    for c in raw.chars() {
+        match c {
+            '0'..='9' | '.' => number.push(c),
+            'H' | 'M' | 'S' => {
+                let value = number.parse::<f64>().ok()?;
+                number.clear();
+
+                seconds += match c {
+                    'H' => value * 3600f64,
+                    'M' => value * 60f64,
+                    _ => value,
+                };
+            }
+            _ => return None,
+        }
+    }
+
+    if !number.is_empty() {
+        return None;
+    }
+
+    Some((seconds * 1000f64).round() as u64)
+}
+
+fn parse_range(raw: &str) -> Option<Value> {
+    let (start, end) = raw.split_once('-')?;
+
+    Some(serde_json::json!({
+        "start": start,
+        "end": end,
+    }))
+}
+
+/// Decode XML entities, numeric character references like `&#39;` included
+pub(crate) fn decode_entities(raw: &str) -> String {
+    static REGEX_NUMERIC_ENTITY: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"&#(x[0-9A-Fa-f]+|[0-9]+);").unwrap());
+
+    let raw = REGEX_NUMERIC_ENTITY.replace_all(raw, |x: &regex::Captures| {
+        let code = match x[1].strip_prefix('x') {
+            Some(hex) => u32::from_str_radix(hex, 16).ok(),
+            None => x[1].parse::<u32>().ok(),
+        };
+
+        code.and_then(char::from_u32)
+            .map(|x| x.to_string())
+            .unwrap_or_else(|| x[0].to_string())
+    });
+
+    raw.replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_dash_manifest() {
+        let body = r#"<?xml version="1.0" encoding="UTF-8"?>
+<MPD xmlns="urn:mpeg:DASH:schema:MPD:2011" type="static" mediaPresentationDuration="PT3M32.5S">
+  <Period>
+    <AdaptationSet id="0" mimeType="audio/mp4" subsegmentAlignment="true">
+      <Representation id="140" codecs="mp4a.40.2" audioSamplingRate="44100" bandwidth="144000">
+        <AudioChannelConfiguration schemeIdUri="urn:mpeg:dash:23003:3:audio_channel_configuration:2011" value="2"/>
+        <BaseURL>https://rr1---sn.googlevideo.com/videoplayback?id=1&amp;itag=140</BaseURL>
+        <SegmentBase indexRange="632-1023"><Initialization range="0-631"/></SegmentBase>
+      </Representation>
+    </AdaptationSet>
+    <AdaptationSet id="1" mimeType="video/mp4" subsegmentAlignment="true">
+      <Representation id="299" codecs="avc1.64002a" width="1920" height="1080" frameRate="60" bandwidth="6000000">
+        <BaseURL>https://rr1---sn.googlevideo.com/videoplayback?id=1&amp;itag=299</BaseURL>
+      </Representation>
+      <Representation id="0" codecs="avc1" width="1" height="1" bandwidth="1"/>
+    </AdaptationSet>
+  </Period>
+</MPD>"#;
+
+        let formats = parse_dash_manifest(
+            body,
+            "https://manifest.googlevideo.com/api/manifest/dash/id/1",
+        );
+        assert_eq!(formats.len(), 2);
+
+        let audio = &formats[0];
+        assert_eq!(audio["itag"], 140);
+        assert_eq!(audio["mimeType"], r#"audio/mp4; codecs="mp4a.40.2""#);
+        assert_eq!(
+            audio["url"],
+            "https://rr1---sn.googlevideo.com/videoplayback?id=1&itag=140"
+        );
+        assert_eq!(audio["audioSampleRate"], "44100");
+        assert_eq!(audio["audioChannels"], 2);
+        assert_eq!(audio["approxDurationMs"], "212500");
+        assert_eq!(audio["indexRange"]["start"], "632");
+        assert_eq!(audio["initRange"]["end"], "631");
+        assert_eq!(audio["hasAudio"], true);
+        assert_eq!(audio["isDashMPD"], true);
+
+        let video = &formats[1];
+        assert_eq!(video["itag"], 299);
+        assert_eq!(video["height"], 1080);
+        assert_eq!(video["fps"], 60);
+        assert_eq!(video["qualityLabel"], "1080p60");
+        assert_eq!(video["hasVideo"], true);
+        assert_eq!(video["hasAudio"], false);
+        assert!(video.get("audioChannels").is_none());
+    }
+
+    #[test]
+    fn test_parse_dash_segments() {
+        let manifest_url = "https://manifest.googlevideo.com/api/manifest/dash/id/1";
+        let body = r#"<?xml version="1.0" encoding="UTF-8"?>
+<MPD xmlns="urn:mpeg:DASH:schema:MPD:2011" type="dynamic">
+  <Period>
+    <AdaptationSet id="0" mimeType="audio/mp4">
+      <Representation id="140" codecs="mp4a.40.2" audioSamplingRate="48000" bandwidth="144000">
+        <BaseURL>https://rr1---sn.googlevideo.com/videoplayback/id/1/itag/140/</BaseURL>
+        <SegmentList><Initialization sourceURL="sq/0"/><SegmentURL media="sq/1"/><SegmentURL media="sq/2"/></SegmentList>
+      </Representation>
+    </AdaptationSet>
+    <AdaptationSet id="1" mimeType="video/mp4">
+      <SegmentTemplate initialization="init/$RepresentationID$.mp4" media="$RepresentationID$/$Number$.m4s" startNumber="5">
+        <SegmentTimeline><S t="0" d="5000" r="2"/><S d="2500"/></SegmentTimeline>
+      </SegmentTemplate>
+      <Representation id="299" codecs="avc1.64002a" width="1920" height="1080" frameRate="60" bandwidth="6000000"/>
+    </AdaptationSet>
+  </Period>
+</MPD>"#;
+
+        let formats = parse_dash_manifest(body, manifest_url);
+        assert_eq!(formats.len(), 2);
+        assert_eq!(formats[0]["url"], manifest_url);
+        assert_eq!(formats[0]["isDashSegmented"], true);
+        assert_eq!(formats[1]["itag"], 299);
+        assert_eq!(formats[1]["isDashSegmented"], true);
+
+        let audio = parse_dash_segments(body, manifest_url, "140").unwrap();
+        assert!(audio.is_dynamic);
+        assert_eq!(
+            audio.initialization.as_deref(),
+            Some("https://rr1---sn.googlevideo.com/videoplayback/id/1/itag/140/sq/0")
+        );
+        assert_eq!(
+            audio.segments,
+            vec![
+                "https://rr1---sn.googlevideo.com/videoplayback/id/1/itag/140/sq/1",
+                "https://rr1---sn.googlevideo.com/videoplayback/id/1/itag/140/sq/2",
+            ]
+        );
+
+        let video = parse_dash_segments(body, manifest_url, "299").unwrap();
+        assert_eq!(
+            video.initialization.as_deref(),
+            Some("https://manifest.googlevideo.com/api/manifest/dash/id/init/299.mp4")
+        );
+        assert_eq!(video.segments.len(), 4);
+        assert_eq!(
+            video.segments[3],
+            "https://manifest.googlevideo.com/api/manifest/dash/id/299/8.m4s"
+        );
+
+        assert!(parse_dash_segments(body, manifest_url, "137").is_none());
+    }
+
+    #[test]
+    fn test_parse_duration_ms() {
+        assert_eq!(parse_duration_ms("PT1H2M3.5S"), Some(3_723_500));
+        assert_eq!(parse_duration_ms("PT212.5S"), Some(212_500));
+        assert_eq!(parse_duration_ms("P1D"), None);
+        assert_eq!(parse_duration_ms("PT12"), None);
+    }
+}
@@ -0,0 +1,116 @@
+use m3u8_rs::parse_master_playlist_res;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value;
+
+use crate::constants::FORMATS;
+use crate::utils::{add_format_meta, make_absolute_url};
+
+static REGEX_ITAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"/itag/(\d+)/").unwrap());
+
+/// Parse HLS master playlist body to the format objects, one per variant stream
+/// - `base_url` is used to resolve relative variant URIs
+#[cfg_attr(feature = "performance_analysis", flamer::flame)]
+pub fn parse_hls_master_playlist(body: &str, base_url: &str) -> Vec<Value> {
+    let master_playlist = match parse_master_playlist_res(body.as_bytes()) {
+        Ok(master_playlist) => master_playlist,
+        Err(_) => return vec![],
+    };
+
+    let default_formats = FORMATS.as_object().expect("IMPOSSIBLE");
+
+    master_playlist
+        .variants
+        .iter()
+        .filter(|x| !x.is_i_frame)
+        .filter_map(|variant| {
+            let url = make_absolute_url(base_url, &variant.uri).ok()?;
+
+            let itag = REGEX_ITAG
+                .captures(url.as_str())
+                .and_then(|x| x.get(1))
+                .and_then(|x| x.as_str().parse::<u64>().ok())?;
+
+            let static_format = default_formats.get(&itag.to_string());
+            let codecs = variant.codecs.clone().unwrap_or_default();
+
+            let mime_type = static_format
+                .and_then(|x| x.get("mimeType"))
+                .and_then(|x| x.as_str())
+                .map(|x| x.to_string())
+                .unwrap_or_else(|| format!(r#"video/ts; codecs="{codecs}""#));
+
+            let mut format = serde_json::json!({
+                "itag": itag,
+                "mimeType": mime_type,
+                "bitrate": variant.average_bandwidth.unwrap_or(variant.bandwidth),
+                "url": url.as_str(),
+            });
+
+            let format_as_object_mut = format.as_object_mut().expect("IMPOSSIBLE");
+
+            if let Some(resolution) = &variant.resolution {
+                let fps = variant.frame_rate.map(|x| x.round() as u64).unwrap_or(0);
+
+                format_as_object_mut.insert("width".to_string(), Value::from(resolution.width));
+                format_as_object_mut.insert("height".to_string(), Value::from(resolution.height));
+                format_as_object_mut.insert("fps".to_string(), Value::from(fps));
+
+                let quality_label = if fps > 30 {
+                    format!("{}p{fps}", resolution.height)
+                } else {
+                    format!("{}p", resolution.height)
+                };
+
+                format_as_object_mut
+                    .insert("qualityLabel".to_string(), Value::String(quality_label));
+            }
+
+            let audio_bitrate = static_format
+                .and_then(|x| x.get("audioBitrate"))
+                .filter(|x| !x.is_null())
+                .cloned();
+
+            if let Some(audio_bitrate) = audio_bitrate {
+                format_as_object_mut.insert("audioBitrate".to_string(), audio_bitrate);
+            } else if codecs.contains("mp4a") {
+                // Unknown itag, audio bitrate is not reported by the playlist
+                format_as_object_mut.insert("audioBitrate".to_string(), Value::from(0));
+            }
+
+            add_format_meta(format_as_object_mut);
+
+            Some(format)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_hls_master_playlist() {
+        let body = r#"#EXTM3U
+#EXT-X-INDEPENDENT-SEGMENTS
+#EXT-X-STREAM-INF:BANDWIDTH=290288,CODECS="mp4a.40.5,avc1.42C00B",RESOLUTION=256x144,FRAME-RATE=30,VIDEO-RANGE=SDR
+https://manifest.googlevideo.com/api/manifest/hls_playlist/expire/1/itag/91/playlist/index.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=5704990,CODECS="mp4a.40.2,avc1.64002A",RESOLUTION=1920x1080,FRAME-RATE=60,VIDEO-RANGE=SDR
+https://manifest.googlevideo.com/api/manifest/hls_playlist/expire/1/itag/301/playlist/index.m3u8
+"#;
+
+        let formats = parse_hls_master_playlist(body, "https://manifest.googlevideo.com/");
+        assert_eq!(formats.len(), 2);
+
+        assert_eq!(formats[0]["itag"], 91);
+        assert_eq!(formats[0]["bitrate"], 290288);
+        assert_eq!(formats[0]["qualityLabel"], "144p");
+        assert_eq!(formats[0]["isHLS"], true);
+
+        assert_eq!(formats[1]["itag"], 301);
+        assert_eq!(formats[1]["width"], 1920);
+        assert_eq!(formats[1]["qualityLabel"], "1080p60");
+        assert_eq!(formats[1]["hasVideo"], true);
+        assert_eq!(formats[1]["hasAudio"], true);
+    }
+}
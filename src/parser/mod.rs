@@ -1,19 +1,33 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
 
-use boa_engine::{Context, JsValue, Source};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use urlencoding::decode;
 
+use crate::js_runtime::JsRuntime;
 use crate::{utils::add_format_meta, VideoFormat};
 
 mod cipher;
+mod dash;
+mod hls;
+mod native;
 mod ncode;
 
+pub(crate) use cipher::DecipherQuery;
+pub(crate) use native::call_native;
+
+pub use dash::parse_dash_manifest;
+pub(crate) use dash::{decode_entities, parse_dash_segments, DashSegments};
+pub use hls::parse_hls_master_playlist;
+
 #[cfg_attr(feature = "performance_analysis", flamer::flame)]
 pub fn parse_video_formats(
     info: &serde_json::Value,
     format_functions: Vec<(String, String)>,
+    js_runtime: Option<Arc<dyn JsRuntime>>,
 ) -> Option<Vec<VideoFormat>> {
     if info.as_object()?.contains_key("streamingData") {
         let formats = info
@@ -28,24 +42,38 @@ pub fn parse_video_formats(
             .as_array()?;
         let mut formats = [&formats[..], &adaptive_formats[..]].concat();
 
-        let mut n_transform_cache: HashMap<String, String> = HashMap::new();
+        // DRM protected formats need a license to decrypt, see `is_drm_protected`
+        formats.retain(|x| x.get("drmFamilies").is_none());
+
+        let mut signature_ciphers: Vec<Option<DecipherQuery>> = vec![];
 
-        let mut cipher_cache: Option<(String, Context)> = None;
         for format in &mut formats {
             let parsed: SetDownloadURLValue = serde_json::from_value(format.clone()).unwrap();
             format.as_object_mut().map(|x| {
-                let new_url = set_download_url(
-                    &parsed,
-                    &format_functions,
-                    &mut n_transform_cache,
-                    &mut cipher_cache,
-                );
+                // Deciphering is deferred to `VideoFormat::resolve_url`, keep the unsigned URL until then
+                let (url, signature_cipher) = match parsed.url {
+                    Some(url) => (url, None),
+                    None => {
+                        let signature_cipher = parsed
+                            .signature_cipher
+                            .or(parsed.cipher)
+                            .and_then(|x| cipher::decode_url(&x));
+                        (
+                            signature_cipher
+                                .as_ref()
+                                .map(|x| x.url.clone())
+                                .unwrap_or_default(),
+                            signature_cipher,
+                        )
+                    }
+                };
+                signature_ciphers.push(signature_cipher);
 
                 // Delete unnecessary cipher, signatureCipher
                 x.remove("signatureCipher");
                 x.remove("cipher");
 
-                x.insert("url".to_string(), Value::String(new_url));
+                x.insert("url".to_string(), Value::String(url));
 
                 // Add Video metaData
                 add_format_meta(x);
@@ -54,10 +82,17 @@ pub fn parse_video_formats(
             });
         }
 
+        // Without a JavaScript runtime URLs can not be deciphered, they are returned as is
+        let player_functions = js_runtime.map(|js_runtime| PlayerFunctions {
+            functions: Arc::new(format_functions),
+            js_runtime,
+            n_cache: Arc::new(Mutex::new(HashMap::new())),
+        });
+
         let mut well_formated_formats: Vec<VideoFormat> = vec![];
 
         // Change formats type serde_json::Value to VideoFormat
-        for format in formats.iter() {
+        for (format, signature_cipher) in formats.iter().zip(signature_ciphers) {
             let well_formated_format: Result<VideoFormat, serde_json::Error> =
                 serde_json::from_value(format.clone());
 
@@ -65,8 +100,11 @@ pub fn parse_video_formats(
                 continue;
             }
 
-            well_formated_formats
-                .insert(well_formated_formats.len(), well_formated_format.unwrap());
+            let mut well_formated_format = well_formated_format.unwrap();
+            well_formated_format.signature_cipher = signature_cipher;
+            well_formated_format.player_functions = player_functions.clone();
+
+            well_formated_formats.insert(well_formated_formats.len(), well_formated_format);
         }
 
         Some(well_formated_formats)
@@ -83,28 +121,114 @@ pub struct SetDownloadURLValue {
     cipher: Option<String>,
 }
 
+/// Extracted player functions with the [`JsRuntime`] that executes them
+#[derive(Clone)]
+pub(crate) struct PlayerFunctions {
+    pub(crate) functions: Arc<Vec<(String, String)>>,
+    pub(crate) js_runtime: Arc<dyn JsRuntime>,
+    /// Transformed `n` parameters, formats of a player response have the same `n`
+    pub(crate) n_cache: Arc<Mutex<HashMap<String, String>>>,
+}
+
+impl std::fmt::Debug for PlayerFunctions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PlayerFunctions")
+            .field("functions", &self.functions)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for PlayerFunctions {
+    fn eq(&self, other: &Self) -> bool {
+        self.functions == other.functions
+    }
+}
+
+/// Decipher the signature and transform `n` parameter of the format URL with the player functions
 #[cfg_attr(feature = "performance_analysis", flamer::flame)]
-pub fn set_download_url(
-    format: &SetDownloadURLValue,
-    functions: &[(String, String)],
-    n_transform_cache: &mut HashMap<String, String>,
-    cipher_cache: &mut Option<(String, Context)>,
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "trace", skip_all))]
+pub(crate) fn resolve_download_url(
+    url: &str,
+    signature_cipher: Option<&DecipherQuery>,
+    player_functions: &PlayerFunctions,
 ) -> String {
+    if url.is_empty() {
+        return String::new();
+    }
+
     let empty_script = ("".to_string(), "".to_string());
+    let functions = &player_functions.functions;
+    let js_runtime = player_functions.js_runtime.as_ref();
     let decipher_script_string = functions.first().unwrap_or(&empty_script);
     let n_transform_script_string = functions.get(1).unwrap_or(&empty_script);
 
+    let url = match signature_cipher {
+        Some(signature_cipher) => {
+            cipher::decipher(url, signature_cipher, decipher_script_string, js_runtime)
+        }
+        None => url.to_string(),
+    };
+
     ncode::ncode(
-        &if let Some(url) = &format.url {
-            url.clone()
-        } else {
-            let url = format
-                .signature_cipher
-                .clone()
-                .unwrap_or(format.cipher.clone().unwrap_or_default());
-            cipher::decipher(&url, decipher_script_string, cipher_cache)
-        },
+        &url,
         n_transform_script_string,
-        n_transform_cache,
+        &mut player_functions.n_cache.lock().expect("IMPOSSIBLE"),
+        js_runtime,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::structs::VideoError;
+    use crate::utils::test_format;
+
+    #[derive(Default)]
+    struct CountingRuntime(AtomicUsize);
+
+    impl JsRuntime for CountingRuntime {
+        fn call(&self, _: &str, _: &str, argument: &str) -> Result<String, VideoError> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            Ok(format!("{argument}x"))
+        }
+    }
+
+    #[test]
+    fn test_resolve_url_is_memoized() {
+        let js_runtime = Arc::new(CountingRuntime::default());
+        let player_functions = PlayerFunctions {
+            functions: Arc::new(vec![
+                ("".to_string(), "".to_string()),
+                ("ncode".to_string(), "var ncode = (n) => n;".to_string()),
+            ]),
+            js_runtime: js_runtime.clone(),
+            n_cache: Arc::new(Mutex::new(HashMap::new())),
+        };
+
+        let formats: Vec<VideoFormat> = [18, 140]
+            .into_iter()
+            .map(|itag| {
+                let mut format = test_format(
+                    itag,
+                    r#"video/mp4; codecs="avc1.42001E, mp4a.40.2""#,
+                    500_000,
+                    serde_json::json!({
+                        "url": format!("https://rr1.googlevideo.com/videoplayback?itag={itag}&n=abc"),
+                    }),
+                );
+                format.player_functions = Some(player_functions.clone());
+                format
+            })
+            .collect();
+
+        assert!(formats[0].resolve_url().contains("n=abcx"));
+        assert_eq!(formats[0].resolve_url(), formats[0].resolve_url());
+        assert!(formats[1].resolve_url().contains("n=abcx"));
+        assert!(formats[1].raw_url.ends_with("n=abc"));
+
+        // `n` of both formats is transformed once
+        assert_eq!(js_runtime.0.load(Ordering::SeqCst), 1);
+    }
+}
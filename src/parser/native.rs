@@ -0,0 +1,799 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+static DECIPHER_MEMBER_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"^([\w$]+|"[\w$]+"):function\([\w$,]*\)\{(.*)\}$"#).unwrap());
+static IDENTIFIER_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"[A-Za-z_$][\w$]*").unwrap());
+static ELEMENT_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^([\w$]+)\[(\d+)\]$").unwrap());
+static CALL_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^([\w$]+)\[(\d+)\]\((.*)\)$").unwrap());
+static ASSIGNMENT_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^([\w$]+)\[(\d+)\]=(.+)$").unwrap());
+static ALPHABET_LOOP_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^for\(var V0=(-?\d+),V1=\[\];\+\+V0-V1\.length-(\d+);\)\{switch\(V0\)\{(.*)\}\}$")
+        .unwrap()
+});
+
+const KEYWORDS: [&str; 16] = [
+    "var", "for", "function", "this", "return", "switch", "case", "default", "continue", "break",
+    "String", "null", "typeof", "if", "else", "new",
+];
+
+/// Run the player function without a JavaScript engine
+/// - Return [`None`] if the function does not match the known signature decipher or `n` transform patterns
+#[cfg_attr(feature = "performance_analysis", flamer::flame)]
+pub(crate) fn call_native(script: &str, function_name: &str, argument: &str) -> Option<String> {
+    let (params, body) = function_body(script, function_name)?;
+
+    if params.len() != 1 {
+        return None;
+    }
+
+    decipher(script, params[0], body, argument).or_else(|| n_transform(params[0], body, argument))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DecipherOperation {
+    Reverse,
+    Splice,
+    Swap,
+}
+
+/// Decipher function splits the signature, calls helper object methods on it and joins it back
+fn decipher(script: &str, param: &str, body: &str, signature: &str) -> Option<String> {
+    let statements = split_top_level(body, ';');
+    let (first, rest) = statements.split_first()?;
+    let (last, calls) = rest.split_last()?;
+
+    if *first != format!(r#"{param}={param}.split("")"#)
+        || *last != format!(r#"return {param}.join("")"#)
+    {
+        return None;
+    }
+
+    let call_regex = Regex::new(&format!(
+        r#"^([\w$]+)(?:\.([\w$]+)|\["([\w$]+)"\])\({},(\d+)\)$"#,
+        regex::escape(param)
+    ))
+    .ok()?;
+
+    let mut helpers: HashMap<&str, HashMap<String, DecipherOperation>> = HashMap::new();
+    let mut signature = signature.chars().collect::<Vec<char>>();
+
+    for call in calls {
+        let captures = call_regex.captures(call)?;
+        let object = captures.get(1)?.as_str();
+        let method = captures.get(2).or_else(|| captures.get(3))?.as_str();
+        let argument = captures.get(4)?.as_str().parse::<usize>().ok()?;
+
+        if !helpers.contains_key(object) {
+            helpers.insert(object, parse_decipher_helpers(script, object)?);
+        }
+
+        match helpers.get(object)?.get(method)? {
+            DecipherOperation::Reverse => signature.reverse(),
+            DecipherOperation::Splice => {
+                signature.drain(..argument.min(signature.len()));
+            }
+            DecipherOperation::Swap => {
+                if signature.is_empty() {
+                    return None;
+                }
+                let index = argument % signature.len();
+                signature.swap(0, index);
+            }
+        }
+    }
+
+    Some(signature.into_iter().collect())
+}
+
+fn parse_decipher_helpers(
+    script: &str,
+    object: &str,
+) -> Option<HashMap<String, DecipherOperation>> {
+    let object_start = format!("var {object}={{");
+    let start = script.find(&object_start)? + object_start.len() - 1;
+    let end = find_closing(script, start)?;
+
+    let mut operations = HashMap::new();
+
+    for member in split_top_level(&script[start + 1..end], ',') {
+        let captures = DECIPHER_MEMBER_REGEX.captures(member)?;
+        let name = captures.get(1)?.as_str().trim_matches('"');
+        let member_body = captures.get(2)?.as_str();
+
+        let operation = if member_body.contains("reverse") {
+            DecipherOperation::Reverse
+        } else if member_body.contains("splice") {
+            DecipherOperation::Splice
+        } else if member_body.contains("length") {
+            DecipherOperation::Swap
+        } else {
+            return None;
+        };
+
+        operations.insert(name.to_string(), operation);
+    }
+
+    Some(operations)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum NValue {
+    Number(i64),
+    String(String),
+    Null,
+    /// Index of the array in the interpreter arrays
+    Array(usize),
+    Function(NOperation),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum NOperation {
+    Reverse,
+    Push,
+    Unshift,
+    Remove,
+    Swap,
+    Rotate,
+    /// `d.splice(-e).reverse().forEach(...)` rotation which does not wrap the count
+    RotateSplice,
+    /// Character substitution with a key
+    /// - `alphabet` is the third argument if it is not generated by the function
+    /// - `counter` is the alphabet length if it is not generated by the function
+    Cipher {
+        alphabet: Option<Vec<char>>,
+        counter: Option<i64>,
+        offset: i64,
+        uses_this: bool,
+    },
+}
+
+/// `n` transform function builds an array of values and operations, then calls them in a try block
+fn n_transform(param: &str, body: &str, n: &str) -> Option<String> {
+    let mut names: HashMap<&str, usize> = HashMap::new();
+    let mut arrays: Vec<Vec<NValue>> = vec![];
+
+    for statement in split_statements(body) {
+        if let Some(declarations) = statement.strip_prefix("var ") {
+            let mut literals = vec![];
+
+            for declaration in split_top_level(declarations, ',') {
+                let (name, value) = declaration.split_once('=')?;
+                let index = arrays.len();
+                names.insert(name, index);
+
+                if is_split_of(value, param) {
+                    arrays.push(n.chars().map(|x| NValue::String(x.to_string())).collect());
+                } else {
+                    arrays.push(vec![]);
+                    literals.push((index, value.strip_prefix('[')?.strip_suffix(']')?));
+                }
+            }
+
+            // Literals can reference every array of the declaration
+            for (index, literal) in literals {
+                arrays[index] = split_top_level(literal, ',')
+                    .into_iter()
+                    .map(|x| parse_n_value(x, &names))
+                    .collect::<Option<Vec<NValue>>>()?;
+            }
+        } else if let Some(block) = statement.strip_prefix("try{") {
+            for call in split_top_level(block.strip_suffix('}')?, ',') {
+                run_n_call(call, &names, &mut arrays)?;
+            }
+        } else if statement.starts_with("catch(") {
+            // Interpreter returns `None` instead of throwing, so catch block never runs
+            continue;
+        } else if let Some(value) = statement.strip_prefix("return ") {
+            return join_array(value, &names, &arrays);
+        } else {
+            let captures = ASSIGNMENT_REGEX.captures(statement)?;
+            let target = *names.get(captures.get(1)?.as_str())?;
+            let index = captures.get(2)?.as_str().parse::<usize>().ok()?;
+            let value = parse_n_value(captures.get(3)?.as_str(), &names)?;
+
+            let values = arrays.get_mut(target)?;
+            match index.cmp(&values.len()) {
+                Ordering::Less => values[index] = value,
+                Ordering::Equal => values.push(value),
+                Ordering::Greater => return None,
+            }
+        }
+    }
+
+    None
+}
+
+fn is_split_of(value: &str, param: &str) -> bool {
+    value == format!(r#"{param}.split("")"#)
+        || value == format!(r#"String.prototype.split.call({param},"")"#)
+        || value == format!(r#"{param}.split({param}.slice(0,0))"#)
+}
+
+fn join_array(value: &str, names: &HashMap<&str, usize>, arrays: &[Vec<NValue>]) -> Option<String> {
+    let name = value.strip_suffix(r#".join("")"#).or_else(|| {
+        value
+            .strip_prefix("Array.prototype.join.call(")?
+            .strip_suffix(r#","")"#)
+    })?;
+
+    arrays
+        .get(*names.get(name)?)?
+        .iter()
+        .map(|x| match x {
+            NValue::String(x) => Some(x.as_str()),
+            _ => None,
+        })
+        .collect::<Option<String>>()
+}
+
+fn parse_n_value(value: &str, names: &HashMap<&str, usize>) -> Option<NValue> {
+    if let Some(index) = names.get(value) {
+        return Some(NValue::Array(*index));
+    }
+
+    if value == "null" {
+        return Some(NValue::Null);
+    }
+
+    if let Ok(number) = value.parse::<i64>() {
+        return Some(NValue::Number(number));
+    }
+
+    if value.starts_with("function(") {
+        return parse_n_operation(value).map(NValue::Function);
+    }
+
+    parse_string_literal(value).map(NValue::String)
+}
+
+fn parse_string_literal(value: &str) -> Option<String> {
+    let quote = value.chars().next().filter(|x| *x == '"' || *x == '\'')?;
+    let inner = value.strip_prefix(quote)?.strip_suffix(quote)?;
+
+    let mut string = String::new();
+    let mut chars = inner.chars();
+
+    while let Some(character) = chars.next() {
+        if character != '\\' {
+            string.push(character);
+            continue;
+        }
+
+        match chars.next()? {
+            'n' => string.push('\n'),
+            't' => string.push('\t'),
+            'u' => {
+                let code = chars.by_ref().take(4).collect::<String>();
+                string.push(char::from_u32(u32::from_str_radix(&code, 16).ok()?)?);
+            }
+            escaped => string.push(escaped),
+        }
+    }
+
+    Some(string)
+}
+
+fn run_n_call(call: &str, names: &HashMap<&str, usize>, arrays: &mut [Vec<NValue>]) -> Option<()> {
+    let captures = CALL_REGEX.captures(call)?;
+
+    let operation = match get_element(
+        captures.get(1)?.as_str(),
+        captures.get(2)?.as_str(),
+        names,
+        arrays,
+    )? {
+        NValue::Function(operation) => operation,
+        _ => return None,
+    };
+
+    let arguments = {
+        let arrays: &[Vec<NValue>] = arrays;
+
+        split_top_level(captures.get(3)?.as_str(), ',')
+            .into_iter()
+            .map(|x| match ELEMENT_REGEX.captures(x) {
+                Some(element) => get_element(
+                    element.get(1)?.as_str(),
+                    element.get(2)?.as_str(),
+                    names,
+                    arrays,
+                ),
+                None => parse_n_value(x, names),
+            })
+            .collect::<Option<Vec<NValue>>>()?
+    };
+
+    apply_n_operation(&operation, &arguments, arrays)
+}
+
+fn get_element(
+    name: &str,
+    index: &str,
+    names: &HashMap<&str, usize>,
+    arrays: &[Vec<NValue>],
+) -> Option<NValue> {
+    arrays
+        .get(*names.get(name)?)?
+        .get(index.parse::<usize>().ok()?)
+        .cloned()
+}
+
+fn apply_n_operation(
+    operation: &NOperation,
+    arguments: &[NValue],
+    arrays: &mut [Vec<NValue>],
+) -> Option<()> {
+    let target = match arguments.first()? {
+        NValue::Array(target) => *target,
+        _ => return None,
+    };
+    let argument = arguments.get(1).cloned();
+
+    let values = arrays.get_mut(target)?;
+    let length = values.len() as i64;
+
+    // `e=(e%d.length+d.length)%d.length` of the operations
+    let wrapped_index = match &argument {
+        Some(NValue::Number(x)) if length > 0 => Some(((x % length + length) % length) as usize),
+        _ => None,
+    };
+
+    match operation {
+        NOperation::Reverse => values.reverse(),
+        NOperation::Push => values.push(argument?),
+        NOperation::Unshift => values.insert(0, argument?),
+        NOperation::Remove => {
+            values.remove(wrapped_index?);
+        }
+        NOperation::Swap => values.swap(0, wrapped_index?),
+        NOperation::Rotate => values.rotate_right(wrapped_index?),
+        NOperation::RotateSplice => {
+            let count = match argument? {
+                NValue::Number(x) if x > 0 => (x as usize).min(values.len()),
+                _ => return None,
+            };
+            values.rotate_right(count);
+        }
+        NOperation::Cipher {
+            alphabet,
+            counter,
+            offset,
+            uses_this,
+        } => {
+            let key = match argument? {
+                NValue::String(key) => key.chars().collect::<Vec<char>>(),
+                _ => return None,
+            };
+            let alphabet = match alphabet {
+                Some(alphabet) => alphabet.clone(),
+                None => match arguments.get(2)? {
+                    NValue::String(alphabet) => alphabet.chars().collect::<Vec<char>>(),
+                    _ => return None,
+                },
+            };
+
+            if alphabet.is_empty() {
+                return None;
+            }
+
+            let index_of = |x: Option<&char>| {
+                x.and_then(|x| alphabet.iter().position(|y| y == x))
+                    .map(|x| x as i64)
+                    .unwrap_or(-1)
+            };
+
+            let mut counter = counter.unwrap_or(alphabet.len() as i64);
+            // `this` starts as the key and every substituted character is pushed to it
+            let mut this = key.clone();
+
+            for (m, value) in values.iter_mut().enumerate() {
+                let character = match value {
+                    NValue::String(x) if x.chars().count() == 1 => x.chars().next(),
+                    NValue::String(_) => None,
+                    _ => return None,
+                };
+                let key_character = if *uses_this { this.get(m) } else { key.get(m) };
+
+                let index = (index_of(character.as_ref()) - index_of(key_character)
+                    + m as i64
+                    + offset
+                    + counter)
+                    % alphabet.len() as i64;
+                counter -= 1;
+
+                let substituted = *alphabet.get(usize::try_from(index).ok()?)?;
+                *value = NValue::String(substituted.to_string());
+                this.push(substituted);
+            }
+        }
+    }
+
+    Some(())
+}
+
+fn parse_n_operation(function: &str) -> Option<NOperation> {
+    let params_end = function.find(')')?;
+    let params = function
+        .get("function(".len()..params_end)?
+        .split(',')
+        .map(str::trim)
+        .filter(|x| !x.is_empty())
+        .collect::<Vec<&str>>();
+    let body = function
+        .get(params_end + 1..)?
+        .strip_prefix('{')?
+        .strip_suffix('}')?;
+
+    let normalized = normalize_function(&params, body)?;
+
+    let operation = match normalized.trim_end_matches(';') {
+        "A.reverse()" | "for(var V0=A.length;V0;)A.push(A.splice(--V0,1)[0])" => {
+            NOperation::Reverse
+        }
+        "A.push(B)" => NOperation::Push,
+        "A.unshift(B)" => NOperation::Unshift,
+        "B=(B%A.length+A.length)%A.length;A.splice(B,1)" => NOperation::Remove,
+        "B=(B%A.length+A.length)%A.length;var V0=A[0];A[0]=A[B];A[B]=V0"
+        | "B=(B%A.length+A.length)%A.length;A.splice(0,1,A.splice(B,1,A[0])[0])" => {
+            NOperation::Swap
+        }
+        "for(B=(B%A.length+A.length)%A.length;B--;)A.unshift(A.pop())" => NOperation::Rotate,
+        "A.splice(-B).reverse().forEach(function(V0){A.unshift(V0)})" => NOperation::RotateSplice,
+        normalized => return parse_cipher_operation(normalized),
+    };
+
+    Some(operation)
+}
+
+/// Rename parameters to `A`, `B`, `C` and locals to `V0`, `V1`... in order of appearance,
+/// so functions can be compared regardless of the minified names
+fn normalize_function(params: &[&str], body: &str) -> Option<String> {
+    if params.len() > 3 {
+        return None;
+    }
+
+    let mut locals: Vec<&str> = vec![];
+    let mut normalized = String::with_capacity(body.len());
+    let mut last_end = 0;
+
+    for identifier in IDENTIFIER_REGEX.find_iter(body) {
+        normalized.push_str(&body[last_end..identifier.start()]);
+        last_end = identifier.end();
+
+        let name = identifier.as_str();
+        let previous = body[..identifier.start()].chars().last();
+
+        // Properties, keywords and exponents of number literals are kept as is
+        if previous == Some('.')
+            || previous.map(|x| x.is_ascii_digit()).unwrap_or(false)
+            || KEYWORDS.contains(&name)
+        {
+            normalized.push_str(name);
+        } else if let Some(index) = params.iter().position(|x| *x == name) {
+            normalized.push((b'A' + index as u8) as char);
+        } else {
+            let index = match locals.iter().position(|x| *x == name) {
+                Some(index) => index,
+                None => {
+                    locals.push(name);
+                    locals.len() - 1
+                }
+            };
+            normalized.push_str(&format!("V{index}"));
+        }
+    }
+
+    normalized.push_str(&body[last_end..]);
+
+    Some(normalized)
+}
+
+fn parse_cipher_operation(normalized: &str) -> Option<NOperation> {
+    match split_statements(normalized).as_slice() {
+        [declaration, for_each] if *declaration == "var V0=C.length" => {
+            let (offset, uses_this) = parse_cipher_for_each(for_each, "C", "V0", 1)?;
+
+            Some(NOperation::Cipher {
+                alphabet: None,
+                counter: None,
+                offset,
+                uses_this,
+            })
+        }
+        [alphabet_loop, for_each] => {
+            let (alphabet, counter) = generate_alphabet(alphabet_loop)?;
+            let (offset, uses_this) = parse_cipher_for_each(for_each, "V1", "V0", 2)?;
+
+            Some(NOperation::Cipher {
+                alphabet: Some(alphabet),
+                counter: Some(counter),
+                offset,
+                uses_this,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// Parse `d.forEach(...)` substitution loop. Return offset constant and whether the key is `this`
+fn parse_cipher_for_each(
+    for_each: &str,
+    alphabet: &str,
+    counter: &str,
+    first_local: usize,
+) -> Option<(i64, bool)> {
+    const THIS_TEMPLATE: &str = r#"^A\.forEach\(function\(<L>,<M>,<N>\)\{this\.push\(<N>\[<M>\]=<A>\[\(<A>\.indexOf\(<L>\)-<A>\.indexOf\(this\[<M>\]\)\+<M>([+-]\d+)?\+<C>--\)%<A>\.length\]\)\},B\.split\(""\)\)$"#;
+    const KEY_TEMPLATE: &str = r#"^A\.forEach\(function\(<L>,<M>,<N>\)\{<N>\[<M>\]=<A>\[\(<A>\.indexOf\(<L>\)-<A>\.indexOf\(B\[<M>\]\)\+<M>([+-]\d+)?\+<C>--\)%<A>\.length\]\}\)$"#;
+
+    let build_regex = |template: &str| {
+        Regex::new(
+            &template
+                .replace("<L>", &format!("V{first_local}"))
+                .replace("<M>", &format!("V{}", first_local + 1))
+                .replace("<N>", &format!("V{}", first_local + 2))
+                .replace("<A>", alphabet)
+                .replace("<C>", counter),
+        )
+        .ok()
+    };
+
+    for (template, uses_this) in [(THIS_TEMPLATE, true), (KEY_TEMPLATE, false)] {
+        if let Some(captures) = build_regex(template)?.captures(for_each) {
+            let offset = match captures.get(1) {
+                Some(offset) => offset.as_str().parse::<i64>().ok()?,
+                None => 0,
+            };
+
+            return Some((offset, uses_this));
+        }
+    }
+
+    None
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum AlphabetStep {
+    Case(i64),
+    Default,
+    Add(i64),
+    Set(i64),
+    Continue,
+    Push,
+}
+
+/// Simulate the `for(...){switch(...){...}}` loop generating the cipher alphabet.
+/// Return the alphabet and the final value of the loop counter
+fn generate_alphabet(alphabet_loop: &str) -> Option<(Vec<char>, i64)> {
+    let captures = ALPHABET_LOOP_REGEX.captures(alphabet_loop)?;
+    let mut counter = captures.get(1)?.as_str().parse::<i64>().ok()?;
+    let limit = captures.get(2)?.as_str().parse::<i64>().ok()?;
+
+    let mut steps = vec![];
+
+    for piece in split_top_level(captures.get(3)?.as_str(), ';') {
+        let mut piece = piece;
+
+        loop {
+            if let Some(rest) = piece.strip_prefix("case ") {
+                let (value, rest) = rest.split_once(':')?;
+                steps.push(AlphabetStep::Case(value.parse::<i64>().ok()?));
+                piece = rest;
+            } else if let Some(rest) = piece.strip_prefix("default:") {
+                steps.push(AlphabetStep::Default);
+                piece = rest;
+            } else {
+                break;
+            }
+        }
+
+        if piece.is_empty() {
+            continue;
+        }
+
+        let step = if let Some(value) = piece.strip_prefix("V0-=") {
+            AlphabetStep::Add(-value.parse::<i64>().ok()?)
+        } else if let Some(value) = piece.strip_prefix("V0+=") {
+            AlphabetStep::Add(value.parse::<i64>().ok()?)
+        } else if let Some(value) = piece.strip_prefix("V0=") {
+            AlphabetStep::Set(value.parse::<i64>().ok()?)
+        } else if piece == "continue" || piece == "break" {
+            AlphabetStep::Continue
+        } else if piece == "V1.push(String.fromCharCode(V0))" {
+            AlphabetStep::Push
+        } else {
+            return None;
+        };
+
+        steps.push(step);
+    }
+
+    let mut alphabet = vec![];
+
+    // Guard against loops which never end
+    for _ in 0..1024 {
+        counter += 1;
+
+        if counter - alphabet.len() as i64 - limit == 0 {
+            return Some((alphabet, counter));
+        }
+
+        let entry = steps
+            .iter()
+            .position(|x| *x == AlphabetStep::Case(counter))
+            .or_else(|| steps.iter().position(|x| *x == AlphabetStep::Default));
+
+        let entry = match entry {
+            Some(entry) => entry,
+            None => continue,
+        };
+
+        // Cases fall through until `continue` or `break`
+        for step in &steps[entry..] {
+            match step {
+                AlphabetStep::Case(_) | AlphabetStep::Default => {}
+                AlphabetStep::Add(value) => counter += value,
+                AlphabetStep::Set(value) => counter = *value,
+                AlphabetStep::Continue => break,
+                AlphabetStep::Push => alphabet.push(char::from_u32(u32::try_from(counter).ok()?)?),
+            }
+        }
+    }
+
+    None
+}
+
+/// Find the index of the bracket closing the one at `open_index`
+fn find_closing(source: &str, open_index: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+
+    for (index, character) in source[open_index..].char_indices() {
+        if let Some(quote_char) = quote {
+            if escaped {
+                escaped = false;
+            } else if character == '\\' {
+                escaped = true;
+            } else if character == quote_char {
+                quote = None;
+            }
+            continue;
+        }
+
+        match character {
+            '"' | '\'' => quote = Some(character),
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(open_index + index);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    None
+}
+
+/// Split `source` by `separator` outside of brackets and string literals
+fn split_top_level(source: &str, separator: char) -> Vec<&str> {
+    split(source, separator, false)
+}
+
+/// Split `source` to statements by `;` and by the end of blocks like `try{...}`
+fn split_statements(source: &str) -> Vec<&str> {
+    split(source, ';', true)
+}
+
+fn split(source: &str, separator: char, split_blocks: bool) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    let mut start = 0;
+
+    for (index, character) in source.char_indices() {
+        if let Some(quote_char) = quote {
+            if escaped {
+                escaped = false;
+            } else if character == '\\' {
+                escaped = true;
+            } else if character == quote_char {
+                quote = None;
+            }
+            continue;
+        }
+
+        match character {
+            '"' | '\'' => quote = Some(character),
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => {
+                depth -= 1;
+                if split_blocks && character == '}' && depth == 0 {
+                    parts.push(&source[start..=index]);
+                    start = index + 1;
+                }
+            }
+            _ if character == separator && depth == 0 => {
+                parts.push(&source[start..index]);
+                start = index + character.len_utf8();
+            }
+            _ => {}
+        }
+    }
+
+    parts.push(&source[start..]);
+
+    parts
+        .into_iter()
+        .map(str::trim)
+        .filter(|x| !x.is_empty())
+        .collect()
+}
+
+/// Find `var {function_name}=function(...){...}` and return its parameters and body
+fn function_body<'a>(script: &'a str, function_name: &str) -> Option<(Vec<&'a str>, &'a str)> {
+    let function_start = format!("var {function_name}=function(");
+    let params_start = script.find(&function_start)? + function_start.len();
+    let params_end = params_start + script[params_start..].find(')')?;
+
+    let params = script[params_start..params_end]
+        .split(',')
+        .map(str::trim)
+        .filter(|x| !x.is_empty())
+        .collect::<Vec<&str>>();
+
+    let body_start = params_end + 1;
+    if !script[body_start..].starts_with('{') {
+        return None;
+    }
+    let body_end = find_closing(script, body_start)?;
+
+    Some((params, &script[body_start + 1..body_end]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_native_decipher() {
+        let script = r#"var Xy={AB:function(a){a.reverse()},CD:function(a,b){a.splice(0,b)},EF:function(a,b){var c=a[0];a[0]=a[b%a.length];a[b%a.length]=c}};var Fn=function(a){a=a.split("");Xy.CD(a,2);Xy.AB(a,45);Xy["EF"](a,3);return a.join("")};"#;
+
+        assert_eq!(
+            call_native(script, "Fn", "abcdefgh"),
+            Some("egfhdc".to_string())
+        );
+    }
+
+    #[test]
+    fn test_native_n_transform() {
+        let script = r#"var Nf=function(a){var b=a.split(""),c=[function(d,e){d.push(e)},"x",b,function(d){d.reverse()},2,function(d,e){e=(e%d.length+d.length)%d.length;var f=d[0];d[0]=d[e];d[e]=f},null];c[6]=c;try{c[0](c[2],c[1]),c[3](c[2]),c[5](c[2],c[4])}catch(d){return"enhanced_except_"+a}return b.join("")};"#;
+
+        assert_eq!(call_native(script, "Nf", "abc"), Some("bcxa".to_string()));
+
+        // Unknown operations are left to the JavaScript engine
+        let script = r#"var Nf=function(a){var b=a.split(""),c=[function(d){d.sort()},b];try{c[0](c[1])}catch(d){return"enhanced_except_"+a}return b.join("")};"#;
+
+        assert_eq!(call_native(script, "Nf", "abc"), None);
+    }
+
+    #[test]
+    fn test_generate_alphabet() {
+        let alphabet_loop = "for(var V0=64,V1=[];++V0-V1.length-32;){switch(V0){case 58:V0-=14;case 91:case 92:case 93:continue;case 123:V0=47;case 94:case 95:case 96:continue;case 46:V0=95;default:V1.push(String.fromCharCode(V0))}}";
+
+        let (alphabet, counter) = generate_alphabet(alphabet_loop).unwrap();
+
+        assert_eq!(
+            alphabet.into_iter().collect::<String>(),
+            "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_"
+        );
+        assert_eq!(counter, 96);
+    }
+}
@@ -0,0 +1,142 @@
+//! Clocks, timers, blocking tasks and child processes of the native and `wasm32` targets.
+//! - `std::time::Instant::now`, `SystemTime::now` and tokio timers panic in browsers and workers
+//! - With `runtime-agnostic` feature nothing here needs a tokio runtime, so async-std and smol applications can use the crate.
+//!   `tokio::sync` and the tokio IO traits work with every runtime
+//! - Child processes and blocking tasks exist only on native targets with `ffmpeg` or `live` features
+
+use std::time::{Duration, SystemTime};
+
+#[cfg(not(feature = "runtime-agnostic"))]
+pub(crate) use tokio::io::{AsyncReadExt, AsyncWriteExt};
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    any(feature = "ffmpeg", feature = "live"),
+    not(feature = "runtime-agnostic")
+))]
+pub(crate) use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+
+#[cfg(all(
+    not(target_arch = "wasm32"),
+    any(feature = "ffmpeg", feature = "live"),
+    feature = "runtime-agnostic"
+))]
+pub(crate) use async_process::{Child, ChildStdin, ChildStdout, Command};
+#[cfg(feature = "runtime-agnostic")]
+pub(crate) use futures::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::structs::VideoError;
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use std::time::Instant;
+#[cfg(target_arch = "wasm32")]
+pub(crate) use web_time::Instant;
+
+/// Current system time, [`SystemTime::now`] is not available on `wasm32`
+pub(crate) fn now() -> SystemTime {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        SystemTime::now()
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        std::time::UNIX_EPOCH
+            + web_time::SystemTime::now()
+                .duration_since(web_time::UNIX_EPOCH)
+                .unwrap_or_default()
+    }
+}
+
+pub(crate) async fn sleep(duration: Duration) {
+    #[cfg(not(any(target_arch = "wasm32", feature = "runtime-agnostic")))]
+    tokio::time::sleep(duration).await;
+    #[cfg(any(target_arch = "wasm32", feature = "runtime-agnostic"))]
+    futures_timer::Delay::new(duration).await;
+}
+
+/// Run blocking work (e.g. libav remuxing) without blocking the executor
+#[cfg(all(not(target_arch = "wasm32"), any(feature = "ffmpeg", feature = "live")))]
+pub(crate) async fn spawn_blocking<F, R>(f: F) -> Result<R, VideoError>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    #[cfg(not(feature = "runtime-agnostic"))]
+    {
+        tokio::task::spawn_blocking(f)
+            .await
+            .map_err(|x| VideoError::DownloadError(x.to_string()))
+    }
+    #[cfg(feature = "runtime-agnostic")]
+    {
+        let (sender, receiver) = futures::channel::oneshot::channel();
+
+        std::thread::spawn(move || {
+            let _ = sender.send(f());
+        });
+
+        receiver
+            .await
+            .map_err(|x| VideoError::DownloadError(x.to_string()))
+    }
+}
+
+/// Wait for the process to exit
+#[cfg(all(not(target_arch = "wasm32"), any(feature = "ffmpeg", feature = "live")))]
+pub(crate) async fn wait(child: &mut Child) -> std::io::Result<std::process::ExitStatus> {
+    #[cfg(not(feature = "runtime-agnostic"))]
+    {
+        child.wait().await
+    }
+    #[cfg(feature = "runtime-agnostic")]
+    {
+        child.status().await
+    }
+}
+
+/// Wait for the process to exit and collect its stdout and stderr
+#[cfg(all(not(target_arch = "wasm32"), any(feature = "ffmpeg", feature = "live")))]
+pub(crate) async fn wait_with_output(child: Child) -> std::io::Result<std::process::Output> {
+    #[cfg(not(feature = "runtime-agnostic"))]
+    {
+        child.wait_with_output().await
+    }
+    #[cfg(feature = "runtime-agnostic")]
+    {
+        child.output().await
+    }
+}
+
+/// Body of the response as a stream of chunks. Fetch responses of `wasm32` have no chunked reads,
+/// their body comes as a single chunk
+pub(crate) fn body_chunks(
+    response: reqwest::Response,
+) -> impl futures::Stream<Item = reqwest::Result<bytes::Bytes>> {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        futures::stream::unfold(response, |mut response| async move {
+            response
+                .chunk()
+                .await
+                .transpose()
+                .map(|chunk| (chunk, response))
+        })
+    }
+    #[cfg(target_arch = "wasm32")]
+    {
+        futures::stream::once(response.bytes())
+    }
+}
+
+/// reqwest has no cookie jar on `wasm32`, browsers keep the cookies of fetch requests themselves
+#[cfg(target_arch = "wasm32")]
+pub(crate) enum Jar {}
+
+#[cfg(target_arch = "wasm32")]
+impl Jar {
+    pub(crate) fn add_cookie_str(&self, _cookie: &str, _url: &url::Url) {
+        match *self {}
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use reqwest::cookie::Jar;
@@ -0,0 +1,167 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use crate::download_manager::{
+    BatchProgressCallback, DownloadJob, DownloadManager, DownloadResult,
+};
+use crate::search::Playlist;
+use crate::structs::{VideoError, VideoOptions};
+
+/// Overall progress of [`Playlist::download_all`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PlaylistProgress {
+    /// Downloaded videos
+    pub completed: usize,
+    /// Failed or cancelled videos
+    pub failed: usize,
+    /// Video count of the download
+    pub total: usize,
+    /// Downloaded bytes of all videos so far
+    pub downloaded: u64,
+}
+
+#[derive(Clone)]
+pub struct PlaylistProgressCallback(Arc<dyn Fn(&PlaylistProgress) + Sync + Send + 'static>);
+
+impl PlaylistProgressCallback {
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: Fn(&PlaylistProgress) + Sync + Send + 'static,
+    {
+        Self(Arc::new(callback))
+    }
+
+    pub fn call(&self, progress: &PlaylistProgress) {
+        (self.0)(progress)
+    }
+}
+
+impl std::fmt::Debug for PlaylistProgressCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "PlaylistProgressCallback()")
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PlaylistDownloadOptions {
+    /// Options of every video of the playlist
+    pub video_options: VideoOptions,
+    /// yt-dlp style file name template, see [`crate::render_filename_template`]
+    pub template: String,
+    /// Count of videos downloaded at once
+    pub concurrency: usize,
+    /// Continue with the other videos when one fails. Otherwise the first error is returned and videos which were not started are cancelled
+    pub skip_errors: bool,
+    /// Resolve formats and estimate sizes without downloading, see [`DownloadManager::with_dry_run`]
+    pub dry_run: bool,
+    pub progress_callback: Option<PlaylistProgressCallback>,
+}
+
+impl Default for PlaylistDownloadOptions {
+    fn default() -> Self {
+        Self {
+            video_options: VideoOptions::default(),
+            template: "%(title)s [%(id)s].%(ext)s".to_string(),
+            concurrency: 4,
+            skip_errors: true,
+            dry_run: false,
+            progress_callback: None,
+        }
+    }
+}
+
+/// Shared state of the overall progress
+struct ProgressState {
+    downloaded: Vec<u64>,
+    completed: usize,
+    failed: usize,
+}
+
+impl Playlist {
+    /// Download every fetched video of the playlist into `dir`, which is created if missing.
+    /// Call [`Playlist::fetch`] first to download more than the first page of videos
+    /// # Example
+    /// ```ignore
+    ///     let mut playlist = Playlist::get("https://www.youtube.com/playlist?list=PL...", None).await.unwrap();
+    ///     playlist.fetch(None).await;
+    ///
+    ///     let options = PlaylistDownloadOptions {
+    ///         progress_callback: Some(PlaylistProgressCallback::new(|progress| {
+    ///             println!("{}/{} videos", progress.completed, progress.total);
+    ///         })),
+    ///         ..Default::default()
+    ///     };
+    ///
+    ///     let results = playlist.download_all("playlist", &options).await.unwrap();
+    /// ```
+    pub async fn download_all<P: AsRef<Path>>(
+        &self,
+        dir: P,
+        options: &PlaylistDownloadOptions,
+    ) -> Result<Vec<DownloadResult>, VideoError> {
+        let dir = dir.as_ref();
+
+        if !options.dry_run {
+            std::fs::create_dir_all(dir).map_err(|e| VideoError::DownloadError(e.to_string()))?;
+        }
+
+        let total = self.videos.len();
+        let state = Arc::new(Mutex::new(ProgressState {
+            downloaded: vec![0; total],
+            completed: 0,
+            failed: 0,
+        }));
+
+        let mut manager = DownloadManager::new(options.video_options.clone(), options.concurrency)
+            .with_stop_on_error(!options.skip_errors)
+            .with_dry_run(options.dry_run);
+
+        if let Some(progress_callback) = options.progress_callback.clone() {
+            let report = move |state: &ProgressState| {
+                progress_callback.call(&PlaylistProgress {
+                    completed: state.completed,
+                    failed: state.failed,
+                    total,
+                    downloaded: state.downloaded.iter().sum(),
+                })
+            };
+            let report = Arc::new(report);
+
+            let progress_state = state.clone();
+            let progress_report = report.clone();
+            manager = manager
+                .with_progress_callback(BatchProgressCallback::new(move |index, progress| {
+                    let mut state = progress_state.lock().unwrap();
+                    state.downloaded[index] = progress.downloaded;
+                    progress_report(&state);
+                }))
+                .with_finish_callback(move |_index, success| {
+                    let mut state = state.lock().unwrap();
+                    if success {
+                        state.completed += 1;
+                    } else {
+                        state.failed += 1;
+                    }
+                    report(&state);
+                });
+        }
+
+        let jobs = self
+            .videos
+            .iter()
+            .map(|video| DownloadJob::with_template(&video.url, dir, &options.template));
+
+        let mut results = manager.download_all(jobs).await?;
+
+        if !options.skip_errors {
+            if let Some(index) = results
+                .iter()
+                .position(|x| x.result.is_err() && !matches!(x.result, Err(VideoError::Cancelled)))
+            {
+                return Err(results.swap_remove(index).result.unwrap_err());
+            }
+        }
+
+        Ok(results)
+    }
+}
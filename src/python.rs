@@ -0,0 +1,147 @@
+// `#[pymethods]` of pyo3 0.20 expands to impl blocks inside consts, which newer compilers warn about
+#![allow(unknown_lints, non_local_definitions)]
+
+use pyo3::exceptions::{PyRuntimeError, PyValueError};
+use pyo3::prelude::*;
+use pythonize::pythonize;
+
+use crate::structs::{VideoError, VideoOptions, VideoQuality, VideoSearchOptions};
+use crate::Video;
+
+fn to_py_err(error: VideoError) -> PyErr {
+    PyRuntimeError::new_err(error.to_string())
+}
+
+fn parse_quality(quality: &str) -> PyResult<VideoQuality> {
+    match quality {
+        "highest" => Ok(VideoQuality::Highest),
+        "lowest" => Ok(VideoQuality::Lowest),
+        "highestaudio" => Ok(VideoQuality::HighestAudio),
+        "lowestaudio" => Ok(VideoQuality::LowestAudio),
+        "highestvideo" => Ok(VideoQuality::HighestVideo),
+        "lowestvideo" => Ok(VideoQuality::LowestVideo),
+        label if label.starts_with(|c: char| c.is_ascii_digit()) => {
+            Ok(VideoQuality::Label(label.to_string()))
+        }
+        _ => Err(PyValueError::new_err(format!("Unknown quality: {quality}"))),
+    }
+}
+
+fn parse_filter(filter: &str) -> PyResult<VideoSearchOptions> {
+    match filter {
+        "audiovideo" => Ok(VideoSearchOptions::VideoAudio),
+        "video" => Ok(VideoSearchOptions::Video),
+        "audio" => Ok(VideoSearchOptions::Audio),
+        _ => Err(PyValueError::new_err(format!("Unknown filter: {filter}"))),
+    }
+}
+
+/// Python `rusty_ytdl.Video`, methods return awaitables running on a tokio runtime
+#[pyclass(name = "Video", module = "rusty_ytdl")]
+struct PyVideo(Video);
+
+#[pymethods]
+impl PyVideo {
+    #[new]
+    #[pyo3(signature = (url_or_id, quality = "highest", filter = "audiovideo"))]
+    fn new(url_or_id: &str, quality: &str, filter: &str) -> PyResult<Self> {
+        let options = VideoOptions {
+            quality: parse_quality(quality)?,
+            filter: parse_filter(filter)?,
+            ..Default::default()
+        };
+
+        Video::new_with_options(url_or_id, options)
+            .map(Self)
+            .map_err(to_py_err)
+    }
+
+    #[getter]
+    fn video_id(&self) -> String {
+        self.0.get_video_id()
+    }
+
+    /// Info of the video as a yt-dlp compatible `info.json` dict
+    fn info<'p>(&self, py: Python<'p>) -> PyResult<&'p PyAny> {
+        let video = self.0.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            let info = video.get_info().await.map_err(to_py_err)?;
+
+            Python::with_gil(|py| Ok(pythonize(py, &info.to_info_json())?))
+        })
+    }
+
+    /// Download the video to `path`
+    fn download<'p>(&self, py: Python<'p>, path: String) -> PyResult<&'p PyAny> {
+        let video = self.0.clone();
+
+        pyo3_asyncio::tokio::future_into_py(py, async move {
+            video.download(path).await.map_err(to_py_err)
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("Video({})", self.0.get_video_id())
+    }
+}
+
+/// Search videos, playlists and channels, results are dicts with a `type` key
+#[cfg(feature = "search")]
+#[pyfunction]
+#[pyo3(signature = (query, limit = 10))]
+fn search(py: Python<'_>, query: String, limit: u64) -> PyResult<&PyAny> {
+    use crate::search::{SearchOptions, SearchResult, YouTube};
+
+    pyo3_asyncio::tokio::future_into_py(py, async move {
+        let youtube = YouTube::new().map_err(to_py_err)?;
+        let results = youtube
+            .search(
+                query,
+                Some(&SearchOptions {
+                    limit,
+                    ..Default::default()
+                }),
+            )
+            .await
+            .map_err(to_py_err)?;
+
+        Python::with_gil(|py| {
+            results
+                .iter()
+                .map(|result| {
+                    let (kind, value) = match result {
+                        SearchResult::Video(x) => ("video", serde_json::to_value(x)),
+                        SearchResult::Playlist(x) => ("playlist", serde_json::to_value(x)),
+                        SearchResult::Channel(x) => ("channel", serde_json::to_value(x)),
+                    };
+
+                    let mut value = value.map_err(|e| PyRuntimeError::new_err(e.to_string()))?;
+                    value["type"] = kind.into();
+
+                    Ok(pythonize(py, &value)?)
+                })
+                .collect::<PyResult<Vec<PyObject>>>()
+        })
+    })
+}
+
+/// Download the video of `url_or_id` to `path` with the highest quality
+#[pyfunction]
+fn download(py: Python<'_>, url_or_id: String, path: String) -> PyResult<&PyAny> {
+    pyo3_asyncio::tokio::future_into_py(py, async move {
+        let video = Video::new(url_or_id).map_err(to_py_err)?;
+
+        video.download(path).await.map_err(to_py_err)
+    })
+}
+
+#[pymodule]
+fn rusty_ytdl(_py: Python<'_>, module: &PyModule) -> PyResult<()> {
+    module.add_class::<PyVideo>()?;
+    #[cfg(feature = "search")]
+    module.add_function(wrap_pyfunction!(search, module)?)?;
+    module.add_function(wrap_pyfunction!(download, module)?)?;
+
+    Ok(())
+}
@@ -0,0 +1,178 @@
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use reqwest::{Request, Response, StatusCode};
+use reqwest_middleware::{Middleware, Next};
+use task_local_extensions::Extensions;
+
+use crate::structs::{IdentityRotation, RateLimitBackoff, RequestOptions, VideoError};
+use crate::utils::build_client;
+
+/// Turn `429 Too Many Requests` responses and the "unusual traffic" interstitial into
+/// [`VideoError::RateLimited`], retrying with exponential backoff first if it is configured
+pub(crate) struct RateLimitMiddleware {
+    pub backoff: Option<RateLimitBackoff>,
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait::async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait::async_trait(?Send))]
+impl Middleware for RateLimitMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let mut req = req;
+        let mut attempt = 0;
+
+        loop {
+            // Streamed bodies cannot be sent again
+            let retry_req = req.try_clone();
+
+            let response = next.clone().run(req, extensions).await?;
+
+            if !is_rate_limited(&response) {
+                return Ok(response);
+            }
+
+            let retry_after = retry_after(&response);
+
+            let delay = self.backoff.as_ref().and_then(|backoff| {
+                if attempt >= backoff.max_retries {
+                    return None;
+                }
+
+                let delay = backoff
+                    .initial_delay
+                    .saturating_mul(2_u32.saturating_pow(attempt))
+                    .min(backoff.max_delay)
+                    .max(retry_after.unwrap_or_default());
+
+                // Waiting longer than allowed is left to the caller
+                (delay <= backoff.max_delay).then_some(delay)
+            });
+
+            match (delay, retry_req) {
+                (Some(delay), Some(retry_req)) => {
+                    crate::platform::sleep(delay).await;
+
+                    req = retry_req;
+                    attempt += 1;
+                }
+                _ => {
+                    return Err(reqwest_middleware::Error::middleware(
+                        VideoError::RateLimited { retry_after },
+                    ))
+                }
+            }
+        }
+    }
+}
+
+fn is_rate_limited(response: &Response) -> bool {
+    response.status() == StatusCode::TOO_MANY_REQUESTS
+        || response.url().path().starts_with("/sorry/")
+}
+
+/// `Retry-After` header in seconds, HTTP dates are not sent by YouTube
+fn retry_after(response: &Response) -> Option<Duration> {
+    response
+        .headers()
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|x| x.to_str().ok())
+        .and_then(|x| x.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+/// Build clients with the next identity of [`RequestOptions::identity_rotation`]
+pub(crate) struct IdentityRotator {
+    request_options: RequestOptions,
+    rotation: IdentityRotation,
+    rotations: AtomicU32,
+}
+
+impl IdentityRotator {
+    /// [`None`] if rotation is not configured or there is nothing to rotate
+    pub fn new(request_options: &RequestOptions) -> Option<Self> {
+        let rotation = request_options.identity_rotation.clone()?;
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let has_proxies = !rotation.proxies.is_empty();
+        #[cfg(target_arch = "wasm32")]
+        let has_proxies = false;
+
+        if !has_proxies && request_options.ipv6_block.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            request_options: request_options.clone(),
+            rotation,
+            rotations: AtomicU32::new(0),
+        })
+    }
+
+    pub fn min_speed(&self) -> Option<u64> {
+        self.rotation.min_speed
+    }
+
+    /// Client with a new identity, [`None`] if [`IdentityRotation::max_rotations`] is reached
+    pub fn next_client(
+        &self,
+    ) -> Result<Option<reqwest_middleware::ClientWithMiddleware>, VideoError> {
+        self.next_request_options()
+            .map(|x| build_client(&x))
+            .transpose()
+    }
+
+    /// Options of the next identity, [`None`] if [`IdentityRotation::max_rotations`] is reached
+    fn next_request_options(&self) -> Option<RequestOptions> {
+        let rotation = self.rotations.fetch_add(1, Ordering::SeqCst);
+
+        if rotation >= self.rotation.max_rotations {
+            return None;
+        }
+
+        let mut request_options = self.request_options.clone();
+        // Injected client keeps the old identity, a new one is built from the other options
+        request_options.client = None;
+
+        // Address of the IPv6 block is picked randomly by every built client
+        #[cfg(not(target_arch = "wasm32"))]
+        if !self.rotation.proxies.is_empty() {
+            let index = rotation as usize % self.rotation.proxies.len();
+            request_options.proxy = Some(self.rotation.proxies[index].clone());
+        }
+
+        Some(request_options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation_ignores_injected_client() {
+        let request_options = RequestOptions {
+            ipv6_block: Some("2001:4::/48".to_string()),
+            identity_rotation: Some(IdentityRotation {
+                max_rotations: 1,
+                ..Default::default()
+            }),
+            client: Some(std::sync::Arc::new(
+                build_client(&RequestOptions::default()).unwrap(),
+            )),
+            ..Default::default()
+        };
+
+        let rotator = IdentityRotator::new(&request_options).unwrap();
+
+        let rotated = rotator.next_request_options().unwrap();
+        assert!(rotated.client.is_none());
+        assert_eq!(rotated.ipv6_block, request_options.ipv6_block);
+
+        assert!(rotator.next_request_options().is_none());
+    }
+}
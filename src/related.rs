@@ -0,0 +1,137 @@
+use crate::info_extras::{get_related_videos, parse_related_lockup, parse_related_video};
+use crate::structs::{RelatedVideo, VideoError};
+use crate::utils::innertube_request;
+
+#[derive(Clone, derivative::Derivative)]
+#[derivative(Debug, PartialEq, Eq)]
+pub struct RelatedVideos {
+    pub video_id: String,
+    /// All fetched related videos
+    pub videos: Vec<RelatedVideo>,
+
+    #[derivative(PartialEq = "ignore")]
+    continuation: Option<String>,
+    #[derivative(PartialEq = "ignore")]
+    client: reqwest_middleware::ClientWithMiddleware,
+}
+
+impl RelatedVideos {
+    pub(crate) async fn get(
+        client: &reqwest_middleware::ClientWithMiddleware,
+        video_id: impl Into<String>,
+    ) -> Result<Self, VideoError> {
+        let video_id: String = video_id.into();
+
+        let response = innertube_request(
+            client,
+            "next",
+            serde_json::json!({
+                "videoId": video_id,
+            }),
+        )
+        .await?;
+
+        let empty_serde_array = vec![];
+
+        let results = &response["contents"]["twoColumnWatchNextResults"]["secondaryResults"]
+            ["secondaryResults"]["results"];
+
+        // Results are wrapped in an item section when the related chips exist
+        let continuation = results
+            .as_array()
+            .unwrap_or(&empty_serde_array)
+            .iter()
+            .flat_map(|x| {
+                x["itemSectionRenderer"]["contents"]
+                    .as_array()
+                    .map(|x| x.iter().collect::<Vec<_>>())
+                    .unwrap_or_else(|| vec![x])
+            })
+            .find_map(continuation_token);
+
+        Ok(Self {
+            video_id,
+            videos: get_related_videos(&response).unwrap_or_default(),
+            continuation: continuation.map(|x| x.to_string()),
+            client: client.clone(),
+        })
+    }
+
+    /// Get next page of related videos and return fetched [`RelatedVideo`] array.
+    /// - If there is no next page this function return empty [`Vec<RelatedVideo>`]
+    pub async fn next(&mut self) -> Result<Vec<RelatedVideo>, VideoError> {
+        let continuation = match &self.continuation {
+            Some(continuation) => continuation.clone(),
+            None => return Ok(vec![]),
+        };
+
+        let response = innertube_request(
+            &self.client,
+            "next",
+            serde_json::json!({
+                "continuation": continuation,
+            }),
+        )
+        .await?;
+
+        let empty_serde_array = vec![];
+
+        let mut videos = vec![];
+        self.continuation = None;
+
+        for endpoint in response["onResponseReceivedEndpoints"]
+            .as_array()
+            .unwrap_or(&empty_serde_array)
+        {
+            for item in endpoint["appendContinuationItemsAction"]["continuationItems"]
+                .as_array()
+                .unwrap_or(&empty_serde_array)
+            {
+                if let Some(token) = continuation_token(item) {
+                    self.continuation = Some(token.to_string());
+                    continue;
+                }
+
+                let video = if let Some(details) = item["compactVideoRenderer"].as_object() {
+                    parse_related_video(details, &[])
+                } else {
+                    parse_related_lockup(&item["lockupViewModel"])
+                };
+
+                if let Some(video) = video {
+                    videos.push(video);
+                }
+            }
+        }
+
+        self.videos.extend(videos.clone());
+
+        Ok(videos)
+    }
+
+    /// Try to fetch related videos until the limit reached or no next page left.
+    /// - If limit is [`None`] it will be [`u64::MAX`]
+    pub async fn fetch(&mut self, limit: Option<u64>) -> Result<&mut Self, VideoError> {
+        let limit = limit.unwrap_or(u64::MAX);
+
+        while self.continuation.is_some() && (self.videos.len() as u64) < limit {
+            if self.next().await?.is_empty() {
+                break;
+            }
+        }
+
+        self.videos.truncate(limit.min(usize::MAX as u64) as usize);
+
+        Ok(self)
+    }
+
+    /// There is a next page to fetch or not
+    pub fn has_next(&self) -> bool {
+        self.continuation.is_some()
+    }
+}
+
+fn continuation_token(item: &serde_json::Value) -> Option<&str> {
+    item["continuationItemRenderer"]["continuationEndpoint"]["continuationCommand"]["token"]
+        .as_str()
+}
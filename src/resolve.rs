@@ -0,0 +1,116 @@
+use crate::structs::{RequestOptions, VideoError};
+use crate::utils::{build_client, get_url_param, get_video_id, innertube_request};
+
+/// Target of a YouTube URL resolved with [`resolve_url`]
+#[derive(Clone, Debug, PartialEq, Eq, derive_more::Display)]
+pub enum ResolvedUrl {
+    #[display(fmt = "Video({id})")]
+    Video {
+        id: String,
+        /// Playlist of the `list` parameter
+        playlist_id: Option<String>,
+        /// Start time of the `t` parameter in seconds
+        start_time: Option<u64>,
+    },
+    #[display(fmt = "Playlist({id})")]
+    Playlist { id: String },
+    #[display(fmt = "Channel({id})")]
+    Channel { id: String },
+    /// Other browse pages like feeds or hashtags
+    #[display(fmt = "Browse({id})")]
+    Browse { id: String },
+    /// URL outside of YouTube (e.g. redirect links of descriptions)
+    #[display(fmt = "External({url})")]
+    External { url: String },
+}
+
+/// Classify any YouTube URL (video, shorts, channel handle, playlist, short link, attribution link) with default [`RequestOptions`]
+/// # Example
+/// ```ignore
+///     let resolved = resolve_url("https://www.youtube.com/@YouTube").await.unwrap();
+///
+///     if let ResolvedUrl::Channel { id } = resolved {
+///         println!("{id}");
+///     }
+/// ```
+pub async fn resolve_url(url: impl Into<String>) -> Result<ResolvedUrl, VideoError> {
+    resolve_url_with_options(url, &RequestOptions::default()).await
+}
+
+/// Classify any YouTube URL (video, shorts, channel handle, playlist, short link, attribution link) with custom [`RequestOptions`]
+pub async fn resolve_url_with_options(
+    url: impl Into<String>,
+    request_options: &RequestOptions,
+) -> Result<ResolvedUrl, VideoError> {
+    let client = build_client(request_options)?;
+
+    resolve_url_with_client(&client, &url.into()).await
+}
+
+pub(crate) async fn resolve_url_with_client(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    url: &str,
+) -> Result<ResolvedUrl, VideoError> {
+    let url = url.trim();
+
+    let response = innertube_request(
+        client,
+        "navigation/resolve_url",
+        serde_json::json!({
+            "url": url,
+        }),
+    )
+    .await?;
+
+    let endpoint = &response["endpoint"];
+
+    if let Some(video_id) = endpoint["watchEndpoint"]["videoId"].as_str() {
+        return Ok(ResolvedUrl::Video {
+            id: video_id.to_string(),
+            playlist_id: endpoint["watchEndpoint"]["playlistId"]
+                .as_str()
+                .map(|x| x.to_string()),
+            start_time: endpoint["watchEndpoint"]["startTimeSeconds"].as_u64(),
+        });
+    }
+
+    if let Some(video_id) = endpoint["reelWatchEndpoint"]["videoId"].as_str() {
+        return Ok(ResolvedUrl::Video {
+            id: video_id.to_string(),
+            playlist_id: None,
+            start_time: None,
+        });
+    }
+
+    if let Some(browse_id) = endpoint["browseEndpoint"]["browseId"].as_str() {
+        return Ok(if let Some(playlist_id) = browse_id.strip_prefix("VL") {
+            ResolvedUrl::Playlist {
+                id: playlist_id.to_string(),
+            }
+        } else if browse_id.starts_with("UC") {
+            ResolvedUrl::Channel {
+                id: browse_id.to_string(),
+            }
+        } else {
+            ResolvedUrl::Browse {
+                id: browse_id.to_string(),
+            }
+        });
+    }
+
+    if let Some(external_url) = endpoint["urlEndpoint"]["url"].as_str() {
+        return Ok(ResolvedUrl::External {
+            url: external_url.to_string(),
+        });
+    }
+
+    // Endpoint does not know every URL form, fallback to the URL parameters
+    match get_video_id(url) {
+        Some(id) => Ok(ResolvedUrl::Video {
+            id,
+            playlist_id: get_url_param(url, "list"),
+            start_time: None,
+        }),
+        None => Err(VideoError::UrlNotResolved(url.to_string())),
+    }
+}
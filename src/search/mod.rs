@@ -1,6 +1,7 @@
 mod youtube;
 
 pub use youtube::{
-    Channel, EmbedOptions, Playlist, PlaylistSearchOptions, RequestOptions, SearchOptions,
-    SearchResult, SearchType, Video, YouTube,
+    Channel, EmbedOptions, Playlist, PlaylistSearchOptions, RequestOptions, SearchDuration,
+    SearchOptions, SearchResult, SearchType, SortBy, SuggestionOptions, TrendingCategory,
+    UploadDate, Video, YouTube,
 };
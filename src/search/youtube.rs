@@ -9,7 +9,7 @@ use urlencoding::encode;
 use crate::{
     constants::DEFAULT_HEADERS,
     structs::VideoError,
-    utils::{get_html, get_random_v6_ip, time_to_ms},
+    utils::{build_client, get_html, innertube_request, time_to_ms},
     Thumbnail,
 };
 
@@ -38,11 +38,7 @@ pub struct YouTube {
 impl YouTube {
     /// Create new YouTube search struct with default [`RequestOptions`]
     pub fn new() -> Result<Self, VideoError> {
-        let client = reqwest::Client::builder()
-            .build()
-            .map_err(VideoError::Reqwest)?;
-
-        let client = reqwest_middleware::ClientBuilder::new(client).build();
+        let client = build_client(&RequestOptions::default())?;
 
         Ok(Self {
             client,
@@ -52,33 +48,7 @@ impl YouTube {
 
     /// Create new YouTube search struct with custom [`RequestOptions`]
     pub fn new_with_options(request_options: &RequestOptions) -> Result<Self, VideoError> {
-        let mut client = reqwest::Client::builder();
-
-        // Assign request options to client
-        if request_options.proxy.is_some() {
-            let proxy = request_options.proxy.as_ref().unwrap().clone();
-            client = client.proxy(proxy);
-        }
-
-        if request_options.ipv6_block.is_some() {
-            let ipv6 = request_options.ipv6_block.as_ref().unwrap();
-            let ipv6 = get_random_v6_ip(ipv6)?;
-            client = client.local_address(ipv6);
-        }
-
-        if request_options.cookies.is_some() {
-            let cookie = request_options.cookies.as_ref().unwrap();
-            let host = "https://youtube.com".parse::<url::Url>().unwrap();
-
-            let jar = reqwest::cookie::Jar::default();
-            jar.add_cookie_str(cookie.as_str(), &host);
-
-            client = client.cookie_provider(Arc::new(jar));
-        }
-
-        let client = client.build().map_err(VideoError::Reqwest)?;
-
-        let client = reqwest_middleware::ClientBuilder::new(client).build();
+        let client = build_client(request_options)?;
 
         Ok(Self {
             client,
@@ -110,7 +80,7 @@ impl YouTube {
         };
 
         let query: String = query.into();
-        let filter = filter_string(&options.search_type);
+        let filter = filter_string(options);
         let query_regex = Regex::new(r"%20").unwrap();
 
         // First try with youtube backend
@@ -149,10 +119,10 @@ impl YouTube {
         }
 
         // get html body if backend return null
-        let filter = if options.search_type == SearchType::All {
+        let filter = if filter.is_empty() {
             "".to_string()
         } else {
-            format!("&sp={}", filter_string(&options.search_type))
+            format!("&sp={filter}")
         };
 
         let url = format!(
@@ -198,6 +168,122 @@ impl YouTube {
         Ok(res.first().cloned())
     }
 
+    /// Get autocomplete suggestions of the `query` ranked as YouTube returns them. If nothing found, its return empty [`Vec<String>`]
+    /// # Example
+    /// ```ignore
+    ///     let youtube = YouTube::new().unwrap();
+    ///
+    ///     let suggestions = youtube.suggestions("i know your", None).await;
+    ///
+    ///     println!("{suggestions:#?}");
+    /// ```
+    pub async fn suggestions(
+        &self,
+        query: impl Into<String>,
+        suggestion_options: Option<&SuggestionOptions>,
+    ) -> Result<Vec<String>, VideoError> {
+        let default_options = SuggestionOptions::default();
+        let options = suggestion_options.unwrap_or(&default_options);
+
+        let query: String = query.into();
+
+        // `firefox` client returns plain JSON `["query", ["suggestion", ...]]` instead of JSONP
+        let url = format!(
+            "https://suggestqueries-clients6.youtube.com/complete/search?client=firefox&ds=yt&q={query}&hl={hl}&gl={gl}",
+            query = encode(query.trim()),
+            hl = encode(&options.language),
+            gl = encode(&options.country),
+        );
+
+        let body = get_html(&self.client, url, Some(&DEFAULT_HEADERS.clone())).await?;
+
+        let response: serde_json::Value =
+            serde_json::from_str(&body).map_err(|_| VideoError::BodyCannotParsed)?;
+
+        Ok(response[1]
+            .as_array()
+            .map(|x| {
+                x.iter()
+                    .filter_map(|x| x.as_str().map(|x| x.to_string()))
+                    .take(options.limit as usize)
+                    .collect()
+            })
+            .unwrap_or_default())
+    }
+
+    /// Get trending videos of the `region` (ISO 3166-1 alpha-2 country code, e.g. `US`) in the `category`
+    /// # Example
+    /// ```ignore
+    ///     let youtube = YouTube::new().unwrap();
+    ///
+    ///     let videos = youtube.trending("US", TrendingCategory::Music).await;
+    ///
+    ///     println!("{videos:#?}");
+    /// ```
+    pub async fn trending(
+        &self,
+        region: impl Into<String>,
+        category: TrendingCategory,
+    ) -> Result<Vec<Video>, VideoError> {
+        let mut body = serde_json::json!({
+            "browseId": "FEtrending",
+            "context": {
+                "client": {
+                    "gl": region.into(),
+                },
+            },
+        });
+
+        if let Some(params) = category.params() {
+            body["params"] = serde_json::Value::String(params.to_string());
+        }
+
+        let response = innertube_request(&self.client, "browse", body).await?;
+
+        // Every category is a tab, selected one has the content
+        let sections = response["contents"]["twoColumnBrowseResultsRenderer"]["tabs"]
+            .as_array()
+            .and_then(|tabs| {
+                tabs.iter()
+                    .find(|x| x["tabRenderer"]["selected"].as_bool().unwrap_or(false))
+            })
+            .and_then(|x| x["tabRenderer"]["content"]["sectionListRenderer"]["contents"].as_array())
+            .cloned()
+            .unwrap_or_default();
+
+        let items = sections
+            .iter()
+            .flat_map(|section| {
+                section["itemSectionRenderer"]["contents"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default()
+            })
+            .flat_map(|content| {
+                content["shelfRenderer"]["content"]["expandedShelfContentsRenderer"]["items"]
+                    .as_array()
+                    .cloned()
+                    .unwrap_or_default()
+            })
+            .collect::<Vec<serde_json::Value>>();
+
+        let options = SearchOptions {
+            limit: 0,
+            search_type: SearchType::Video,
+            ..Default::default()
+        };
+
+        Ok(
+            format_search_result(&self.client, &serde_json::Value::Array(items), &options)
+                .into_iter()
+                .filter_map(|x| match x {
+                    SearchResult::Video(video) => Some(video),
+                    _ => None,
+                })
+                .collect(),
+        )
+    }
+
     async fn innertube_key(&self) -> String {
         {
             let innertube_cache = self.innertube_cache.read().unwrap();
@@ -242,6 +328,37 @@ pub enum SearchType {
     All,
 }
 
+/// Upload date filter of the search
+#[derive(Clone, Copy, Debug, PartialEq, Eq, derive_more::Display)]
+pub enum UploadDate {
+    LastHour,
+    Today,
+    ThisWeek,
+    ThisMonth,
+    ThisYear,
+}
+
+/// Duration filter of the search
+#[derive(Clone, Copy, Debug, PartialEq, Eq, derive_more::Display)]
+pub enum SearchDuration {
+    /// Under 4 minutes
+    Under4Min,
+    /// 4 - 20 minutes
+    Between4And20Min,
+    /// Over 20 minutes
+    Over20Min,
+}
+
+/// Sort order of the search results
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, derive_more::Display)]
+pub enum SortBy {
+    #[default]
+    Relevance,
+    Rating,
+    UploadDate,
+    ViewCount,
+}
+
 #[derive(Clone, derive_more::Display, derivative::Derivative)]
 #[display(fmt = "SearchOptions()")]
 #[derivative(Debug, PartialEq, Eq)]
@@ -249,6 +366,9 @@ pub struct SearchOptions {
     pub limit: u64,
     pub search_type: SearchType,
     pub safe_search: bool,
+    pub upload_date: Option<UploadDate>,
+    pub duration: Option<SearchDuration>,
+    pub sort_by: SortBy,
 }
 
 impl Default for SearchOptions {
@@ -257,6 +377,51 @@ impl Default for SearchOptions {
             limit: 100,
             search_type: SearchType::Video,
             safe_search: false,
+            upload_date: None,
+            duration: None,
+            sort_by: SortBy::default(),
+        }
+    }
+}
+
+/// Category of the trending feed
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, derive_more::Display)]
+pub enum TrendingCategory {
+    #[default]
+    Now,
+    Music,
+    Gaming,
+    Movies,
+}
+
+impl TrendingCategory {
+    /// `params` of the browse request selecting the category tab
+    fn params(&self) -> Option<&'static str> {
+        match self {
+            TrendingCategory::Now => None,
+            TrendingCategory::Music => Some("4gINGgt5dG1hX2NoYXJ0cw=="),
+            TrendingCategory::Gaming => Some("4gIcGhpnYW1pbmdfY29ycHVzX21vc3RfcG9wdWxhcg=="),
+            TrendingCategory::Movies => Some("4gIKGgh0cmFpbGVycw=="),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, derive_more::Display)]
+#[display(fmt = "SuggestionOptions()")]
+pub struct SuggestionOptions {
+    pub limit: u64,
+    /// Language of the suggestions (`hl`)
+    pub language: String,
+    /// Country of the suggestions (`gl`)
+    pub country: String,
+}
+
+impl Default for SuggestionOptions {
+    fn default() -> Self {
+        Self {
+            limit: 10,
+            language: "en".to_string(),
+            country: "US".to_string(),
         }
     }
 }
@@ -373,6 +538,20 @@ impl Video {
 
         Some(format!("https://www.youtube.com/embed/{}", self.id))
     }
+
+    /// Turn the lightweight search/playlist [`Video`] into a [`crate::Video`] with default [`crate::VideoOptions`]
+    /// to get full information or download it
+    pub fn to_video(&self) -> Result<crate::Video, VideoError> {
+        crate::Video::new(&self.id)
+    }
+
+    /// Turn the lightweight search/playlist [`Video`] into a [`crate::Video`] with custom [`crate::VideoOptions`]
+    pub fn to_video_with_options(
+        &self,
+        options: crate::VideoOptions,
+    ) -> Result<crate::Video, VideoError> {
+        crate::Video::new_with_options(&self.id, options)
+    }
 }
 
 #[derive(Clone, derivative::Derivative)]
@@ -412,15 +591,17 @@ pub struct Playlist {
     pub channel: Channel,
     pub thumbnails: Vec<Thumbnail>,
     pub views: u64,
+    /// Total video count of the playlist reported by YouTube
+    pub video_count: u64,
     pub videos: Vec<Video>,
     pub last_update: Option<String>,
 
     #[serde(skip_serializing)]
     #[derivative(PartialEq = "ignore")]
-    continuation: Option<Continuation>,
+    pub(crate) continuation: Option<Continuation>,
     #[serde(skip_serializing)]
     #[derivative(PartialEq = "ignore")]
-    client: reqwest_middleware::ClientWithMiddleware,
+    pub(crate) client: reqwest_middleware::ClientWithMiddleware,
 }
 
 impl Playlist {
@@ -450,66 +631,10 @@ impl Playlist {
 
         let url = url_option.unwrap();
 
-        // Assign request options to client
-        let mut client = reqwest::Client::builder();
-
-        if options
-            .request_options
-            .as_ref()
-            .map(|x| x.proxy.is_some())
-            .unwrap_or(false)
-        {
-            let proxy = options
-                .request_options
-                .as_ref()
-                .unwrap()
-                .proxy
-                .as_ref()
-                .unwrap()
-                .clone();
-            client = client.proxy(proxy);
-        }
-
-        if options
-            .request_options
-            .as_ref()
-            .map(|x| x.ipv6_block.is_some())
-            .unwrap_or(false)
-        {
-            let ipv6 = options
-                .request_options
-                .as_ref()
-                .unwrap()
-                .ipv6_block
-                .as_ref()
-                .unwrap();
-            let ipv6 = get_random_v6_ip(ipv6)?;
-            client = client.local_address(ipv6);
-        }
-
-        if options
-            .request_options
-            .as_ref()
-            .map(|x| x.cookies.is_some())
-            .unwrap_or(false)
-        {
-            let cookie = options
-                .request_options
-                .as_ref()
-                .unwrap()
-                .cookies
-                .as_ref()
-                .unwrap();
-            let host = "https://youtube.com".parse::<url::Url>().unwrap();
-
-            let jar = reqwest::cookie::Jar::default();
-            jar.add_cookie_str(cookie.as_str(), &host);
-
-            client = client.cookie_provider(Arc::new(jar));
-        }
-
-        let client = client.build().map_err(VideoError::Reqwest)?;
-        let client = reqwest_middleware::ClientBuilder::new(client).build();
+        let client = match &options.request_options {
+            Some(request_options) => build_client(request_options)?,
+            None => build_client(&RequestOptions::default())?,
+        };
 
         let html_first = get_html(
             &client,
@@ -719,6 +844,24 @@ impl Playlist {
                     } else {
                         0
                     },
+                    video_count: {
+                        let video_count = if playlist_primary_data["stats"][0]["runs"].is_array() {
+                            playlist_primary_data["stats"][0]["runs"][0]["text"]
+                                .as_str()
+                                .unwrap_or("")
+                        } else {
+                            playlist_primary_data["stats"][0]["simpleText"]
+                                .as_str()
+                                .unwrap_or("")
+                        };
+
+                        video_count
+                            .chars()
+                            .filter(char::is_ascii_digit)
+                            .collect::<String>()
+                            .parse::<u64>()
+                            .unwrap_or(0)
+                    },
                     videos,
                     last_update: if playlist_primary_data["stats"].is_array() {
                         playlist_primary_data["stats"]
@@ -874,7 +1017,7 @@ impl Playlist {
             .await;
 
         if res.is_err() {
-            return Err(VideoError::ReqwestMiddleware(res.err().unwrap()));
+            return Err(VideoError::from(res.err().unwrap()));
         }
 
         let res = res.unwrap().json::<serde_json::Value>().await;
@@ -1047,7 +1190,10 @@ impl Playlist {
             videos.push(Video {
                 id: video["videoId"].as_str().unwrap_or("").to_string(),
                 url: if video["videoId"].is_string() {
-                    video["videoId"].as_str().unwrap_or("").to_string()
+                    format!(
+                        "https://www.youtube.com/watch?v={}",
+                        video["videoId"].as_str().unwrap_or("")
+                    )
                 } else {
                     String::from("")
                 },
@@ -1203,14 +1349,86 @@ pub struct Channel {
     pub subscribers: u64,
 }
 
-fn filter_string(filter: &SearchType) -> String {
-    match filter {
-        SearchType::Video => "EgIQAQ%253D%253D".to_string(),
-        SearchType::Channel => "EgIQAg%253D%253D".to_string(),
-        SearchType::Playlist => "EgIQAw%253D%253D".to_string(),
-        SearchType::Film => "EgIQBA%253D%253D".to_string(),
-        SearchType::All => "".to_string(),
+/// Encode filters and sort order of the search to the `sp` parameter
+/// - `sp` is base64 of the protobuf message `{ 1: sort, 2: { 1: upload date, 2: type, 3: duration } }`
+fn filter_string(options: &SearchOptions) -> String {
+    let mut filters: Vec<u8> = vec![];
+
+    if let Some(upload_date) = options.upload_date {
+        let value = match upload_date {
+            UploadDate::LastHour => 1,
+            UploadDate::Today => 2,
+            UploadDate::ThisWeek => 3,
+            UploadDate::ThisMonth => 4,
+            UploadDate::ThisYear => 5,
+        };
+        filters.extend([0x08, value]);
+    }
+
+    let search_type = match options.search_type {
+        SearchType::Video => Some(1),
+        SearchType::Channel => Some(2),
+        SearchType::Playlist => Some(3),
+        SearchType::Film => Some(4),
+        SearchType::All => None,
+    };
+    if let Some(search_type) = search_type {
+        filters.extend([0x10, search_type]);
+    }
+
+    if let Some(duration) = options.duration {
+        let value = match duration {
+            SearchDuration::Under4Min => 1,
+            SearchDuration::Over20Min => 2,
+            SearchDuration::Between4And20Min => 3,
+        };
+        filters.extend([0x18, value]);
+    }
+
+    let mut params: Vec<u8> = vec![];
+
+    let sort_by = match options.sort_by {
+        SortBy::Relevance => 0,
+        SortBy::Rating => 1,
+        SortBy::UploadDate => 2,
+        SortBy::ViewCount => 3,
+    };
+    if sort_by != 0 {
+        params.extend([0x08, sort_by]);
+    }
+
+    if !filters.is_empty() {
+        params.extend([0x12, filters.len() as u8]);
+        params.extend(filters);
+    }
+
+    if params.is_empty() {
+        return "".to_string();
+    }
+
+    encode(&encode(&encode_base64(&params))).to_string()
+}
+
+fn encode_base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity((bytes.len() + 2) / 3 * 4);
+
+    for chunk in bytes.chunks(3) {
+        let triple = ((chunk[0] as u32) << 16)
+            | ((*chunk.get(1).unwrap_or(&0) as u32) << 8)
+            | (*chunk.get(2).unwrap_or(&0) as u32);
+
+        for index in 0..4 {
+            if index <= chunk.len() {
+                encoded.push(ALPHABET[((triple >> (18 - index * 6)) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
     }
+
+    encoded
 }
 
 fn get_client_version(html: impl Into<String>) -> String {
@@ -2065,6 +2283,14 @@ fn format_search_result(
                         } else {
                             vec![]
                         },
+                        video_count: data["playlistRenderer"]["videoCount"]
+                            .as_str()
+                            .unwrap_or("")
+                            .chars()
+                            .filter(char::is_ascii_digit)
+                            .collect::<String>()
+                            .parse::<u64>()
+                            .unwrap_or(0),
                         // we cannot get videos, views and last_update from search we need to send request to playlist url
                         views: 0,
                         videos: vec![],
@@ -2085,3 +2311,29 @@ fn format_search_result(
     // return results array
     res
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_string() {
+        assert_eq!(filter_string(&SearchOptions::default()), "EgIQAQ%253D%253D");
+        assert_eq!(
+            filter_string(&SearchOptions {
+                search_type: SearchType::All,
+                ..Default::default()
+            }),
+            ""
+        );
+        assert_eq!(
+            filter_string(&SearchOptions {
+                upload_date: Some(UploadDate::ThisWeek),
+                duration: Some(SearchDuration::Under4Min),
+                sort_by: SortBy::ViewCount,
+                ..Default::default()
+            }),
+            "CAMSBggDEAEYAQ%253D%253D"
+        );
+    }
+}
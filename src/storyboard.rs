@@ -0,0 +1,95 @@
+use std::io::Cursor;
+use std::time::Duration;
+
+use bytes::Bytes;
+
+use crate::structs::{RequestOptions, StoryBoard, VideoError};
+use crate::utils::build_client;
+
+/// JPEG quality of the sliced frames
+const FRAME_QUALITY: u8 = 90;
+
+impl StoryBoard {
+    /// Download the sprite sheets of the storyboard and slice them into JPEG preview frames with their timestamps
+    /// # Example
+    /// ```ignore
+    ///     let video = Video::new("https://www.youtube.com/watch?v=FZ8BxMU3BYc").unwrap();
+    ///
+    ///     let info = video.get_basic_info().await.unwrap();
+    ///
+    ///     // Last storyboard has the biggest frames
+    ///     if let Some(storyboard) = info.video_details.storyboards.last() {
+    ///         for (timestamp, jpeg) in storyboard.frames().await.unwrap() {
+    ///             println!("{:?}: {} bytes", timestamp, jpeg.len());
+    ///         }
+    ///     }
+    /// ```
+    pub async fn frames(&self) -> Result<Vec<(Duration, Bytes)>, VideoError> {
+        self.frames_with_options(&RequestOptions::default()).await
+    }
+
+    /// Same as [`StoryBoard::frames`] with custom [`RequestOptions`] (e.g. proxy)
+    pub async fn frames_with_options(
+        &self,
+        request_options: &RequestOptions,
+    ) -> Result<Vec<(Duration, Bytes)>, VideoError> {
+        let client = build_client(request_options)?;
+
+        let frames_per_sheet = (self.columns * self.rows).max(0) as usize;
+        let thumbnail_count = self.thumbnail_count.max(0) as usize;
+        let width = self.thumbnail_width.max(0) as u32;
+        let height = self.thumbnail_height.max(0) as u32;
+
+        if frames_per_sheet == 0 || width == 0 || height == 0 {
+            return Ok(vec![]);
+        }
+
+        let mut frames = Vec::with_capacity(thumbnail_count);
+
+        for sheet_index in 0..self.storyboard_count.max(0) {
+            let url = self.template_url.replace("$M", &sheet_index.to_string());
+
+            let sheet = client
+                .get(&url)
+                .send()
+                .await
+                .map_err(VideoError::from)?
+                .error_for_status()
+                .map_err(VideoError::Reqwest)?
+                .bytes()
+                .await
+                .map_err(VideoError::Reqwest)?;
+
+            let sheet =
+                image::load_from_memory(&sheet).map_err(|x| VideoError::Image(x.to_string()))?;
+
+            for cell in 0..frames_per_sheet {
+                let index = sheet_index as usize * frames_per_sheet + cell;
+                if index >= thumbnail_count {
+                    break;
+                }
+
+                let x = (cell % self.columns as usize) as u32 * width;
+                let y = (cell / self.columns as usize) as u32 * height;
+
+                // Last sheet is cut after its last row
+                if x + width > sheet.width() || y + height > sheet.height() {
+                    break;
+                }
+
+                let mut jpeg = Cursor::new(vec![]);
+                sheet
+                    .crop_imm(x, y, width, height)
+                    .write_to(&mut jpeg, image::ImageOutputFormat::Jpeg(FRAME_QUALITY))
+                    .map_err(|x| VideoError::Image(x.to_string()))?;
+
+                frames.push((
+                    Duration::from_millis(index as u64 * self.interval.max(0) as u64),
+                    Bytes::from(jpeg.into_inner()),
+                ));
+            }
+        }
+
+        Ok(frames)
+    }
+}
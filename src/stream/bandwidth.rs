@@ -0,0 +1,38 @@
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+
+use crate::platform::Instant;
+
+/// Token bucket shared by the concurrent requests of a stream, bursts are limited to one second of data
+pub(crate) struct BandwidthLimiter {
+    bytes_per_second: u64,
+    /// Available bytes and the time they were last refilled. Negative after a burst is spent
+    bucket: Mutex<(f64, Instant)>,
+}
+
+impl BandwidthLimiter {
+    pub(crate) fn new(bytes_per_second: u64) -> Self {
+        Self {
+            bytes_per_second: bytes_per_second.max(1),
+            bucket: Mutex::new((bytes_per_second as f64, Instant::now())),
+        }
+    }
+
+    /// Take `bytes` from the bucket, waiting until the download is back under the limit
+    pub(crate) async fn consume(&self, bytes: u64) {
+        let rate = self.bytes_per_second as f64;
+
+        // Lock is held while waiting so the other requests queue behind
+        let mut bucket = self.bucket.lock().await;
+
+        let (tokens, refilled_at) = *bucket;
+        let tokens = (tokens + refilled_at.elapsed().as_secs_f64() * rate).min(rate) - bytes as f64;
+
+        if tokens < 0f64 {
+            crate::platform::sleep(Duration::from_secs_f64(-tokens / rate)).await;
+        }
+
+        *bucket = (tokens.max(0f64), Instant::now());
+    }
+}
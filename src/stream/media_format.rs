@@ -1,10 +1,9 @@
 #![allow(dead_code)]
 use std::process::Stdio;
 
-use serde::Deserialize;
-use tokio::{io::AsyncWriteExt, process};
-
+use crate::platform::{wait_with_output, AsyncWriteExt, Command};
 use crate::VideoError;
+use serde::Deserialize;
 
 #[non_exhaustive]
 #[allow(dead_code)]
@@ -40,7 +39,7 @@ impl MediaFormat {
         }
 
         // Call ffprobe to check format
-        let mut cmd = process::Command::new("ffprobe");
+        let mut cmd = Command::new("ffprobe");
         cmd.arg("-loglevel")
             .arg("quiet")
             .arg("-show_entries")
@@ -61,14 +60,14 @@ impl MediaFormat {
             .take()
             .ok_or_else(|| VideoError::ChildProcessError("Can't open ffprobe stdin".to_string()))?;
 
-        // Write to ffprobe stdin
-        tokio::spawn(async move { stdin.write_all(&data).await });
+        // Write to ffprobe stdin while running it, ffprobe may exit before reading everything
+        let write = async move {
+            let _ = stdin.write_all(&data).await;
+        };
 
         // Run ffprobe
-        let output = child
-            .wait_with_output()
-            .await
-            .map_err(|e| VideoError::ChildProcessError(e.to_string()))?;
+        let (output, _) = futures::join!(wait_with_output(child), write);
+        let output = output.map_err(|e| VideoError::ChildProcessError(e.to_string()))?;
         let utf8_output = String::from_utf8(output.stdout)
             .map_err(|e| VideoError::ChildProcessError(e.to_string()))?;
 
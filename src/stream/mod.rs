@@ -1,5 +1,7 @@
+mod bandwidth;
 mod encryption;
 mod hashable_byte_range;
+mod progress;
 mod remote_data;
 mod streams;
 
@@ -8,6 +10,11 @@ mod media_format;
 #[cfg(feature = "live")]
 mod segment;
 
+#[cfg(feature = "ffmpeg")]
+pub use streams::FFmpegStream;
+pub use streams::{
+    DashStream, DashStreamOptions, LinkRefresher, NonLiveStream, NonLiveStreamOptions, OtfStream,
+    OtfStreamOptions, Stream,
+};
 #[cfg(feature = "live")]
 pub use streams::{LiveStream, LiveStreamOptions};
-pub use streams::{NonLiveStream, NonLiveStreamOptions, Stream};
@@ -0,0 +1,79 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+
+use crate::platform::Instant;
+use crate::structs::{DownloadEvent, DownloadEventCallback, DownloadProgress, ProgressCallback};
+
+/// Tracks downloaded bytes of a stream and reports them to the [`ProgressCallback`] and [`DownloadEventCallback`]
+pub(crate) struct ProgressTracker {
+    callback: Option<ProgressCallback>,
+    events: Option<DownloadEventCallback>,
+    total: u64,
+    /// Bytes already downloaded before the stream started (e.g. resumed downloads)
+    initial: u64,
+    downloaded: AtomicU64,
+    started: Instant,
+}
+
+impl ProgressTracker {
+    pub(crate) fn new(
+        callback: Option<ProgressCallback>,
+        events: Option<DownloadEventCallback>,
+        initial: u64,
+        total: u64,
+    ) -> Self {
+        Self {
+            callback,
+            events,
+            total,
+            initial,
+            downloaded: AtomicU64::new(initial),
+            started: Instant::now(),
+        }
+    }
+
+    pub(crate) fn advance(&self, bytes: u64) {
+        let downloaded = self.downloaded.fetch_add(bytes, Ordering::SeqCst) + bytes;
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::increment(crate::metrics::DOWNLOADED_BYTES, bytes);
+
+        if self.callback.is_none() && self.events.is_none() {
+            return;
+        }
+
+        let elapsed = self.started.elapsed().as_secs_f64();
+        let speed = if elapsed > 0f64 {
+            downloaded.saturating_sub(self.initial) as f64 / elapsed
+        } else {
+            0f64
+        };
+
+        let eta = if self.total > 0 && speed > 0f64 {
+            Some(Duration::from_secs_f64(
+                self.total.saturating_sub(downloaded) as f64 / speed,
+            ))
+        } else {
+            None
+        };
+
+        let progress = DownloadProgress {
+            downloaded,
+            total: self.total,
+            speed,
+            eta,
+        };
+
+        if let Some(callback) = &self.callback {
+            callback.call(&progress);
+        }
+
+        self.emit(DownloadEvent::ChunkDownloaded { bytes, progress });
+    }
+
+    pub(crate) fn emit(&self, event: DownloadEvent) {
+        if let Some(events) = &self.events {
+            events.call(&event);
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use crate::constants::DEFAULT_HEADERS;
+use crate::parser::{parse_dash_segments, DashSegments};
+use crate::platform::{body_chunks, sleep, Instant};
+use crate::stream::bandwidth::BandwidthLimiter;
+use crate::stream::progress::ProgressTracker;
+use crate::stream::streams::Stream;
+use crate::structs::{DownloadEvent, DownloadEventCallback, ProgressCallback, VideoError};
+use crate::utils::get_html;
+
+use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
+use futures::StreamExt;
+use std::collections::{HashSet, VecDeque};
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+/// Manifest refresh interval of dynamic manifests if [`DashStreamOptions::poll_interval`] is not set
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(20);
+/// Retry count of a segment if [`DashStreamOptions::segment_retries`] is not set
+const DEFAULT_SEGMENT_RETRIES: u32 = 3;
+/// Waiting time before retrying a segment after a network error
+const SEGMENT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+pub struct DashStreamOptions {
+    pub client: Option<reqwest_middleware::ClientWithMiddleware>,
+    /// URL of the DASH MPD manifest
+    pub manifest_url: String,
+    /// `id` of the representation in the manifest, itag for YouTube manifests
+    pub representation_id: String,
+    pub progress_callback: Option<ProgressCallback>,
+    /// Receives [`DownloadEvent::ChunkDownloaded`] and [`DownloadEvent::Retrying`] events
+    pub event_callback: Option<DownloadEventCallback>,
+    /// Interval of manifest refreshes while waiting for new segments of a dynamic manifest. Default is 20 seconds
+    pub poll_interval: Option<Duration>,
+    /// Count of retries of a failed segment before the error is returned. Default is `3`
+    pub segment_retries: Option<u32>,
+    /// Download speed limit in bytes per second, [`None`] is unlimited
+    pub max_bytes_per_second: Option<u64>,
+}
+
+/// Stream of a DASH representation downloaded segment by segment from its `SegmentList` or `SegmentTemplate`.
+/// Live DVR and post-live manifests have no byte ranges for Range requests
+/// - Static manifests end after the last segment
+/// - Dynamic manifests are refreshed every [`DashStreamOptions::poll_interval`] to get new segments
+///   until the manifest becomes static
+pub struct DashStream {
+    client: reqwest_middleware::ClientWithMiddleware,
+    manifest_url: String,
+    representation_id: String,
+    poll_interval: Duration,
+    segment_retries: u32,
+
+    queue: RwLock<VecDeque<String>>,
+    /// Queued segment URLs, so refreshed manifests add only new segments
+    seen: RwLock<HashSet<String>>,
+    last_refresh: RwLock<Option<Instant>>,
+    is_end: RwLock<bool>,
+    progress: ProgressTracker,
+    bandwidth_limiter: Option<BandwidthLimiter>,
+}
+
+impl DashStream {
+    pub fn new(options: DashStreamOptions) -> Result<Self, VideoError> {
+        let client = if let Some(client) = options.client {
+            client
+        } else {
+            let client = reqwest::Client::builder()
+                .build()
+                .map_err(VideoError::Reqwest)?;
+
+            let retry_policy = reqwest_retry::policies::ExponentialBackoff::builder()
+                .retry_bounds(
+                    std::time::Duration::from_millis(500),
+                    std::time::Duration::from_millis(10000),
+                )
+                .build_with_max_retries(3);
+            reqwest_middleware::ClientBuilder::new(client)
+                .with(reqwest_retry::RetryTransientMiddleware::new_with_policy(
+                    retry_policy,
+                ))
+                .build()
+        };
+
+        Ok(Self {
+            client,
+            manifest_url: options.manifest_url,
+            representation_id: options.representation_id,
+            poll_interval: options.poll_interval.unwrap_or(DEFAULT_POLL_INTERVAL),
+            segment_retries: options.segment_retries.unwrap_or(DEFAULT_SEGMENT_RETRIES),
+            queue: RwLock::new(VecDeque::new()),
+            seen: RwLock::new(HashSet::new()),
+            last_refresh: RwLock::new(None),
+            is_end: RwLock::new(false),
+            progress: ProgressTracker::new(options.progress_callback, options.event_callback, 0, 0),
+            bandwidth_limiter: options.max_bytes_per_second.map(BandwidthLimiter::new),
+        })
+    }
+
+    /// Fetch the manifest and queue the segments which were not queued before
+    async fn refresh_manifest(&self) -> Result<(), VideoError> {
+        let body = get_html(&self.client, &self.manifest_url, None).await?;
+
+        let DashSegments {
+            initialization,
+            segments,
+            is_dynamic,
+        } = parse_dash_segments(&body, &self.manifest_url, &self.representation_id).ok_or_else(
+            || {
+                VideoError::DownloadError(format!(
+                    "Representation {} has no segments in the DASH manifest",
+                    self.representation_id
+                ))
+            },
+        )?;
+
+        let mut seen = self.seen.write().await;
+        let mut queue = self.queue.write().await;
+
+        for url in initialization.into_iter().chain(segments) {
+            if seen.insert(url.clone()) {
+                queue.push_back(url);
+            }
+        }
+
+        *self.last_refresh.write().await = Some(Instant::now());
+        *self.is_end.write().await = !is_dynamic;
+
+        Ok(())
+    }
+
+    /// Download a segment, responses with error status are returned as errors
+    async fn fetch_segment(&self, url: &str) -> Result<BytesMut, VideoError> {
+        let headers = DEFAULT_HEADERS.clone();
+
+        let response = self
+            .client
+            .get(url)
+            .headers(headers)
+            .send()
+            .await
+            .map_err(VideoError::from)?
+            .error_for_status()
+            .map_err(VideoError::Reqwest)?;
+
+        let mut buf: BytesMut = BytesMut::new();
+
+        let mut body = std::pin::pin!(body_chunks(response));
+
+        while let Some(chunk) = body.next().await.transpose().map_err(VideoError::Reqwest)? {
+            if let Some(bandwidth_limiter) = &self.bandwidth_limiter {
+                bandwidth_limiter.consume(chunk.len() as u64).await;
+            }
+
+            buf.extend(chunk);
+        }
+
+        Ok(buf)
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Stream for DashStream {
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self), err))]
+    async fn chunk(&self) -> Result<Option<Bytes>, VideoError> {
+        if self.queue.read().await.is_empty() {
+            if *self.is_end.read().await {
+                return Ok(None);
+            }
+
+            // Wait for new segments of the dynamic manifest
+            let last_refresh = *self.last_refresh.read().await;
+            if let Some(last_refresh) = last_refresh {
+                let elapsed = last_refresh.elapsed();
+                if elapsed < self.poll_interval {
+                    sleep(self.poll_interval - elapsed).await;
+                }
+            }
+
+            self.refresh_manifest().await?;
+        }
+
+        // cannot get any segments return empty buffer array
+        let Some(url) = self.queue.read().await.front().cloned() else {
+            return Ok(Some(Bytes::new()));
+        };
+
+        let mut retries = 0;
+
+        let buf = loop {
+            match self.fetch_segment(&url).await {
+                Ok(buf) => break buf,
+                Err(e) if retries < self.segment_retries => {
+                    retries += 1;
+
+                    #[cfg(feature = "metrics")]
+                    crate::metrics::increment(crate::metrics::RETRIES, 1);
+
+                    self.progress.emit(DownloadEvent::Retrying {
+                        attempt: retries,
+                        error: e.to_string(),
+                    });
+
+                    sleep(SEGMENT_RETRY_DELAY).await;
+                }
+                Err(e) => return Err(e),
+            }
+        };
+
+        self.queue.write().await.pop_front();
+
+        self.progress.advance(buf.len() as u64);
+
+        Ok(Some(buf.into()))
+    }
+}
@@ -0,0 +1,118 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::future::{select, Either};
+use tokio::sync::Mutex;
+
+use crate::platform::{wait, AsyncReadExt, AsyncWriteExt, Child, ChildStdout};
+use crate::stream::streams::Stream;
+use crate::structs::VideoError;
+use crate::utils::ffmpeg_cmd_spawn;
+
+/// Maximum size of a transcoded chunk
+const FFMPEG_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Writes the source into stdin of ffmpeg
+type Feeder = Pin<Box<dyn Future<Output = Result<(), VideoError>> + Send>>;
+
+/// Pipes chunks of the source [`Stream`] into an ffmpeg process and streams its output.
+/// Source is fed while the output is read, so neither side is buffered in memory.
+/// Feeding is driven by [`Stream::chunk`] instead of a spawned task, so it works with every async runtime
+///
+/// [`Stream::content_length`] is always `0` since the transcoded size is unknown
+pub struct FFmpegStream {
+    process: Mutex<Child>,
+    stdout: Mutex<ChildStdout>,
+    /// [`None`] after the source is finished
+    feeder: Mutex<Option<Feeder>>,
+}
+
+impl FFmpegStream {
+    pub fn new(
+        source: Box<dyn Stream + Send + Sync>,
+        ffmpeg_args: &[String],
+    ) -> Result<Self, VideoError> {
+        let mut process = ffmpeg_cmd_spawn(ffmpeg_args)?;
+
+        let mut stdin = process
+            .stdin
+            .take()
+            .ok_or(VideoError::FFmpeg("Failed to open stdin".to_string()))?;
+        let stdout = process
+            .stdout
+            .take()
+            .ok_or(VideoError::FFmpeg("Failed to open stdout".to_string()))?;
+
+        let feeder: Feeder = Box::pin(async move {
+            while let Some(chunk) = source.chunk().await? {
+                stdin
+                    .write_all(&chunk)
+                    .await
+                    .map_err(|x| VideoError::FFmpeg(x.to_string()))?;
+            }
+
+            // Closing stdin lets ffmpeg flush the remaining output and exit
+            drop(stdin);
+
+            Ok(())
+        });
+
+        Ok(Self {
+            process: Mutex::new(process),
+            stdout: Mutex::new(stdout),
+            feeder: Mutex::new(Some(feeder)),
+        })
+    }
+}
+
+#[async_trait]
+impl Stream for FFmpegStream {
+    async fn chunk(&self) -> Result<Option<Bytes>, VideoError> {
+        let mut stdout = self.stdout.lock().await;
+        let mut feeder = self.feeder.lock().await;
+        let mut buf = vec![0; FFMPEG_CHUNK_SIZE];
+
+        // Keep feeding the source until ffmpeg writes some output
+        let read = loop {
+            let read = match feeder.as_mut() {
+                Some(feed) => {
+                    let read = stdout.read(&mut buf);
+                    futures::pin_mut!(read);
+
+                    match select(read, feed.as_mut()).await {
+                        Either::Left((read, _)) => read,
+                        Either::Right((fed, _)) => {
+                            *feeder = None;
+                            fed?;
+                            continue;
+                        }
+                    }
+                }
+                None => stdout.read(&mut buf).await,
+            };
+
+            break read.map_err(|x| VideoError::FFmpeg(x.to_string()))?;
+        };
+
+        if read > 0 {
+            buf.truncate(read);
+            return Ok(Some(buf.into()));
+        }
+
+        // Output is finished, ffmpeg exited before reading the whole source if the feeder is left.
+        // Dropping it stops downloading the source
+        *feeder = None;
+
+        let status = wait(&mut *self.process.lock().await)
+            .await
+            .map_err(|x| VideoError::FFmpeg(x.to_string()))?;
+
+        if !status.success() {
+            return Err(VideoError::FFmpeg(format!("ffmpeg exited with {status}")));
+        }
+
+        Ok(None)
+    }
+}
@@ -1,37 +1,89 @@
 use crate::constants::DEFAULT_HEADERS;
+use crate::platform::{now, sleep, Instant};
+use crate::stream::bandwidth::BandwidthLimiter;
 use crate::stream::encryption::Encryption;
 use crate::stream::media_format::MediaFormat;
+use crate::stream::progress::ProgressTracker;
 use crate::stream::remote_data::RemoteData;
 use crate::stream::segment::Segment;
 use crate::stream::streams::Stream;
-use crate::structs::VideoError;
+use crate::structs::{
+    DownloadEvent, DownloadEventCallback, LiveLatency, ProgressCallback, VideoError,
+};
 use crate::utils::{get_html, make_absolute_url};
 
 use async_trait::async_trait;
 use bytes::{Bytes, BytesMut};
-use m3u8_rs::parse_media_playlist;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use m3u8_rs::{
+    parse_media_playlist, parse_playlist_res, AlternativeMediaType, MasterPlaylist, MediaPlaylist,
+    Playlist,
+};
+use std::time::{Duration, UNIX_EPOCH};
 use tokio::sync::RwLock;
 
+/// Playlist refresh interval if [`LiveStreamOptions::poll_interval`] is not set
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(20);
+/// Retry count of a segment if [`LiveStreamOptions::segment_retries`] is not set
+const DEFAULT_SEGMENT_RETRIES: u32 = 3;
+/// Waiting time before retrying a segment after a network error
+const SEGMENT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
 pub struct LiveStreamOptions {
     pub client: Option<reqwest_middleware::ClientWithMiddleware>,
     pub stream_url: String,
+    pub progress_callback: Option<ProgressCallback>,
+    /// Receives [`DownloadEvent::ChunkDownloaded`] and [`DownloadEvent::Retrying`] events
+    pub event_callback: Option<DownloadEventCallback>,
+    /// Interval of playlist refreshes while waiting for new segments. Default is 20 seconds
+    pub poll_interval: Option<Duration>,
+    /// Count of retries of a failed segment before the error is returned. Default is `3`
+    ///
+    /// Playlist is refreshed before retrying segments responding 4xx, so expired segment URLs are renewed
+    pub segment_retries: Option<u32>,
+    /// Return an error if no new segment appears for this long, [`None`] waits forever
+    pub stall_timeout: Option<Duration>,
+    /// Stream already ended (e.g. post-live DVR), every segment of the playlist is downloaded once
+    /// instead of waiting for new segments
+    pub ended: bool,
+    /// Download speed limit in bytes per second, [`None`] is unlimited
+    pub max_bytes_per_second: Option<u64>,
+    /// If `stream_url` is a master playlist, record its audio-only rendition instead of a video variant.
+    /// Without an audio-only rendition the variant with the lowest bandwidth is used
+    ///
+    /// Media playlist `stream_url` is downloaded as it is
+    pub audio_only: bool,
+    /// Switch to the DVR playlist or the live edge playlist of the stream. [`None`] keeps the playlist of `stream_url`
+    ///
+    /// The playlist of `stream_url` is used if the requested one cannot be loaded on the first refresh
+    pub latency: Option<LiveLatency>,
 }
 
 pub struct LiveStream {
     client: reqwest_middleware::ClientWithMiddleware,
-    stream_url: String,
+    stream_url: RwLock<String>,
+    /// Playlist before the [`LiveStreamOptions::latency`] switch, used if the switched one does not exist
+    fallback_url: RwLock<Option<String>>,
+    audio_only: bool,
+    latency: Option<LiveLatency>,
+    variant_selected: RwLock<bool>,
+    poll_interval: Duration,
+    segment_retries: u32,
+    stall_timeout: Option<Duration>,
+    ended: bool,
 
     last_refresh: RwLock<u128>,
+    last_new_segment: RwLock<Instant>,
     segments: RwLock<Vec<(Segment, Encryption)>>,
     is_end: RwLock<bool>,
     last_seg: RwLock<Option<(u64, u64)>>,
+    progress: ProgressTracker,
+    bandwidth_limiter: Option<BandwidthLimiter>,
 }
 
 impl LiveStream {
     pub fn new(options: LiveStreamOptions) -> Result<Self, VideoError> {
-        let client = if options.client.is_some() {
-            options.client.unwrap()
+        let client = if let Some(client) = options.client {
+            client
         } else {
             let client = reqwest::Client::builder()
                 .build()
@@ -50,13 +102,28 @@ impl LiveStream {
                 .build()
         };
 
+        let stream_url = playlist_url_with_latency(&options.stream_url, options.latency);
+
         Ok(Self {
             client,
-            stream_url: options.stream_url,
+            fallback_url: RwLock::new(
+                (stream_url != options.stream_url).then_some(options.stream_url),
+            ),
+            stream_url: RwLock::new(stream_url),
+            audio_only: options.audio_only,
+            latency: options.latency,
+            variant_selected: RwLock::new(false),
+            poll_interval: options.poll_interval.unwrap_or(DEFAULT_POLL_INTERVAL),
+            segment_retries: options.segment_retries.unwrap_or(DEFAULT_SEGMENT_RETRIES),
+            stall_timeout: options.stall_timeout,
+            ended: options.ended,
             last_refresh: RwLock::new(0),
+            last_new_segment: RwLock::new(Instant::now()),
             segments: RwLock::new(vec![]),
             is_end: RwLock::new(false),
             last_seg: RwLock::new(None),
+            progress: ProgressTracker::new(options.progress_callback, options.event_callback, 0, 0),
+            bandwidth_limiter: options.max_bytes_per_second.map(BandwidthLimiter::new),
         })
     }
 
@@ -76,13 +143,62 @@ impl LiveStream {
         *self.last_seg.read().await
     }
 
-    async fn refresh_playlist(&self) -> Result<(), VideoError> {
-        let body = get_html(&self.client, &self.stream_url, None).await?;
+    async fn stream_url(&self) -> String {
+        (*self.stream_url.read().await).clone()
+    }
+
+    /// Replace a master playlist `stream_url` with its audio-only rendition, see [`LiveStreamOptions::audio_only`]
+    async fn select_audio_variant(&self) -> Result<(), VideoError> {
+        let master_url = self.stream_url().await;
+        let body = get_html(&self.client, &master_url, None).await?;
+
+        if let Ok(Playlist::MasterPlaylist(master_playlist)) = parse_playlist_res(body.as_bytes()) {
+            let uri = audio_variant_uri(&master_playlist).ok_or_else(|| {
+                VideoError::M3U8ParseError("Master playlist has no variants".to_string())
+            })?;
+
+            let variant_url = make_absolute_url(&master_url, &uri)?.to_string();
+            let stream_url = playlist_url_with_latency(&variant_url, self.latency);
+
+            *self.fallback_url.write().await = (stream_url != variant_url).then_some(variant_url);
+            *self.stream_url.write().await = stream_url;
+        }
+
+        *self.variant_selected.write().await = true;
+
+        Ok(())
+    }
+
+    async fn media_playlist(&self, url: String) -> Result<(String, MediaPlaylist), VideoError> {
+        let body = get_html(&self.client, &url, None).await?;
 
         let media_playlist = parse_media_playlist(body.as_bytes())
             .map_err(|e| VideoError::M3U8ParseError(e.to_string()))?
             .1;
 
+        Ok((url, media_playlist))
+    }
+
+    async fn refresh_playlist(&self) -> Result<(), VideoError> {
+        if self.audio_only && !*self.variant_selected.read().await {
+            self.select_audio_variant().await?;
+        }
+
+        let fallback_url = self.fallback_url.write().await.take();
+
+        let requested = self.media_playlist(self.stream_url().await).await;
+
+        let (stream_url, media_playlist) = match (requested, fallback_url) {
+            (Ok(x), _) => x,
+            // Requested playlist of `latency` does not exist for this stream
+            (Err(_), Some(fallback_url)) => {
+                *self.stream_url.write().await = fallback_url.clone();
+
+                self.media_playlist(fallback_url).await?
+            }
+            (Err(e), None) => return Err(e),
+        };
+
         let mut cur_init = None;
 
         // Loop through media segments
@@ -99,13 +215,25 @@ impl LiveStream {
             // Skip segment if already downloaded
             if let Some(s) = self.last_seg().await {
                 if s >= (discon_seq, seq) {
+                    // Renew the URL if the segment is still queued, expired URLs respond 404
+                    let mut segment_vector = self.segments.write().await;
+                    if let Some(queued) = segment_vector
+                        .iter_mut()
+                        .find(|x| (x.0.discon_seq, x.0.seq) == (discon_seq, seq))
+                    {
+                        queued.0.data = RemoteData::new(
+                            make_absolute_url(&stream_url, &segment.uri)?,
+                            segment.byte_range.clone(),
+                        );
+                    }
+
                     continue;
                 }
             }
 
             // Check encryption
             if let Some(key) = &segment.key {
-                encryption = Encryption::new(key, &self.stream_url, seq).await?;
+                encryption = Encryption::new(key, &stream_url, seq).await?;
             }
 
             // Segment is new
@@ -113,12 +241,12 @@ impl LiveStream {
             *mut_last_seg = Some((discon_seq, seq));
 
             // Parse URL
-            let seg_url = make_absolute_url(&self.stream_url, &segment.uri)?;
+            let seg_url = make_absolute_url(&stream_url, &segment.uri)?;
 
             // Make Initialization
             let init = if let Some(map) = &segment.map {
                 let init = RemoteData::new(
-                    make_absolute_url(&self.stream_url, &map.uri)?,
+                    make_absolute_url(&stream_url, &map.uri)?,
                     map.byte_range.clone(),
                 );
                 cur_init = Some(init.clone());
@@ -144,12 +272,14 @@ impl LiveStream {
             {
                 let mut segment_vector = self.segments.write().await;
                 segment_vector.push((segment.clone(), encryption.clone()));
+
+                *self.last_new_segment.write().await = Instant::now();
             }
         }
 
         // Set last refresh to check refresh playlist functionality
         let mut last_refresh = self.last_refresh.write().await;
-        let start = SystemTime::now();
+        let start = now();
         *last_refresh = start
             .duration_since(UNIX_EPOCH)
             .expect("Time went backwards")
@@ -158,17 +288,88 @@ impl LiveStream {
 
         // Set is_end bool to control chunk function
         // if stream ended
-        if media_playlist.end_list {
+        if media_playlist.end_list || self.ended {
             let mut is_end = self.is_end.write().await;
-            *is_end = media_playlist.end_list;
+            *is_end = true;
         }
 
         Ok(())
     }
+
+    /// Download a segment, responses with error status are returned as errors
+    async fn fetch_segment(&self, segment: &Segment) -> Result<BytesMut, VideoError> {
+        let headers = DEFAULT_HEADERS.clone();
+
+        let mut response = self
+            .client
+            .get(segment.url().as_str())
+            .headers(headers)
+            .send()
+            .await
+            .map_err(VideoError::from)?
+            .error_for_status()
+            .map_err(VideoError::Reqwest)?;
+
+        let mut buf: BytesMut = BytesMut::new();
+
+        while let Some(chunk) = response.chunk().await.map_err(VideoError::Reqwest)? {
+            if let Some(bandwidth_limiter) = &self.bandwidth_limiter {
+                bandwidth_limiter.consume(chunk.len() as u64).await;
+            }
+
+            buf.extend(chunk);
+        }
+
+        Ok(buf)
+    }
+}
+
+/// YouTube live playlist URLs have `/playlist_type/DVR/` for the DVR window and `/playlist_type/LIVE/` for the live edge
+fn playlist_url_with_latency(url: &str, latency: Option<LiveLatency>) -> String {
+    let (from, to) = match latency {
+        Some(LiveLatency::Standard) => ("/playlist_type/LIVE/", "/playlist_type/DVR/"),
+        Some(LiveLatency::Low) => ("/playlist_type/DVR/", "/playlist_type/LIVE/"),
+        None => return url.to_string(),
+    };
+
+    url.replacen(from, to, 1)
+}
+
+/// URI of the audio-only rendition of the master playlist: an `EXT-X-MEDIA` audio rendition, otherwise
+/// the variant with the highest bandwidth and only audio codecs. Falls back to the variant with the lowest bandwidth
+fn audio_variant_uri(master_playlist: &MasterPlaylist) -> Option<String> {
+    let rendition = master_playlist
+        .alternatives
+        .iter()
+        .filter(|x| x.media_type == AlternativeMediaType::Audio && x.uri.is_some())
+        .max_by_key(|x| x.default);
+
+    if let Some(uri) = rendition.and_then(|x| x.uri.clone()) {
+        return Some(uri);
+    }
+
+    let variants = master_playlist.variants.iter().filter(|x| !x.is_i_frame);
+
+    let is_audio_only = |codecs: &str| {
+        codecs.split(',').all(|codec| {
+            let codec = codec.trim();
+            ["mp4a", "opus", "ac-3", "ec-3", "flac"]
+                .iter()
+                .any(|x| codec.starts_with(x))
+        })
+    };
+
+    variants
+        .clone()
+        .filter(|x| x.resolution.is_none() && x.codecs.as_deref().map(is_audio_only) == Some(true))
+        .max_by_key(|x| x.bandwidth)
+        .or_else(|| variants.min_by_key(|x| x.bandwidth))
+        .map(|x| x.uri.clone())
 }
 
 #[async_trait]
 impl Stream for LiveStream {
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self), err))]
     async fn chunk(&self) -> Result<Option<Bytes>, VideoError> {
         let segments = self.segments().await;
 
@@ -177,9 +378,9 @@ impl Stream for LiveStream {
             return Ok(None);
         }
 
-        let live_seconds = 20000; // refresh millis
+        let live_seconds = self.poll_interval.as_millis(); // refresh millis
 
-        let start = SystemTime::now();
+        let start = now();
         let current_time = start
             .duration_since(UNIX_EPOCH)
             .expect("Time went backwards")
@@ -189,14 +390,10 @@ impl Stream for LiveStream {
 
         // Sleep until to wait new segments uploaded to get new segments
         if sleep_time < live_seconds && segments.is_empty() && !self.is_end().await {
-            tokio::time::sleep_until(
-                tokio::time::Instant::now()
-                    + Duration::from_millis((live_seconds - sleep_time) as u64),
-            )
-            .await;
+            sleep(Duration::from_millis((live_seconds - sleep_time) as u64)).await;
         }
 
-        let start = SystemTime::now();
+        let start = now();
         let current_time = start
             .duration_since(UNIX_EPOCH)
             .expect("Time went backwards")
@@ -210,31 +407,58 @@ impl Stream for LiveStream {
         // cannot get any segments return empty buffer array
         let segments = self.segments().await;
         if segments.is_empty() {
-            return Ok(Some(Bytes::new()));
-        }
-
-        let first_segment = segments.first().unwrap();
-
-        let headers = DEFAULT_HEADERS.clone();
-
-        let response = self
-            .client
-            .get(first_segment.0.url().as_str())
-            .headers(headers)
-            .send()
-            .await;
+            if let Some(stall_timeout) = self.stall_timeout {
+                if !self.is_end().await
+                    && self.last_new_segment.read().await.elapsed() >= stall_timeout
+                {
+                    return Err(VideoError::DownloadError(format!(
+                        "No new live segment for {stall_timeout:?}"
+                    )));
+                }
+            }
 
-        if response.is_err() {
-            return Err(VideoError::ReqwestMiddleware(response.err().unwrap()));
+            return Ok(Some(Bytes::new()));
         }
 
-        let mut response = response.expect("IMPOSSIBLE");
-
-        let mut buf: BytesMut = BytesMut::new();
+        let mut first_segment = segments.first().unwrap().clone();
+        let mut retries = 0;
+
+        let mut buf = loop {
+            match self.fetch_segment(&first_segment.0).await {
+                Ok(buf) => break buf,
+                Err(e) if retries < self.segment_retries => {
+                    retries += 1;
+
+                    #[cfg(feature = "metrics")]
+                    crate::metrics::increment(crate::metrics::RETRIES, 1);
+
+                    self.progress.emit(DownloadEvent::Retrying {
+                        attempt: retries,
+                        error: e.to_string(),
+                    });
+
+                    let client_error = match &e {
+                        VideoError::Reqwest(x) => {
+                            x.status().map(|x| x.is_client_error()).unwrap_or(false)
+                        }
+                        _ => false,
+                    };
+
+                    if client_error {
+                        self.refresh_playlist().await?;
+
+                        if let Some(segment) = self.segments().await.first() {
+                            first_segment = segment.clone();
+                        }
+                    } else {
+                        sleep(SEGMENT_RETRY_DELAY).await;
+                    }
+                }
+                Err(e) => return Err(e),
+            }
+        };
 
-        while let Some(chunk) = response.chunk().await.map_err(VideoError::Reqwest)? {
-            buf.extend(chunk);
-        }
+        self.progress.advance(buf.len() as u64);
 
         // Decrypt data bytes
         buf = BytesMut::from_iter(first_segment.1.decrypt(&self.client, &buf).await?);
@@ -246,3 +470,135 @@ impl Stream for LiveStream {
         Ok(Some(buf.into()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_playlist_url_with_latency() {
+        let dvr = "https://manifest.googlevideo.com/api/manifest/hls_playlist/itag/301/playlist_type/DVR/index.m3u8";
+        let live = "https://manifest.googlevideo.com/api/manifest/hls_playlist/itag/301/playlist_type/LIVE/index.m3u8";
+
+        assert_eq!(playlist_url_with_latency(dvr, Some(LiveLatency::Low)), live);
+        assert_eq!(
+            playlist_url_with_latency(live, Some(LiveLatency::Standard)),
+            dvr
+        );
+        assert_eq!(
+            playlist_url_with_latency(dvr, Some(LiveLatency::Standard)),
+            dvr
+        );
+        assert_eq!(playlist_url_with_latency(live, None), live);
+    }
+
+    /// Serve `routes` over HTTP on a local port, other paths respond `404`
+    async fn serve(routes: Vec<(&'static str, &'static str)>) -> String {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                let mut request = vec![0; 4096];
+                let len = socket.read(&mut request).await.unwrap_or(0);
+                let request = String::from_utf8_lossy(&request[..len]).to_string();
+                let path = request.split(' ').nth(1).unwrap_or_default();
+
+                let response = match routes.iter().find(|(route, _)| *route == path) {
+                    Some((_, body)) => format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    ),
+                    None => {
+                        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                            .to_string()
+                    }
+                };
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+
+        format!("http://{addr}")
+    }
+
+    #[tokio::test]
+    async fn test_live_latency_fallback() {
+        const DVR: &str =
+            "#EXTM3U\n#EXT-X-TARGETDURATION:5\n#EXTINF:5.0,\n/dvr.ts\n#EXT-X-ENDLIST\n";
+        const LIVE: &str =
+            "#EXTM3U\n#EXT-X-TARGETDURATION:5\n#EXTINF:5.0,\n/live.ts\n#EXT-X-ENDLIST\n";
+
+        let record = |base: String, latency: Option<LiveLatency>| async move {
+            let stream = LiveStream::new(LiveStreamOptions {
+                client: None,
+                stream_url: format!("{base}/playlist_type/DVR/index.m3u8"),
+                progress_callback: None,
+                event_callback: None,
+                poll_interval: None,
+                segment_retries: None,
+                stall_timeout: None,
+                max_bytes_per_second: None,
+                ended: false,
+                audio_only: false,
+                latency,
+            })
+            .unwrap();
+
+            stream.chunk().await.unwrap().unwrap()
+        };
+
+        // Stream without DVR window still has the live edge playlist
+        let base = serve(vec![
+            ("/playlist_type/LIVE/index.m3u8", LIVE),
+            ("/live.ts", "live"),
+        ])
+        .await;
+        assert_eq!(record(base, Some(LiveLatency::Low)).await, "live");
+
+        let base = serve(vec![
+            ("/playlist_type/DVR/index.m3u8", DVR),
+            ("/dvr.ts", "dvr"),
+        ])
+        .await;
+        assert_eq!(record(base.clone(), Some(LiveLatency::Low)).await, "dvr");
+        assert_eq!(record(base, None).await, "dvr");
+    }
+
+    #[test]
+    fn test_audio_variant_uri() {
+        let master = |body: &str| match parse_playlist_res(body.as_bytes()) {
+            Ok(Playlist::MasterPlaylist(x)) => x,
+            _ => panic!("not a master playlist"),
+        };
+
+        let with_audio = master(
+            r#"#EXTM3U
+#EXT-X-STREAM-INF:BANDWIDTH=290288,CODECS="mp4a.40.5,avc1.42C00B",RESOLUTION=256x144
+https://manifest.googlevideo.com/itag/91/index.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=144000,CODECS="mp4a.40.2"
+https://manifest.googlevideo.com/itag/233/index.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=5704990,CODECS="mp4a.40.2,avc1.64002A",RESOLUTION=1920x1080
+https://manifest.googlevideo.com/itag/301/index.m3u8
+"#,
+        );
+        assert_eq!(
+            audio_variant_uri(&with_audio).as_deref(),
+            Some("https://manifest.googlevideo.com/itag/233/index.m3u8")
+        );
+
+        let without_audio = master(
+            r#"#EXTM3U
+#EXT-X-STREAM-INF:BANDWIDTH=5704990,CODECS="mp4a.40.2,avc1.64002A",RESOLUTION=1920x1080
+https://manifest.googlevideo.com/itag/301/index.m3u8
+#EXT-X-STREAM-INF:BANDWIDTH=290288,CODECS="mp4a.40.5,avc1.42C00B",RESOLUTION=256x144
+https://manifest.googlevideo.com/itag/91/index.m3u8
+"#,
+        );
+        assert_eq!(
+            audio_variant_uri(&without_audio).as_deref(),
+            Some("https://manifest.googlevideo.com/itag/91/index.m3u8")
+        );
+    }
+}
@@ -1,17 +1,26 @@
+mod dash;
+#[cfg(feature = "ffmpeg")]
+mod ffmpeg;
 #[cfg(feature = "live")]
 mod live;
 mod non_live;
+mod otf;
 
 use bytes::Bytes;
 
+pub use dash::{DashStream, DashStreamOptions};
+#[cfg(feature = "ffmpeg")]
+pub use ffmpeg::FFmpegStream;
 #[cfg(feature = "live")]
 pub use live::{LiveStream, LiveStreamOptions};
-pub use non_live::{NonLiveStream, NonLiveStreamOptions};
+pub use non_live::{LinkRefresher, NonLiveStream, NonLiveStreamOptions};
+pub use otf::{OtfStream, OtfStreamOptions};
 
 use crate::VideoError;
 use async_trait::async_trait;
 
-#[async_trait]
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 pub trait Stream {
     /// Stream a chunk of the [`u8`] bytes
     ///
@@ -24,4 +33,11 @@ pub trait Stream {
     fn content_length(&self) -> usize {
         0
     }
+
+    /// Move the stream to the `offset` byte, next [`Stream::chunk`] call starts from there
+    ///
+    /// If stream is [`LiveStream`] returns always [`VideoError::StreamNotSeekable`]
+    async fn seek(&self, _offset: u64) -> Result<(), VideoError> {
+        Err(VideoError::StreamNotSeekable)
+    }
 }
@@ -1,13 +1,27 @@
+use std::sync::Arc;
+use std::time::Duration;
+
 use async_trait::async_trait;
 use bytes::{Bytes, BytesMut};
+use futures::StreamExt;
 use tokio::sync::RwLock;
 
 use crate::constants::DEFAULT_HEADERS;
+use crate::platform::{body_chunks, sleep, Instant};
+use crate::rate_limit::IdentityRotator;
+use crate::stream::bandwidth::BandwidthLimiter;
+use crate::stream::progress::ProgressTracker;
 use crate::stream::streams::Stream;
-use crate::structs::VideoError;
+use crate::structs::{
+    ChunkRetry, DownloadEvent, DownloadEventCallback, ProgressCallback, RequestOptions, VideoError,
+};
 
-#[cfg(feature = "ffmpeg")]
-use crate::{structs::FFmpegArgs, utils::ffmpeg_cmd_run};
+/// Chunks in a row slower than [`crate::IdentityRotation::min_speed`] before the identity is rotated
+const THROTTLED_CHUNKS: u32 = 2;
+/// First and smallest range size of adaptive chunks
+const MIN_ADAPTIVE_CHUNK_SIZE: u64 = 256 * 1024;
+/// Adaptive chunks grow if they are downloaded faster than this and shrink if they take twice as long
+const ADAPTIVE_CHUNK_DURATION: Duration = Duration::from_secs(2);
 
 pub struct NonLiveStreamOptions {
     pub client: Option<reqwest_middleware::ClientWithMiddleware>,
@@ -16,32 +30,60 @@ pub struct NonLiveStreamOptions {
     pub dl_chunk_size: u64,
     pub start: u64,
     pub end: u64,
+    pub progress_callback: Option<ProgressCallback>,
+    /// Receives [`DownloadEvent::ChunkDownloaded`] and [`DownloadEvent::Retrying`] events
+    pub event_callback: Option<DownloadEventCallback>,
+    /// Count of byte ranges fetched concurrently per chunk
+    pub concurrency: usize,
+    /// Get a new link of the same format when the current one returns `403` (e.g. expired).
+    /// If it is [`None`], `403` fails the stream
+    pub link_refresher: Option<Arc<dyn LinkRefresher>>,
+    /// Options of the client, new clients are built from them if [`RequestOptions::identity_rotation`] is set
+    pub request_options: Option<RequestOptions>,
+    /// Download speed limit in bytes per second, [`None`] is unlimited
+    pub max_bytes_per_second: Option<u64>,
+    /// Adjust the range size to the throughput and errors, `dl_chunk_size` is the upper bound
+    pub adaptive_chunk_size: bool,
+    /// Retry policy of failed ranges, [`None`] uses [`ChunkRetry::default`]
+    pub chunk_retry: Option<ChunkRetry>,
+}
 
-    #[cfg(feature = "ffmpeg")]
-    pub ffmpeg_args: Option<FFmpegArgs>,
+/// Source of a new URL for the same bytes when googlevideo rejects the current one
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+pub trait LinkRefresher: Send + Sync {
+    /// URLs are bound to the IP address, the new one is requested with the `client` of the stream
+    async fn refresh_link(
+        &self,
+        client: &reqwest_middleware::ClientWithMiddleware,
+    ) -> Result<String, VideoError>;
 }
 
 pub struct NonLiveStream {
-    link: String,
+    link: RwLock<String>,
+    link_refresher: Option<Arc<dyn LinkRefresher>>,
+    identity_rotator: Option<IdentityRotator>,
+    /// Count of the last chunks that were slower than the minimum speed
+    throttled_chunks: RwLock<u32>,
     content_length: u64,
     dl_chunk_size: u64,
+    adaptive_chunk_size: bool,
+    /// Current range size, changes only if `adaptive_chunk_size` is set
+    chunk_size: RwLock<u64>,
     start: RwLock<u64>,
     end: RwLock<u64>,
+    concurrency: usize,
+    chunk_retry: ChunkRetry,
+    progress: ProgressTracker,
+    bandwidth_limiter: Option<BandwidthLimiter>,
 
-    client: reqwest_middleware::ClientWithMiddleware,
-
-    #[cfg(feature = "ffmpeg")]
-    ffmpeg_args: Option<FFmpegArgs>,
-    #[cfg(feature = "ffmpeg")]
-    ffmpeg_start_byte: RwLock<Bytes>,
-    #[cfg(feature = "ffmpeg")]
-    ffmpeg_end_byte: RwLock<usize>,
+    client: RwLock<reqwest_middleware::ClientWithMiddleware>,
 }
 
 impl NonLiveStream {
     pub fn new(options: NonLiveStreamOptions) -> Result<Self, VideoError> {
-        let client = if options.client.is_some() {
-            options.client.unwrap()
+        let client = if let Some(client) = options.client {
+            client
         } else {
             let client = reqwest::Client::builder()
                 .build()
@@ -61,18 +103,33 @@ impl NonLiveStream {
         };
 
         Ok(Self {
-            client,
-            link: options.link,
+            client: RwLock::new(client),
+            link: RwLock::new(options.link),
+            link_refresher: options.link_refresher,
+            identity_rotator: options
+                .request_options
+                .as_ref()
+                .and_then(IdentityRotator::new),
+            throttled_chunks: RwLock::new(0),
             content_length: options.content_length,
             dl_chunk_size: options.dl_chunk_size,
+            adaptive_chunk_size: options.adaptive_chunk_size,
+            chunk_size: RwLock::new(if options.adaptive_chunk_size {
+                MIN_ADAPTIVE_CHUNK_SIZE.min(options.dl_chunk_size)
+            } else {
+                options.dl_chunk_size
+            }),
             start: RwLock::new(options.start),
             end: RwLock::new(options.end),
-            #[cfg(feature = "ffmpeg")]
-            ffmpeg_args: options.ffmpeg_args,
-            #[cfg(feature = "ffmpeg")]
-            ffmpeg_end_byte: RwLock::new(0),
-            #[cfg(feature = "ffmpeg")]
-            ffmpeg_start_byte: RwLock::new(Bytes::new()),
+            concurrency: options.concurrency,
+            chunk_retry: options.chunk_retry.unwrap_or_default(),
+            progress: ProgressTracker::new(
+                options.progress_callback,
+                options.event_callback,
+                options.start,
+                options.content_length,
+            ),
+            bandwidth_limiter: options.max_bytes_per_second.map(BandwidthLimiter::new),
         })
     }
 
@@ -88,113 +145,340 @@ impl NonLiveStream {
         *self.start.read().await
     }
 
-    #[cfg(feature = "ffmpeg")]
-    async fn ffmpeg_end_byte_index(&self) -> usize {
-        *self.ffmpeg_end_byte.read().await
+    /// Double the range size after fast chunks and halve it after slow or failed ones
+    async fn adapt_chunk_size(&self, elapsed: Option<Duration>) {
+        if !self.adaptive_chunk_size {
+            return;
+        }
+
+        let mut chunk_size = self.chunk_size.write().await;
+
+        *chunk_size = match elapsed {
+            Some(elapsed) if elapsed < ADAPTIVE_CHUNK_DURATION => *chunk_size * 2,
+            Some(elapsed) if elapsed < ADAPTIVE_CHUNK_DURATION * 2 => *chunk_size,
+            _ => *chunk_size / 2,
+        }
+        .clamp(
+            MIN_ADAPTIVE_CHUNK_SIZE.min(self.dl_chunk_size),
+            self.dl_chunk_size,
+        );
     }
 
-    #[cfg(feature = "ffmpeg")]
-    async fn ffmpeg_start_byte_index(&self) -> Bytes {
-        self.ffmpeg_start_byte.read().await.to_vec().into()
+    /// Fetch `start`-`end` byte range of the stream. If `end` is [`None`] fetch until the end
+    async fn fetch_range(&self, start: u64, end: Option<u64>) -> Result<BytesMut, VideoError> {
+        let mut buf: BytesMut = BytesMut::new();
+        let mut retries = 0;
+
+        loop {
+            // Bytes received before a failure are kept, only the rest of the range is requested again
+            let offset = start + buf.len() as u64;
+
+            if end.map(|x| offset > x).unwrap_or(false) {
+                return Ok(buf);
+            }
+
+            match self.fetch_range_into(offset, end, &mut buf).await {
+                Ok(()) => return Ok(buf),
+                Err(e) if retries < self.chunk_retry.max_retries && is_transient(&e) => {
+                    let delay = self
+                        .chunk_retry
+                        .initial_delay
+                        .saturating_mul(2_u32.saturating_pow(retries))
+                        .min(self.chunk_retry.max_delay);
+
+                    retries += 1;
+
+                    #[cfg(feature = "metrics")]
+                    crate::metrics::increment(crate::metrics::RETRIES, 1);
+
+                    self.progress.emit(DownloadEvent::Retrying {
+                        attempt: retries,
+                        error: e.to_string(),
+                    });
+
+                    self.adapt_chunk_size(None).await;
+                    sleep(delay).await;
+                }
+                Err(e) => return Err(e),
+            }
+        }
     }
-}
 
-#[async_trait]
-impl Stream for NonLiveStream {
-    async fn chunk(&self) -> Result<Option<Bytes>, VideoError> {
-        let end = self.end_index().await;
+    /// Fetch `start`-`end` byte range of the stream and append it to `buf`
+    async fn fetch_range_into(
+        &self,
+        start: u64,
+        end: Option<u64>,
+        buf: &mut BytesMut,
+    ) -> Result<(), VideoError> {
+        let link = self.link.read().await.clone();
 
-        // Nothing else remain send None to finish
-        if end == 0 {
-            // Send None to close
-            return Ok(None);
+        let mut response = self.request_range(&link, start, end).await?;
+
+        // Retry the range once with a new link, the download continues from the same offset
+        if response.status() == reqwest::StatusCode::FORBIDDEN {
+            if let Some(link_refresher) = &self.link_refresher {
+                let link = self.refresh_link(&link, link_refresher.as_ref()).await?;
+
+                response = self.request_range(&link, start, end).await?;
+            }
         }
 
-        if end >= self.content_length {
-            let mut end = self.end.write().await;
-            *end = 0;
+        let response = response.error_for_status().map_err(VideoError::Reqwest)?;
+
+        let expected = content_range_end(&response).map(|x| (x + 1).saturating_sub(start));
+        let received = buf.len();
+
+        let mut body = std::pin::pin!(body_chunks(response));
+
+        while let Some(chunk) = body.next().await.transpose().map_err(VideoError::Reqwest)? {
+            if let Some(bandwidth_limiter) = &self.bandwidth_limiter {
+                bandwidth_limiter.consume(chunk.len() as u64).await;
+            }
+
+            buf.extend(chunk);
+        }
+
+        // Connections closed early end the body without an error
+        if let Some(expected) = expected {
+            let got = (buf.len() - received) as u64;
+
+            if got < expected {
+                return Err(VideoError::IncompleteDownload { expected, got });
+            }
         }
 
+        Ok(())
+    }
+
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", skip(self, link))
+    )]
+    async fn request_range(
+        &self,
+        link: &str,
+        start: u64,
+        end: Option<u64>,
+    ) -> Result<reqwest::Response, VideoError> {
         let mut headers = DEFAULT_HEADERS.clone();
 
-        let end = self.end_index().await;
-        let range_end = if end == 0 {
-            "".to_string()
-        } else {
-            end.to_string()
-        };
+        let range_end = end.map(|x| x.to_string()).unwrap_or_default();
 
         headers.insert(
             reqwest::header::RANGE,
-            format!("bytes={}-{}", self.start_index().await, range_end)
-                .parse()
-                .unwrap(),
+            format!("bytes={start}-{range_end}").parse().unwrap(),
         );
 
-        let mut response = self
-            .client
-            .get(&self.link)
+        self.client
+            .read()
+            .await
+            .get(link)
             .headers(headers)
             .send()
             .await
-            .map_err(VideoError::ReqwestMiddleware)?;
+            .map_err(VideoError::from)
+    }
 
-        let mut buf: BytesMut = BytesMut::new();
+    /// Replace the `rejected` link, concurrent ranges rejected with the same link share one refresh
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, err))]
+    async fn refresh_link(
+        &self,
+        rejected: &str,
+        link_refresher: &dyn LinkRefresher,
+    ) -> Result<String, VideoError> {
+        let mut link = self.link.write().await;
+
+        if *link == rejected {
+            let client = self.client.read().await.clone();
+            *link = link_refresher.refresh_link(&client).await?;
+        }
 
-        while let Some(chunk) = response.chunk().await.map_err(VideoError::Reqwest)? {
-            buf.extend(chunk);
+        Ok(link.clone())
+    }
+
+    /// Switch the client to the next identity, returns `false` if it is not possible anymore
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, err))]
+    async fn rotate_identity(&self) -> Result<bool, VideoError> {
+        let client = match &self.identity_rotator {
+            Some(identity_rotator) => identity_rotator.next_client()?,
+            None => None,
+        };
+
+        match client {
+            Some(client) => {
+                *self.client.write().await = client;
+                *self.throttled_chunks.write().await = 0;
+
+                Ok(true)
+            }
+            None => Ok(false),
         }
+    }
 
-        #[cfg(feature = "ffmpeg")]
-        {
-            let ffmpeg_args = self
-                .ffmpeg_args
-                .clone()
-                .map(|x| x.build())
-                .unwrap_or_default();
-
-            if !ffmpeg_args.is_empty() {
-                let ffmpeg_start_byte_index = self.ffmpeg_start_byte_index().await;
-
-                let cmd_output = ffmpeg_cmd_run(
-                    &ffmpeg_args,
-                    Bytes::from(
-                        [
-                            BytesMut::from_iter(ffmpeg_start_byte_index.clone()),
-                            buf.clone(),
-                        ]
-                        .concat(),
-                    ),
-                )
-                .await?;
+    async fn fetch_ranges(
+        &self,
+        ranges: &[(u64, Option<u64>)],
+    ) -> Result<Vec<BytesMut>, VideoError> {
+        futures::future::try_join_all(
+            ranges
+                .iter()
+                .map(|(start, end)| self.fetch_range(*start, *end)),
+        )
+        .await
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Stream for NonLiveStream {
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip(self), fields(content_length = self.content_length), err)
+    )]
+    async fn chunk(&self) -> Result<Option<Bytes>, VideoError> {
+        let end = self.end_index().await;
+
+        // Nothing else remain send None to finish
+        if end == 0 || self.start_index().await >= self.content_length {
+            // Send None to close
+            return Ok(None);
+        }
 
-                let end_index = self.ffmpeg_end_byte_index().await;
+        let chunk_size = *self.chunk_size.read().await;
 
-                let mut first_buffer_trim = if cmd_output.is_empty() { 0 } else { 1 };
-                if ffmpeg_start_byte_index.is_empty() {
-                    let mut start_byte = self.ffmpeg_start_byte.write().await;
-                    *start_byte = buf.into();
-                    let mut end_byte = self.ffmpeg_end_byte.write().await;
-                    *end_byte = cmd_output.len();
+        // Split the next part of the stream to `concurrency` ranges
+        let mut ranges: Vec<(u64, Option<u64>)> = vec![];
+        let mut range_start = self.start_index().await;
+        // Adaptive ranges start with the current size instead of the stored end
+        let mut range_end = if self.adaptive_chunk_size {
+            range_start + chunk_size
+        } else {
+            end
+        };
 
-                    first_buffer_trim = 0;
-                }
+        for _ in 0..self.concurrency.max(1) {
+            if range_start >= self.content_length {
+                range_end = 0;
+                break;
+            }
+
+            // Bounded to the content length so a stream can stop before the end of the source
+            if range_end >= self.content_length {
+                ranges.push((range_start, Some(self.content_length - 1)));
+                range_end = 0;
+                break;
+            }
+
+            ranges.push((range_start, Some(range_end)));
+            range_start = range_end + 1;
+            range_end += chunk_size;
+        }
+
+        let started_at = Instant::now();
 
-                buf = BytesMut::from(&cmd_output[(end_index + first_buffer_trim)..]);
+        let mut rotations = 0;
+
+        let parts = loop {
+            match self.fetch_ranges(&ranges).await {
+                // Failed ranges are fetched again with a new identity
+                Err(e @ VideoError::RateLimited { .. }) if self.rotate_identity().await? => {
+                    self.adapt_chunk_size(None).await;
+
+                    #[cfg(feature = "metrics")]
+                    crate::metrics::increment(crate::metrics::RETRIES, 1);
+
+                    rotations += 1;
+                    self.progress.emit(DownloadEvent::Retrying {
+                        attempt: rotations,
+                        error: e.to_string(),
+                    });
+
+                    continue;
+                }
+                parts => break parts?,
             }
+        };
+
+        let mut buf: BytesMut = BytesMut::new();
+
+        // Reassemble ranges in order
+        for part in parts {
+            buf.extend_from_slice(&part);
         }
 
-        if end != 0 {
-            let mut start = self.start.write().await;
-            *start = end + 1;
-            let mut end = self.end.write().await;
-            *end += self.dl_chunk_size;
+        if let Some(min_speed) = self.identity_rotator.as_ref().and_then(|x| x.min_speed()) {
+            let speed = buf.len() as f64 / started_at.elapsed().as_secs_f64().max(0.001);
+
+            let mut throttled_chunks = self.throttled_chunks.write().await;
+            *throttled_chunks = if speed < min_speed as f64 {
+                *throttled_chunks + 1
+            } else {
+                0
+            };
+
+            // The chunk is already downloaded, next chunks use the new identity
+            if *throttled_chunks >= THROTTLED_CHUNKS {
+                drop(throttled_chunks);
+                self.rotate_identity().await?;
+            }
         }
 
+        self.adapt_chunk_size(Some(started_at.elapsed())).await;
+
+        self.progress.advance(buf.len() as u64);
+
+        let mut start = self.start.write().await;
+        *start = range_start;
+        let mut end = self.end.write().await;
+        *end = range_end;
+
         Ok(Some(buf.into()))
     }
 
     fn content_length(&self) -> usize {
         self.content_length() as usize
     }
+
+    async fn seek(&self, offset: u64) -> Result<(), VideoError> {
+        if offset >= self.content_length {
+            return Err(VideoError::DownloadError(format!(
+                "Seek offset {offset} is out of range (content length: {})",
+                self.content_length
+            )));
+        }
+
+        let mut start = self.start.write().await;
+        *start = offset;
+        let mut end = self.end.write().await;
+        *end = offset + self.dl_chunk_size;
+
+        Ok(())
+    }
+}
+
+/// Network errors, `5xx` responses and truncated bodies, other errors fail the same way on every retry
+fn is_transient(error: &VideoError) -> bool {
+    match error {
+        VideoError::IncompleteDownload { .. } => true,
+        VideoError::Reqwest(x)
+        | VideoError::ReqwestMiddleware(reqwest_middleware::Error::Reqwest(x)) => {
+            x.status().map(|x| x.is_server_error()).unwrap_or(true)
+        }
+        _ => false,
+    }
+}
+
+/// Last byte of the `Content-Range: bytes <start>-<end>/<size>` header
+fn content_range_end(response: &reqwest::Response) -> Option<u64> {
+    response
+        .headers()
+        .get(reqwest::header::CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .strip_prefix("bytes ")?
+        .split(['-', '/'])
+        .nth(1)?
+        .parse()
+        .ok()
 }
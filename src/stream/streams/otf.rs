@@ -0,0 +1,190 @@
+use crate::constants::DEFAULT_HEADERS;
+use crate::platform::{body_chunks, sleep};
+use crate::stream::bandwidth::BandwidthLimiter;
+use crate::stream::progress::ProgressTracker;
+use crate::stream::streams::Stream;
+use crate::structs::{DownloadEvent, DownloadEventCallback, ProgressCallback, VideoError};
+
+use async_trait::async_trait;
+use bytes::{Bytes, BytesMut};
+use futures::StreamExt;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::time::Duration;
+use tokio::sync::RwLock;
+
+/// Retry count of a segment if [`OtfStreamOptions::segment_retries`] is not set
+const DEFAULT_SEGMENT_RETRIES: u32 = 3;
+/// Waiting time before retrying a segment after a network error
+const SEGMENT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+static REGEX_SEGMENT_COUNT: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"Segment-Count: (\d+)").unwrap());
+
+pub struct OtfStreamOptions {
+    pub client: Option<reqwest_middleware::ClientWithMiddleware>,
+    pub link: String,
+    pub progress_callback: Option<ProgressCallback>,
+    /// Receives [`DownloadEvent::ChunkDownloaded`] and [`DownloadEvent::Retrying`] events
+    pub event_callback: Option<DownloadEventCallback>,
+    /// Count of retries of a failed segment before the error is returned. Default is `3`
+    pub segment_retries: Option<u32>,
+    /// Download speed limit in bytes per second, [`None`] is unlimited
+    pub max_bytes_per_second: Option<u64>,
+}
+
+/// Stream of `FORMAT_STREAM_TYPE_OTF` formats. These formats can't be downloaded with Range requests,
+/// the initialization segment (`sq=0`) is requested first and holds the `Segment-Count` of the format,
+/// then every media segment is requested with its sequence number
+pub struct OtfStream {
+    client: reqwest_middleware::ClientWithMiddleware,
+    link: String,
+    segment_retries: u32,
+
+    next_sequence: RwLock<u64>,
+    segment_count: RwLock<Option<u64>>,
+    progress: ProgressTracker,
+    bandwidth_limiter: Option<BandwidthLimiter>,
+}
+
+impl OtfStream {
+    pub fn new(options: OtfStreamOptions) -> Result<Self, VideoError> {
+        let client = if let Some(client) = options.client {
+            client
+        } else {
+            let client = reqwest::Client::builder()
+                .build()
+                .map_err(VideoError::Reqwest)?;
+
+            let retry_policy = reqwest_retry::policies::ExponentialBackoff::builder()
+                .retry_bounds(
+                    std::time::Duration::from_millis(500),
+                    std::time::Duration::from_millis(10000),
+                )
+                .build_with_max_retries(3);
+            reqwest_middleware::ClientBuilder::new(client)
+                .with(reqwest_retry::RetryTransientMiddleware::new_with_policy(
+                    retry_policy,
+                ))
+                .build()
+        };
+
+        Ok(Self {
+            client,
+            link: options.link,
+            segment_retries: options.segment_retries.unwrap_or(DEFAULT_SEGMENT_RETRIES),
+            next_sequence: RwLock::new(0),
+            segment_count: RwLock::new(None),
+            progress: ProgressTracker::new(options.progress_callback, options.event_callback, 0, 0),
+            bandwidth_limiter: options.max_bytes_per_second.map(BandwidthLimiter::new),
+        })
+    }
+
+    fn segment_url(&self, sequence: u64) -> String {
+        let separator = if self.link.contains('?') { '&' } else { '?' };
+
+        format!("{}{separator}sq={sequence}", self.link)
+    }
+
+    /// Download a segment, responses with error status are returned as errors
+    async fn fetch_segment(&self, sequence: u64) -> Result<BytesMut, VideoError> {
+        let headers = DEFAULT_HEADERS.clone();
+
+        let response = self
+            .client
+            .get(self.segment_url(sequence))
+            .headers(headers)
+            .send()
+            .await
+            .map_err(VideoError::from)?
+            .error_for_status()
+            .map_err(VideoError::Reqwest)?;
+
+        let mut buf: BytesMut = BytesMut::new();
+
+        let mut body = std::pin::pin!(body_chunks(response));
+
+        while let Some(chunk) = body.next().await.transpose().map_err(VideoError::Reqwest)? {
+            if let Some(bandwidth_limiter) = &self.bandwidth_limiter {
+                bandwidth_limiter.consume(chunk.len() as u64).await;
+            }
+
+            buf.extend(chunk);
+        }
+
+        Ok(buf)
+    }
+}
+
+/// `Segment-Count` of the initialization segment, the last media segment has this sequence number
+pub(crate) fn parse_segment_count(init: &[u8]) -> Option<u64> {
+    REGEX_SEGMENT_COUNT
+        .captures(&String::from_utf8_lossy(init))
+        .and_then(|x| x.get(1))
+        .and_then(|x| x.as_str().parse().ok())
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), async_trait)]
+#[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
+impl Stream for OtfStream {
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip(self), err))]
+    async fn chunk(&self) -> Result<Option<Bytes>, VideoError> {
+        let sequence = *self.next_sequence.read().await;
+
+        if let Some(segment_count) = *self.segment_count.read().await {
+            if sequence > segment_count {
+                return Ok(None);
+            }
+        }
+
+        let mut retries = 0;
+
+        let buf = loop {
+            match self.fetch_segment(sequence).await {
+                Ok(buf) => break buf,
+                Err(e) if retries < self.segment_retries => {
+                    retries += 1;
+
+                    #[cfg(feature = "metrics")]
+                    crate::metrics::increment(crate::metrics::RETRIES, 1);
+
+                    self.progress.emit(DownloadEvent::Retrying {
+                        attempt: retries,
+                        error: e.to_string(),
+                    });
+
+                    sleep(SEGMENT_RETRY_DELAY).await;
+                }
+                Err(e) => return Err(e),
+            }
+        };
+
+        if sequence == 0 {
+            let segment_count = parse_segment_count(&buf).ok_or_else(|| {
+                VideoError::DownloadError(
+                    "OTF initialization segment has no Segment-Count".to_string(),
+                )
+            })?;
+
+            *self.segment_count.write().await = Some(segment_count);
+        }
+
+        *self.next_sequence.write().await = sequence + 1;
+
+        self.progress.advance(buf.len() as u64);
+
+        Ok(Some(buf.into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_segment_count() {
+        let init = b"\x00\x00\x00\x1cftypdash\r\nSequence-Number: 0\r\nSegment-Count: 42\r\n";
+        assert_eq!(parse_segment_count(init), Some(42));
+        assert_eq!(parse_segment_count(b"\x00\x00\x00\x1cftypdash"), None);
+    }
+}
@@ -9,7 +9,13 @@ use std::{
     sync::Arc,
 };
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::constants::INNERTUBE_CLIENT_VERSION;
+use crate::hooks::RequestHook;
+use crate::js_runtime::JsRuntime;
+use crate::parser::{resolve_download_url, DecipherQuery, PlayerFunctions};
+
+#[derive(Clone, Serialize, Deserialize, derivative::Derivative)]
+#[derivative(Debug)]
 pub struct VideoInfo {
     #[serde(rename = "dashManifestUrl")]
     pub dash_manifest_url: Option<String>,
@@ -20,6 +26,111 @@ pub struct VideoInfo {
     pub related_videos: Vec<RelatedVideo>,
     #[serde(rename = "videoDetails")]
     pub video_details: VideoDetails,
+    #[serde(rename = "captionTracks", default)]
+    pub captions: Vec<CaptionTrack>,
+    #[serde(rename = "playabilityStatus", default)]
+    pub playability_status: PlayabilityStatus,
+    #[serde(skip)]
+    #[derivative(Debug = "ignore")]
+    pub(crate) player_response: Arc<serde_json::Value>,
+    #[serde(skip)]
+    #[derivative(Debug = "ignore")]
+    pub(crate) initial_response: Arc<serde_json::Value>,
+}
+
+impl VideoInfo {
+    /// Raw player response the formats are parsed from, to extract fields which are not modeled yet
+    /// - [`serde_json::Value::Null`] if the info is deserialized
+    pub fn raw_player_response(&self) -> &serde_json::Value {
+        &self.player_response
+    }
+
+    /// Raw initial data of the watch page (or `next` endpoint)
+    /// - [`serde_json::Value::Null`] if the info is deserialized
+    pub fn raw_initial_response(&self) -> &serde_json::Value {
+        &self.initial_response
+    }
+}
+
+/// Playability status of the player response, tells why a video cannot be played
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlayabilityStatus {
+    /// `OK`, `ERROR`, `UNPLAYABLE`, `LOGIN_REQUIRED`, `LIVE_STREAM_OFFLINE`, `AGE_CHECK_REQUIRED`...
+    pub status: String,
+    pub reason: Option<String>,
+    /// Details of the error screen (e.g. the removal reason)
+    #[serde(rename = "subReason")]
+    pub sub_reason: Option<String>,
+    /// Countries the video is available in, empty if YouTube does not list them
+    #[serde(rename = "availableCountries")]
+    pub available_countries: Vec<String>,
+}
+
+impl PlayabilityStatus {
+    pub fn is_ok(&self) -> bool {
+        self.status == "OK"
+    }
+
+    /// Video does not exist, was removed or the account of the uploader was terminated
+    pub fn is_removed(&self) -> bool {
+        self.status == "ERROR"
+    }
+
+    pub fn is_login_required(&self) -> bool {
+        self.status == "LOGIN_REQUIRED"
+    }
+
+    pub fn is_private(&self) -> bool {
+        self.is_login_required() && self.mentions("private")
+    }
+
+    /// YouTube asks to sign in to confirm you are not a bot, usually fixed by a PO token or cookies
+    pub fn is_bot_check(&self) -> bool {
+        self.is_login_required() && self.mentions("not a bot")
+    }
+
+    pub fn is_age_restricted(&self) -> bool {
+        matches!(
+            self.status.as_str(),
+            "AGE_CHECK_REQUIRED" | "AGE_VERIFICATION_REQUIRED"
+        ) || (self.is_login_required()
+            && ["confirm your age", "age-restricted", "age restricted"]
+                .into_iter()
+                .any(|x| self.mentions(x)))
+    }
+
+    /// Video is not available in the country of the request
+    pub fn is_geo_blocked(&self) -> bool {
+        self.status == "UNPLAYABLE" && self.mentions("country")
+    }
+
+    /// Upcoming live stream or premiere
+    pub fn is_upcoming(&self) -> bool {
+        self.status == "LIVE_STREAM_OFFLINE"
+    }
+
+    fn mentions(&self, text: &str) -> bool {
+        [&self.reason, &self.sub_reason]
+            .into_iter()
+            .flatten()
+            .any(|x| x.to_lowercase().contains(text))
+    }
+}
+
+impl std::fmt::Display for PlayabilityStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.status)?;
+
+        if let Some(reason) = &self.reason {
+            write!(f, ": {reason}")?;
+        }
+
+        if let Some(sub_reason) = &self.sub_reason {
+            write!(f, " ({sub_reason})")?;
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Clone, derive_more::Display)]
@@ -84,6 +195,11 @@ pub enum VideoQuality {
     /// Only Lowest Video
     #[display(fmt = "Lowest Video")]
     LowestVideo,
+    /// Video of a quality label like `720p`, `1080p60` or `2160p60 HDR`. The nearest lower resolution is picked
+    /// if the video does not have it, and the lowest resolution if all are higher.
+    /// [`VideoSearchOptions::Audio`] filter (the default) is treated as [`VideoSearchOptions::Video`], audio formats have no label
+    #[display(fmt = "Label {}", _0)]
+    Label(String),
     /// Custom ranking function and filter
     #[display(fmt = "Custom")]
     Custom(
@@ -101,6 +217,7 @@ impl std::fmt::Debug for VideoQuality {
             VideoQuality::LowestAudio => write!(f, "LowestAudio"),
             VideoQuality::HighestVideo => write!(f, "HighestVideo"),
             VideoQuality::LowestVideo => write!(f, "LowestVideo"),
+            VideoQuality::Label(label) => write!(f, "Label({label:?})"),
             VideoQuality::Custom(filter, _) => write!(f, "Custom({filter:?})"),
         }
     }
@@ -115,6 +232,7 @@ impl PartialEq for VideoQuality {
             (VideoQuality::LowestAudio, VideoQuality::LowestAudio) => true,
             (VideoQuality::HighestVideo, VideoQuality::HighestVideo) => true,
             (VideoQuality::LowestVideo, VideoQuality::LowestVideo) => true,
+            (VideoQuality::Label(a), VideoQuality::Label(b)) => a == b,
             (VideoQuality::Custom(i, a), VideoQuality::Custom(j, b)) => {
                 // Compare the function pointer
                 Arc::ptr_eq(a, b) && i == j
@@ -134,6 +252,37 @@ pub struct VideoOptions {
     pub download_options: DownloadOptions,
     #[derivative(PartialEq = "ignore")]
     pub request_options: RequestOptions,
+    /// External Proof of Origin token generator. Tokens are appended to stream URLs (`pot`) and sent with
+    /// InnerTube `/player` requests. The scraped watch page does not carry them, enable
+    /// [`VideoOptions::use_player_endpoint`] to send the token for the web client too
+    #[derivative(Debug = "ignore", PartialEq = "ignore")]
+    pub po_token_provider: Option<Arc<dyn PoTokenProvider>>,
+    /// InnerTube client used for the player request
+    pub client: InnertubeClient,
+    /// Clients tried in order when player response of [`VideoOptions::client`] is unplayable
+    /// (e.g. `LOGIN_REQUIRED`, `UNPLAYABLE` or no streaming data)
+    pub fallback_clients: Vec<InnertubeClient>,
+    /// Request player response and initial data from InnerTube API instead of scraping the watch page
+    pub use_player_endpoint: bool,
+    /// Directory to persist extracted player functions, so new processes do not download the player script again
+    pub player_cache_dir: Option<std::path::PathBuf>,
+    /// JavaScript engine to decipher format URLs. Default engine of enabled features is used if [`None`]
+    #[derivative(Debug = "ignore", PartialEq = "ignore")]
+    pub js_runtime: Option<Arc<dyn JsRuntime>>,
+    /// Memoize [`crate::Video::get_basic_info`] and [`crate::Video::get_info`] results per video ID for the duration,
+    /// [`None`] disables the cache. Cache is shared between [`crate::Video`] instances with the same request options
+    /// (proxy, cookies, headers, middlewares, injected client, InnerTube clients, PO token provider). Keep it shorter than
+    /// the stream URL expiry (6 hours). Capacity of the cache is set with [`crate::set_info_cache_capacity`]
+    pub info_cache_ttl: Option<std::time::Duration>,
+    /// Playback start offset, filled from `t`/`start` parameter of the URL if [`None`].
+    /// Exposed as [`VideoDetails::start_offset`], downloads still start from the beginning
+    pub start_time: Option<std::time::Duration>,
+    /// Choice between an audio format and its dynamic range compressed duplicate, see [`VideoFormat::is_drc`]
+    pub drc: DrcPolicy,
+    /// Pick HDR formats, see [`VideoFormat::is_hdr`], over SDR formats of the same resolution
+    pub prefer_hdr: bool,
+    /// Pick formats above 30 fps over lower frame rate formats of the same resolution
+    pub prefer_60fps: bool,
 }
 
 impl Default for VideoOptions {
@@ -143,22 +292,449 @@ impl Default for VideoOptions {
             filter: VideoSearchOptions::Audio,
             download_options: DownloadOptions::default(),
             request_options: RequestOptions::default(),
+            po_token_provider: None,
+            client: InnertubeClient::default(),
+            fallback_clients: vec![
+                InnertubeClient::Ios,
+                InnertubeClient::Android,
+                InnertubeClient::TvHtml5,
+            ],
+            use_player_endpoint: false,
+            player_cache_dir: None,
+            js_runtime: None,
+            info_cache_ttl: None,
+            start_time: None,
+            drc: DrcPolicy::default(),
+            prefer_hdr: false,
+            prefer_60fps: false,
         }
     }
 }
 
+/// Handling of DRC (dynamic range compressed) audio formats, which YouTube serves with the same itag
+/// as the original audio, see [`VideoOptions::drc`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DrcPolicy {
+    /// Drop DRC formats which have an original variant
+    #[default]
+    Avoid,
+    /// Drop original formats which have a DRC variant
+    Prefer,
+    /// Keep both variants
+    Allow,
+}
+
+/// Latency setting the streamer chose for a live stream, see [`VideoDetails::latency_class`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LatencyClass {
+    Normal,
+    Low,
+    UltraLow,
+}
+
+/// Playlist of a live stream to record, see [`DownloadOptions::live_latency`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LiveLatency {
+    /// Playlist with the whole DVR window, segments appear with the normal delay
+    Standard,
+    /// Playlist of the live edge, segments appear as soon as possible
+    Low,
+}
+
+/// Proof of Origin (PO) token provider
+///
+/// YouTube requires PO tokens for many web client formats, without them stream URLs return `403`.
+/// Implement this trait to supply tokens from an external generator
+///
+/// # Example
+/// ```ignore
+///     struct StaticPoToken(String);
+///
+///     #[async_trait::async_trait]
+///     impl PoTokenProvider for StaticPoToken {
+///         async fn get_po_token(&self, _video_id: &str) -> Result<Option<String>, VideoError> {
+///             Ok(Some(self.0.clone()))
+///         }
+///     }
+///
+///     let video_options = VideoOptions {
+///         po_token_provider: Some(Arc::new(StaticPoToken("token".to_string()))),
+///         ..Default::default()
+///     };
+/// ```
+#[async_trait::async_trait]
+pub trait PoTokenProvider: Send + Sync {
+    /// Get PO token for the video. Return [`None`] to continue without token
+    async fn get_po_token(&self, video_id: &str) -> Result<Option<String>, VideoError>;
+}
+
+/// InnerTube client that the player response is requested as
+///
+/// Clients differ in the returned formats and restrictions. `Android` and `Ios` return
+/// unciphered URLs, `TvHtml5` and `WebEmbedded` can play some videos that web client can not
+#[derive(Clone, Debug, Default, PartialEq, Eq, derive_more::Display)]
+pub enum InnertubeClient {
+    #[default]
+    #[display(fmt = "WEB")]
+    Web,
+    #[display(fmt = "ANDROID")]
+    Android,
+    #[display(fmt = "IOS")]
+    Ios,
+    #[display(fmt = "TVHTML5")]
+    TvHtml5,
+    #[display(fmt = "WEB_EMBEDDED_PLAYER")]
+    WebEmbedded,
+    /// Embedded TV player, returns formats of age-restricted videos without login
+    #[display(fmt = "TVHTML5_SIMPLY_EMBEDDED_PLAYER")]
+    TvEmbedded,
+}
+
+impl InnertubeClient {
+    /// `clientName` of the InnerTube context
+    pub fn name(&self) -> &'static str {
+        match self {
+            InnertubeClient::Web => "WEB",
+            InnertubeClient::Android => "ANDROID",
+            InnertubeClient::Ios => "IOS",
+            InnertubeClient::TvHtml5 => "TVHTML5",
+            InnertubeClient::WebEmbedded => "WEB_EMBEDDED_PLAYER",
+            InnertubeClient::TvEmbedded => "TVHTML5_SIMPLY_EMBEDDED_PLAYER",
+        }
+    }
+
+    /// `clientVersion` of the InnerTube context
+    pub fn version(&self) -> &'static str {
+        match self {
+            InnertubeClient::Web => INNERTUBE_CLIENT_VERSION,
+            InnertubeClient::Android => "19.09.37",
+            InnertubeClient::Ios => "19.09.3",
+            InnertubeClient::TvHtml5 => "7.20230405.08.01",
+            InnertubeClient::WebEmbedded => "1.20230331.01.00",
+            InnertubeClient::TvEmbedded => "2.0",
+        }
+    }
+
+    /// Numeric client id, sent as `X-YouTube-Client-Name` header
+    pub fn id(&self) -> u32 {
+        match self {
+            InnertubeClient::Web => 1,
+            InnertubeClient::Android => 3,
+            InnertubeClient::Ios => 5,
+            InnertubeClient::TvHtml5 => 7,
+            InnertubeClient::WebEmbedded => 56,
+            InnertubeClient::TvEmbedded => 85,
+        }
+    }
+
+    /// Mobile clients are rejected when they are not requested with their app user agent
+    pub(crate) fn user_agent(&self) -> Option<&'static str> {
+        match self {
+            InnertubeClient::Android => {
+                Some("com.google.android.youtube/19.09.37 (Linux; U; Android 11) gzip")
+            }
+            InnertubeClient::Ios => {
+                Some("com.google.ios.youtube/19.09.3 (iPhone14,3; U; CPU iOS 15_6 like Mac OS X)")
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether stream URLs of this client can be ciphered and need the player script
+    pub(crate) fn is_ciphered(&self) -> bool {
+        !matches!(self, InnertubeClient::Android | InnertubeClient::Ios)
+    }
+
+    /// InnerTube request `context` object of the client
+    pub(crate) fn context(&self) -> serde_json::Value {
+        let mut context = serde_json::json!({
+            "client": {
+                "utcOffsetMinutes": 0,
+                "gl": "US",
+                "hl": "en",
+                "clientName": self.name(),
+                "clientVersion": self.version(),
+            },
+            "user": {},
+            "request": {},
+        });
+
+        match self {
+            InnertubeClient::Android => {
+                context["client"]["androidSdkVersion"] = serde_json::json!(30);
+                context["client"]["osName"] = serde_json::json!("Android");
+                context["client"]["osVersion"] = serde_json::json!("11");
+            }
+            InnertubeClient::Ios => {
+                context["client"]["deviceMake"] = serde_json::json!("Apple");
+                context["client"]["deviceModel"] = serde_json::json!("iPhone14,3");
+                context["client"]["osName"] = serde_json::json!("iPhone");
+                context["client"]["osVersion"] = serde_json::json!("15.6.0.19G71");
+            }
+            InnertubeClient::WebEmbedded | InnertubeClient::TvEmbedded => {
+                context["thirdParty"] = serde_json::json!({
+                    "embedUrl": "https://www.youtube.com/",
+                });
+            }
+            _ => {}
+        }
+
+        context
+    }
+}
+
 /// Video download options
 #[derive(Clone, PartialEq, Debug, Default, derive_more::Display)]
 #[display(fmt = "DownloadOptions()")]
 pub struct DownloadOptions {
     /// Maximum chunk size on per request
     pub dl_chunk_size: Option<u64>,
+    /// Start non-live downloads with small ranges and grow or shrink them with the measured throughput,
+    /// [`DownloadOptions::dl_chunk_size`] becomes the upper bound
+    pub adaptive_chunk_size: bool,
+    /// Count of byte ranges downloaded in parallel for non-live streams. Default is `1`
+    ///
+    /// Each chunk of the stream consists of `concurrency` ranges of `dl_chunk_size`, fetched concurrently and reassembled in order
+    pub concurrency: Option<usize>,
+    /// Callback invoked after every downloaded chunk
+    ///
+    /// # Example
+    /// ```ignore
+    ///     let video_options = VideoOptions {
+    ///         download_options: DownloadOptions {
+    ///             progress_callback: Some(ProgressCallback::new(|progress| {
+    ///                 println!("{}/{} bytes", progress.downloaded, progress.total);
+    ///             })),
+    ///             ..Default::default()
+    ///         },
+    ///         ..Default::default()
+    ///     };
+    /// ```
+    pub progress_callback: Option<ProgressCallback>,
+    /// Subscriber of the [`DownloadEvent`]s of the download, one integration point for GUIs and loggers
+    ///
+    /// # Example
+    /// ```ignore
+    ///     let video_options = VideoOptions {
+    ///         download_options: DownloadOptions {
+    ///             event_callback: Some(DownloadEventCallback::new(|event| match event {
+    ///                 DownloadEvent::Retrying { attempt, error } => println!("retry #{attempt}: {error}"),
+    ///                 DownloadEvent::Failed { error } => println!("failed: {error}"),
+    ///                 _ => {}
+    ///             })),
+    ///             ..Default::default()
+    ///         },
+    ///         ..Default::default()
+    ///     };
+    /// ```
+    pub event_callback: Option<DownloadEventCallback>,
+    /// Continue downloading from the end of the existing `.part` file in [`Video::download`](crate::Video::download) instead of overwriting it
+    pub resume: bool,
+    /// Keep the `.part` file of a failed [`Video::download`](crate::Video::download), so it can be continued with [`DownloadOptions::resume`].
+    /// Default removes it
+    pub keep_part_file: bool,
+    /// What [`Video::download`](crate::Video::download) does if the file already exists. Default is [`OverwritePolicy::Overwrite`]
+    pub overwrite: OverwritePolicy,
+    /// Write [`VideoInfo::to_info_json`] next to the file of [`Video::download`](crate::Video::download),
+    /// `video.mp4` gets `video.info.json` like in yt-dlp
+    pub write_info_json: bool,
+    /// Interval of playlist refreshes of live streams while waiting for new segments. Default is 20 seconds
+    pub live_poll_interval: Option<std::time::Duration>,
+    /// Count of retries of a failed live segment before the error is returned. Default is `3`
+    pub live_segment_retries: Option<u32>,
+    /// Stop live downloads with an error if no new segment appears for this long. Default waits forever
+    pub live_stall_timeout: Option<std::time::Duration>,
+    /// Record only the audio of live streams from the audio-only HLS rendition of the master playlist, when YouTube provides one.
+    /// See [`LiveStreamOptions::audio_only`](crate::stream::LiveStreamOptions::audio_only)
+    #[cfg(feature = "live")]
+    pub live_audio_only: bool,
+    /// Record the DVR playlist or the live edge playlist of live streams, the playlist of the format is used if the
+    /// stream does not have the requested one. Default keeps the playlist of the format.
+    /// See [`LiveStreamOptions::latency`](crate::stream::LiveStreamOptions::latency)
+    #[cfg(feature = "live")]
+    pub live_latency: Option<LiveLatency>,
+    /// Download speed limit in bytes per second, shared by the concurrent ranges of a stream. Default is unlimited
+    pub max_bytes_per_second: Option<u64>,
+    /// Retry policy of byte ranges failed with network errors or `5xx` responses. Default is [`ChunkRetry::default`]
+    ///
+    /// Bytes received before the failure are kept, only the rest of the range is requested again
+    pub chunk_retry: Option<ChunkRetry>,
+    /// Write title, artist, album and year tags of the video into the downloaded file
+    ///
+    /// Applies to [`Video::download`](crate::Video::download) and other download methods, file types without tag support (e.g. WebM) are left untouched
+    #[cfg(feature = "tags")]
+    pub write_tags: bool,
+    /// Write chapters of the video into the file of [`Video::download`](crate::Video::download) by remuxing it after the download.
+    /// [`Video::download_muxed`](crate::Video::download_muxed) and [`Video::download_section`](crate::Video::download_section)
+    /// always write chapters. Live streams and videos without chapters are left untouched
+    #[cfg(feature = "ffmpeg")]
+    pub embed_chapters: bool,
+    /// Hash the downloaded data of [`Video::download`](crate::Video::download) on the fly,
+    /// the hash is returned by [`Video::download_with_hash`](crate::Video::download_with_hash).
+    /// Tags written by `write_tags` and chapters written by `embed_chapters` are not included
+    #[cfg(feature = "checksum")]
+    pub hash: Option<HashAlgo>,
+    /// Write the hash next to the file as `<file>.sha256` or `<file>.md5`, readable by `sha256sum -c` and `md5sum -c`
+    #[cfg(feature = "checksum")]
+    pub hash_sidecar: bool,
+}
+
+/// Hash algorithm of [`DownloadOptions::hash`]
+#[cfg(feature = "checksum")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashAlgo {
+    Sha256,
+    Md5,
+}
+
+#[cfg(feature = "checksum")]
+impl HashAlgo {
+    /// Extension of the sidecar file
+    pub fn extension(&self) -> &'static str {
+        match self {
+            HashAlgo::Sha256 => "sha256",
+            HashAlgo::Md5 => "md5",
+        }
+    }
+}
+
+/// Handling of existing files, see [`DownloadOptions::overwrite`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OverwritePolicy {
+    /// Download again and replace the file
+    #[default]
+    Overwrite,
+    /// Keep the file and return without downloading
+    Skip,
+    /// Return [`VideoError::FileAlreadyExists`]
+    Error,
+    /// Keep a finished file like [`OverwritePolicy::Skip`], continue an unfinished `.part` file and keep it if the download fails
+    Resume,
+}
+
+/// Options of [`crate::sanitize_filename`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SanitizeOptions {
+    /// Replacement of path separators, characters invalid on Windows and control characters. Default is `_`
+    pub replacement: String,
+    /// Longest file name in bytes, cut at a grapheme boundary. Default is `200`, leaving room for `.part` and sidecar
+    /// extensions under the 255 byte limit of most file systems. [`None`] does not cut
+    pub max_length: Option<usize>,
+    /// Remove emoji, for file systems and tools without full Unicode support
+    pub remove_emoji: bool,
+}
+
+impl Default for SanitizeOptions {
+    fn default() -> Self {
+        Self {
+            replacement: "_".to_string(),
+            max_length: Some(200),
+            remove_emoji: false,
+        }
+    }
+}
+
+/// Download progress passed to the [`ProgressCallback`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DownloadProgress {
+    /// Downloaded bytes so far
+    pub downloaded: u64,
+    /// Total bytes of the stream. Always `0` for live streams
+    pub total: u64,
+    /// Average download speed as bytes per second
+    pub speed: f64,
+    /// Estimated remaining time. [`None`] if total size or speed unknown
+    pub eta: Option<std::time::Duration>,
+}
+
+#[derive(Clone)]
+pub struct ProgressCallback(Arc<dyn Fn(&DownloadProgress) + Sync + Send + 'static>);
+
+impl ProgressCallback {
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: Fn(&DownloadProgress) + Sync + Send + 'static,
+    {
+        Self(Arc::new(callback))
+    }
+
+    pub fn call(&self, progress: &DownloadProgress) {
+        (self.0)(progress)
+    }
+}
+
+impl std::fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ProgressCallback()")
+    }
 }
 
-#[derive(Clone, Debug, Default, derive_more::Display)]
+impl PartialEq for ProgressCallback {
+    fn eq(&self, other: &Self) -> bool {
+        // Compare the function pointer
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+/// Lifecycle event of a download passed to the [`DownloadEventCallback`]
+#[derive(Clone, Debug, PartialEq)]
+pub enum DownloadEvent {
+    /// Info of the video is available
+    InfoFetched { video_id: String, title: String },
+    /// Format to download is chosen
+    FormatChosen {
+        itag: u64,
+        mime_type: String,
+        content_length: Option<u64>,
+    },
+    /// A chunk or live segment is downloaded
+    ChunkDownloaded {
+        bytes: u64,
+        progress: DownloadProgress,
+    },
+    /// A failed chunk or live segment is requested again
+    Retrying { attempt: u32, error: String },
+    /// ffmpeg process is started to filter, mux or cut the download
+    FfmpegStarted,
+    /// [`Video::download`](crate::Video::download) finished
+    Completed { path: std::path::PathBuf },
+    /// [`Video::download`](crate::Video::download) failed
+    Failed { error: String },
+}
+
+#[derive(Clone)]
+pub struct DownloadEventCallback(Arc<dyn Fn(&DownloadEvent) + Sync + Send + 'static>);
+
+impl DownloadEventCallback {
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: Fn(&DownloadEvent) + Sync + Send + 'static,
+    {
+        Self(Arc::new(callback))
+    }
+
+    pub fn call(&self, event: &DownloadEvent) {
+        (self.0)(event)
+    }
+}
+
+impl std::fmt::Debug for DownloadEventCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "DownloadEventCallback()")
+    }
+}
+
+impl PartialEq for DownloadEventCallback {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[derive(Clone, Default, derive_more::Display, derivative::Derivative)]
 #[display(fmt = "RequestOptions()")]
+#[derivative(Debug)]
 pub struct RequestOptions {
-    /// [`reqwest::Proxy`] to on use request
+    /// [`reqwest::Proxy`] to on use request. Not available on `wasm32`, requests go through the proxy of the browser
     ///
     /// # Example
     /// ```ignore
@@ -174,6 +750,7 @@ pub struct RequestOptions {
     ///         ..Default::default()
     ///     };
     /// ```
+    #[cfg(not(target_arch = "wasm32"))]
     pub proxy: Option<reqwest::Proxy>,
     /// Cookies String
     ///
@@ -182,6 +759,16 @@ pub struct RequestOptions {
     /// Some("key1=value1; key2=value2; key3=value3".to_string())
     /// ```
     pub cookies: Option<String>,
+    /// Path of the Netscape formatted `cookies.txt` file. Can be used together with [`RequestOptions::cookies`]
+    ///
+    /// # Example
+    /// ```ignore
+    /// Some(std::path::PathBuf::from("cookies.txt"))
+    /// ```
+    pub cookies_file: Option<std::path::PathBuf>,
+    /// Keep cookies set by YouTube (e.g. `VISITOR_INFO1_LIVE`, consent) for the next requests of the same
+    /// [`crate::Video`] or search client. Always enabled if [`RequestOptions::cookies`] or [`RequestOptions::cookies_file`] is set
+    pub enable_cookies: bool,
     /// Custom IPv6 String
     ///
     /// # Example
@@ -198,6 +785,183 @@ pub struct RequestOptions {
     ///     };
     /// ```
     pub ipv6_block: Option<String>,
+    /// Retry requests answered with `429 Too Many Requests` instead of returning [`VideoError::RateLimited`]
+    ///
+    /// # Example
+    /// ```ignore
+    ///     let video_options = VideoOptions {
+    ///          request_options: RequestOptions {
+    ///               rate_limit_backoff: Some(RateLimitBackoff::default()),
+    ///                ..Default::default()
+    ///          },
+    ///          ..Default::default()
+    ///     };
+    /// ```
+    pub rate_limit_backoff: Option<RateLimitBackoff>,
+    /// Timeout of opening a connection
+    pub connect_timeout: Option<std::time::Duration>,
+    /// Timeout of a whole request, including reading the body of a download chunk. Keep chunks small
+    /// ([`DownloadOptions::dl_chunk_size`]) so it also stops downloads from hanging on stalled connections
+    ///
+    /// # Example
+    /// ```ignore
+    ///     let video_options = VideoOptions {
+    ///          request_options: RequestOptions {
+    ///               connect_timeout: Some(Duration::from_secs(10)),
+    ///               timeout: Some(Duration::from_secs(5 * 60)),
+    ///                ..Default::default()
+    ///          },
+    ///          ..Default::default()
+    ///     };
+    /// ```
+    pub timeout: Option<std::time::Duration>,
+    /// Extra headers of watch page, InnerTube and media requests, e.g. `Accept-Language`
+    ///
+    /// # Example
+    /// ```ignore
+    ///     let mut headers = reqwest::header::HeaderMap::new();
+    ///     headers.insert("Accept-Language", "en-US,en;q=0.9".parse().unwrap());
+    ///
+    ///     let video_options = VideoOptions {
+    ///          request_options: RequestOptions {
+    ///               headers: Some(headers),
+    ///                ..Default::default()
+    ///          },
+    ///          ..Default::default()
+    ///     };
+    /// ```
+    pub headers: Option<reqwest::header::HeaderMap>,
+    /// Replace the `User-Agent` of every request, including the ones of [`InnertubeClient`]s
+    pub user_agent: Option<String>,
+    /// Use `User-Agent` and client hints of a browser from [`crate::set_browser_identities`] list.
+    /// Requests of app [`InnertubeClient`]s keep their own `User-Agent`, [`RequestOptions::user_agent`] has priority
+    pub user_agent_rotation: Option<UserAgentRotation>,
+    /// Switch to another proxy or IPv6 address when downloads are rate limited or throttled
+    ///
+    /// # Example
+    /// ```ignore
+    ///     let video_options = VideoOptions {
+    ///          request_options: RequestOptions {
+    ///               ipv6_block: Some("2001:4::/48".to_string()),
+    ///               identity_rotation: Some(IdentityRotation {
+    ///                    min_speed: Some(512 * 1024),
+    ///                    ..Default::default()
+    ///               }),
+    ///                ..Default::default()
+    ///          },
+    ///          ..Default::default()
+    ///     };
+    /// ```
+    pub identity_rotation: Option<IdentityRotation>,
+    /// Extra [`reqwest_middleware::Middleware`] layers (e.g. caching, logging) added before the internal ones,
+    /// they see every request and its final response
+    ///
+    /// # Example
+    /// ```ignore
+    ///     let video_options = VideoOptions {
+    ///          request_options: RequestOptions {
+    ///               middlewares: vec![Arc::new(MyLoggingMiddleware)],
+    ///                ..Default::default()
+    ///          },
+    ///          ..Default::default()
+    ///     };
+    /// ```
+    #[derivative(Debug = "ignore")]
+    pub middlewares: Vec<Arc<dyn reqwest_middleware::Middleware>>,
+    /// Pre-built client used for all requests instead of building one from the other options.
+    /// Rotated identities of [`RequestOptions::identity_rotation`] ignore it and build new clients from the other options.
+    /// Clones of the same [`Arc`] share info cache entries (see [`VideoOptions::info_cache_ttl`])
+    #[derivative(Debug = "ignore")]
+    pub client: Option<Arc<reqwest_middleware::ClientWithMiddleware>>,
+    /// Callbacks before every HTTP request and after its response, see [`RequestHook`]
+    #[derivative(Debug = "ignore")]
+    pub hooks: Option<Arc<dyn RequestHook>>,
+}
+
+/// When to pick a browser identity, see [`RequestOptions::user_agent_rotation`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UserAgentRotation {
+    /// One random browser for all requests of the client
+    PerClient,
+    /// New random browser for every request
+    PerRequest,
+}
+
+/// `User-Agent` and matching client hint headers of a browser
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BrowserIdentity {
+    pub user_agent: String,
+    /// `sec-ch-ua` header, [`None`] for browsers without client hints (Firefox, Safari)
+    pub sec_ch_ua: Option<String>,
+    /// `sec-ch-ua-platform` header
+    pub sec_ch_ua_platform: Option<String>,
+    /// `sec-ch-ua-mobile` header
+    pub mobile: bool,
+}
+
+/// Identity rotation of downloads, see [`RequestOptions::identity_rotation`]
+#[derive(Clone, Debug)]
+pub struct IdentityRotation {
+    /// Proxies used in turn. If it is empty, a new address of [`RequestOptions::ipv6_block`] is used
+    #[cfg(not(target_arch = "wasm32"))]
+    pub proxies: Vec<reqwest::Proxy>,
+    /// Download speed in bytes per second, slower chunks in a row count as throttling
+    pub min_speed: Option<u64>,
+    /// Rotations of a download before rate limit errors are returned
+    pub max_rotations: u32,
+}
+
+impl Default for IdentityRotation {
+    fn default() -> Self {
+        Self {
+            #[cfg(not(target_arch = "wasm32"))]
+            proxies: vec![],
+            min_speed: None,
+            max_rotations: 5,
+        }
+    }
+}
+
+/// Exponential backoff of rate limited requests
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RateLimitBackoff {
+    /// Retries before [`VideoError::RateLimited`] is returned
+    pub max_retries: u32,
+    /// Waiting time before the first retry, doubled on every retry. `Retry-After` header is used if it is longer
+    pub initial_delay: std::time::Duration,
+    /// Longest waiting time, if `Retry-After` is longer the error is returned without waiting
+    pub max_delay: std::time::Duration,
+}
+
+impl Default for RateLimitBackoff {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_delay: std::time::Duration::from_secs(1),
+            max_delay: std::time::Duration::from_secs(60),
+        }
+    }
+}
+
+/// Retries of a failed byte range of a non-live download, see [`DownloadOptions::chunk_retry`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChunkRetry {
+    /// Retries of the same range before the error is returned
+    pub max_retries: u32,
+    /// Waiting time before the first retry, doubled on every retry
+    pub initial_delay: std::time::Duration,
+    /// Longest waiting time between retries
+    pub max_delay: std::time::Duration,
+}
+
+impl Default for ChunkRetry {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_delay: std::time::Duration::from_millis(500),
+            max_delay: std::time::Duration::from_secs(10),
+        }
+    }
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -208,24 +972,58 @@ pub enum VideoError {
     /// Video source empty
     #[error("Video source empty")]
     VideoSourceNotFound,
-    /// Video is private
-    #[error("Video is private")]
-    VideoIsPrivate,
+    /// Video cannot be played, check [`PlayabilityStatus`] for the reason
+    #[error("Video is not playable: {0}")]
+    NotPlayable(PlayabilityStatus),
+    /// YouTube asks to sign in to confirm you are not a bot
+    #[error("Sign in to confirm you're not a bot (cookies may help: {cookies_may_help}, PO token may help: {po_token_may_help})")]
+    BotCheckRequired {
+        /// [`RequestOptions::cookies`] and [`RequestOptions::cookies_file`] are not set
+        cookies_may_help: bool,
+        /// [`VideoOptions::po_token_provider`] is not set
+        po_token_may_help: bool,
+    },
     /// Reqwest error
     #[error(transparent)]
     Reqwest(#[from] reqwest::Error),
     /// ReqwestMiddleware error
     #[error(transparent)]
-    ReqwestMiddleware(#[from] reqwest_middleware::Error),
+    ReqwestMiddleware(reqwest_middleware::Error),
+    /// YouTube answered `429 Too Many Requests` or the "unusual traffic" page
+    #[error("Too many requests, retry after {retry_after:?}")]
+    RateLimited {
+        retry_after: Option<std::time::Duration>,
+    },
+    /// Download was not started because [`crate::DownloadManager::shutdown`] was called
+    #[error("Download is cancelled")]
+    Cancelled,
+    /// File of the download already exists and [`OverwritePolicy::Error`] is set
+    #[error("File already exists: {}", .0.display())]
+    FileAlreadyExists(std::path::PathBuf),
+    /// Download ended with less bytes than the content length or the `Content-Range` of a response
+    #[error("Download is incomplete, expected {expected} bytes but got {got}")]
+    IncompleteDownload { expected: u64, got: u64 },
     /// URL cannot parsed
     #[error(transparent)]
     URLParseError(#[from] url::ParseError),
     /// Body cannot parsed
     #[error("Body cannot parsed")]
     BodyCannotParsed,
+    /// InnerTube API answered with an error status, `message` is the error message of the response body
+    #[error("InnerTube request failed with status {status}: {message}")]
+    InnertubeError { status: u16, message: String },
     /// Format not found
     #[error("Format not found")]
     FormatNotFound,
+    /// Video is DRM protected (e.g. movies), its formats cannot be downloaded
+    #[error("Video is DRM protected")]
+    DrmProtected,
+    /// Format filter of [`crate::FormatFilter`] cannot be parsed
+    #[error("Invalid format filter: {0}")]
+    InvalidFormatFilter(String),
+    /// Quality label of [`VideoQuality::Label`] cannot be parsed
+    #[error("Invalid quality label: {0}")]
+    InvalidQualityLabel(String),
     /// Invalid IPv6 format
     #[error("Invalid IPv6 format")]
     InvalidIPv6Format,
@@ -241,6 +1039,21 @@ pub enum VideoError {
     /// Playlist body cannot parsed
     #[error("Playlist body cannot parsed")]
     PlaylistBodyCannotParsed,
+    /// Channel not found
+    #[error("Channel not found: {0}")]
+    ChannelNotFound(String),
+    /// URL cannot resolved
+    #[error("URL cannot resolved: {0}")]
+    UrlNotResolved(String),
+    /// Caption track not found
+    #[error("Caption track not found: {0}")]
+    CaptionNotFound(String),
+    /// Thumbnail not found
+    #[error("Thumbnail not found")]
+    ThumbnailNotFound,
+    /// Live chat not found, the video is not live or the chat is disabled
+    #[error("Live chat not found")]
+    LiveChatNotFound,
     /// Download error
     #[error("Download Error: {0}")]
     DownloadError(String),
@@ -256,6 +1069,21 @@ pub enum VideoError {
     /// Child process error
     #[error("Process Error: {0}")]
     ChildProcessError(String),
+    /// Invalid header name or value
+    #[error("Invalid header: {0}")]
+    InvalidHeader(String),
+    /// Cookie error
+    #[error("Cookie Error: {0}")]
+    CookieError(String),
+    /// Stream does not support seeking
+    #[error("Stream is not seekable")]
+    StreamNotSeekable,
+    /// JavaScript runtime error
+    #[error("JavaScript runtime error: {0}")]
+    JsRuntime(String),
+    /// Media container cannot be parsed or written
+    #[error("Container Error: {0}")]
+    ContainerError(String),
     /// Downloading live streams not supported, compile with `live` feature to enable
     #[error("Downloading live streams not supported, compile with `live` feature to enable")]
     LiveStreamNotSupported,
@@ -263,6 +1091,62 @@ pub enum VideoError {
     #[error("FFmpeg command error: {0}")]
     #[cfg(feature = "ffmpeg")]
     FFmpeg(String),
+    /// Metadata tag error
+    #[error("Tag Error: {0}")]
+    #[cfg(feature = "tags")]
+    Tags(String),
+    /// Image decoding or encoding error
+    #[error("Image Error: {0}")]
+    #[cfg(feature = "storyboard")]
+    Image(String),
+    /// Object store upload error
+    #[error("Object Store Error: {0}")]
+    #[cfg(feature = "object_store")]
+    ObjectStore(String),
+}
+
+impl From<reqwest_middleware::Error> for VideoError {
+    fn from(err: reqwest_middleware::Error) -> Self {
+        // Errors of our own middlewares are returned as they are
+        match err {
+            reqwest_middleware::Error::Middleware(err) => match err.downcast::<VideoError>() {
+                Ok(err) => err,
+                Err(err) => {
+                    VideoError::ReqwestMiddleware(reqwest_middleware::Error::Middleware(err))
+                }
+            },
+            err => VideoError::ReqwestMiddleware(err),
+        }
+    }
+}
+
+/// YouTube sends some numbers of formats as strings (`"contentLength": "1234"`) and others as numbers,
+/// accept both. Values which are not numbers are [`None`]
+fn deserialize_lenient_u64<'de, D>(deserializer: D) -> Result<Option<u64>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrNumber {
+        Integer(u64),
+        Float(f64),
+        String(String),
+        Other(serde_json::Value),
+    }
+
+    Ok(match Option::<StringOrNumber>::deserialize(deserializer)? {
+        Some(StringOrNumber::Integer(x)) => Some(x),
+        Some(StringOrNumber::Float(x)) if x >= 0. => Some(x as u64),
+        Some(StringOrNumber::String(x)) => x.trim().parse::<u64>().ok().or_else(|| {
+            x.trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|x| *x >= 0.)
+                .map(|x| x as u64)
+        }),
+        _ => None,
+    })
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -274,8 +1158,10 @@ pub struct VideoFormat {
     pub mime_type: MimeType,
     pub bitrate: u64,
     /// Video format width
+    #[serde(default, deserialize_with = "deserialize_lenient_u64")]
     pub width: Option<u64>, // VIDEO & DASH MPD ONLY
     /// Video format height
+    #[serde(default, deserialize_with = "deserialize_lenient_u64")]
     pub height: Option<u64>, // VIDEO & DASH MPD ONLY
     #[serde(rename = "initRange")]
     pub init_range: Option<RangeObject>,
@@ -283,9 +1169,15 @@ pub struct VideoFormat {
     pub index_range: Option<RangeObject>,
     #[serde(rename = "lastModified")]
     pub last_modified: Option<String>,
-    #[serde(rename = "contentLength")]
-    pub content_length: Option<String>,
+    /// Size of the format in bytes
+    #[serde(
+        rename = "contentLength",
+        default,
+        deserialize_with = "deserialize_lenient_u64"
+    )]
+    pub content_length: Option<u64>,
     pub quality: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_lenient_u64")]
     pub fps: Option<u64>, // VIDEO & DASH MPD ONLY
     #[serde(rename = "qualityLabel")]
     pub quality_label: Option<String>,
@@ -299,18 +1191,39 @@ pub struct VideoFormat {
     pub audio_quality: Option<String>, // AUDIO ONLY
     #[serde(rename = "colorInfo")]
     pub color_info: Option<ColorInfo>, // VIDEO ONLY
-    #[serde(rename = "approxDurationMs")]
-    pub approx_duration_ms: Option<String>,
+    /// Duration of the format in milliseconds
+    #[serde(
+        rename = "approxDurationMs",
+        default,
+        deserialize_with = "deserialize_lenient_u64"
+    )]
+    pub approx_duration_ms: Option<u64>,
     #[serde(rename = "audioSampleRate")]
     pub audio_sample_rate: Option<String>, // AUDIO & DASH MPD ONLY
+    /// Channel count of the audio track, `2` for stereo and `6` for 5.1 surround
     #[serde(rename = "audioChannels")]
     pub audio_channels: Option<u8>, // AUDIO ONLY
     #[serde(rename = "audioBitrate")]
     pub audio_bitrate: Option<u64>, // LIVE HLS VIDEO ONLY
+    /// Loudness of the audio track relative to YouTube's reference level, negative if it is quieter
     #[serde(rename = "loudnessDb")]
     pub loudness_db: Option<f64>, // AUDIO ONLY
-    /// Video format URL
-    pub url: String,
+    /// Video format URL of the player response
+    /// - Signature and `n` parameter may not be applied yet, use [`VideoFormat::resolve_url`] to download
+    #[serde(rename = "url")]
+    pub raw_url: String,
+    /// [`VideoFormat::resolve_url`] result, the player script runs once per format
+    #[serde(skip)]
+    pub(crate) resolved_url: ResolvedUrl,
+    /// Time the URL stops working, from its `expire` parameter
+    #[serde(rename = "expiresAt", default)]
+    pub expires_at: Option<std::time::SystemTime>,
+    /// Signature cipher which is deciphered by [`VideoFormat::resolve_url`]
+    #[serde(skip)]
+    pub(crate) signature_cipher: Option<DecipherQuery>,
+    /// Player functions to decipher signature and transform `n` parameter
+    #[serde(skip)]
+    pub(crate) player_functions: Option<PlayerFunctions>,
     /// Video format has video or not
     #[serde(rename = "hasVideo")]
     pub has_video: bool,
@@ -326,6 +1239,93 @@ pub struct VideoFormat {
     /// Video format is DashMPD or not
     #[serde(rename = "isDashMPD")]
     pub is_dash_mpd: bool,
+    /// Video format is downloaded segment by segment from the DASH manifest in `url`, see [`crate::stream::DashStream`]
+    #[serde(rename = "isDashSegmented", default)]
+    pub is_dash_segmented: bool,
+    /// Audio is dynamic range compressed, a quieter duplicate of the format with the same itag
+    #[serde(rename = "isDrc", default)]
+    pub is_drc: bool,
+    /// Video format is an OTF (on the fly) stream, downloaded as `sq=` numbered segments instead of byte ranges
+    #[serde(rename = "isOtf", default)]
+    pub is_otf: bool,
+}
+
+/// Memoized downloadable URL of a [`VideoFormat`], it is not a part of the format identity.
+/// Clones of the format share it, so the format chosen for a download resolves the one in [`VideoInfo::formats`] too
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ResolvedUrl(Arc<once_cell::sync::OnceCell<String>>);
+
+impl PartialEq for ResolvedUrl {
+    fn eq(&self, _: &Self) -> bool {
+        true
+    }
+}
+
+impl VideoFormat {
+    /// Get downloadable URL of the format
+    ///
+    /// Deciphering runs the player script, so it is done only for the requested format
+    /// instead of every format of the video
+    pub fn resolve_url(&self) -> String {
+        match &self.player_functions {
+            Some(functions) => self
+                .resolved_url
+                .0
+                .get_or_init(|| {
+                    resolve_download_url(&self.raw_url, self.signature_cipher.as_ref(), functions)
+                })
+                .clone(),
+            None => self.raw_url.clone(),
+        }
+    }
+
+    /// Downloadable URL if [`VideoFormat::resolve_url`] was already called, otherwise [`VideoFormat::raw_url`].
+    /// Does not run the player script
+    pub(crate) fn resolved_or_raw_url(&self) -> String {
+        self.resolved_url
+            .0
+            .get()
+            .cloned()
+            .unwrap_or_else(|| self.raw_url.clone())
+    }
+
+    /// URL is expired and a new one has to be requested with [`crate::Video::get_info`]
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .map(|x| x <= crate::platform::now())
+            .unwrap_or(false)
+    }
+
+    /// Premium (enhanced bitrate) format, which is only playable with the cookies of a YouTube Premium account.
+    /// [`crate::choose_format`] skips them without [`RequestOptions::cookies`] or [`RequestOptions::cookies_file`]
+    pub fn is_premium(&self) -> bool {
+        // HLS variants of the enhanced bitrate 1080p, same as yt-dlp
+        const PREMIUM_ITAGS: [u64; 2] = [616, 235];
+
+        PREMIUM_ITAGS.contains(&self.itag)
+            || self
+                .quality_label
+                .as_ref()
+                .map(|x| x.contains("Premium"))
+                .unwrap_or(false)
+    }
+
+    /// Video uses the PQ (HDR10) or HLG transfer characteristics
+    pub fn is_hdr(&self) -> bool {
+        match &self.color_info {
+            Some(color_info) => {
+                color_info.transfer_characteristics.ends_with("SMPTEST2084")
+                    || color_info
+                        .transfer_characteristics
+                        .ends_with("ARIB_STD_B67")
+            }
+            None => self
+                .quality_label
+                .as_ref()
+                .map(|x| x.contains("HDR"))
+                .unwrap_or(false),
+        }
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -396,7 +1396,48 @@ pub struct VideoDetails {
     pub is_unplugged_corpus: bool,
     #[serde(rename = "isLiveContent")]
     pub is_live_content: bool,
+    /// Live stream ended but is not processed into a regular video yet, only the HLS DVR manifest is downloadable
+    #[serde(rename = "isPostLiveDvr", default)]
+    pub is_post_live_dvr: bool,
+    /// Live stream can be rewound, its manifests have a DVR playlist besides the live edge playlist
+    #[serde(rename = "isLiveDvrEnabled", default)]
+    pub is_live_dvr_enabled: bool,
+    /// Latency setting of the live stream, [`None`] for regular videos
+    #[serde(rename = "latencyClass", default)]
+    pub latency_class: Option<LatencyClass>,
+    /// Live stream or premiere is scheduled but not started yet, formats are not available
+    #[serde(rename = "isUpcoming", default)]
+    pub is_upcoming: bool,
+    /// Scheduled start of an upcoming live stream or premiere in seconds since the Unix epoch
+    #[serde(rename = "scheduledStartTime", default)]
+    pub scheduled_start_time: Option<u64>,
     pub thumbnails: Vec<Thumbnail>,
+    /// Start of the clip in milliseconds if the video is created with a `youtube.com/clip/...` URL
+    #[serde(rename = "clipStartMs", default)]
+    pub clip_start_ms: Option<u64>,
+    /// End of the clip in milliseconds if the video is created with a `youtube.com/clip/...` URL
+    #[serde(rename = "clipEndMs", default)]
+    pub clip_end_ms: Option<u64>,
+    /// Start offset in seconds of [`VideoOptions::start_time`] or `t`/`start` parameter of the URL
+    #[serde(rename = "startOffset", default)]
+    pub start_offset: Option<u64>,
+}
+
+/// Video info of the oEmbed endpoint, see [`Video::quick_info`](crate::Video::quick_info)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuickInfo {
+    pub title: String,
+    #[serde(default)]
+    pub author_name: String,
+    /// Channel URL of the author
+    #[serde(default)]
+    pub author_url: String,
+    #[serde(default)]
+    pub thumbnail_url: String,
+    #[serde(default)]
+    pub thumbnail_width: u64,
+    #[serde(default)]
+    pub thumbnail_height: u64,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -414,6 +1455,93 @@ pub struct RelatedVideo {
     pub length_seconds: String,
     pub thumbnails: Vec<Thumbnail>,
     pub is_live: bool,
+    #[serde(rename = "channelId", default)]
+    pub channel_id: String,
+    #[serde(rename = "durationSeconds", default)]
+    pub duration_seconds: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchNext {
+    /// Video that YouTube will play automatically after the current one
+    pub autoplay: Option<RelatedVideo>,
+    /// "Up next" queue
+    #[serde(rename = "upNext")]
+    pub up_next: Vec<RelatedVideo>,
+    /// Playlist panel, only available if the video opened with a `list` parameter
+    pub playlist: Option<WatchNextPlaylist>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchNextPlaylist {
+    pub id: String,
+    pub title: String,
+    #[serde(rename = "ownerName")]
+    pub owner_name: String,
+    /// Index of the current video in the playlist
+    #[serde(rename = "currentIndex")]
+    pub current_index: u64,
+    #[serde(rename = "totalVideos")]
+    pub total_videos: u64,
+    #[serde(rename = "isInfinite")]
+    pub is_infinite: bool,
+    pub videos: Vec<PlaylistPanelVideo>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlaylistPanelVideo {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    pub author: String,
+    #[serde(rename = "lengthSeconds")]
+    pub length_seconds: String,
+    /// Index of the video in the playlist (starts from `1`)
+    pub index: u64,
+    /// Video is the currently playing video or not
+    pub selected: bool,
+    pub thumbnails: Vec<Thumbnail>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Comment {
+    pub id: String,
+    pub author: String,
+    pub author_channel_id: String,
+    pub author_thumbnails: Vec<Thumbnail>,
+    /// Comment written by the video owner
+    pub author_is_owner: bool,
+    pub text: String,
+    pub likes: u64,
+    pub reply_count: u64,
+    /// Relative published time text (e.g. `2 years ago`)
+    pub published_time: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LiveChatMessage {
+    pub id: String,
+    pub author: String,
+    pub author_channel_id: String,
+    pub author_thumbnails: Vec<Thumbnail>,
+    /// Message text, emojis are written with their shortcuts (e.g. `:smile:`)
+    pub message: String,
+    /// Amount text of Super Chats (e.g. `$5.00`)
+    pub purchase_amount: Option<String>,
+    /// Microseconds since the Unix epoch
+    pub timestamp_usec: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, derive_more::Display)]
+pub enum CommentSort {
+    /// Top comments
+    #[default]
+    #[display(fmt = "Top")]
+    Top,
+    /// Newest comments first
+    #[display(fmt = "Newest")]
+    Newest,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -442,6 +1570,7 @@ pub struct Chapter {
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct StoryBoard {
+    /// URL of the sprite sheets, `$M` is replaced with the sheet index
     #[serde(rename = "templateUrl")]
     pub template_url: String,
     #[serde(rename = "thumbnailWidth")]
@@ -450,13 +1579,39 @@ pub struct StoryBoard {
     pub thumbnail_height: i32,
     #[serde(rename = "thumbnailCount")]
     pub thumbnail_count: i32,
+    /// Milliseconds between two frames
     pub interval: i32,
     pub columns: i32,
     pub rows: i32,
+    /// Count of sprite sheets
     #[serde(rename = "storyboardCount")]
     pub storyboard_count: i32,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptionTrack {
+    pub base_url: String,
+    /// Language code of the track (e.g. `en`, `de`, `pt-BR`)
+    pub language_code: String,
+    /// Human readable language name (e.g. `English (auto-generated)`)
+    pub name: String,
+    /// Kind of the track, `asr` for automatic speech recognition tracks
+    pub kind: Option<String>,
+    pub is_auto_generated: bool,
+    pub is_translatable: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, derive_more::Display)]
+pub enum CaptionFormat {
+    /// SubRip
+    #[display(fmt = "SRT")]
+    Srt,
+    /// WebVTT
+    #[display(fmt = "VTT")]
+    Vtt,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Thumbnail {
     pub width: u64,
@@ -464,6 +1619,58 @@ pub struct Thumbnail {
     pub url: String,
 }
 
+impl Thumbnail {
+    /// Pick the `maxresdefault` thumbnail, or the biggest one if the video does not have it
+    pub fn best(thumbnails: &[Thumbnail]) -> Option<&Thumbnail> {
+        thumbnails
+            .iter()
+            .find(|x| x.url.contains("/maxresdefault"))
+            .or_else(|| thumbnails.iter().max_by_key(|x| x.width * x.height))
+    }
+}
+
+/// Size of the thumbnail downloaded by [`Video::download_thumbnail`](crate::Video::download_thumbnail)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, derive_more::Display)]
+pub enum ThumbnailQuality {
+    /// 1280x720, only available for HD videos
+    #[display(fmt = "Max")]
+    Max,
+    /// 640x480
+    #[display(fmt = "Standard")]
+    Standard,
+    /// 480x360
+    #[display(fmt = "High")]
+    High,
+    /// 320x180
+    #[display(fmt = "Medium")]
+    Medium,
+    /// 120x90
+    #[display(fmt = "Default")]
+    Default,
+}
+
+impl ThumbnailQuality {
+    /// Qualities from the highest to the lowest
+    pub(crate) const ALL: [ThumbnailQuality; 5] = [
+        ThumbnailQuality::Max,
+        ThumbnailQuality::Standard,
+        ThumbnailQuality::High,
+        ThumbnailQuality::Medium,
+        ThumbnailQuality::Default,
+    ];
+
+    /// Name of the image on `i.ytimg.com`
+    pub fn file_name(&self) -> &'static str {
+        match self {
+            ThumbnailQuality::Max => "maxresdefault",
+            ThumbnailQuality::Standard => "sddefault",
+            ThumbnailQuality::High => "hqdefault",
+            ThumbnailQuality::Medium => "mqdefault",
+            ThumbnailQuality::Default => "default",
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Embed {
     #[serde(rename = "flashSecureUrl")]
@@ -655,6 +1862,7 @@ impl<'de> Deserialize<'de> for MimeType {
     }
 }
 
+/// Arguments of the `ffmpeg` binary, which must be installed even with the `libav-remux` feature
 #[cfg(feature = "ffmpeg")]
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct FFmpegArgs {
@@ -707,3 +1915,26 @@ impl FFmpegArgs {
         args
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deserialize_lenient_u64() {
+        let lenient = |value: serde_json::Value| deserialize_lenient_u64(value).unwrap();
+
+        assert_eq!(lenient(serde_json::json!("1234")), Some(1234));
+        assert_eq!(lenient(serde_json::json!(" 1234 ")), Some(1234));
+        assert_eq!(lenient(serde_json::json!("12.5")), Some(12));
+        assert_eq!(lenient(serde_json::json!(1234)), Some(1234));
+        assert_eq!(lenient(serde_json::json!(12.5)), Some(12));
+        assert_eq!(lenient(serde_json::json!("")), None);
+        assert_eq!(lenient(serde_json::Value::Null), None);
+        assert_eq!(lenient(serde_json::json!("abc")), None);
+        assert_eq!(lenient(serde_json::json!("-1")), None);
+        assert_eq!(lenient(serde_json::json!(-1)), None);
+        assert_eq!(lenient(serde_json::json!({ "a": 1 })), None);
+        assert_eq!(lenient(serde_json::json!([1])), None);
+    }
+}
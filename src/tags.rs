@@ -0,0 +1,91 @@
+use std::path::Path;
+
+use lofty::{Accessor, Probe, Tag, TagExt, TaggedFileExt};
+
+use crate::structs::{VideoError, VideoInfo};
+
+/// Write title, artist, album, year and video URL tags of the video to the file.
+/// ID3v2, MP4 and Vorbis comments are chosen by the file type.
+/// Returns `false` if tags cannot be written to the file type (e.g. WebM)
+pub(crate) fn write_tags(path: &Path, info: &VideoInfo) -> Result<bool, VideoError> {
+    let probe = Probe::open(path)
+        .and_then(|x| x.guess_file_type().map_err(Into::into))
+        .map_err(|x| VideoError::Tags(x.to_string()))?;
+
+    if probe.file_type().is_none() {
+        return Ok(false);
+    }
+
+    let mut tagged_file = probe.read().map_err(|x| VideoError::Tags(x.to_string()))?;
+
+    let tag_type = tagged_file.primary_tag_type();
+    if tagged_file.primary_tag().is_none() {
+        tagged_file.insert_tag(Tag::new(tag_type));
+    }
+
+    let tag = tagged_file
+        .primary_tag_mut()
+        .ok_or(VideoError::Tags("Tag cannot be created".to_string()))?;
+
+    let details = &info.video_details;
+
+    tag.set_title(details.title.clone());
+    tag.set_artist(
+        details
+            .author
+            .as_ref()
+            .map(|x| x.name.clone())
+            .unwrap_or_else(|| details.owner_channel_name.clone()),
+    );
+
+    if let Some(album) = music_album(&info.initial_response) {
+        tag.set_album(album);
+    }
+
+    let year = if details.publish_date.is_empty() {
+        &details.upload_date
+    } else {
+        &details.publish_date
+    };
+    if let Some(year) = year.get(..4).and_then(|x| x.parse::<u32>().ok()) {
+        tag.set_year(year);
+    }
+
+    tag.set_comment(details.video_url.clone());
+
+    tag.save_to_path(path)
+        .map_err(|x| VideoError::Tags(x.to_string()))?;
+
+    Ok(true)
+}
+
+/// Album of the music section in the description, if the video is a song
+fn music_album(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::Object(object) => {
+            if let Some(row) = object.get("infoRowRenderer") {
+                let title = row["title"]["simpleText"].as_str().unwrap_or("");
+
+                if title.eq_ignore_ascii_case("album") {
+                    let metadata = &row["defaultMetadata"];
+
+                    return metadata["simpleText"]
+                        .as_str()
+                        .map(|x| x.to_string())
+                        .or_else(|| {
+                            metadata["runs"].as_array().map(|runs| {
+                                runs.iter()
+                                    .filter_map(|x| x["text"].as_str())
+                                    .collect::<String>()
+                            })
+                        })
+                        .filter(|x| !x.is_empty());
+                }
+            }
+
+            object.values().find_map(music_album)
+        }
+        serde_json::Value::Array(array) => array.iter().find_map(music_album),
+        _ => None,
+    }
+}
@@ -0,0 +1,135 @@
+use crate::structs::{SanitizeOptions, VideoDetails, VideoFormat};
+use crate::utils::sanitize_filename;
+
+/// Value of fields which are not available for the video, same as yt-dlp
+const NOT_AVAILABLE: &str = "NA";
+
+/// Render a yt-dlp style output template (e.g. `%(title)s [%(id)s].%(ext)s`) into a file name.
+/// Values are sanitized with [`crate::sanitize_filename`], so they cannot add path separators or characters invalid on Windows.
+/// Long names are cut before the extension
+///
+/// Fields: `id`, `title`, `uploader`, `channel`, `channel_id`, `upload_date` (`YYYYMMDD`), `duration`,
+/// `view_count`, `ext`, `format_id`, `height`, `fps`, `resolution`.
+/// Unknown fields are rendered as `NA`, `%%` is a literal `%`
+/// # Example
+/// ```ignore
+///     let info = video.get_info().await.unwrap();
+///     let format = choose_format(&info.formats, &VideoOptions::default()).unwrap();
+///
+///     let file_name = render_filename_template("%(title)s [%(id)s].%(ext)s", &info.video_details, &format);
+/// ```
+pub fn render_filename_template(
+    template: &str,
+    details: &VideoDetails,
+    format: &VideoFormat,
+) -> String {
+    let sanitize_options = SanitizeOptions {
+        max_length: None,
+        ..Default::default()
+    };
+
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(index) = rest.find('%') {
+        rendered.push_str(&rest[..index]);
+        rest = &rest[index..];
+
+        if let Some(after) = rest.strip_prefix("%%") {
+            rendered.push('%');
+            rest = after;
+            continue;
+        }
+
+        // `%(name)s` or `%(name)d`, anything else is kept as it is
+        let field = rest.strip_prefix("%(").and_then(|x| {
+            let end = x.find(')')?;
+            let conversion = x[end + 1..].chars().next()?;
+
+            matches!(conversion, 's' | 'd').then(|| (&x[..end], &x[end + 2..]))
+        });
+
+        match field {
+            Some((name, after)) => {
+                let value = template_field(name, details, format)
+                    .unwrap_or_else(|| NOT_AVAILABLE.to_string());
+
+                rendered.push_str(&sanitize_filename(&value, &sanitize_options));
+                rest = after;
+            }
+            None => {
+                rendered.push('%');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    rendered.push_str(rest);
+
+    let max_length = SanitizeOptions::default().max_length.unwrap_or(usize::MAX);
+
+    // Titles are cut instead of the extension
+    if rendered.len() > max_length {
+        if let Some((stem, extension)) = rendered.rsplit_once('.') {
+            let stem = sanitize_filename(
+                stem,
+                &SanitizeOptions {
+                    max_length: Some(max_length.saturating_sub(extension.len() + 1)),
+                    ..Default::default()
+                },
+            );
+
+            return format!("{stem}.{extension}");
+        }
+    }
+
+    rendered
+}
+
+fn template_field(name: &str, details: &VideoDetails, format: &VideoFormat) -> Option<String> {
+    let non_empty = |x: &str| (!x.is_empty()).then(|| x.to_string());
+
+    match name {
+        "id" => non_empty(&details.video_id),
+        "title" => non_empty(&details.title),
+        "uploader" | "channel" => details
+            .author
+            .as_ref()
+            .map(|x| x.name.clone())
+            .or_else(|| non_empty(&details.owner_channel_name)),
+        "channel_id" => non_empty(&details.channel_id),
+        "upload_date" => {
+            let date = if details.upload_date.is_empty() {
+                &details.publish_date
+            } else {
+                &details.upload_date
+            };
+
+            // Dates are ISO 8601, yt-dlp uses `YYYYMMDD`
+            date.get(..10).map(|x| x.replace('-', ""))
+        }
+        "duration" => non_empty(&details.length_seconds),
+        "view_count" => non_empty(&details.view_count),
+        "ext" => Some(format_extension(format)),
+        "format_id" => Some(format.itag.to_string()),
+        "height" => format.height.map(|x| x.to_string()),
+        "fps" => format.fps.map(|x| x.to_string()),
+        "resolution" => match (format.width, format.height) {
+            (Some(width), Some(height)) => Some(format!("{width}x{height}")),
+            _ if !format.has_video => Some("audio only".to_string()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// File extension of the format, audio-only MP4 is `m4a` like in yt-dlp
+pub(crate) fn format_extension(format: &VideoFormat) -> String {
+    let container = format.mime_type.container.as_str();
+
+    if container == "mp4" && !format.has_video {
+        return "m4a".to_string();
+    }
+
+    container.to_string()
+}
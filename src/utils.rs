@@ -1,6 +1,6 @@
-use boa_engine::optimizer::OptimizerOptions;
-use boa_engine::{Context, JsValue, Source};
-use bytes::Bytes;
+#[cfg(feature = "ffmpeg")]
+use crate::platform::Command;
+use crate::platform::Instant;
 use once_cell::sync::Lazy;
 use rand::Rng;
 use regex::Regex;
@@ -8,46 +8,234 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::process::Stdio;
 use std::sync::Mutex;
-use std::time::Instant;
-use tokio::sync::RwLock;
-use tokio::{io::AsyncWriteExt, process::Command};
 use unicode_segmentation::UnicodeSegmentation;
 use urlencoding::decode;
 
+use crate::cache::LruCache;
+use crate::consent::ConsentMiddleware;
 use crate::constants::{
-    AGE_RESTRICTED_URLS, AUDIO_ENCODING_RANKS, BASE_URL, ESCAPING_SEQUENZES, IPV6_REGEX,
-    PARSE_INT_REGEX, VALID_QUERY_DOMAINS, VIDEO_ENCODING_RANKS,
+    AGE_RESTRICTED_URLS, AUDIO_ENCODING_RANKS, BASE_URL, DEFAULT_HEADERS, ESCAPING_SEQUENZES,
+    INNERTUBE_API_KEY, INNERTUBE_URL, IPV6_REGEX, PARSE_INT_REGEX, VALID_QUERY_DOMAINS,
+    VIDEO_ENCODING_RANKS,
 };
+use crate::headers::{random_browser_identity, HeadersMiddleware};
+use crate::hooks::HookMiddleware;
+#[cfg(feature = "http3")]
+use crate::http3::Http3Middleware;
 use crate::info_extras::{get_author, get_chapters, get_dislikes, get_likes, get_storyboards};
+use crate::rate_limit::RateLimitMiddleware;
 use crate::structs::{
-    Embed, EscapeSequence, StringUtils, Thumbnail, VideoDetails, VideoError, VideoFormat,
+    DrcPolicy, Embed, EscapeSequence, InnertubeClient, LatencyClass, PlayabilityStatus,
+    RequestOptions, SanitizeOptions, StringUtils, Thumbnail, VideoDetails, VideoError, VideoFormat,
     VideoOptions, VideoQuality, VideoSearchOptions,
 };
 
 #[cfg(feature = "ffmpeg")]
-pub async fn ffmpeg_cmd_run(args: &Vec<String>, data: Bytes) -> Result<Bytes, VideoError> {
-    use tokio::io::AsyncReadExt;
+use crate::structs::Chapter;
 
-    let mut cmd = Command::new("ffmpeg");
-    cmd.args(args)
+/// Spawn ffmpeg with piped stdin and stdout, the process is killed when dropped
+#[cfg(feature = "ffmpeg")]
+pub fn ffmpeg_cmd_spawn(args: &[String]) -> Result<crate::platform::Child, VideoError> {
+    Command::new("ffmpeg")
+        .args(args)
         .stdin(Stdio::piped())
         .stdout(Stdio::piped())
-        .kill_on_drop(true);
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|x| VideoError::FFmpeg(x.to_string()))
+}
+
+/// Streams of a [`RemuxInput`] copied to the output
+#[cfg(feature = "ffmpeg")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RemuxStream {
+    All,
+    /// Best video stream only
+    Video,
+    /// Best audio stream only
+    Audio,
+}
+
+/// Input of [`ffmpeg_remux`], `start`-`end` limits it to a time window
+#[cfg(feature = "ffmpeg")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemuxInput {
+    /// File path or URL
+    pub url: String,
+    pub stream: RemuxStream,
+    pub start: Option<std::time::Duration>,
+    pub end: Option<std::time::Duration>,
+}
+
+/// Chapter of a [`ffmpeg_remux`] output, times are relative to the start of the output
+#[cfg(feature = "ffmpeg")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemuxChapter {
+    pub title: String,
+    pub start: std::time::Duration,
+    pub end: std::time::Duration,
+}
+
+/// Convert chapters of the video into chapters of an output covering `start`-`end` of it.
+/// Every chapter ends where the next one starts, the last one at `duration`
+#[cfg(feature = "ffmpeg")]
+pub fn remux_chapters(
+    chapters: &[Chapter],
+    duration: std::time::Duration,
+    start: std::time::Duration,
+    end: Option<std::time::Duration>,
+) -> Vec<RemuxChapter> {
+    use std::time::Duration;
+
+    let end = end.unwrap_or(duration).min(duration);
+
+    chapters
+        .iter()
+        .enumerate()
+        .filter_map(|(index, chapter)| {
+            let chapter_start = Duration::from_secs(chapter.start_time.max(0) as u64);
+            let chapter_end = chapters
+                .get(index + 1)
+                .map(|x| Duration::from_secs(x.start_time.max(0) as u64))
+                .unwrap_or(duration);
+
+            let chapter_start = chapter_start.max(start);
+            let chapter_end = chapter_end.min(end);
+
+            (chapter_start < chapter_end).then(|| RemuxChapter {
+                title: chapter.title.clone(),
+                start: chapter_start - start,
+                end: chapter_end - start,
+            })
+        })
+        .collect()
+}
+
+/// Build an ffmetadata file with the chapters, in milliseconds
+#[cfg(all(feature = "ffmpeg", not(feature = "libav-remux")))]
+fn ffmetadata_chapters(chapters: &[RemuxChapter]) -> String {
+    let mut metadata = String::from(";FFMETADATA1\n");
+
+    for chapter in chapters {
+        // Special characters of ffmetadata are escaped with a backslash
+        let mut title = String::with_capacity(chapter.title.len());
+        for character in chapter.title.chars() {
+            if matches!(character, '=' | ';' | '#' | '\\' | '\n') {
+                title.push('\\');
+            }
+            title.push(character);
+        }
+
+        metadata.push_str(&format!(
+            "[CHAPTER]\nTIMEBASE=1/1000\nSTART={}\nEND={}\ntitle={}\n",
+            chapter.start.as_millis(),
+            chapter.end.as_millis(),
+            title
+        ));
+    }
+
+    metadata
+}
+
+/// Copy the streams of `inputs` into `output` without re-encoding.
+/// Uses libav bindings with `libav-remux` feature, otherwise the ffmpeg binary
+#[cfg(feature = "ffmpeg")]
+pub async fn ffmpeg_remux(
+    inputs: Vec<RemuxInput>,
+    chapters: Vec<RemuxChapter>,
+    output: &std::path::Path,
+) -> Result<(), VideoError> {
+    #[cfg(feature = "libav-remux")]
+    {
+        let output = output.to_path_buf();
+
+        crate::platform::spawn_blocking(move || crate::libav::remux(&inputs, &chapters, &output))
+            .await?
+    }
+    #[cfg(not(feature = "libav-remux"))]
+    {
+        let mut args = vec![];
+
+        for input in inputs.iter() {
+            if let Some(start) = input.start {
+                args.push("-ss".to_string());
+                args.push(format!("{:.3}", start.as_secs_f64()));
+            }
 
-    let mut process = cmd.spawn().map_err(|x| VideoError::FFmpeg(x.to_string()))?;
-    let mut stdin = process
-        .stdin
-        .take()
-        .ok_or(VideoError::FFmpeg("Failed to open stdin".to_string()))?;
+            if let Some(end) = input.end {
+                args.push("-to".to_string());
+                args.push(format!("{:.3}", end.as_secs_f64()));
+            }
+
+            args.push("-i".to_string());
+            args.push(input.url.clone());
+        }
+
+        // Chapters are read from an ffmetadata file given as the last input
+        let mut metadata_path = output.as_os_str().to_owned();
+        metadata_path.push(".chapters.txt");
+        let metadata_path = std::path::PathBuf::from(metadata_path);
+
+        if !chapters.is_empty() {
+            std::fs::write(&metadata_path, ffmetadata_chapters(&chapters))
+                .map_err(|x| VideoError::FFmpeg(x.to_string()))?;
+
+            args.push("-i".to_string());
+            args.push(metadata_path.to_string_lossy().to_string());
+        }
+
+        for (index, input) in inputs.iter().enumerate() {
+            args.push("-map".to_string());
+            args.push(match input.stream {
+                RemuxStream::All => index.to_string(),
+                RemuxStream::Video => format!("{index}:v:0"),
+                RemuxStream::Audio => format!("{index}:a:0"),
+            });
+        }
+
+        if !chapters.is_empty() {
+            args.push("-map_chapters".to_string());
+            args.push(inputs.len().to_string());
+        }
+
+        args.extend([
+            "-c".to_string(),
+            "copy".to_string(),
+            "-y".to_string(),
+            output.to_string_lossy().to_string(),
+        ]);
 
-    tokio::spawn(async move { stdin.write_all(&data).await });
+        let result = ffmpeg_cmd_exec(&args).await;
+
+        if !chapters.is_empty() {
+            let _ = std::fs::remove_file(&metadata_path);
+        }
+
+        result
+    }
+}
 
-    let output = process
-        .wait_with_output()
+/// Run ffmpeg without piping data, for commands reading inputs and writing outputs by themselves
+#[cfg(feature = "ffmpeg")]
+pub async fn ffmpeg_cmd_exec(args: &[String]) -> Result<(), VideoError> {
+    let output = Command::new("ffmpeg")
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .output()
         .await
         .map_err(|x| VideoError::FFmpeg(x.to_string()))?;
 
-    Ok(Bytes::from(output.stdout))
+    if !output.status.success() {
+        return Err(VideoError::FFmpeg(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ));
+    }
+
+    Ok(())
 }
 
 #[allow(dead_code)]
@@ -107,6 +295,25 @@ pub fn get_html5player(body: &str) -> Option<String> {
     }
 }
 
+/// Get player script path from the IFrame API script, which is much smaller than the watch page
+pub async fn get_iframe_html5player(
+    client: &reqwest_middleware::ClientWithMiddleware,
+) -> Result<String, VideoError> {
+    static PLAYER_ID_REGEX: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"player\\?/([0-9a-fA-F]{8})\\?/").unwrap());
+
+    let response = get_html(client, "https://www.youtube.com/iframe_api", None).await?;
+
+    let player_id = PLAYER_ID_REGEX
+        .captures(&response)
+        .and_then(|x| x.get(1))
+        .map(|x| x.as_str())
+        .ok_or(VideoError::BodyCannotParsed)?;
+
+    Ok(format!(
+        "/s/player/{player_id}/player_ias.vflset/en_US/base.js"
+    ))
+}
 
 #[cfg_attr(feature = "performance_analysis", flamer::flame)]
 pub fn add_format_meta(format: &mut serde_json::Map<String, serde_json::Value>) {
@@ -150,6 +357,109 @@ pub fn add_format_meta(format: &mut serde_json::Map<String, serde_json::Value>)
             REGEX_IS_DASHMPD.is_match(format.get("url").and_then(|x| x.as_str()).unwrap_or("")),
         ),
     );
+
+    format.insert(
+        "isOtf".to_string(),
+        serde_json::Value::Bool(
+            format.get("type").and_then(|x| x.as_str()) == Some("FORMAT_STREAM_TYPE_OTF"),
+        ),
+    );
+
+    let is_drc = is_drc_format(format);
+    format.insert("isDrc".to_string(), serde_json::Value::Bool(is_drc));
+
+    if let Some(expires_at) =
+        get_url_expiry(format.get("url").and_then(|x| x.as_str()).unwrap_or(""))
+    {
+        format.insert(
+            "expiresAt".to_string(),
+            serde_json::to_value(expires_at).unwrap_or_default(),
+        );
+    }
+}
+
+/// DRC formats have `isDrc`, or `drc=1` in their `xtags`. `xtags` is a base64 protobuf in the format
+/// and plain text in the `xtags` parameter of the URL
+fn is_drc_format(format: &serde_json::Map<String, serde_json::Value>) -> bool {
+    if format.get("isDrc").and_then(|x| x.as_bool()) == Some(true) {
+        return true;
+    }
+
+    let xtags_field = format
+        .get("xtags")
+        .and_then(|x| x.as_str())
+        .and_then(decode_base64)
+        .map(|x| x.windows(6).any(|x| x == b"drc\x12\x011"))
+        .unwrap_or(false);
+
+    let xtags_param = format
+        .get("url")
+        .and_then(|x| x.as_str())
+        .and_then(|x| url::Url::parse(x).ok())
+        .and_then(|x| {
+            x.query_pairs()
+                .find(|(key, _)| key == "xtags")
+                .map(|(_, value)| value.split(':').any(|x| x == "drc=1"))
+        })
+        .unwrap_or(false);
+
+    xtags_field || xtags_param
+}
+
+/// Decode standard or URL-safe base64, padding is optional
+fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(encoded.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for c in encoded.trim_end_matches('=').bytes() {
+        let value = match c {
+            b'A'..=b'Z' => c - b'A',
+            b'a'..=b'z' => c - b'a' + 26,
+            b'0'..=b'9' => c - b'0' + 52,
+            b'+' | b'-' => 62,
+            b'/' | b'_' => 63,
+            _ => return None,
+        };
+
+        buffer = (buffer << 6) | value as u32;
+        bits += 6;
+
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+    }
+
+    Some(decoded)
+}
+
+/// Remove one variant of the audio formats which have both an original and a DRC variant
+pub fn apply_drc_policy(formats: &mut Vec<VideoFormat>, policy: DrcPolicy) {
+    let keep_drc = match policy {
+        DrcPolicy::Avoid => false,
+        DrcPolicy::Prefer => true,
+        DrcPolicy::Allow => return,
+    };
+
+    let paired = formats
+        .iter()
+        .filter(|x| x.is_drc == keep_drc)
+        .map(|x| x.itag)
+        .collect::<std::collections::HashSet<_>>();
+
+    formats.retain(|x| x.is_drc == keep_drc || !paired.contains(&x.itag));
+}
+
+/// Expiry time of a googlevideo URL, `expire` is a query parameter or a path segment of manifest URLs
+pub fn get_url_expiry(url: &str) -> Option<std::time::SystemTime> {
+    static REGEX_EXPIRE: Lazy<Regex> = Lazy::new(|| Regex::new(r"[?&/]expire[=/](\d+)").unwrap());
+
+    REGEX_EXPIRE
+        .captures(url)
+        .and_then(|x| x[1].parse::<u64>().ok())
+        .map(|x| std::time::UNIX_EPOCH + std::time::Duration::from_secs(x))
 }
 
 #[cfg_attr(feature = "performance_analysis", flamer::flame)]
@@ -176,16 +486,27 @@ pub fn choose_format<'a>(
     formats: &'a [VideoFormat],
     options: &'a VideoOptions,
 ) -> Result<VideoFormat, VideoError> {
-    let filter = &options.filter;
+    let filter = match (&options.quality, &options.filter) {
+        // Audio formats have no quality label, so the default filter would never match
+        (VideoQuality::Label(_), VideoSearchOptions::Audio) => &VideoSearchOptions::Video,
+        (_, filter) => filter,
+    };
     let mut formats = formats.to_owned();
 
+    apply_drc_policy(&mut formats, options.drc);
     filter_formats(&mut formats, filter);
 
+    // Premium formats return 403 without the cookies of a Premium account
+    let request_options = &options.request_options;
+    if request_options.cookies.is_none() && request_options.cookies_file.is_none() {
+        formats.retain(|x| !x.is_premium());
+    }
+
     if formats.iter().any(|x| x.is_hls) {
         formats.retain(|fmt| (fmt.is_hls) || !(fmt.is_live));
     }
 
-    formats.sort_by(sort_formats);
+    formats.sort_by(|a, b| sort_formats_with_preferences(a, b, options));
     match &options.quality {
         VideoQuality::Highest => {
             filter_formats(&mut formats, filter);
@@ -220,7 +541,7 @@ pub fn choose_format<'a>(
         }
         VideoQuality::HighestVideo => {
             filter_formats(&mut formats, &VideoSearchOptions::Video);
-            formats.sort_by(sort_formats_by_video);
+            formats.sort_by(|a, b| sort_formats_by_video_with_preferences(a, b, options));
 
             let return_format = formats.first().ok_or(VideoError::FormatNotFound)?;
 
@@ -229,12 +550,43 @@ pub fn choose_format<'a>(
         VideoQuality::LowestVideo => {
             filter_formats(&mut formats, &VideoSearchOptions::Video);
 
-            formats.sort_by(sort_formats_by_video);
+            formats.sort_by(|a, b| sort_formats_by_video_with_preferences(a, b, options));
 
             let return_format = formats.last().ok_or(VideoError::FormatNotFound)?;
 
             Ok(return_format.clone())
         }
+        VideoQuality::Label(label) => {
+            let (height, fps, hdr) = parse_quality_label(label)
+                .ok_or_else(|| VideoError::InvalidQualityLabel(label.clone()))?;
+
+            formats.retain(|x| x.has_video);
+
+            let target_height = formats
+                .iter()
+                .map(quality_label_height)
+                .filter(|x| *x <= height)
+                .max()
+                .or_else(|| formats.iter().map(quality_label_height).min())
+                .ok_or(VideoError::FormatNotFound)?;
+
+            formats.retain(|x| quality_label_height(x) == target_height);
+
+            // Highest frame rate up to the requested one, then the requested dynamic range
+            let fps = fps.unwrap_or(30);
+            formats.sort_by_key(|x| {
+                let format_fps = x.fps.unwrap_or(0);
+                (
+                    format_fps > fps,
+                    std::cmp::Reverse(format_fps),
+                    x.is_hdr() != hdr,
+                )
+            });
+
+            let return_format = formats.first().ok_or(VideoError::FormatNotFound)?;
+
+            Ok(return_format.clone())
+        }
         VideoQuality::Custom(filter, func) => {
             filter_formats(&mut formats, filter);
 
@@ -247,6 +599,126 @@ pub fn choose_format<'a>(
     }
 }
 
+/// Pick either a progressive format with video and audio, or a video only format with an audio format to mux,
+/// whichever is closer to [`VideoOptions::quality`]. YouTube serves progressive formats only up to 360p or 720p.
+/// The audio of a pair has the same container as the video if possible, so it can be muxed without transcoding
+///
+/// Audio qualities return the audio format alone, custom qualities add audio if the chosen format has none
+/// # Example
+/// ```ignore
+///     let info = video.get_info().await.unwrap();
+///
+///     match choose_format_pair(&info.formats, &VideoOptions::default()).unwrap() {
+///         (format, None) => println!("Progressive {}", format.itag),
+///         (video, Some(audio)) => println!("Mux {} and {}", video.itag, audio.itag),
+///     }
+/// ```
+pub fn choose_format_pair(
+    formats: &[VideoFormat],
+    options: &VideoOptions,
+) -> Result<(VideoFormat, Option<VideoFormat>), VideoError> {
+    let with_quality = |quality: VideoQuality, filter: VideoSearchOptions| {
+        choose_format(
+            formats,
+            &VideoOptions {
+                quality,
+                filter,
+                ..options.clone()
+            },
+        )
+    };
+
+    let (video_quality, progressive_quality) = match &options.quality {
+        VideoQuality::HighestAudio | VideoQuality::LowestAudio => {
+            return Ok((choose_format(formats, options)?, None));
+        }
+        VideoQuality::Custom(..) => {
+            let format = choose_format(formats, options)?;
+
+            if format.has_audio || format.is_live {
+                return Ok((format, None));
+            }
+
+            let audio = choose_audio_for(formats, options, &format)?;
+            return Ok((format, Some(audio)));
+        }
+        VideoQuality::Highest | VideoQuality::HighestVideo => {
+            (VideoQuality::HighestVideo, VideoQuality::Highest)
+        }
+        VideoQuality::Lowest | VideoQuality::LowestVideo => {
+            (VideoQuality::LowestVideo, VideoQuality::Lowest)
+        }
+        VideoQuality::Label(label) => (
+            VideoQuality::Label(label.clone()),
+            VideoQuality::Label(label.clone()),
+        ),
+    };
+
+    let progressive = with_quality(progressive_quality, VideoSearchOptions::VideoAudio);
+
+    // Live and HLS formats always have both tracks
+    if let Ok(format) = progressive.as_ref() {
+        if format.is_live || format.is_hls {
+            return progressive.map(|x| (x, None));
+        }
+    }
+
+    let pair = with_quality(video_quality, VideoSearchOptions::Video).and_then(|video| {
+        let audio = choose_audio_for(formats, options, &video)?;
+        Ok((video, audio))
+    });
+
+    let (progressive, (video, audio)) = match (progressive, pair) {
+        (Ok(progressive), Ok(pair)) => (progressive, pair),
+        (Ok(progressive), Err(_)) => return Ok((progressive, None)),
+        (Err(_), Ok((video, audio))) => return Ok((video, Some(audio))),
+        (Err(err), Err(_)) => return Err(err),
+    };
+
+    let progressive_height = quality_label_height(&progressive);
+    let video_height = quality_label_height(&video);
+
+    let prefer_pair = match &options.quality {
+        VideoQuality::Lowest | VideoQuality::LowestVideo => video_height < progressive_height,
+        VideoQuality::Label(label) => {
+            let (height, _, _) = parse_quality_label(label)
+                .ok_or_else(|| VideoError::InvalidQualityLabel(label.clone()))?;
+
+            (video_height <= height, video_height)
+                > (progressive_height <= height, progressive_height)
+        }
+        _ => video_height > progressive_height,
+    };
+
+    if prefer_pair {
+        Ok((video, Some(audio)))
+    } else {
+        Ok((progressive, None))
+    }
+}
+
+/// Highest audio only format, of the same container as `video` if there is one
+fn choose_audio_for(
+    formats: &[VideoFormat],
+    options: &VideoOptions,
+    video: &VideoFormat,
+) -> Result<VideoFormat, VideoError> {
+    let container = video.mime_type.container.clone();
+    let audio_options = |filter: VideoSearchOptions| VideoOptions {
+        quality: VideoQuality::HighestAudio,
+        filter,
+        ..options.clone()
+    };
+
+    choose_format(
+        formats,
+        &audio_options(VideoSearchOptions::Custom(std::sync::Arc::new(
+            move |x: &VideoFormat| x.mime_type.container == container,
+        ))),
+    )
+    .or_else(|_| choose_format(formats, &audio_options(VideoSearchOptions::Audio)))
+}
+
 #[cfg_attr(feature = "performance_analysis", flamer::flame)]
 pub fn sort_formats_by<F>(a: &VideoFormat, b: &VideoFormat, sort_by: Vec<F>) -> std::cmp::Ordering
 where
@@ -266,24 +738,82 @@ where
     res
 }
 
-#[cfg_attr(feature = "performance_analysis", flamer::flame)]
-pub fn sort_formats_by_video(a: &VideoFormat, b: &VideoFormat) -> std::cmp::Ordering {
+/// Leading number of the quality label, e.g. `1080` of `1080p60 HDR`
+fn quality_label_height(form: &VideoFormat) -> i32 {
+    form.quality_label
+        .as_deref()
+        .and_then(|x| PARSE_INT_REGEX.captures(x))
+        .and_then(|x| x.get(0))
+        .and_then(|x| x.as_str().parse::<i32>().ok())
+        .unwrap_or(0i32)
+}
+
+/// Parse quality labels like `720p`, `1080p60` and `2160p60 HDR` to height, frame rate and HDR
+fn parse_quality_label(label: &str) -> Option<(i32, Option<u64>, bool)> {
+    let label = label.trim().to_lowercase();
+    let (label, hdr) = match label.strip_suffix("hdr") {
+        Some(label) => (label.trim_end(), true),
+        None => (label.as_str(), false),
+    };
+    let (height, fps) = label.split_once('p').unwrap_or((label, ""));
+
+    let height = height.parse::<i32>().ok()?;
+    let fps = match fps {
+        "" => None,
+        fps => Some(fps.parse::<u64>().ok()?),
+    };
+
+    Some((height, fps, hdr))
+}
+
+/// Rank of [`VideoOptions::prefer_hdr`] and [`VideoOptions::prefer_60fps`], only breaks ties between formats of the same resolution
+fn preference_rank(form: &VideoFormat, options: &VideoOptions) -> i32 {
+    (options.prefer_hdr && form.is_hdr()) as i32 * 2
+        + (options.prefer_60fps && form.fps.unwrap_or(0) > 30) as i32
+}
+
+/// [`sort_formats`] with HDR and high frame rate formats first among formats of the same resolution if preferred
+pub fn sort_formats_with_preferences(
+    a: &VideoFormat,
+    b: &VideoFormat,
+    options: &VideoOptions,
+) -> std::cmp::Ordering {
     sort_formats_by(
         a,
         b,
         [
-            |form: &VideoFormat| {
-                let quality_label = form.quality_label.clone().unwrap_or("".to_string());
+            |form: &VideoFormat| form.is_hls as i32,
+            |form: &VideoFormat| form.is_dash_mpd as i32,
+            |form: &VideoFormat| (form.has_video && form.has_audio) as i32,
+            |form: &VideoFormat| form.has_video as i32,
+            |form: &VideoFormat| (form.content_length.unwrap_or(0) > 0) as i32,
+            quality_label_height,
+        ]
+        .to_vec(),
+    )
+    .then_with(|| preference_rank(b, options).cmp(&preference_rank(a, options)))
+    .then_with(|| sort_formats(a, b))
+}
 
-                let quality_label = PARSE_INT_REGEX
-                    .captures(&quality_label)
-                    .and_then(|x| x.get(0))
-                    .map(|x| x.as_str())
-                    .and_then(|x| x.parse::<i32>().ok())
-                    .unwrap_or(0i32);
+/// [`sort_formats_by_video`] with HDR and high frame rate formats first among formats of the same resolution if preferred
+pub fn sort_formats_by_video_with_preferences(
+    a: &VideoFormat,
+    b: &VideoFormat,
+    options: &VideoOptions,
+) -> std::cmp::Ordering {
+    quality_label_height(b)
+        .cmp(&quality_label_height(a))
+        .then_with(|| preference_rank(b, options).cmp(&preference_rank(a, options)))
+        .then_with(|| sort_formats_by_video(a, b))
+}
 
-                quality_label
-            },
+#[cfg_attr(feature = "performance_analysis", flamer::flame)]
+pub fn sort_formats_by_video(a: &VideoFormat, b: &VideoFormat) -> std::cmp::Ordering {
+    sort_formats_by(
+        a,
+        b,
+        [
+            quality_label_height,
             |form: &VideoFormat| form.bitrate as i32,
             // getVideoEncodingRank,
             |form: &VideoFormat| {
@@ -333,27 +863,8 @@ pub fn sort_formats(a: &VideoFormat, b: &VideoFormat) -> std::cmp::Ordering {
             |form: &VideoFormat| form.is_dash_mpd as i32,
             |form: &VideoFormat| (form.has_video && form.has_audio) as i32,
             |form: &VideoFormat| form.has_video as i32,
-            |form: &VideoFormat| {
-                (form
-                    .content_length
-                    .clone()
-                    .unwrap_or("0".to_string())
-                    .parse::<u64>()
-                    .unwrap_or(0)
-                    > 0) as i32
-            },
-            |form: &VideoFormat| {
-                let quality_label = form.quality_label.clone().unwrap_or("".to_string());
-
-                let quality_label = PARSE_INT_REGEX
-                    .captures(&quality_label)
-                    .and_then(|x| x.get(0))
-                    .map(|x| x.as_str())
-                    .and_then(|x| x.parse::<i32>().ok())
-                    .unwrap_or(0i32);
-
-                quality_label
-            },
+            |form: &VideoFormat| (form.content_length.unwrap_or(0) > 0) as i32,
+            quality_label_height,
             |form: &VideoFormat| form.bitrate as i32,
             |form: &VideoFormat| form.audio_bitrate.unwrap_or(0) as i32,
             // getVideoEncodingRank,
@@ -395,6 +906,72 @@ pub fn get_video_id(url: &str) -> Option<String> {
     }
 }
 
+/// Excavate clip id from `youtube.com/clip/...` URLs
+pub fn get_clip_id(url: &str) -> Option<String> {
+    let parsed = url::Url::parse(url.trim()).ok()?;
+
+    let host = parsed.host_str()?;
+    if host != "youtube.com" && !host.ends_with(".youtube.com") {
+        return None;
+    }
+
+    let mut segments = parsed.path_segments()?;
+
+    match (segments.next(), segments.next()) {
+        (Some("clip"), Some(id)) if !id.is_empty() => Some(id.to_string()),
+        _ => None,
+    }
+}
+
+/// Excavate start time from `t` or `start` parameter of URLs (e.g. `t=90`, `t=1m30s`, `#t=1h2m`)
+pub fn get_start_time(url: &str) -> Option<std::time::Duration> {
+    let parsed = url::Url::parse(url.trim()).ok()?;
+
+    let fragment_pairs = parsed
+        .fragment()
+        .map(|x| url::form_urlencoded::parse(x.as_bytes()).collect::<Vec<_>>())
+        .unwrap_or_default();
+
+    parsed
+        .query_pairs()
+        .chain(fragment_pairs)
+        .find(|(name, _)| name == "t" || name == "start")
+        .and_then(|(_, value)| parse_time_param(&value))
+        .map(std::time::Duration::from_secs)
+}
+
+/// Parse `90`, `90s` or `1h2m3s` formatted time to seconds
+fn parse_time_param(value: &str) -> Option<u64> {
+    let mut seconds = 0;
+    let mut number = String::new();
+
+    for character in value.trim().chars() {
+        if character.is_ascii_digit() {
+            number.push(character);
+            continue;
+        }
+
+        let multiplier = match character {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return None,
+        };
+        seconds = number
+            .parse::<u64>()
+            .ok()?
+            .checked_mul(multiplier)
+            .and_then(|x| x.checked_add(seconds))?;
+        number.clear();
+    }
+
+    if !number.is_empty() {
+        seconds = number.parse::<u64>().ok()?.checked_add(seconds)?;
+    }
+
+    Some(seconds)
+}
+
 #[cfg_attr(feature = "performance_analysis", flamer::flame)]
 pub fn validate_id(id: String) -> bool {
     let id_regex = Regex::new(r"^[a-zA-Z0-9-_]{11}$").unwrap();
@@ -456,6 +1033,49 @@ fn get_url_video_id(url: &str) -> Option<String> {
     }
 }
 
+/// Get the value of `name` query parameter from the URL if exists
+#[cfg_attr(feature = "performance_analysis", flamer::flame)]
+pub fn get_url_param(url: &str, name: &str) -> Option<String> {
+    let parsed = url::Url::parse(url.trim()).ok()?;
+
+    let value = parsed
+        .query_pairs()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.to_string());
+
+    value
+}
+
+/// Append PO token to the stream URL as `pot` query parameter
+/// - If URL cannot parsed or already has `pot` parameter, URL returns unchanged
+pub fn add_po_token(url: &str, po_token: &str) -> String {
+    match url::Url::parse(url) {
+        Ok(mut parsed) if !parsed.query_pairs().any(|(key, _)| key == "pot") => {
+            parsed.query_pairs_mut().append_pair("pot", po_token);
+            parsed.to_string()
+        }
+        _ => url.to_string(),
+    }
+}
+
+/// Set `name` query parameter of the URL to `value`, replacing every existing value of it
+pub fn set_url_param(url: &str, name: &str, value: &str) -> Result<url::Url, url::ParseError> {
+    let mut parsed = url::Url::parse(url)?;
+    let pairs: Vec<(String, String)> = parsed
+        .query_pairs()
+        .filter(|(key, _)| key != name)
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect();
+
+    parsed
+        .query_pairs_mut()
+        .clear()
+        .extend_pairs(pairs)
+        .append_pair(name, value);
+
+    Ok(parsed)
+}
+
 #[cfg_attr(feature = "performance_analysis", flamer::flame)]
 pub fn get_text(obj: &serde_json::Value) -> &serde_json::Value {
     let null_referance = &serde_json::Value::Null;
@@ -673,6 +1293,18 @@ pub fn clean_video_details(
             .get("isLiveContent")
             .and_then(|x| x.as_bool())
             .unwrap_or(false),
+        is_post_live_dvr: is_post_live(player_response),
+        is_live_dvr_enabled: data
+            .get("isLiveDvrEnabled")
+            .and_then(|x| x.as_bool())
+            .unwrap_or(false),
+        latency_class: get_latency_class(&data),
+        is_upcoming: data
+            .get("isUpcoming")
+            .and_then(|x| x.as_bool())
+            .unwrap_or(false)
+            || is_not_yet_broadcasted(player_response),
+        scheduled_start_time: get_scheduled_start_time(player_response),
         thumbnails: data
             .get("thumbnail")
             .and_then(|x| x.get("thumbnails"))
@@ -713,6 +1345,9 @@ pub fn clean_video_details(
                     .to_string(),
             })
             .collect::<Vec<Thumbnail>>(),
+        clip_start_ms: None,
+        clip_end_ms: None,
+        start_offset: None,
     }
 }
 
@@ -780,86 +1415,305 @@ pub fn is_rental(player_response: &serde_json::Value) -> bool {
             .is_some()
 }
 
+/// DRM protected videos (e.g. movies) have `licenseInfos` and only formats with `drmFamilies`, which cannot be downloaded
+pub fn is_drm_protected(player_response: &serde_json::Value) -> bool {
+    let streaming_data = &player_response["streamingData"];
+
+    let has_license = streaming_data["licenseInfos"]
+        .as_array()
+        .map(|x| !x.is_empty())
+        .unwrap_or(false);
+
+    let formats = ["formats", "adaptiveFormats"]
+        .iter()
+        .filter_map(|key| streaming_data[key].as_array())
+        .flatten()
+        .collect::<Vec<_>>();
+
+    (has_license || !formats.is_empty()) && formats.iter().all(|x| x.get("drmFamilies").is_some())
+}
+
 #[cfg_attr(feature = "performance_analysis", flamer::flame)]
 pub fn is_not_yet_broadcasted(player_response: &serde_json::Value) -> bool {
-    let playability = player_response.get("playabilityStatus");
+    get_playability_status(player_response).is_upcoming()
+}
 
-    if playability.is_none() {
-        return false;
+/// Scheduled start time of an upcoming live stream or premiere from its offline slate
+pub fn get_scheduled_start_time(player_response: &serde_json::Value) -> Option<u64> {
+    player_response["playabilityStatus"]["liveStreamability"]["liveStreamabilityRenderer"]
+        ["offlineSlate"]["liveStreamOfflineSlateRenderer"]["scheduledStartTime"]
+        .as_str()
+        .and_then(|x| x.parse::<u64>().ok())
+}
+
+/// Latency class of a live stream from `videoDetails`, older responses only have `isLowLatencyLiveStream`
+pub fn get_latency_class(video_details: &serde_json::Value) -> Option<LatencyClass> {
+    match video_details["latencyClass"].as_str() {
+        Some(x) if x.ends_with("_ULTRA_LOW") => Some(LatencyClass::UltraLow),
+        Some(x) if x.ends_with("_LOW") => Some(LatencyClass::Low),
+        Some(x) if x.ends_with("_NORMAL") => Some(LatencyClass::Normal),
+        _ if video_details["isLowLatencyLiveStream"].as_bool() == Some(true) => {
+            Some(LatencyClass::Low)
+        }
+        _ => None,
     }
+}
 
-    playability
-        .and_then(|x| x.get("status"))
-        .and_then(|x| x.as_str())
-        .unwrap_or("")
-        == "LIVE_STREAM_OFFLINE"
+/// Delay between checks of an upcoming live stream asked by YouTube (`pollDelayMs` of `liveStreamability`)
+pub fn get_live_poll_delay(player_response: &serde_json::Value) -> Option<std::time::Duration> {
+    let poll_delay = &player_response["playabilityStatus"]["liveStreamability"]
+        ["liveStreamabilityRenderer"]["pollDelayMs"];
+
+    poll_delay
+        .as_u64()
+        .or_else(|| poll_delay.as_str().and_then(|x| x.parse::<u64>().ok()))
+        .map(std::time::Duration::from_millis)
 }
 
+/// Live stream ended and its formats are only served from the live manifests until it is processed
 #[cfg_attr(feature = "performance_analysis", flamer::flame)]
-pub fn is_play_error(player_response: &serde_json::Value, statuses: Vec<&str>) -> bool {
-    let playability = player_response
-        .get("playabilityStatus")
-        .and_then(|x| x.get("status").and_then(|x| x.as_str()));
+pub fn is_post_live(player_response: &serde_json::Value) -> bool {
+    let video_details = &player_response["videoDetails"];
 
-    if let Some(playability_some) = playability {
-        return statuses.contains(&playability_some);
+    if video_details["isPostLiveDvr"].as_bool().unwrap_or(false) {
+        return true;
     }
 
-    false
+    // Older responses do not have the flag, finished streams still have manifests but no regular formats
+    let streaming_data = &player_response["streamingData"];
+    let has_regular_formats = ["formats", "adaptiveFormats"].iter().any(|key| {
+        streaming_data[key]
+            .as_array()
+            .map(|x| {
+                x.iter().any(|format| {
+                    !format["url"]
+                        .as_str()
+                        .unwrap_or("")
+                        .contains("yt_live_broadcast")
+                })
+            })
+            .unwrap_or(false)
+    });
+
+    video_details["isLiveContent"].as_bool().unwrap_or(false)
+        && !video_details["isLive"].as_bool().unwrap_or(false)
+        && !video_details["isUpcoming"].as_bool().unwrap_or(false)
+        && streaming_data["hlsManifestUrl"].is_string()
+        && !has_regular_formats
 }
 
 #[cfg_attr(feature = "performance_analysis", flamer::flame)]
-pub fn is_private_video(player_response: &serde_json::Value) -> bool {
-    if player_response
-        .get("playabilityStatus")
-        .and_then(|x| x.get("status"))
-        .and_then(|x| x.as_str())
-        .unwrap_or("")
-        == "LOGIN_REQUIRED"
-    {
-        return true;
-    }
+pub fn get_playability_status(player_response: &serde_json::Value) -> PlayabilityStatus {
+    let playability = &player_response["playabilityStatus"];
+
+    let text = |value: &serde_json::Value| {
+        value["simpleText"]
+            .as_str()
+            .map(|x| x.to_string())
+            .or_else(|| {
+                value["runs"].as_array().map(|runs| {
+                    runs.iter()
+                        .filter_map(|x| x["text"].as_str())
+                        .collect::<String>()
+                })
+            })
+            .filter(|x| !x.is_empty())
+    };
 
-    false
+    let error_message = &playability["errorScreen"]["playerErrorMessageRenderer"];
+
+    PlayabilityStatus {
+        status: playability["status"].as_str().unwrap_or("").to_string(),
+        reason: playability["reason"]
+            .as_str()
+            .map(|x| x.to_string())
+            .or_else(|| text(&error_message["reason"])),
+        sub_reason: text(&error_message["subreason"]),
+        available_countries: player_response["microformat"]["playerMicroformatRenderer"]
+            ["availableCountries"]
+            .as_array()
+            .map(|x| {
+                x.iter()
+                    .filter_map(|x| x.as_str().map(|x| x.to_string()))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
 }
 
-// Cache hit reported ~90% of the time with one entry
-// 98% of the time with two entries but twice as much memory used (Probably insignificant)
-// No gain for the first execution but then ~80ms gain per query on my computer
-static FUNCTIONS: Lazy<RwLock<Option<(String, Vec<(String, String)>)>>> =
-    Lazy::new(|| RwLock::new(None));
+/// Player response is blocked by age verification
+#[cfg_attr(feature = "performance_analysis", flamer::flame)]
+pub fn is_age_gated(player_response: &serde_json::Value) -> bool {
+    get_playability_status(player_response).is_age_restricted()
+}
 
+/// Player response has no usable formats but another InnerTube client may return them
 #[cfg_attr(feature = "performance_analysis", flamer::flame)]
-pub async fn get_functions(
+pub fn needs_client_fallback(player_response: &serde_json::Value) -> bool {
+    let playability_status = get_playability_status(player_response);
+
+    if playability_status.is_removed()
+        || playability_status.is_upcoming()
+        || is_rental(player_response)
+    {
+        return false;
+    }
+
+    matches!(
+        playability_status.status.as_str(),
+        "LOGIN_REQUIRED" | "UNPLAYABLE"
+    ) || player_response.get("streamingData").is_none()
+}
+
+// Cache hit reported ~90% of the time with one entry
+// 98% of the time with two entries but twice as much memory used (Probably insignificant)
+// No gain for the first execution but then ~80ms gain per query on my computer
+static PLAYER_CACHE: Lazy<Mutex<LruCache<PlayerCache>>> =
+    Lazy::new(|| Mutex::new(LruCache::new(2, None)));
+
+/// Configure in-memory cache of the extracted player functions
+/// - `capacity` is the count of player versions kept at the same time, `0` disables the cache
+/// - `ttl` is the lifetime of an entry, [`None`] keeps entries until they are evicted
+/// # Example
+/// ```ignore
+/// set_player_cache_options(4, Some(std::time::Duration::from_secs(60 * 60)));
+/// ```
+pub fn set_player_cache_options(capacity: usize, ttl: Option<std::time::Duration>) {
+    PLAYER_CACHE
+        .lock()
+        .expect("IMPOSSIBLE")
+        .configure(capacity, ttl);
+}
+
+#[cfg_attr(feature = "performance_analysis", flamer::flame)]
+pub async fn get_functions(
     html5player: impl Into<String>,
     client: &reqwest_middleware::ClientWithMiddleware,
+    cache_dir: Option<&std::path::Path>,
 ) -> Result<Vec<(String, String)>, VideoError> {
+    Ok(get_player_cache(html5player, client, cache_dir)
+        .await?
+        .functions)
+}
+
+/// Get `signatureTimestamp` of the player script. Player requests of ciphered clients must send it,
+/// otherwise returned signatures can not be deciphered with the current player functions
+pub async fn get_signature_timestamp(
+    html5player: impl Into<String>,
+    client: &reqwest_middleware::ClientWithMiddleware,
+    cache_dir: Option<&std::path::Path>,
+) -> Result<Option<u64>, VideoError> {
+    Ok(get_player_cache(html5player, client, cache_dir)
+        .await?
+        .signature_timestamp)
+}
+
+#[cfg_attr(feature = "tracing", tracing::instrument(skip_all, err))]
+async fn get_player_cache(
+    html5player: impl Into<String>,
+    client: &reqwest_middleware::ClientWithMiddleware,
+    cache_dir: Option<&std::path::Path>,
+) -> Result<PlayerCache, VideoError> {
     let mut url = url::Url::parse(BASE_URL).expect("IMPOSSIBLE");
     url.set_path(&html5player.into());
     url.query_pairs_mut().clear();
 
     let url = url.as_str();
 
-    {
-        // Check if an URL is already cached
-        if let Some((cached_url, cached_functions)) = FUNCTIONS.read().await.as_ref() {
-            // Check if the cache is the same as the URL
-            if cached_url == url {
-                return Ok(cached_functions.clone());
+    // Check if an URL is already cached
+    if let Some(player_cache) = PLAYER_CACHE.lock().expect("IMPOSSIBLE").get(url) {
+        #[cfg(feature = "metrics")]
+        crate::metrics::increment(crate::metrics::CIPHER_CACHE_HITS, 1);
+
+        return Ok(player_cache);
+    }
+
+    let player_cache = match cache_dir.and_then(|x| read_player_cache(x, url)) {
+        Some(player_cache) => {
+            #[cfg(feature = "metrics")]
+            crate::metrics::increment(crate::metrics::CIPHER_CACHE_HITS, 1);
+
+            player_cache
+        }
+        None => {
+            #[cfg(feature = "metrics")]
+            crate::metrics::increment(crate::metrics::CIPHER_CACHE_MISSES, 1);
+
+            let response = get_html(client, url, None).await?;
+
+            let player_cache = PlayerCache {
+                signature_timestamp: extract_signature_timestamp(&response),
+                functions: extract_functions(response),
+            };
+
+            if let Some(cache_dir) = cache_dir {
+                // Disk cache is best effort, failing to write it does not fail the request
+                write_player_cache(cache_dir, url, &player_cache);
             }
+
+            player_cache
         }
+    };
+
+    // Update the cache
+    PLAYER_CACHE
+        .lock()
+        .expect("IMPOSSIBLE")
+        .insert(url.to_string(), player_cache.clone());
+
+    Ok(player_cache)
+}
+
+/// Extracted player script data, kept in memory and persisted in the disk cache
+#[derive(Clone, Serialize, Deserialize)]
+struct PlayerCache {
+    functions: Vec<(String, String)>,
+    #[serde(rename = "signatureTimestamp")]
+    signature_timestamp: Option<u64>,
+}
+
+/// Player script URLs are unique per player version, so they are used as the cache file name
+fn player_cache_path(cache_dir: &std::path::Path, url: &str) -> std::path::PathBuf {
+    let file_name = url
+        .trim_start_matches("https://www.youtube.com/")
+        .chars()
+        .map(|x| if x.is_ascii_alphanumeric() { x } else { '_' })
+        .collect::<String>();
+
+    cache_dir.join(format!("{file_name}.json"))
+}
+
+fn read_player_cache(cache_dir: &std::path::Path, url: &str) -> Option<PlayerCache> {
+    let content = std::fs::read_to_string(player_cache_path(cache_dir, url)).ok()?;
+
+    let player_cache: PlayerCache = serde_json::from_str(&content).ok()?;
+
+    // Ignore broken caches to download the player script again
+    if player_cache.functions.is_empty() {
+        return None;
     }
 
-    let response = get_html(client, url, None).await?;
+    Some(player_cache)
+}
 
-    let functions = extract_functions(response);
+fn write_player_cache(cache_dir: &std::path::Path, url: &str, player_cache: &PlayerCache) {
+    if player_cache.functions.is_empty() || std::fs::create_dir_all(cache_dir).is_err() {
+        return;
+    }
 
-    // Update the cache
-    {
-        *FUNCTIONS.write().await = Some((url.to_string(), functions.clone()));
+    if let Ok(content) = serde_json::to_string(player_cache) {
+        let _ = std::fs::write(player_cache_path(cache_dir, url), content);
     }
+}
+
+fn extract_signature_timestamp(body: &str) -> Option<u64> {
+    static SIGNATURE_TIMESTAMP_REGEX: Lazy<Regex> =
+        Lazy::new(|| Regex::new(r"(?:signatureTimestamp|sts)\s*:\s*(\d{5})").unwrap());
 
-    Ok(functions)
+    SIGNATURE_TIMESTAMP_REGEX
+        .captures(body)
+        .and_then(|x| x.get(1))
+        .and_then(|x| x.as_str().parse::<u64>().ok())
 }
 
 #[cfg_attr(feature = "performance_analysis", flamer::flame)]
@@ -1005,7 +1859,7 @@ pub async fn get_html(
     .await;
 
     if request.is_err() {
-        return Err(VideoError::ReqwestMiddleware(request.err().unwrap()));
+        return Err(VideoError::from(request.err().unwrap()));
     }
 
     let response_first = request.unwrap().text().await;
@@ -1017,6 +1871,276 @@ pub async fn get_html(
     Ok(response_first.unwrap())
 }
 
+/// Build cookie jar from [`RequestOptions::cookies`] and [`RequestOptions::cookies_file`]
+/// - Returns [`None`] if both of them are empty and [`RequestOptions::enable_cookies`] is `false`
+#[cfg(not(target_arch = "wasm32"))]
+pub fn build_cookie_jar(
+    request_options: &RequestOptions,
+) -> Result<Option<reqwest::cookie::Jar>, VideoError> {
+    if request_options.cookies.is_none()
+        && request_options.cookies_file.is_none()
+        && !request_options.enable_cookies
+    {
+        return Ok(None);
+    }
+
+    let jar = reqwest::cookie::Jar::default();
+
+    if let Some(cookies) = &request_options.cookies {
+        let host = "https://www.youtube.com".parse::<url::Url>().unwrap();
+
+        // Raw cookie header contains multiple cookies, add them one by one
+        for cookie in cookies
+            .split(';')
+            .map(|x| x.trim())
+            .filter(|x| x.contains('='))
+        {
+            jar.add_cookie_str(&format!("{cookie}; Domain=youtube.com; Path=/"), &host);
+        }
+    }
+
+    if let Some(cookies_file) = &request_options.cookies_file {
+        let content = std::fs::read_to_string(cookies_file)
+            .map_err(|e| VideoError::CookieError(format!("{}: {e}", cookies_file.display())))?;
+
+        for (domain, path, name, value) in parse_netscape_cookies(&content) {
+            let host = match url::Url::parse(&format!("https://{}", domain.trim_start_matches('.')))
+            {
+                Ok(host) => host,
+                Err(_) => continue,
+            };
+
+            jar.add_cookie_str(
+                &format!("{name}={value}; Domain={domain}; Path={path}"),
+                &host,
+            );
+        }
+    }
+
+    Ok(Some(jar))
+}
+
+/// Browsers keep the cookies of fetch requests themselves, so there is no jar on `wasm32`
+#[cfg(target_arch = "wasm32")]
+pub fn build_cookie_jar(
+    _request_options: &RequestOptions,
+) -> Result<Option<crate::platform::Jar>, VideoError> {
+    Ok(None)
+}
+
+/// Parse Netscape formatted `cookies.txt` content to `(domain, path, name, value)` list
+pub fn parse_netscape_cookies(content: &str) -> Vec<(String, String, String, String)> {
+    content
+        .lines()
+        .filter_map(|line| {
+            // `#HttpOnly_` prefixed lines are cookies, other `#` lines are comments
+            let line = line.trim().trim_start_matches("#HttpOnly_");
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+
+            let fields = line.split('\t').collect::<Vec<&str>>();
+            if fields.len() < 7 {
+                return None;
+            }
+
+            Some((
+                fields[0].to_string(),
+                fields[2].to_string(),
+                fields[5].to_string(),
+                fields[6].to_string(),
+            ))
+        })
+        .collect()
+}
+
+/// Build the HTTP client with retry middleware according to the [`RequestOptions`]
+#[cfg_attr(target_arch = "wasm32", allow(unused_mut, unused_variables))]
+pub fn build_client(
+    request_options: &RequestOptions,
+) -> Result<reqwest_middleware::ClientWithMiddleware, VideoError> {
+    if let Some(client) = &request_options.client {
+        return Ok(client.as_ref().clone());
+    }
+
+    let ipv6 = request_options
+        .ipv6_block
+        .as_ref()
+        .map(get_random_v6_ip)
+        .transpose()?;
+    let jar = build_cookie_jar(request_options)?.map(std::sync::Arc::new);
+
+    // Clients of the same options share the address and the cookies, format URLs are bound to the address.
+    // The fetch API of `wasm32` does not support any of them
+    let configure = |mut client: reqwest::ClientBuilder| {
+        #[cfg(not(target_arch = "wasm32"))]
+        {
+            if let Some(ipv6) = ipv6 {
+                client = client.local_address(ipv6);
+            }
+
+            if let Some(jar) = &jar {
+                client = client.cookie_provider(jar.clone());
+            }
+
+            if let Some(connect_timeout) = request_options.connect_timeout {
+                client = client.connect_timeout(connect_timeout);
+            }
+
+            if let Some(timeout) = request_options.timeout {
+                client = client.timeout(timeout);
+            }
+        }
+
+        client
+    };
+
+    let mut client = configure(reqwest::Client::builder());
+
+    #[cfg(not(target_arch = "wasm32"))]
+    if let Some(proxy) = &request_options.proxy {
+        client = client.proxy(proxy.clone());
+    }
+
+    let client = client.build().map_err(VideoError::Reqwest)?;
+
+    let mut headers = request_options.headers.clone().unwrap_or_default();
+
+    if let Some(user_agent) = &request_options.user_agent {
+        headers.insert(
+            reqwest::header::USER_AGENT,
+            reqwest::header::HeaderValue::from_str(user_agent)
+                .map_err(|x| VideoError::InvalidHeader(x.to_string()))?,
+        );
+    }
+
+    let retry_policy = reqwest_retry::policies::ExponentialBackoff::builder()
+        .retry_bounds(
+            std::time::Duration::from_millis(500),
+            std::time::Duration::from_millis(10000),
+        )
+        .build_with_max_retries(3);
+
+    // Rate limit middleware runs inside the retry middleware, so 429 is not retried twice
+    let client = request_options.middlewares.iter().fold(
+        reqwest_middleware::ClientBuilder::new(client),
+        |client, middleware| client.with_arc(middleware.clone()),
+    );
+
+    let client = client
+        .with(HeadersMiddleware {
+            headers,
+            user_agent_rotation: request_options.user_agent_rotation,
+            browser: random_browser_identity(),
+        })
+        .with(ConsentMiddleware::new(jar.clone()))
+        .with(reqwest_retry::RetryTransientMiddleware::new_with_policy(
+            retry_policy,
+        ))
+        .with(RateLimitMiddleware {
+            backoff: request_options.rate_limit_backoff.clone(),
+        });
+
+    // Runs inside the retry middlewares to see every attempt
+    #[cfg(feature = "metrics")]
+    let client = client.with(crate::metrics::MetricsMiddleware);
+
+    let client = match &request_options.hooks {
+        Some(hooks) => client.with(HookMiddleware {
+            hooks: hooks.clone(),
+        }),
+        None => client,
+    };
+
+    // QUIC can not be tunneled through HTTP proxies
+    #[cfg(feature = "http3")]
+    let client = if request_options.proxy.is_none() {
+        client.with(Http3Middleware {
+            client: configure(reqwest::Client::builder().http3_prior_knowledge())
+                .build()
+                .map_err(VideoError::Reqwest)?,
+        })
+    } else {
+        client
+    };
+
+    Ok(client.build())
+}
+
+/// Send POST request to the InnerTube `endpoint` (e.g. `next`, `browse`) with `WEB` client context
+pub async fn innertube_request(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    endpoint: &str,
+    body: serde_json::Value,
+) -> Result<serde_json::Value, VideoError> {
+    innertube_request_with_client(client, endpoint, body, &InnertubeClient::Web).await
+}
+
+/// Send POST request to the InnerTube `endpoint` with the context of given [`InnertubeClient`]
+pub async fn innertube_request_with_client(
+    client: &reqwest_middleware::ClientWithMiddleware,
+    endpoint: &str,
+    body: serde_json::Value,
+    innertube_client: &InnertubeClient,
+) -> Result<serde_json::Value, VideoError> {
+    let context = serde_json::json!({
+        "context": innertube_client.context(),
+    });
+
+    // Keep the context given in body if exists
+    let mut request_body = context;
+    merge(&mut request_body, &body);
+
+    let mut headers = DEFAULT_HEADERS.clone();
+    headers.insert(
+        "X-YouTube-Client-Name",
+        reqwest::header::HeaderValue::from(innertube_client.id()),
+    );
+    headers.insert(
+        "X-YouTube-Client-Version",
+        reqwest::header::HeaderValue::from_static(innertube_client.version()),
+    );
+    if let Some(user_agent) = innertube_client.user_agent() {
+        headers.insert(
+            reqwest::header::USER_AGENT,
+            reqwest::header::HeaderValue::from_static(user_agent),
+        );
+    }
+
+    let response = client
+        .post(format!(
+            "{INNERTUBE_URL}{endpoint}?key={INNERTUBE_API_KEY}&prettyPrint=false"
+        ))
+        .headers(headers)
+        .json(&request_body)
+        .send()
+        .await
+        .map_err(VideoError::from)?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+
+        return Err(VideoError::InnertubeError {
+            status: status.as_u16(),
+            message: innertube_error_message(&body),
+        });
+    }
+
+    response
+        .json::<serde_json::Value>()
+        .await
+        .map_err(|_| VideoError::BodyCannotParsed)
+}
+
+/// `error.message` of an InnerTube error body (e.g. `Request contains an invalid argument.`), otherwise the body itself
+fn innertube_error_message(body: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|x| x["error"]["message"].as_str().map(|x| x.to_string()))
+        .unwrap_or_else(|| body.trim().to_string())
+}
+
 /// Try to generate IPv6 with custom valid block
 /// # Example
 /// ```ignore
@@ -1101,6 +2225,74 @@ pub fn normalize_ip(ip: impl Into<String>) -> Vec<u16> {
 }
 
 #[cfg_attr(feature = "performance_analysis", flamer::flame)]
+/// Device names of Windows, files cannot be named after them even with an extension
+const WINDOWS_RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Turn a video title (or any text) into a file name which is valid on Windows, macOS and Linux
+/// - Path separators, characters invalid on Windows and control characters are replaced
+/// - Windows device names (e.g. `CON`, `nul.txt`) get the replacement appended
+/// - Trailing dots and spaces are removed, Windows drops them silently
+/// - Long names are cut without splitting characters or emoji sequences
+/// # Example
+/// ```ignore
+///     let file_name = sanitize_filename("AC/DC: Back In Black?", &SanitizeOptions::default());
+///
+///     assert_eq!(file_name, "AC_DC_ Back In Black_");
+/// ```
+pub fn sanitize_filename(title: &str, options: &SanitizeOptions) -> String {
+    let mut file_name = title
+        .graphemes(true)
+        .filter(|x| !(options.remove_emoji && x.chars().any(is_emoji)))
+        .map(|x| match x {
+            "/" | "\\" | ":" | "*" | "?" | "\"" | "<" | ">" | "|" => options.replacement.as_str(),
+            x if x.chars().any(char::is_control) => options.replacement.as_str(),
+            x => x,
+        })
+        .collect::<String>();
+
+    if let Some(max_length) = options.max_length {
+        let mut length = 0;
+
+        file_name = file_name
+            .graphemes(true)
+            .take_while(|x| {
+                length += x.len();
+                length <= max_length
+            })
+            .collect();
+    }
+
+    let mut file_name = file_name
+        .trim_start()
+        .trim_end_matches(|x: char| x == '.' || x.is_whitespace())
+        .to_string();
+
+    let stem = file_name.split('.').next().unwrap_or("").trim_end();
+    if WINDOWS_RESERVED_NAMES
+        .iter()
+        .any(|x| x.eq_ignore_ascii_case(stem))
+    {
+        file_name.insert_str(stem.len(), &options.replacement);
+    }
+
+    if file_name.is_empty() {
+        return options.replacement.clone();
+    }
+
+    file_name
+}
+
+/// Pictographs, symbols, regional indicators and the joiners of emoji sequences
+fn is_emoji(x: char) -> bool {
+    matches!(
+        x as u32,
+        0x1F000..=0x1FAFF | 0x2600..=0x27BF | 0x2B00..=0x2BFF | 0xFE0F | 0x200D | 0xE0020..=0xE007F
+    )
+}
+
 pub fn make_absolute_url(base: &str, url: &str) -> Result<url::Url, VideoError> {
     match url::Url::parse(url) {
         Ok(u) => Ok(u),
@@ -1163,6 +2355,51 @@ pub fn parse_abbreviated_number(time_str: &str) -> usize {
     return_value
 }
 
+/// Parse count texts like `1,234 views`, `12K subscribers` or `1.2M` to number
+/// - If the text contains no digits this function return `0`
+pub fn parse_count(text: &str) -> u64 {
+    let token = match text
+        .split_whitespace()
+        .find(|x| x.chars().any(|c| c.is_ascii_digit()))
+    {
+        Some(token) => token,
+        None => return 0,
+    };
+
+    let multiplier = match token.chars().last() {
+        Some('K') => 1_000f64,
+        Some('M') => 1_000_000f64,
+        Some('B') => 1_000_000_000f64,
+        _ => {
+            let digits = token
+                .chars()
+                .filter(|c| c.is_ascii_digit())
+                .collect::<String>();
+            return digits.parse::<u64>().unwrap_or(0);
+        }
+    };
+
+    let number = token[..token.len() - 1].replace(',', ".");
+
+    (number.parse::<f64>().unwrap_or(0f64) * multiplier).round() as u64
+}
+
+/// Parse InnerTube thumbnail array to [`Vec<Thumbnail>`]
+pub fn parse_thumbnails(thumbnails: &serde_json::Value) -> Vec<Thumbnail> {
+    thumbnails
+        .as_array()
+        .map(|x| {
+            x.iter()
+                .map(|x| Thumbnail {
+                    width: x["width"].as_u64().unwrap_or(0),
+                    height: x["height"].as_u64().unwrap_or(0),
+                    url: x["url"].as_str().unwrap_or("").to_string(),
+                })
+                .collect::<Vec<Thumbnail>>()
+        })
+        .unwrap_or_default()
+}
+
 #[cfg_attr(feature = "performance_analysis", flamer::flame)]
 pub fn merge(a: &mut serde_json::Value, b: &serde_json::Value) {
     match (a, b) {
@@ -1261,6 +2498,29 @@ pub fn cut_after_js(mixed_json: &str) -> Option<&str> {
     }
     Some(&mixed_json[0..index])
 }
+/// Builds a [`VideoFormat`] fixture the way the player response parser does,
+/// with `extra` merged over the base fields
+#[cfg(test)]
+pub(crate) fn test_format(
+    itag: u64,
+    mime_type: &str,
+    bitrate: u64,
+    extra: serde_json::Value,
+) -> VideoFormat {
+    let mut format = serde_json::json!({
+        "itag": itag,
+        "mimeType": mime_type,
+        "bitrate": bitrate,
+        "url": format!("https://rr1.googlevideo.com/videoplayback?itag={itag}"),
+    });
+    format
+        .as_object_mut()
+        .unwrap()
+        .extend(extra.as_object().unwrap().clone());
+    add_format_meta(format.as_object_mut().unwrap());
+
+    serde_json::from_value(format).unwrap()
+}
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1378,4 +2638,568 @@ mod tests {
         assert!(cut_after_js(r#"{"a": 1,{ "b": 1}"#).is_none());
         println!("[PASSED] test_returns_error_when_missing_closing_bracket");
     }
+    #[test]
+    fn test_parse_count() {
+        assert_eq!(parse_count("1,234,567 views"), 1234567);
+        assert_eq!(parse_count("12K subscribers"), 12000);
+        assert_eq!(parse_count("1.2M"), 1200000);
+        assert_eq!(parse_count("No views"), 0);
+        assert_eq!(parse_count(""), 0);
+    }
+    #[test]
+    #[cfg(feature = "ffmpeg")]
+    fn test_remux_chapters() {
+        use std::time::Duration;
+
+        let chapters = [
+            Chapter {
+                title: "Intro".to_string(),
+                start_time: 0,
+            },
+            Chapter {
+                title: "Verse".to_string(),
+                start_time: 30,
+            },
+            Chapter {
+                title: "Outro".to_string(),
+                start_time: 90,
+            },
+        ];
+
+        let whole = remux_chapters(&chapters, Duration::from_secs(120), Duration::ZERO, None);
+        assert_eq!(whole.len(), 3);
+        assert_eq!(whole[0].end, Duration::from_secs(30));
+        assert_eq!(whole[2].end, Duration::from_secs(120));
+
+        // Section from 0:45 to 1:40 keeps the end of the verse and the start of the outro
+        let section = remux_chapters(
+            &chapters,
+            Duration::from_secs(120),
+            Duration::from_secs(45),
+            Some(Duration::from_secs(100)),
+        );
+        assert_eq!(
+            section,
+            vec![
+                RemuxChapter {
+                    title: "Verse".to_string(),
+                    start: Duration::ZERO,
+                    end: Duration::from_secs(45),
+                },
+                RemuxChapter {
+                    title: "Outro".to_string(),
+                    start: Duration::from_secs(45),
+                    end: Duration::from_secs(55),
+                },
+            ]
+        );
+    }
+    #[test]
+    #[cfg(all(feature = "ffmpeg", not(feature = "libav-remux")))]
+    fn test_ffmetadata_chapters() {
+        let metadata = ffmetadata_chapters(&[RemuxChapter {
+            title: "Q&A; part=1".to_string(),
+            start: std::time::Duration::from_millis(1500),
+            end: std::time::Duration::from_secs(3),
+        }]);
+
+        assert_eq!(
+            metadata,
+            ";FFMETADATA1\n[CHAPTER]\nTIMEBASE=1/1000\nSTART=1500\nEND=3000\ntitle=Q&A\\; part\\=1\n"
+        );
+    }
+    #[test]
+    fn test_get_scheduled_start_time() {
+        let upcoming = serde_json::json!({
+            "playabilityStatus": {
+                "status": "LIVE_STREAM_OFFLINE",
+                "liveStreamability": { "liveStreamabilityRenderer": { "offlineSlate": {
+                    "liveStreamOfflineSlateRenderer": { "scheduledStartTime": "1767225600" },
+                } } },
+            },
+        });
+
+        assert!(is_not_yet_broadcasted(&upcoming));
+        assert_eq!(get_scheduled_start_time(&upcoming), Some(1767225600));
+        assert_eq!(get_scheduled_start_time(&serde_json::json!({})), None);
+    }
+    #[test]
+    fn test_is_post_live() {
+        let flagged = serde_json::json!({
+            "videoDetails": { "isLiveContent": true, "isPostLiveDvr": true },
+        });
+        assert!(is_post_live(&flagged));
+
+        let finished = serde_json::json!({
+            "videoDetails": { "isLiveContent": true, "isLive": false },
+            "streamingData": {
+                "hlsManifestUrl": "https://manifest.googlevideo.com/api/manifest/hls_variant/",
+                "adaptiveFormats": [
+                    { "url": "https://rr1.googlevideo.com/videoplayback?source=yt_live_broadcast" },
+                ],
+            },
+        });
+        assert!(is_post_live(&finished));
+
+        // Processed streams have regular formats
+        let processed = serde_json::json!({
+            "videoDetails": { "isLiveContent": true, "isLive": false },
+            "streamingData": {
+                "hlsManifestUrl": "https://manifest.googlevideo.com/api/manifest/hls_variant/",
+                "adaptiveFormats": [
+                    { "url": "https://rr1.googlevideo.com/videoplayback?source=youtube" },
+                ],
+            },
+        });
+        assert!(!is_post_live(&processed));
+    }
+    #[test]
+    fn test_innertube_error_message() {
+        let body = r#"{"error":{"code":400,"message":"Request contains an invalid argument.","status":"INVALID_ARGUMENT"}}"#;
+        assert_eq!(
+            innertube_error_message(body),
+            "Request contains an invalid argument."
+        );
+        assert_eq!(innertube_error_message(" Not Found \n"), "Not Found");
+    }
+
+    #[test]
+    fn test_get_playability_status() {
+        let geo_blocked = serde_json::json!({
+            "playabilityStatus": {
+                "status": "UNPLAYABLE",
+                "reason": "Video unavailable",
+                "errorScreen": { "playerErrorMessageRenderer": {
+                    "subreason": { "runs": [
+                        { "text": "The uploader has not made this video available in your " },
+                        { "text": "country" },
+                    ] },
+                } },
+            },
+            "microformat": { "playerMicroformatRenderer": { "availableCountries": ["JP"] } },
+        });
+        let status = get_playability_status(&geo_blocked);
+        assert!(status.is_geo_blocked());
+        assert!(!status.is_private());
+        assert_eq!(status.available_countries, vec!["JP".to_string()]);
+
+        let bot_check = serde_json::json!({
+            "playabilityStatus": {
+                "status": "LOGIN_REQUIRED",
+                "reason": "Sign in to confirm you’re not a bot",
+            },
+        });
+        let status = get_playability_status(&bot_check);
+        assert!(status.is_bot_check());
+        assert!(!status.is_age_restricted());
+        assert!(!is_age_gated(&bot_check));
+
+        let private = serde_json::json!({
+            "playabilityStatus": { "status": "LOGIN_REQUIRED", "reason": "This video is private" },
+        });
+        assert!(get_playability_status(&private).is_private());
+
+        let removed = serde_json::json!({
+            "playabilityStatus": { "status": "ERROR", "reason": "This video has been removed" },
+        });
+        assert!(get_playability_status(&removed).is_removed());
+        assert!(!needs_client_fallback(&removed));
+
+        let age_restricted = serde_json::json!({
+            "playabilityStatus": {
+                "status": "LOGIN_REQUIRED",
+                "reason": "Sign in to confirm your age",
+            },
+        });
+        assert!(get_playability_status(&age_restricted).is_age_restricted());
+
+        // "page" is not an age check
+        let unavailable = serde_json::json!({
+            "playabilityStatus": {
+                "status": "LOGIN_REQUIRED",
+                "reason": "This page is unavailable",
+            },
+        });
+        assert!(!get_playability_status(&unavailable).is_age_restricted());
+    }
+    #[test]
+    fn test_set_url_param() {
+        let base = "https://www.youtube.com/api/timedtext?v=a&lang=en";
+        assert_eq!(
+            set_url_param(base, "fmt", "json3").unwrap().as_str(),
+            "https://www.youtube.com/api/timedtext?v=a&lang=en&fmt=json3"
+        );
+        assert_eq!(
+            set_url_param(&format!("{base}&fmt=srv3"), "fmt", "json3")
+                .unwrap()
+                .as_str(),
+            "https://www.youtube.com/api/timedtext?v=a&lang=en&fmt=json3"
+        );
+        assert_eq!(
+            set_url_param(
+                "https://www.youtube.com/api/timedtext?fmt=srv3&v=a&fmt=vtt",
+                "fmt",
+                "json3"
+            )
+            .unwrap()
+            .as_str(),
+            "https://www.youtube.com/api/timedtext?v=a&fmt=json3"
+        );
+        assert!(set_url_param("/api/timedtext", "fmt", "json3").is_err());
+    }
+    #[test]
+    fn test_get_url_expiry() {
+        let expiry = Some(std::time::UNIX_EPOCH + std::time::Duration::from_secs(1700000000));
+        assert_eq!(
+            get_url_expiry("https://rr1.googlevideo.com/videoplayback?expire=1700000000&itag=18"),
+            expiry
+        );
+        assert_eq!(
+            get_url_expiry(
+                "https://manifest.googlevideo.com/api/manifest/dash/expire/1700000000/id/a"
+            ),
+            expiry
+        );
+        assert_eq!(
+            get_url_expiry("https://rr1.googlevideo.com/videoplayback?itag=18"),
+            None
+        );
+
+        let mut format = serde_json::json!({
+            "url": "https://rr1.googlevideo.com/videoplayback?expire=1700000000",
+        });
+        add_format_meta(format.as_object_mut().unwrap());
+        assert_eq!(
+            serde_json::from_value::<std::time::SystemTime>(format["expiresAt"].clone()).ok(),
+            expiry
+        );
+    }
+    #[test]
+    fn test_is_drc_format() {
+        let mut drc = serde_json::json!({
+            "url": "https://rr1.googlevideo.com/videoplayback?itag=140",
+            "xtags": "CggKA2RyYxIBMQ",
+        });
+        add_format_meta(drc.as_object_mut().unwrap());
+        assert_eq!(drc["isDrc"], true);
+
+        let mut drc_param = serde_json::json!({
+            "url": "https://rr1.googlevideo.com/videoplayback?itag=251&xtags=drc%3D1",
+        });
+        add_format_meta(drc_param.as_object_mut().unwrap());
+        assert_eq!(drc_param["isDrc"], true);
+
+        let mut original = serde_json::json!({
+            "url": "https://rr1.googlevideo.com/videoplayback?itag=140",
+        });
+        add_format_meta(original.as_object_mut().unwrap());
+        assert_eq!(original["isDrc"], false);
+    }
+    #[test]
+    fn test_get_latency_class() {
+        let latency_class = |video_details: serde_json::Value| get_latency_class(&video_details);
+
+        assert_eq!(
+            latency_class(serde_json::json!({
+                "latencyClass": "MDE_STREAM_OPTIMIZATIONS_RENDERER_LATENCY_ULTRA_LOW"
+            })),
+            Some(LatencyClass::UltraLow)
+        );
+        assert_eq!(
+            latency_class(serde_json::json!({
+                "latencyClass": "MDE_STREAM_OPTIMIZATIONS_RENDERER_LATENCY_NORMAL"
+            })),
+            Some(LatencyClass::Normal)
+        );
+        assert_eq!(
+            latency_class(serde_json::json!({ "isLowLatencyLiveStream": true })),
+            Some(LatencyClass::Low)
+        );
+        assert_eq!(latency_class(serde_json::json!({})), None);
+    }
+    #[test]
+    fn test_get_live_poll_delay() {
+        let upcoming = serde_json::json!({
+            "playabilityStatus": {
+                "status": "LIVE_STREAM_OFFLINE",
+                "liveStreamability": { "liveStreamabilityRenderer": { "pollDelayMs": "15000" } },
+            },
+        });
+        assert_eq!(
+            get_live_poll_delay(&upcoming),
+            Some(std::time::Duration::from_secs(15))
+        );
+        assert_eq!(get_live_poll_delay(&serde_json::json!({})), None);
+    }
+    #[test]
+    fn test_choose_format_preferences() {
+        let format = |itag: u64, label: &str, fps: u64, bitrate: u64, transfer: &str| {
+            test_format(
+                itag,
+                r#"video/webm; codecs="vp9""#,
+                bitrate,
+                serde_json::json!({
+                    "fps": fps,
+                    "qualityLabel": label,
+                    "colorInfo": {
+                        "primaries": "COLOR_PRIMARIES_BT709",
+                        "transferCharacteristics": transfer,
+                        "matrixCoefficients": "COLOR_MATRIX_COEFFICIENTS_BT709",
+                    },
+                }),
+            )
+        };
+        let formats = [
+            format(
+                248,
+                "1080p",
+                30,
+                3_000_000,
+                "COLOR_TRANSFER_CHARACTERISTICS_BT709",
+            ),
+            format(
+                303,
+                "1080p60",
+                60,
+                2_000_000,
+                "COLOR_TRANSFER_CHARACTERISTICS_BT709",
+            ),
+            format(
+                337,
+                "1080p60 HDR",
+                60,
+                1_000_000,
+                "COLOR_TRANSFER_CHARACTERISTICS_SMPTEST2084",
+            ),
+            format(
+                247,
+                "720p",
+                30,
+                4_000_000,
+                "COLOR_TRANSFER_CHARACTERISTICS_BT709",
+            ),
+        ];
+        assert!(formats[2].is_hdr());
+        assert!(!formats[1].is_hdr());
+
+        let mut options = VideoOptions {
+            quality: VideoQuality::HighestVideo,
+            filter: VideoSearchOptions::Video,
+            ..Default::default()
+        };
+        assert_eq!(choose_format(&formats, &options).unwrap().itag, 248);
+
+        options.prefer_60fps = true;
+        assert_eq!(choose_format(&formats, &options).unwrap().itag, 303);
+
+        options.prefer_hdr = true;
+        assert_eq!(choose_format(&formats, &options).unwrap().itag, 337);
+    }
+    #[test]
+    fn test_choose_format_label() {
+        let format = |itag: u64, label: &str, fps: u64| {
+            test_format(
+                itag,
+                r#"video/mp4; codecs="avc1.4d401f""#,
+                1_000_000,
+                serde_json::json!({ "fps": fps, "qualityLabel": label }),
+            )
+        };
+        let formats = [
+            format(299, "1080p60", 60),
+            format(137, "1080p", 30),
+            format(136, "720p", 30),
+            format(134, "360p", 30),
+        ];
+        let choose = |label: &str| {
+            let options = VideoOptions {
+                quality: VideoQuality::Label(label.to_string()),
+                filter: VideoSearchOptions::Video,
+                ..Default::default()
+            };
+            choose_format(&formats, &options).map(|x| x.itag)
+        };
+
+        assert_eq!(choose("1080p60").unwrap(), 299);
+        assert_eq!(choose("1080p").unwrap(), 137);
+        assert_eq!(choose("1440p60").unwrap(), 299);
+        assert_eq!(choose("720p60").unwrap(), 136);
+        assert_eq!(choose("480p").unwrap(), 134);
+        assert_eq!(choose("144p").unwrap(), 134);
+        assert!(matches!(
+            choose("best"),
+            Err(VideoError::InvalidQualityLabel(_))
+        ));
+
+        let options = VideoOptions {
+            quality: VideoQuality::Label("720p".to_string()),
+            ..Default::default()
+        };
+        let with_audio = [
+            format(136, "720p", 30),
+            test_format(
+                140,
+                r#"audio/mp4; codecs="mp4a.40.2""#,
+                128_000,
+                serde_json::json!({ "audioQuality": "AUDIO_QUALITY_MEDIUM" }),
+            ),
+        ];
+        assert_eq!(choose_format(&with_audio, &options).unwrap().itag, 136);
+
+        assert_eq!(
+            parse_quality_label("2160p60 HDR"),
+            Some((2160, Some(60), true))
+        );
+        assert_eq!(parse_quality_label("720"), Some((720, None, false)));
+    }
+    #[test]
+    fn test_choose_format_pair() {
+        let format = |itag: u64, mime_type: &str, extra: serde_json::Value| {
+            test_format(itag, mime_type, 1_000_000, extra)
+        };
+        let formats = [
+            format(
+                18,
+                r#"video/mp4; codecs="avc1.42001E, mp4a.40.2""#,
+                serde_json::json!({ "qualityLabel": "360p", "audioQuality": "AUDIO_QUALITY_LOW" }),
+            ),
+            format(
+                137,
+                r#"video/mp4; codecs="avc1.640028""#,
+                serde_json::json!({ "qualityLabel": "1080p" }),
+            ),
+            format(
+                136,
+                r#"video/mp4; codecs="avc1.4d401f""#,
+                serde_json::json!({ "qualityLabel": "720p" }),
+            ),
+            format(
+                140,
+                r#"audio/mp4; codecs="mp4a.40.2""#,
+                serde_json::json!({ "audioBitrate": 128 }),
+            ),
+            format(
+                251,
+                r#"audio/webm; codecs="opus""#,
+                serde_json::json!({ "audioBitrate": 160 }),
+            ),
+        ];
+        let choose = |quality: VideoQuality| {
+            let options = VideoOptions {
+                quality,
+                ..Default::default()
+            };
+            choose_format_pair(&formats, &options)
+                .map(|(video, audio)| (video.itag, audio.map(|x| x.itag)))
+                .unwrap()
+        };
+
+        assert_eq!(choose(VideoQuality::Highest), (137, Some(140)));
+        assert_eq!(
+            choose(VideoQuality::Label("720p".to_string())),
+            (136, Some(140))
+        );
+        assert_eq!(choose(VideoQuality::Label("480p".to_string())), (18, None));
+        assert_eq!(choose(VideoQuality::Lowest), (18, None));
+        assert_eq!(choose(VideoQuality::HighestAudio), (251, None));
+    }
+    #[test]
+    fn test_choose_format_premium() {
+        let format = |itag: u64, label: &str, bitrate: u64| {
+            test_format(
+                itag,
+                r#"video/webm; codecs="vp9""#,
+                bitrate,
+                serde_json::json!({ "qualityLabel": label }),
+            )
+        };
+        let formats = [
+            format(356, "1080p Premium", 4_000_000),
+            format(248, "1080p", 2_000_000),
+        ];
+        assert!(formats[0].is_premium());
+        assert!(!formats[1].is_premium());
+
+        let mut options = VideoOptions {
+            quality: VideoQuality::HighestVideo,
+            filter: VideoSearchOptions::Video,
+            ..Default::default()
+        };
+        assert_eq!(choose_format(&formats, &options).unwrap().itag, 248);
+
+        options.request_options.cookies = Some("SAPISID=a".to_string());
+        assert_eq!(choose_format(&formats, &options).unwrap().itag, 356);
+    }
+    #[test]
+    fn test_is_drm_protected() {
+        let drm = serde_json::json!({
+            "streamingData": {
+                "licenseInfos": [{ "drmFamily": "WIDEVINE", "url": "https://www.youtube.com/api/drm/widevine" }],
+                "adaptiveFormats": [{ "itag": 136, "drmFamilies": ["WIDEVINE"] }],
+            },
+        });
+        assert!(is_drm_protected(&drm));
+
+        let mixed = serde_json::json!({
+            "streamingData": {
+                "adaptiveFormats": [{ "itag": 136, "drmFamilies": ["WIDEVINE"] }, { "itag": 140 }],
+            },
+        });
+        assert!(!is_drm_protected(&mixed));
+        assert!(!is_drm_protected(
+            &serde_json::json!({ "streamingData": {} })
+        ));
+    }
+    #[test]
+    fn test_rate_limited_error() {
+        let retry_after = Some(std::time::Duration::from_secs(30));
+        let err = VideoError::from(reqwest_middleware::Error::middleware(
+            VideoError::RateLimited { retry_after },
+        ));
+        assert!(matches!(err, VideoError::RateLimited { retry_after: x } if x == retry_after));
+    }
+    #[test]
+    fn test_sanitize_filename() {
+        let options = SanitizeOptions::default();
+
+        assert_eq!(
+            sanitize_filename("AC/DC: Back In Black?", &options),
+            "AC_DC_ Back In Black_"
+        );
+        assert_eq!(sanitize_filename("con.mp4", &options), "con_.mp4");
+        assert_eq!(sanitize_filename("Title... ", &options), "Title");
+        assert_eq!(sanitize_filename("", &options), "_");
+
+        let options = SanitizeOptions {
+            max_length: Some(7),
+            remove_emoji: true,
+            ..Default::default()
+        };
+
+        // Family emoji is one grapheme of joined code points
+        assert_eq!(sanitize_filename("a👨‍👩‍👧b", &options), "ab");
+        assert_eq!(sanitize_filename("caféééé", &options), "caféé");
+    }
+    #[test]
+    fn test_parse_netscape_cookies() {
+        let content = "# Netscape HTTP Cookie File\n\
+            .youtube.com\tTRUE\t/\tTRUE\t1735689600\tPREF\tf6=40000000\n\
+            #HttpOnly_.youtube.com\tTRUE\t/\tTRUE\t1735689600\tLOGIN_INFO\tabc\n\
+            \n\
+            invalid line\n";
+
+        let cookies = parse_netscape_cookies(content);
+
+        assert_eq!(cookies.len(), 2);
+        assert_eq!(
+            cookies[0],
+            (
+                ".youtube.com".to_string(),
+                "/".to_string(),
+                "PREF".to_string(),
+                "f6=40000000".to_string()
+            )
+        );
+        assert_eq!(cookies[1].2, "LOGIN_INFO");
+    }
 }
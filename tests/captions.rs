@@ -0,0 +1,19 @@
+#[tokio::test]
+async fn download_caption() {
+    use rusty_ytdl::{CaptionFormat, Video};
+
+    let video = Video::new("https://www.youtube.com/watch?v=FZ8BxMU3BYc").unwrap();
+
+    let info = video.get_basic_info().await.unwrap();
+
+    println!("{:#?}", info.captions);
+
+    if let Some(track) = info.captions.first() {
+        let vtt = video
+            .download_caption(track.language_code.clone(), CaptionFormat::Vtt)
+            .await
+            .unwrap();
+
+        assert!(vtt.starts_with("WEBVTT"));
+    }
+}
@@ -0,0 +1,20 @@
+#[tokio::test]
+async fn channel_tabs() {
+    use rusty_ytdl::Channel;
+
+    let channel = Channel::get("https://www.youtube.com/@YouTube")
+        .await
+        .unwrap();
+
+    println!(
+        "{} ({}) subscribers: {}",
+        channel.name, channel.id, channel.subscribers
+    );
+
+    let mut videos = channel.videos().await.unwrap();
+    videos.fetch(Some(60)).await.unwrap();
+    println!("Videos: {}", videos.items.len());
+
+    let playlists = channel.playlists().await.unwrap();
+    println!("Playlists: {}", playlists.items.len());
+}
@@ -0,0 +1,13 @@
+#[tokio::test]
+async fn comments() {
+    use rusty_ytdl::{CommentSort, Video};
+
+    let video = Video::new("https://www.youtube.com/watch?v=FZ8BxMU3BYc").unwrap();
+
+    let mut comments = video.comments(CommentSort::Newest).await.unwrap();
+
+    comments.fetch(Some(50)).await.unwrap();
+
+    println!("{:#?}", comments.comments.first());
+    println!("Comments: {}", comments.comments.len());
+}
@@ -0,0 +1,25 @@
+#[tokio::test]
+async fn get_info_with_cookies_file() {
+    use rusty_ytdl::{RequestOptions, Video, VideoOptions};
+
+    let cookies_file = std::env::temp_dir().join("rusty_ytdl_cookies.txt");
+    std::fs::write(
+        &cookies_file,
+        "# Netscape HTTP Cookie File\n.youtube.com\tTRUE\t/\tTRUE\t2147483647\tPREF\thl=en\n",
+    )
+    .unwrap();
+
+    let video_options = VideoOptions {
+        request_options: RequestOptions {
+            cookies_file: Some(cookies_file),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let video = Video::new_with_options("FZ8BxMU3BYc", video_options).unwrap();
+
+    let info = video.get_basic_info().await.unwrap();
+
+    println!("{}", info.video_details.title);
+}
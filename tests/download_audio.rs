@@ -0,0 +1,62 @@
+#[tokio::test]
+async fn download_opus() {
+    use rusty_ytdl::Video;
+
+    let video = Video::new("https://www.youtube.com/watch?v=FZ8BxMU3BYc").unwrap();
+
+    let path = std::env::temp_dir().join("rusty_ytdl_download_opus.opus");
+
+    video.download_opus(&path).await.unwrap();
+
+    let data = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(data.starts_with(b"OggS"));
+    assert_eq!(&data[28..36], b"OpusHead");
+}
+
+#[tokio::test]
+async fn download_m4a() {
+    use rusty_ytdl::Video;
+
+    let video = Video::new("https://www.youtube.com/watch?v=FZ8BxMU3BYc").unwrap();
+
+    let path = std::env::temp_dir().join("rusty_ytdl_download_m4a.m4a");
+
+    video.download_m4a(&path).await.unwrap();
+
+    let data = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(&data[4..12], b"ftypM4A ");
+    // Regular file without movie fragments
+    assert!(!data.windows(4).any(|x| x == b"moof"));
+}
+
+#[tokio::test]
+#[cfg(feature = "tags")]
+async fn download_m4a_with_tags() {
+    use rusty_ytdl::{DownloadOptions, Video, VideoOptions};
+
+    let video_options = VideoOptions {
+        download_options: DownloadOptions {
+            write_tags: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let video =
+        Video::new_with_options("https://www.youtube.com/watch?v=FZ8BxMU3BYc", video_options)
+            .unwrap();
+
+    let path = std::env::temp_dir().join("rusty_ytdl_download_m4a_with_tags.m4a");
+
+    video.download_m4a(&path).await.unwrap();
+
+    let data = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    // iTunes metadata with the title atom
+    assert!(data.windows(4).any(|x| x == b"ilst"));
+    assert!(data.windows(4).any(|x| x == b"\xA9nam"));
+}
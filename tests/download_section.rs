@@ -0,0 +1,35 @@
+#[tokio::test]
+async fn download_section() {
+    use rusty_ytdl::{Video, VideoOptions, VideoQuality, VideoSearchOptions};
+    use std::time::Duration;
+
+    let url = "https://www.youtube.com/watch?v=FZ8BxMU3BYc";
+
+    let video_options = VideoOptions {
+        quality: VideoQuality::Lowest,
+        filter: VideoSearchOptions::VideoAudio,
+        ..Default::default()
+    };
+
+    let video = Video::new_with_options(url, video_options).unwrap();
+
+    let path = std::env::temp_dir().join("rusty_ytdl_download_section.mp4");
+
+    video
+        .download_section(&path, Duration::from_secs(10), Duration::from_secs(20))
+        .await
+        .unwrap();
+
+    let section_size = std::fs::metadata(&path).unwrap().len();
+    std::fs::remove_file(&path).unwrap();
+
+    let stream = video.stream().await.unwrap();
+
+    assert!(section_size > 0);
+    assert!(section_size < stream.content_length() as u64);
+
+    assert!(video
+        .download_section(&path, Duration::from_secs(20), Duration::from_secs(10))
+        .await
+        .is_err());
+}
@@ -0,0 +1,29 @@
+#[tokio::test]
+async fn download_with_progress() {
+    use rusty_ytdl::{
+        DownloadOptions, ProgressCallback, Video, VideoOptions, VideoQuality, VideoSearchOptions,
+    };
+
+    let url = "https://www.youtube.com/watch?v=FZ8BxMU3BYc";
+
+    let video_options = VideoOptions {
+        quality: VideoQuality::Lowest,
+        filter: VideoSearchOptions::Audio,
+        download_options: DownloadOptions {
+            progress_callback: Some(ProgressCallback::new(|progress| {
+                println!(
+                    "{}/{} bytes, {:.0} B/s, eta: {:?}",
+                    progress.downloaded, progress.total, progress.speed, progress.eta
+                );
+            })),
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let video = Video::new_with_options(url, video_options).unwrap();
+
+    let stream = video.stream().await.unwrap();
+
+    while let Some(_chunk) = stream.chunk().await.unwrap() {}
+}
@@ -28,3 +28,21 @@ async fn ffmpeg_download_test() {
             .unwrap();
     }
 }
+
+#[ignore]
+#[tokio::test]
+async fn ffmpeg_download_muxed_test() {
+    #[cfg(feature = "ffmpeg")]
+    {
+        use rusty_ytdl::Video;
+
+        let video = Video::new("FZ8BxMU3BYc").unwrap();
+
+        let path = std::env::temp_dir().join("rusty_ytdl_muxed.mkv");
+
+        video.download_muxed(&path).await.unwrap();
+
+        assert!(std::fs::metadata(&path).unwrap().len() > 0);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -10,3 +10,48 @@ async fn get_info() {
 
     println!("Formats: {:#?}", video_info.formats);
 }
+
+#[tokio::test]
+async fn resolve_format_url() {
+    use rusty_ytdl::Video;
+
+    let video = Video::new("FZ8BxMU3BYc").unwrap();
+
+    let video_info = video.get_basic_info().await.unwrap();
+
+    let url = video_info.formats.first().unwrap().resolve_url();
+
+    assert!(url.starts_with("https://"));
+}
+
+#[tokio::test]
+async fn get_info_cached() {
+    use rusty_ytdl::{Video, VideoOptions};
+
+    let video_options = VideoOptions {
+        info_cache_ttl: Some(std::time::Duration::from_secs(60)),
+        ..Default::default()
+    };
+
+    let video = Video::new_with_options("FZ8BxMU3BYc", video_options).unwrap();
+
+    let first = video.get_info().await.unwrap();
+    let second = video.get_info().await.unwrap();
+
+    assert_eq!(first.formats, second.formats);
+}
+
+#[tokio::test]
+async fn raw_responses() {
+    use rusty_ytdl::Video;
+
+    let video = Video::new("FZ8BxMU3BYc").unwrap();
+
+    let video_info = video.get_basic_info().await.unwrap();
+
+    assert_eq!(
+        video_info.raw_player_response()["videoDetails"]["videoId"].as_str(),
+        Some("FZ8BxMU3BYc")
+    );
+    assert!(video_info.raw_initial_response().is_object());
+}
@@ -0,0 +1,31 @@
+#[tokio::test]
+async fn get_info_with_android_client() {
+    use rusty_ytdl::{InnertubeClient, Video, VideoOptions};
+
+    let video_options = VideoOptions {
+        client: InnertubeClient::Android,
+        ..Default::default()
+    };
+
+    let video = Video::new_with_options("FZ8BxMU3BYc", video_options).unwrap();
+
+    let info = video.get_basic_info().await.unwrap();
+
+    println!("{} formats", info.formats.len());
+}
+
+#[tokio::test]
+async fn get_info_with_player_endpoint() {
+    use rusty_ytdl::{Video, VideoOptions};
+
+    let video_options = VideoOptions {
+        use_player_endpoint: true,
+        ..Default::default()
+    };
+
+    let video = Video::new_with_options("FZ8BxMU3BYc", video_options).unwrap();
+
+    let info = video.get_basic_info().await.unwrap();
+
+    println!("{}", info.video_details.title);
+}
@@ -0,0 +1,14 @@
+#[tokio::test]
+async fn live_chat() {
+    use rusty_ytdl::Video;
+
+    // Lofi Girl 24/7 live stream
+    let video = Video::new("https://www.youtube.com/watch?v=jfKfPfyJRdk").unwrap();
+
+    let mut chat = video.live_chat().await.unwrap();
+
+    let messages = chat.next().await.unwrap();
+
+    println!("{:#?}", messages.first());
+    assert!(chat.has_next());
+}
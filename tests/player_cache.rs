@@ -0,0 +1,17 @@
+#[tokio::test]
+async fn get_info_with_player_cache() {
+    use rusty_ytdl::{Video, VideoOptions};
+
+    let cache_dir = std::env::temp_dir().join("rusty_ytdl_player_cache");
+
+    let video_options = VideoOptions {
+        player_cache_dir: Some(cache_dir.clone()),
+        ..Default::default()
+    };
+
+    let video = Video::new_with_options("FZ8BxMU3BYc", video_options).unwrap();
+
+    video.get_basic_info().await.unwrap();
+
+    assert!(std::fs::read_dir(cache_dir).unwrap().next().is_some());
+}
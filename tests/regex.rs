@@ -27,3 +27,54 @@ async fn is_valid_id_or_link() {
     // Not valid video id
     assert_eq!(None, get_video_id("FZ8BxU3BYc"));
 }
+
+#[tokio::test]
+async fn is_valid_clip_link() {
+    use rusty_ytdl::get_clip_id;
+
+    assert_eq!(
+        Some("UgkxU2HSeGL_NvmDJ-nQJrlLwllwMDBdGZFs".to_string()),
+        get_clip_id("https://www.youtube.com/clip/UgkxU2HSeGL_NvmDJ-nQJrlLwllwMDBdGZFs")
+    );
+
+    // Not a clip link
+    assert_eq!(
+        None,
+        get_clip_id("https://www.youtube.com/watch?v=FZ8BxMU3BYc")
+    );
+
+    // Not a YouTube host
+    assert_eq!(
+        None,
+        get_clip_id("https://notyoutube.com/clip/UgkxU2HSeGL_NvmDJ-nQJrlLwllwMDBdGZFs")
+    );
+}
+
+#[tokio::test]
+async fn start_time_param() {
+    use rusty_ytdl::get_start_time;
+    use std::time::Duration;
+
+    assert_eq!(
+        Some(Duration::from_secs(90)),
+        get_start_time("https://youtu.be/FZ8BxMU3BYc?t=90")
+    );
+    assert_eq!(
+        Some(Duration::from_secs(3723)),
+        get_start_time("https://www.youtube.com/watch?v=FZ8BxMU3BYc&t=1h2m3s")
+    );
+    assert_eq!(
+        Some(Duration::from_secs(30)),
+        get_start_time("https://www.youtube.com/embed/FZ8BxMU3BYc?start=30")
+    );
+    assert_eq!(
+        None,
+        get_start_time("https://www.youtube.com/watch?v=FZ8BxMU3BYc")
+    );
+
+    // Overflowing time
+    assert_eq!(
+        None,
+        get_start_time("https://www.youtube.com/watch?v=FZ8BxMU3BYc&t=99999999999999999h")
+    );
+}
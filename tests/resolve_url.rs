@@ -0,0 +1,18 @@
+#[tokio::test]
+async fn resolve_url() {
+    use rusty_ytdl::{resolve_url, ResolvedUrl};
+
+    let resolved = resolve_url("https://youtu.be/FZ8BxMU3BYc").await.unwrap();
+    assert!(matches!(resolved, ResolvedUrl::Video { id, .. } if id == "FZ8BxMU3BYc"));
+
+    let resolved = resolve_url("https://www.youtube.com/@YouTube")
+        .await
+        .unwrap();
+    assert!(matches!(resolved, ResolvedUrl::Channel { .. }));
+
+    let resolved =
+        resolve_url("https://www.youtube.com/playlist?list=PLwMEL7UNT4o9iMzrvNBXZqXbNPFfT6rVD")
+            .await
+            .unwrap();
+    assert!(matches!(resolved, ResolvedUrl::Playlist { .. }));
+}
@@ -8,3 +8,46 @@ async fn search() {
 
     println!("{res:#?}");
 }
+
+#[tokio::test]
+async fn search_with_filters() {
+    use rusty_ytdl::search::{SearchDuration, SearchOptions, SortBy, UploadDate, YouTube};
+
+    let youtube = YouTube::new().unwrap();
+
+    let res = youtube
+        .search(
+            "lofi",
+            Some(&SearchOptions {
+                upload_date: Some(UploadDate::ThisMonth),
+                duration: Some(SearchDuration::Over20Min),
+                sort_by: SortBy::ViewCount,
+                ..Default::default()
+            }),
+        )
+        .await;
+
+    println!("{res:#?}");
+}
+
+#[tokio::test]
+async fn suggestions() {
+    use rusty_ytdl::search::YouTube;
+
+    let youtube = YouTube::new().unwrap();
+
+    let suggestions = youtube.suggestions("i know your", None).await.unwrap();
+
+    assert!(!suggestions.is_empty());
+}
+
+#[tokio::test]
+async fn trending() {
+    use rusty_ytdl::search::{TrendingCategory, YouTube};
+
+    let youtube = YouTube::new().unwrap();
+
+    let videos = youtube.trending("US", TrendingCategory::Now).await;
+
+    println!("{videos:#?}");
+}
@@ -0,0 +1,22 @@
+#[tokio::test]
+#[cfg(feature = "storyboard")]
+async fn storyboard_frames() {
+    use rusty_ytdl::Video;
+
+    let video = Video::new("https://www.youtube.com/watch?v=FZ8BxMU3BYc").unwrap();
+
+    let info = video.get_basic_info().await.unwrap();
+
+    let storyboard = info.video_details.storyboards.last().unwrap();
+
+    let frames = storyboard.frames().await.unwrap();
+
+    assert!(frames.len() > 1);
+    assert!(frames.len() <= storyboard.thumbnail_count as usize);
+    assert!(frames[0].1.starts_with(&[0xFF, 0xD8]));
+    // Timestamps increase by the interval
+    assert_eq!(
+        frames[1].0 - frames[0].0,
+        std::time::Duration::from_millis(storyboard.interval as u64)
+    );
+}
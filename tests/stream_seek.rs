@@ -0,0 +1,26 @@
+#[tokio::test]
+async fn stream_seek() {
+    use rusty_ytdl::{Video, VideoOptions, VideoQuality, VideoSearchOptions};
+
+    let url = "https://www.youtube.com/watch?v=FZ8BxMU3BYc";
+
+    let video_options = VideoOptions {
+        quality: VideoQuality::Lowest,
+        filter: VideoSearchOptions::Audio,
+        ..Default::default()
+    };
+
+    let video = Video::new_with_options(url, video_options).unwrap();
+
+    let stream = video.stream().await.unwrap();
+
+    let half = stream.content_length() as u64 / 2;
+    stream.seek(half).await.unwrap();
+
+    let mut downloaded = 0;
+    while let Some(chunk) = stream.chunk().await.unwrap() {
+        downloaded += chunk.len() as u64;
+    }
+
+    assert_eq!(downloaded, stream.content_length() as u64 - half);
+}
@@ -0,0 +1,22 @@
+#[tokio::test]
+async fn download_thumbnail() {
+    use rusty_ytdl::{Thumbnail, ThumbnailQuality, Video};
+
+    let video = Video::new("https://www.youtube.com/watch?v=FZ8BxMU3BYc").unwrap();
+
+    let info = video.get_basic_info().await.unwrap();
+    assert!(Thumbnail::best(&info.video_details.thumbnails).is_some());
+
+    let path = std::env::temp_dir().join("rusty_ytdl_download_thumbnail.jpg");
+
+    video
+        .download_thumbnail(&path, ThumbnailQuality::Max)
+        .await
+        .unwrap();
+
+    let data = std::fs::read(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    // JPEG start of image marker
+    assert!(data.starts_with(&[0xFF, 0xD8]));
+}
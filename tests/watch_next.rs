@@ -0,0 +1,28 @@
+#[tokio::test]
+async fn watch_next() {
+    use rusty_ytdl::Video;
+
+    let url = "https://www.youtube.com/watch?v=FZ8BxMU3BYc&list=PLwMEL7UNT4o9iMzrvNBXZqXbNPFfT6rVD";
+
+    let video = Video::new(url).unwrap();
+
+    let watch_next = video.get_watch_next().await.unwrap();
+
+    println!("Autoplay: {:#?}", watch_next.autoplay);
+    println!("Up next: {}", watch_next.up_next.len());
+    println!("Playlist: {:#?}", watch_next.playlist.map(|x| x.title));
+}
+
+#[tokio::test]
+async fn related_videos() {
+    use rusty_ytdl::Video;
+
+    let video = Video::new("FZ8BxMU3BYc").unwrap();
+
+    let mut related = video.related().await.unwrap();
+
+    related.fetch(Some(40)).await.unwrap();
+
+    println!("Related: {}", related.videos.len());
+    println!("{:#?}", related.videos.first());
+}